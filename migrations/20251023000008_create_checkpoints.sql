@@ -0,0 +1,17 @@
+-- Create checkpoints table
+-- Tracks the last committed slot per pipeline job so interrupted runs can resume
+
+CREATE TABLE checkpoints (
+    -- Job identifier (e.g. a backfill name)
+    job_name VARCHAR(100) PRIMARY KEY,
+    
+    -- Last slot whose batch was committed
+    last_slot BIGINT NOT NULL,
+    
+    -- Processing metadata
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Add comments
+COMMENT ON TABLE checkpoints IS 'Resumable progress of pipeline jobs';
+COMMENT ON COLUMN checkpoints.last_slot IS 'End slot of the last successfully committed batch';
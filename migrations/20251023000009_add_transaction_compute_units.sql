@@ -0,0 +1,7 @@
+-- Add compute units consumed per transaction
+-- NULL means the RPC did not report the value (older transactions), not zero
+
+ALTER TABLE transactions
+ADD COLUMN compute_units BIGINT;
+
+COMMENT ON COLUMN transactions.compute_units IS 'Compute units consumed (meta.computeUnitsConsumed), NULL when unavailable';
@@ -0,0 +1,32 @@
+-- Create token_balances table
+-- Net token movement per owner and mint for each transaction,
+-- derived from meta.preTokenBalances / meta.postTokenBalances
+
+CREATE TABLE token_balances (
+    -- Primary identifier
+    id BIGSERIAL PRIMARY KEY,
+    
+    -- Transaction relationship
+    transaction_signature VARCHAR(88) NOT NULL REFERENCES transactions(signature) ON DELETE CASCADE,
+    
+    -- Balance change details
+    owner VARCHAR(44) NOT NULL, -- Wallet owning the token account
+    mint VARCHAR(44) NOT NULL, -- Token mint address
+    decimals SMALLINT NOT NULL,
+    amount_delta NUMERIC(40, 0) NOT NULL, -- Post minus pre balance in base units
+    
+    -- Processing metadata
+    processed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    
+    UNIQUE(transaction_signature, owner, mint)
+);
+
+-- Indexes for common queries
+CREATE INDEX idx_token_balances_transaction ON token_balances(transaction_signature);
+CREATE INDEX idx_token_balances_owner ON token_balances(owner);
+CREATE INDEX idx_token_balances_mint ON token_balances(mint);
+
+-- Add comments
+COMMENT ON TABLE token_balances IS 'Per-owner, per-mint token balance changes for each transaction';
+COMMENT ON COLUMN token_balances.owner IS 'Token account owner, or the token account address when the owner is unknown';
+COMMENT ON COLUMN token_balances.amount_delta IS 'Post minus pre token balance in base units (negative = outflow)';
@@ -0,0 +1,8 @@
+-- Add the memo attached to a transaction (SPL Memo program)
+-- Multiple memos in one transaction are concatenated in instruction order, separated by newlines.
+-- Non-UTF-8 memo bytes are stored lossily (invalid sequences replaced with U+FFFD).
+
+ALTER TABLE transactions
+ADD COLUMN memo TEXT;
+
+COMMENT ON COLUMN transactions.memo IS 'Memo text from SPL Memo instructions, NULL when the transaction has no memo';
@@ -0,0 +1,10 @@
+-- Add priority fee details from Compute Budget instructions
+-- transactions.fee is the total fee (base signature fee + priority fee); the base fee is fee - priority_fee.
+-- Both columns are NULL when the transaction sets no compute unit price.
+
+ALTER TABLE transactions
+ADD COLUMN priority_fee BIGINT,
+ADD COLUMN compute_unit_price BIGINT;
+
+COMMENT ON COLUMN transactions.priority_fee IS 'Priority fee in lamports (ceil(compute_unit_price * unit limit / 1e6)), included in fee';
+COMMENT ON COLUMN transactions.compute_unit_price IS 'SetComputeUnitPrice value in micro-lamports per compute unit';
@@ -0,0 +1,7 @@
+-- Add the native Stake Program to the program registry
+-- Program type 'Stake' drives the Stake transaction classification (delegate, deactivate, withdraw).
+-- Liquid staking protocols keep the 'Staking' type.
+
+INSERT INTO program_registry (program_id, program_name, program_type, description) VALUES
+    ('Stake11111111111111111111111111111111111111', 'Stake Program', 'Stake', 'Native stake delegation, deactivation and withdrawal')
+ON CONFLICT (program_id) DO NOTHING;
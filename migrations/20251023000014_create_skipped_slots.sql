@@ -0,0 +1,10 @@
+-- Create skipped_slots table
+-- Slots whose leader produced no block. Recorded by backfill so these slots
+-- are not reported as gaps in the blocks table again.
+
+CREATE TABLE skipped_slots (
+    slot BIGINT PRIMARY KEY,
+    recorded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+COMMENT ON TABLE skipped_slots IS 'Slots confirmed by getBlocks to have no block (skipped by the leader)';
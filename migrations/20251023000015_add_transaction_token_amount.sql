@@ -0,0 +1,14 @@
+-- Add token transfer amount columns to transactions
+-- Raw amounts are stored with the mint decimals so human-readable amounts can be
+-- derived as token_amount / 10^token_decimals without another RPC lookup.
+
+ALTER TABLE transactions
+    ADD COLUMN token_mint VARCHAR(44),
+    ADD COLUMN token_amount NUMERIC(20, 0),
+    ADD COLUMN token_decimals SMALLINT;
+
+CREATE INDEX idx_transactions_token_mint ON transactions(token_mint) WHERE token_mint IS NOT NULL;
+
+COMMENT ON COLUMN transactions.token_mint IS 'Mint of the first top-level SPL token transfer';
+COMMENT ON COLUMN transactions.token_amount IS 'Raw amount of that transfer in token base units';
+COMMENT ON COLUMN transactions.token_decimals IS 'Decimals of the mint, NULL when they could not be resolved';
@@ -0,0 +1,7 @@
+-- Add the fee payer's SOL balance change to transactions
+-- postBalances[0] - preBalances[0] in lamports. The fee is charged to the fee payer,
+-- so the delta already includes it: a failed transaction shows -fee.
+
+ALTER TABLE transactions ADD COLUMN signer_balance_delta BIGINT;
+
+COMMENT ON COLUMN transactions.signer_balance_delta IS 'Fee payer lamport balance change (post - pre), fee included';
@@ -0,0 +1,9 @@
+-- Add program log messages to transactions
+-- meta.logMessages as returned by the RPC. NULL when the transaction logged nothing.
+-- The RPC cuts logs off at the log size limit and appends "Log truncated".
+
+ALTER TABLE transactions ADD COLUMN log_messages TEXT[];
+ALTER TABLE transactions ADD COLUMN logs_truncated BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN transactions.log_messages IS 'Program log lines (meta.logMessages), NULL when empty';
+COMMENT ON COLUMN transactions.logs_truncated IS 'Logs exceeded the RPC log limit and are incomplete';
@@ -0,0 +1,34 @@
+-- Create block_rewards table
+-- Rewards credited in each block, from the block's `rewards` list:
+-- the leader's fee and rent rewards every block, staking and voting
+-- inflation rewards at epoch boundaries
+
+CREATE TABLE block_rewards (
+    -- Primary identifier
+    id BIGSERIAL PRIMARY KEY,
+    
+    -- Block relationship
+    block_slot BIGINT NOT NULL REFERENCES blocks(slot) ON DELETE CASCADE,
+    
+    -- Reward details
+    pubkey VARCHAR(44) NOT NULL, -- Account receiving the reward
+    lamports BIGINT NOT NULL, -- Reward amount (negative for rent debits)
+    post_balance BIGINT NOT NULL, -- Account balance after the reward
+    reward_type VARCHAR(8) NOT NULL CHECK (reward_type IN ('Fee', 'Rent', 'Staking', 'Voting')),
+    commission SMALLINT, -- Vote account commission, only for staking and voting rewards
+    
+    -- Processing metadata
+    processed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    
+    UNIQUE(block_slot, pubkey, reward_type)
+);
+
+-- Indexes for common queries
+CREATE INDEX idx_block_rewards_block_slot ON block_rewards(block_slot);
+CREATE INDEX idx_block_rewards_pubkey ON block_rewards(pubkey);
+CREATE INDEX idx_block_rewards_reward_type ON block_rewards(reward_type);
+
+-- Add comments
+COMMENT ON TABLE block_rewards IS 'Validator and stake rewards credited in each block';
+COMMENT ON COLUMN block_rewards.reward_type IS 'Fee (leader fees), Rent, Staking (stake account inflation) or Voting (vote account inflation)';
+COMMENT ON COLUMN block_rewards.lamports IS 'Reward in lamports; negative when rent was debited';
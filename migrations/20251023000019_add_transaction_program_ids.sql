@@ -0,0 +1,10 @@
+-- Add the invoked program IDs to transactions
+-- Deduplicated top-level program IDs in instruction order, so program-level queries
+-- (WHERE program_ids @> ARRAY['JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4']) no longer
+-- scan raw_data. Rows loaded before this migration keep NULL until they are re-processed.
+
+ALTER TABLE transactions ADD COLUMN program_ids TEXT[];
+
+CREATE INDEX idx_transactions_program_ids ON transactions USING GIN (program_ids);
+
+COMMENT ON COLUMN transactions.program_ids IS 'Top-level program IDs, deduplicated, in instruction order';
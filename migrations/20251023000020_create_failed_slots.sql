@@ -0,0 +1,20 @@
+-- Create failed_slots table
+-- Dead-letter queue of slots that failed to extract or load after all retries.
+-- Re-attempted by the retry-failed command, which deletes a row once its slot is loaded.
+
+CREATE TABLE failed_slots (
+    slot BIGINT PRIMARY KEY,
+    
+    -- Failure details (of the latest failure)
+    stage VARCHAR(16) NOT NULL, -- Pipeline stage that failed: Extract or Load
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0, -- Attempts across all runs
+    
+    -- Processing metadata
+    first_failed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_failed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Add comments
+COMMENT ON TABLE failed_slots IS 'Slots that exhausted their retries, pending re-attempt by retry-failed';
+COMMENT ON COLUMN failed_slots.attempts IS 'Fetch or load attempts made for the slot, summed across runs';
@@ -0,0 +1,8 @@
+-- Add the on-chain transaction count to blocks
+-- Number of transactions in the block, including any the fetcher did not store
+-- (filtered out, votes skipped or failed to parse). Compared with the stored
+-- transactions by --skip-existing --verify-tx-count to spot partially loaded blocks.
+
+ALTER TABLE blocks ADD COLUMN transaction_count INTEGER;
+
+COMMENT ON COLUMN blocks.transaction_count IS 'Transactions in the block on chain (NULL for blocks loaded before this column existed)';
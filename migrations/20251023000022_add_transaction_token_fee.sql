@@ -0,0 +1,10 @@
+-- Add the token standard and Token-2022 transfer fee to transactions
+-- Transfers on a Token-2022 mint with the transfer-fee extension withhold part of the
+-- amount; the destination receives token_amount - token_transfer_fee.
+
+ALTER TABLE transactions
+    ADD COLUMN token_program VARCHAR(16),
+    ADD COLUMN token_transfer_fee NUMERIC(20, 0);
+
+COMMENT ON COLUMN transactions.token_program IS 'Token standard of that transfer: spl-token or spl-token-2022';
+COMMENT ON COLUMN transactions.token_transfer_fee IS 'Fee withheld from that transfer in token base units, NULL without a transfer-fee extension';
@@ -0,0 +1,82 @@
+-- Partition blocks and transactions by slot range
+-- Both tables become declaratively partitioned (RANGE on the slot), so old slot ranges can be
+-- vacuumed, reindexed, detached or dropped one partition at a time. Existing rows are copied
+-- into partitions of 10,000,000 slots; the fetcher creates the partitions of new slots on
+-- demand (Database::ensure_partition, sized by --partition-size).
+--
+-- PostgreSQL requires every unique constraint of a partitioned table to include the
+-- partition key, so:
+-- - blocks.blockhash is indexed but no longer unique
+-- - transactions are unique per (signature, block_slot) and keyed by (id, block_slot)
+-- - instructions and token_balances no longer reference transactions(signature) with a
+--   foreign key; they are written in the same database transaction as their transaction
+
+-- Foreign keys to the tables being replaced
+ALTER TABLE blocks DROP CONSTRAINT blocks_parent_slot_fkey;
+ALTER TABLE transactions DROP CONSTRAINT transactions_block_slot_fkey;
+ALTER TABLE instructions DROP CONSTRAINT instructions_transaction_signature_fkey;
+ALTER TABLE token_balances DROP CONSTRAINT token_balances_transaction_signature_fkey;
+ALTER TABLE accounts DROP CONSTRAINT accounts_first_seen_slot_fkey;
+ALTER TABLE accounts DROP CONSTRAINT accounts_last_seen_slot_fkey;
+ALTER TABLE block_rewards DROP CONSTRAINT block_rewards_block_slot_fkey;
+
+ALTER TABLE blocks RENAME TO blocks_unpartitioned;
+ALTER TABLE transactions RENAME TO transactions_unpartitioned;
+-- Keep the id sequence when the old table is dropped
+ALTER SEQUENCE transactions_id_seq OWNED BY NONE;
+
+CREATE TABLE blocks (LIKE blocks_unpartitioned INCLUDING DEFAULTS INCLUDING COMMENTS) PARTITION BY RANGE (slot);
+CREATE TABLE transactions (LIKE transactions_unpartitioned INCLUDING DEFAULTS INCLUDING COMMENTS)
+    PARTITION BY RANGE (block_slot);
+ALTER SEQUENCE transactions_id_seq OWNED BY transactions.id;
+
+-- One partition per 10,000,000-slot bucket holding data (transactions always have a block)
+DO $$
+DECLARE
+    partition_size CONSTANT BIGINT := 10000000;
+    bucket BIGINT;
+BEGIN
+    FOR bucket IN SELECT DISTINCT slot / partition_size * partition_size FROM blocks_unpartitioned LOOP
+        EXECUTE format('CREATE TABLE blocks_p%s PARTITION OF blocks FOR VALUES FROM (%s) TO (%s)',
+            bucket, bucket, bucket + partition_size);
+        EXECUTE format('CREATE TABLE transactions_p%s PARTITION OF transactions FOR VALUES FROM (%s) TO (%s)',
+            bucket, bucket, bucket + partition_size);
+    END LOOP;
+END $$;
+
+INSERT INTO blocks SELECT * FROM blocks_unpartitioned;
+INSERT INTO transactions SELECT * FROM transactions_unpartitioned;
+
+DROP TABLE transactions_unpartitioned;
+DROP TABLE blocks_unpartitioned;
+
+-- Constraints and indexes, created on every partition
+ALTER TABLE blocks ADD PRIMARY KEY (slot);
+ALTER TABLE blocks ADD FOREIGN KEY (parent_slot) REFERENCES blocks(slot) ON DELETE SET NULL;
+
+CREATE INDEX idx_blocks_blockhash ON blocks(blockhash);
+CREATE INDEX idx_blocks_block_time ON blocks(block_time);
+CREATE INDEX idx_blocks_block_height ON blocks(block_height);
+CREATE INDEX idx_blocks_parent_slot ON blocks(parent_slot);
+
+ALTER TABLE transactions ADD PRIMARY KEY (id, block_slot);
+ALTER TABLE transactions ADD UNIQUE (signature, block_slot);
+ALTER TABLE transactions ADD UNIQUE (block_slot, transaction_index);
+ALTER TABLE transactions ADD FOREIGN KEY (block_slot) REFERENCES blocks(slot) ON DELETE CASCADE;
+
+CREATE INDEX idx_transactions_block_slot ON transactions(block_slot);
+CREATE INDEX idx_transactions_signature ON transactions(signature);
+CREATE INDEX idx_transactions_type ON transactions(transaction_type);
+CREATE INDEX idx_transactions_signer ON transactions(signer);
+CREATE INDEX idx_transactions_success ON transactions(success);
+CREATE INDEX idx_transactions_raw_data ON transactions USING GIN(raw_data);
+CREATE INDEX idx_transactions_token_mint ON transactions(token_mint) WHERE token_mint IS NOT NULL;
+CREATE INDEX idx_transactions_program_ids ON transactions USING GIN(program_ids);
+
+-- Foreign keys to the new tables
+ALTER TABLE accounts ADD FOREIGN KEY (first_seen_slot) REFERENCES blocks(slot);
+ALTER TABLE accounts ADD FOREIGN KEY (last_seen_slot) REFERENCES blocks(slot);
+ALTER TABLE block_rewards ADD FOREIGN KEY (block_slot) REFERENCES blocks(slot) ON DELETE CASCADE;
+
+COMMENT ON TABLE blocks IS 'Stores Solana block metadata, range-partitioned by slot';
+COMMENT ON TABLE transactions IS 'Stores Solana transactions with classification, range-partitioned by block_slot';
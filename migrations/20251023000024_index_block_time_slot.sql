@@ -0,0 +1,8 @@
+-- Index blocks by (block_time, slot) for time-range queries
+-- Replaces the block_time index: the slot range of a time window
+-- (Database::blocks_between_times) is answered from the index alone, one partition at a time.
+
+DROP INDEX idx_blocks_block_time;
+CREATE INDEX idx_blocks_block_time_slot ON blocks(block_time, slot);
+
+COMMENT ON COLUMN blocks.block_time IS 'Estimated production time of the block (NULL when the node did not record one)';
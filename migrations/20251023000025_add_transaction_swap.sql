@@ -0,0 +1,16 @@
+-- Add Jupiter swap input and output to transactions
+-- Read from the route's SwapEvents, or inferred from the signer's balance changes when no
+-- event is available (e.g. truncated logs). Amounts are raw base units of their mint.
+
+ALTER TABLE transactions ADD COLUMN swap_input_mint VARCHAR(44);
+ALTER TABLE transactions ADD COLUMN swap_output_mint VARCHAR(44);
+ALTER TABLE transactions ADD COLUMN swap_in_amount NUMERIC(20, 0);
+ALTER TABLE transactions ADD COLUMN swap_out_amount NUMERIC(20, 0);
+
+CREATE INDEX idx_transactions_swap_mints ON transactions(swap_input_mint, swap_output_mint)
+    WHERE swap_input_mint IS NOT NULL;
+
+COMMENT ON COLUMN transactions.swap_input_mint IS 'Mint sold in a Jupiter swap (wrapped SOL for native SOL)';
+COMMENT ON COLUMN transactions.swap_output_mint IS 'Mint bought in a Jupiter swap';
+COMMENT ON COLUMN transactions.swap_in_amount IS 'Amount sold in input mint base units';
+COMMENT ON COLUMN transactions.swap_out_amount IS 'Amount bought in output mint base units';
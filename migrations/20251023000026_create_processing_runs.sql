@@ -0,0 +1,29 @@
+-- Create processing_runs table
+-- One audit row per pipeline run: inserted when the run starts, completed when it finishes.
+-- A row without finished_at is a run that is still going or was killed.
+
+CREATE TABLE processing_runs (
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Job and requested range
+    job_name VARCHAR(100) NOT NULL,
+    start_slot BIGINT NOT NULL,
+    end_slot BIGINT NOT NULL,
+
+    -- Timing
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP WITH TIME ZONE,
+
+    -- Outcome, set at completion
+    blocks_succeeded BIGINT,
+    blocks_failed BIGINT,
+    transactions_inserted BIGINT,
+    success_rate DOUBLE PRECISION
+);
+
+CREATE INDEX idx_processing_runs_started_at ON processing_runs(started_at);
+
+-- Add comments
+COMMENT ON TABLE processing_runs IS 'History of pipeline runs and their outcome';
+COMMENT ON COLUMN processing_runs.end_slot IS 'Configured end slot, or the last processed slot of a continuous run';
+COMMENT ON COLUMN processing_runs.success_rate IS 'Percentage of attempted blocks that were loaded';
@@ -0,0 +1,9 @@
+-- Add instruction counts to transactions
+-- num_instructions counts the top-level instructions of the message, num_inner_instructions
+-- the instructions they invoked through CPI. NULL for transactions loaded before these columns.
+
+ALTER TABLE transactions ADD COLUMN num_instructions INTEGER;
+ALTER TABLE transactions ADD COLUMN num_inner_instructions INTEGER;
+
+COMMENT ON COLUMN transactions.num_instructions IS 'Top-level instructions in the transaction message';
+COMMENT ON COLUMN transactions.num_inner_instructions IS 'Instructions invoked through CPI (meta.innerInstructions)';
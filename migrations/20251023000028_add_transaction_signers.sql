@@ -0,0 +1,10 @@
+-- Add every signer of a transaction, not only the fee payer
+-- The first numRequiredSignatures account keys of the message, fee payer first (signer keeps
+-- the fee payer alone). Indexed for co-signer queries such as
+-- WHERE signers @> ARRAY['<multisig member>']. NULL for transactions loaded before this column.
+
+ALTER TABLE transactions ADD COLUMN signers TEXT[];
+
+CREATE INDEX idx_transactions_signers ON transactions USING GIN (signers);
+
+COMMENT ON COLUMN transactions.signers IS 'Accounts that signed the transaction, fee payer first';
@@ -0,0 +1,8 @@
+-- Add the message version of transactions
+-- NULL for legacy transactions, 0 for v0 transactions (which can load accounts from Address
+-- Lookup Tables), e.g. SELECT version IS NOT NULL AS versioned, COUNT(*) ... GROUP BY 1 to track
+-- adoption. Also NULL for transactions loaded before this column.
+
+ALTER TABLE transactions ADD COLUMN version SMALLINT;
+
+COMMENT ON COLUMN transactions.version IS 'Message version: NULL for legacy, 0 for v0';
@@ -0,0 +1,9 @@
+-- Add the recent blockhash of transactions
+-- The blockhash the transaction was signed against (message.recentBlockhash), for staleness and
+-- blockhash reuse analysis: joined with blocks.blockhash it gives the slot the blockhash comes
+-- from, so block_slot minus that slot is how old the transaction was when it landed. NULL for
+-- transactions loaded before this column.
+
+ALTER TABLE transactions ADD COLUMN recent_blockhash TEXT;
+
+COMMENT ON COLUMN transactions.recent_blockhash IS 'Blockhash the transaction was signed against (message.recentBlockhash)';
@@ -0,0 +1,10 @@
+-- Add the wallets owning the token accounts of a token transfer
+-- token_transfer source and destination are token accounts; analysts usually want the wallets
+-- behind them. Owners come from the transaction's token balances, or from getAccountInfo with
+-- --resolve-owners. NULL when unknown, e.g. a destination closed before it could be looked up.
+
+ALTER TABLE transactions ADD COLUMN token_source_owner TEXT;
+ALTER TABLE transactions ADD COLUMN token_destination_owner TEXT;
+
+COMMENT ON COLUMN transactions.token_source_owner IS 'Wallet owning the source token account of the token transfer';
+COMMENT ON COLUMN transactions.token_destination_owner IS 'Wallet owning the destination token account of the token transfer';
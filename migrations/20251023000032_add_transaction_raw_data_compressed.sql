@@ -0,0 +1,12 @@
+-- Add zstd-compressed raw transaction JSON
+-- With --compress-raw the full transaction JSON is stored here instead of in raw_data, which then
+-- stays NULL. Much smaller than JSONB, but not queryable in SQL: read it through
+-- Database::load_raw_transaction, which decompresses it. Exactly one of the two columns is set
+-- for rows loaded with raw data.
+
+ALTER TABLE transactions ADD COLUMN raw_data_compressed BYTEA;
+
+-- zstd output does not compress further; skip PostgreSQL's own TOAST compression
+ALTER TABLE transactions ALTER COLUMN raw_data_compressed SET STORAGE EXTERNAL;
+
+COMMENT ON COLUMN transactions.raw_data_compressed IS 'zstd-compressed transaction JSON (--compress-raw), NULL when raw_data is set';
@@ -0,0 +1,39 @@
+-- Create metrics_timeseries table
+-- One row per committed batch with its throughput and transaction mix, for dashboards over the
+-- history of a deployment (e.g. a Grafana PostgreSQL data source) without a separate metrics store.
+
+CREATE TABLE metrics_timeseries (
+    id BIGSERIAL PRIMARY KEY,
+    recorded_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    -- Job and slots of the batch
+    job_name VARCHAR(100) NOT NULL,
+    slot_range_start BIGINT NOT NULL,
+    slot_range_end BIGINT NOT NULL,
+
+    -- Throughput
+    blocks_processed BIGINT NOT NULL,
+    txs_processed BIGINT NOT NULL,
+    txs_successful BIGINT NOT NULL,
+    txs_failed BIGINT NOT NULL,
+
+    -- Fees in lamports
+    total_fees BIGINT NOT NULL,
+    avg_fee DOUBLE PRECISION NOT NULL,
+    avg_priority_fee DOUBLE PRECISION NOT NULL,
+
+    -- Transaction mix
+    vote_count BIGINT NOT NULL,
+    sol_transfer_count BIGINT NOT NULL,
+    token_transfer_count BIGINT NOT NULL,
+    dex_swap_count BIGINT NOT NULL,
+    nft_operation_count BIGINT NOT NULL,
+    unknown_count BIGINT NOT NULL
+);
+
+CREATE INDEX idx_metrics_timeseries_recorded_at ON metrics_timeseries(recorded_at);
+
+-- Add comments
+COMMENT ON TABLE metrics_timeseries IS 'Throughput and transaction mix of each committed batch';
+COMMENT ON COLUMN metrics_timeseries.txs_processed IS 'Transactions extracted from the batch, including votes skipped by --skip-votes';
+COMMENT ON COLUMN metrics_timeseries.avg_fee IS 'Mean fee per transaction in lamports, 0 for a batch without transactions';
@@ -0,0 +1,12 @@
+-- Add the secondary types of a transaction
+-- transaction_type holds the highest-priority type; a composite transaction also matches others,
+-- e.g. a swap that creates the associated token account receiving its output. Stored with the
+-- same names as transaction_type, highest priority first, empty for a single-purpose transaction.
+-- Queried with e.g. WHERE secondary_types @> ARRAY['Account Creation']. NULL for transactions
+-- loaded before this column.
+
+ALTER TABLE transactions ADD COLUMN secondary_types TEXT[];
+
+CREATE INDEX idx_transactions_secondary_types ON transactions USING GIN (secondary_types);
+
+COMMENT ON COLUMN transactions.secondary_types IS 'Other transaction types the transaction matches, highest priority first';
@@ -0,0 +1,10 @@
+-- Add the liquid staking programs to the program registry
+-- Program type 'LiquidStaking' drives the Liquid Staking transaction classification (deposit,
+-- withdraw, claim). Entries seeded with the old 'Staking' type move to it.
+
+UPDATE program_registry SET program_type = 'LiquidStaking' WHERE program_type = 'Staking';
+
+INSERT INTO program_registry (program_id, program_name, program_type, description) VALUES
+    ('MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD', 'Marinade Finance', 'LiquidStaking', 'Marinade liquid staking (mSOL)'),
+    ('SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy', 'SPL Stake Pool', 'LiquidStaking', 'Stake pool program behind JitoSOL, bSOL and other LSTs')
+ON CONFLICT (program_id) DO NOTHING;
@@ -0,0 +1,9 @@
+-- Add the reason a failed transaction failed
+-- Rendered from meta.err: an instruction error names the failing top-level instruction and, for
+-- a program-defined error, the custom code in decimal and hex, e.g.
+-- 'Instruction 3 failed: custom program error 6001 (0x1771)'. NULL for successful transactions
+-- and for transactions loaded before this column.
+
+ALTER TABLE transactions ADD COLUMN error_reason TEXT;
+
+COMMENT ON COLUMN transactions.error_reason IS 'Human-readable meta.err of a failed transaction, NULL on success';
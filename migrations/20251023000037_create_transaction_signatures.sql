@@ -0,0 +1,28 @@
+-- Create transaction_signatures table
+-- Signature index written by `--signatures-only` runs: blocks are fetched with
+-- transactionDetails = signatures, so only each transaction's signature and position in its
+-- block are known (no status, fee, accounts or raw JSON, and no classification)
+
+CREATE TABLE transaction_signatures (
+    -- Primary identifier
+    id BIGSERIAL PRIMARY KEY,
+    
+    -- Block relationship
+    block_slot BIGINT NOT NULL REFERENCES blocks(slot) ON DELETE CASCADE,
+    
+    -- Transaction identity
+    signature VARCHAR(88) NOT NULL,
+    transaction_index INTEGER NOT NULL, -- Position of the transaction in its block
+    
+    -- Processing metadata
+    processed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    
+    UNIQUE(signature, block_slot)
+);
+
+-- Indexes for common queries
+CREATE INDEX idx_transaction_signatures_block_slot ON transaction_signatures(block_slot);
+
+-- Add comments
+COMMENT ON TABLE transaction_signatures IS 'Transaction signatures per block, loaded with --signatures-only';
+COMMENT ON COLUMN transaction_signatures.transaction_index IS 'Position of the transaction in its block (order of the block''s signatures)';
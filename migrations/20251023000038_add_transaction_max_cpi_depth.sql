@@ -0,0 +1,12 @@
+-- Add the deepest cross-program invocation of each transaction
+-- Taken from the stackHeight of meta.innerInstructions: 1 for a program invoked by a top-level
+-- instruction, 2 for a program that one invokes, and so on; 0 when nothing was invoked through
+-- CPI. NULL when the RPC reported no stack heights (older blocks) and for transactions loaded
+-- before this column.
+
+ALTER TABLE transactions ADD COLUMN max_cpi_depth SMALLINT;
+
+-- For queries such as WHERE max_cpi_depth > 3 (complex composability)
+CREATE INDEX idx_transactions_max_cpi_depth ON transactions(max_cpi_depth);
+
+COMMENT ON COLUMN transactions.max_cpi_depth IS 'Deepest CPI nesting from meta.innerInstructions stackHeight, 0 without CPI';
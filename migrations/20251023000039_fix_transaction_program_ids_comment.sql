@@ -0,0 +1,6 @@
+-- Correct the comment of transactions.program_ids
+-- The column has held the programs invoked through CPI (meta.innerInstructions) since they were
+-- merged into program_ids, not only the top-level ones: top-level programs come first in
+-- instruction order, followed by CPI programs not already listed.
+
+COMMENT ON COLUMN transactions.program_ids IS 'Top-level and CPI program IDs, deduplicated, top-level first in instruction order';
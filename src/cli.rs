@@ -1,7 +1,12 @@
+use block_data_fetcher::etl::load::ConflictMode;
 /// CLI Module
 ///
 /// Command-line interface configuration using clap.
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use solana_commitment_config::CommitmentConfig;
+use std::path::PathBuf;
 
 /// Solana Block Data Fetcher - ETL Pipeline
 ///
@@ -10,30 +15,134 @@ use clap::Parser;
 #[command(name = "block-data-fetcher")]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
-    /// Starting slot number (defaults to latest - 30)
-    #[arg(short = 's', long, value_name = "SLOT")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// TOML file with default values for the options below (flags given here take precedence)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Starting slot number (defaults to 10 slots before the end slot's default)
+    #[arg(short = 's', long, value_name = "SLOT", conflicts_with = "start_time")]
     pub start_slot: Option<u64>,
 
-    /// Ending slot number (defaults to latest - 20)
+    /// Ending slot number (defaults to latest - finality offset)
     #[arg(short = 'e', long, value_name = "SLOT")]
     pub end_slot: Option<u64>,
 
     /// Number of blocks to fetch (alternative to specifying end_slot)
-    #[arg(short = 'n', long, value_name = "COUNT", conflicts_with = "end_slot")]
+    #[arg(short = 'n', long, value_name = "COUNT", conflicts_with_all = ["end_slot", "end_time"])]
     pub num_blocks: Option<u64>,
 
+    /// Start at the first slot produced at or after this time (RFC 3339, e.g. 2025-10-23T12:00:00Z)
+    #[arg(long, value_name = "TIME")]
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End at the first slot produced at or after this time (RFC 3339)
+    #[arg(long, value_name = "TIME", conflicts_with = "end_slot")]
+    pub end_time: Option<DateTime<Utc>>,
+
     /// RPC endpoint URL (overrides HELIUS_RPC_URL env var)
     #[arg(short = 'r', long, value_name = "URL")]
     pub rpc_url: Option<String>,
 
+    /// Additional comma-separated RPC endpoint URLs to fail over to
+    #[arg(long, value_name = "URLS", value_delimiter = ',')]
+    pub rpc_urls: Vec<String>,
+
+    /// Commitment level for block fetching (non-finalized blocks can still be rolled back by a fork)
+    #[arg(long, value_enum, default_value = "finalized")]
+    pub commitment: Commitment,
+
+    /// Slots to stay behind the chain tip, so only settled blocks are read: the default range
+    /// ends this far back and continuous mode follows the tip at this distance (a smaller
+    /// offset suits `--commitment confirmed`)
+    #[arg(long, value_name = "SLOTS", default_value = "20")]
+    pub finality_offset: u64,
+
     /// Database connection URL (overrides DATABASE_URL env var)
     #[arg(short = 'd', long, value_name = "URL")]
     pub database_url: Option<String>,
 
-    /// Batch size for processing blocks
+    /// Maximum number of pooled database connections
+    #[arg(long, value_name = "COUNT", default_value_t = block_data_fetcher::db::DEFAULT_MAX_CONNECTIONS)]
+    pub db_max_connections: u32,
+
+    /// Seconds a query waits for a free pooled database connection before failing
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = block_data_fetcher::db::DEFAULT_ACQUIRE_TIMEOUT.as_secs()
+    )]
+    pub db_acquire_timeout: u64,
+
+    /// Slots per partition created for new blocks and transactions
+    #[arg(long, value_name = "SLOTS", default_value_t = block_data_fetcher::db::DEFAULT_PARTITION_SIZE)]
+    pub partition_size: u64,
+
+    /// Only load transactions involving this address (wallet or program) instead of whole blocks
+    #[arg(long, value_name = "PUBKEY", conflicts_with_all = ["continuous", "resume"])]
+    pub address: Option<String>,
+
+    /// Maximum transactions to load in `--address` mode (newest first)
+    #[arg(long, value_name = "COUNT", default_value = "1000")]
+    pub address_limit: usize,
+
+    /// Batch size for processing blocks (the starting size with --adaptive-batching)
     #[arg(short = 'b', long, value_name = "SIZE", default_value = "10")]
     pub batch_size: usize,
 
+    /// Grow or shrink the batch size after every load toward --target-commit-secs
+    #[arg(long)]
+    pub adaptive_batching: bool,
+
+    /// Load duration in seconds that --adaptive-batching steers toward
+    #[arg(long, value_name = "SECONDS", default_value = "2", requires = "adaptive_batching")]
+    pub target_commit_secs: f64,
+
+    /// Smallest batch size --adaptive-batching may choose
+    #[arg(long, value_name = "SIZE", default_value = "1", requires = "adaptive_batching")]
+    pub min_batch_size: usize,
+
+    /// Largest batch size --adaptive-batching may choose
+    #[arg(long, value_name = "SIZE", default_value = "500", requires = "adaptive_batching")]
+    pub max_batch_size: usize,
+
+    /// Number of blocks to fetch in parallel (1 = sequential, strictly ordered)
+    #[arg(long, value_name = "COUNT", default_value = "1")]
+    pub concurrency: usize,
+
+    /// Blocks per JSON-RPC batch request (1 = one getBlock request per block); needs a provider that accepts batches
+    #[arg(long, value_name = "COUNT", default_value = "1")]
+    pub rpc_batch_size: usize,
+
+    /// Cap on RPC requests per second across all workers (each call of a batch counts); replaces the fixed delay
+    /// between requests
+    #[arg(long, value_name = "N")]
+    pub rps: Option<u32>,
+
+    /// Timeout of an RPC HTTP request in seconds, including reading the response (raise it for large blocks
+    /// over slow links)
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = block_data_fetcher::rpc::transport::DEFAULT_HTTP_TIMEOUT.as_secs()
+    )]
+    pub http_timeout: u64,
+
+    /// Idle HTTP connections kept open per RPC host for reuse (at least --concurrency avoids new handshakes)
+    #[arg(long, value_name = "COUNT", default_value_t = block_data_fetcher::rpc::transport::DEFAULT_HTTP_POOL_SIZE)]
+    pub http_pool_size: usize,
+
+    /// Directory caching fetched finalized blocks (zstd-compressed JSON per slot); cached slots are read from
+    /// disk instead of the RPC (default: BLOCK_CACHE_DIR)
+    #[arg(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Neither read nor write the block cache, even when --cache-dir or BLOCK_CACHE_DIR is set
+    #[arg(long)]
+    pub no_cache: bool,
+
     /// Maximum number of retry attempts
     #[arg(long, value_name = "COUNT", default_value = "3")]
     pub max_retries: usize,
@@ -42,6 +151,139 @@ pub struct Cli {
     #[arg(long, value_name = "SECONDS", default_value = "2")]
     pub retry_delay: u64,
 
+    /// Also export extracted transactions to files in this format
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub export: Option<ExportFormat>,
+
+    /// Directory for exported Parquet files (one file per batch)
+    #[arg(long, value_name = "DIR", default_value = "./out")]
+    pub output_dir: PathBuf,
+
+    /// CSV file that exported transactions are appended to (required for `--export csv`)
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Console output: decorated progress and statistics, or one JSON record per transaction on stdout
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "pretty")]
+    pub format: OutputFormat,
+
+    /// Suppress the decorated console output; progress and statistics are logged as `tracing` events instead
+    #[arg(short = 'q', long)]
+    pub quiet: bool,
+
+    /// Show extraction progress as a single updating bar with rate and ETA (stdout on a terminal);
+    /// when stdout is piped, progress is logged as `tracing` events instead
+    #[arg(long)]
+    pub progress_bar: bool,
+
+    /// Log format: human-readable lines, or one JSON object per event (implies --quiet)
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "pretty")]
+    pub log_format: LogFormat,
+
+    /// Store raw transaction JSON zstd-compressed in raw_data_compressed (BYTEA) instead of the raw_data JSONB
+    /// column; smaller, but raw_data can no longer be queried in SQL
+    #[arg(long)]
+    pub compress_raw: bool,
+
+    /// Do not store the raw transaction JSON (raw_data and raw_data_compressed stay NULL); transactions
+    /// are still parsed and classified, only the parsed columns are kept
+    #[arg(long, conflicts_with = "compress_raw")]
+    pub no_raw_json: bool,
+
+    /// Load transactions with binary COPY and a single merge per batch (faster for large backfills)
+    #[arg(long)]
+    pub bulk_load: bool,
+
+    /// Commit loads every N transactions instead of once per batch, so huge blocks do not become
+    /// one giant database transaction; a failed batch may then be partly stored until retried
+    #[arg(long, value_name = "TRANSACTIONS", conflicts_with = "bulk_load")]
+    pub commit_every: Option<usize>,
+
+    /// Fetch blocks with transaction signatures only and store them in transaction_signatures, without
+    /// status, fee, accounts or classification (a fraction of the bandwidth, for a signature index)
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "address", "subscribe", "bulk_load", "export", "only_success", "only_failed", "programs", "verify_tx_count"
+        ]
+    )]
+    pub signatures_only: bool,
+
+    /// What loads do with blocks and transactions that are already stored: overwrite them
+    /// (update), leave them untouched (skip), or fail the batch (error, to catch duplicates)
+    #[arg(long, value_enum, value_name = "MODE", default_value = "update")]
+    pub on_conflict: OnConflict,
+
+    /// Extract and classify without writing anything to the database (no loads, no checkpoints)
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Drop validator vote transactions instead of storing them (they are still counted in the stats)
+    #[arg(long)]
+    pub skip_votes: bool,
+
+    /// Keep only successful transactions (failed ones are still counted in the stats)
+    #[arg(long)]
+    pub only_success: bool,
+
+    /// Keep only failed transactions (successful ones are still counted in the stats)
+    #[arg(long)]
+    pub only_failed: bool,
+
+    /// Keep each transaction with this probability (0.0-1.0); blocks are still fetched in full and every
+    /// transaction is counted in the stats
+    #[arg(long, value_name = "RATE")]
+    pub sample_rate: Option<f64>,
+
+    /// Seed of --sample-rate: the same seed keeps the same transactions on every run
+    #[arg(long, value_name = "SEED", default_value = "0", requires = "sample_rate")]
+    pub sample_seed: u64,
+
+    /// Keep only transactions invoking one of these comma-separated program IDs (top-level or CPI)
+    #[arg(long, value_name = "IDS", value_delimiter = ',')]
+    pub programs: Vec<String>,
+
+    /// Look up the wallets owning token transfer accounts over RPC when the token balances do not name
+    /// them (one getAccountInfo per unknown account, cached)
+    #[arg(long)]
+    pub resolve_owners: bool,
+
+    /// Skip blocks with more than this many transactions, logging a warning (default: unlimited)
+    #[arg(long, value_name = "N")]
+    pub max_txs_per_block: Option<usize>,
+
+    /// With --max-txs-per-block, keep the first N transactions of a larger block instead of skipping it
+    #[arg(long, requires = "max_txs_per_block")]
+    pub truncate_large_blocks: bool,
+
+    /// Do not re-fetch blocks that are already stored in the database
+    #[arg(long)]
+    pub skip_existing: bool,
+
+    /// With --skip-existing, only skip blocks whose stored transactions match the block's on-chain count
+    #[arg(long, requires = "skip_existing", conflicts_with_all = ["skip_votes", "only_success", "only_failed", "sample_rate", "programs"])]
+    pub verify_tx_count: bool,
+
+    /// Reload the program registry from the database every N batches (0 = never)
+    #[arg(long, value_name = "BATCHES", default_value = "0")]
+    pub registry_reload_batches: usize,
+
+    /// Serve Prometheus metrics on this port at /metrics
+    #[arg(long, value_name = "PORT")]
+    pub metrics_port: Option<u16>,
+
+    /// Write a JSON summary of the run (statistics, errors and configuration) to this file
+    #[arg(long, value_name = "PATH")]
+    pub stats_out: Option<PathBuf>,
+
+    /// Job name used to store and resume checkpoints
+    #[arg(long, value_name = "NAME", default_value = "default")]
+    pub job_name: String,
+
+    /// Resume from the last committed checkpoint of the job instead of start_slot
+    #[arg(long)]
+    pub resume: bool,
+
     /// Fetch blocks continuously (keep processing latest blocks)
     #[arg(short = 'c', long)]
     pub continuous: bool,
@@ -49,6 +291,158 @@ pub struct Cli {
     /// Interval between continuous fetches in seconds
     #[arg(long, value_name = "SECONDS", default_value = "10")]
     pub interval: u64,
+
+    /// In continuous mode, receive new blocks over a WebSocket block subscription instead of polling
+    #[arg(long, requires = "continuous")]
+    pub subscribe: bool,
+
+    /// WebSocket URL for --subscribe (default: derived from the RPC URL)
+    #[arg(long, value_name = "URL", requires = "subscribe")]
+    pub ws_url: Option<String>,
+
+    /// In continuous mode below finalized commitment, slots behind the last processed one to
+    /// re-check for forks on every pass
+    #[arg(long, value_name = "SLOTS", default_value = "32", requires = "continuous")]
+    pub reorg_overlap: u64,
+
+    /// In continuous mode, recently loaded slots remembered so settled slots are not re-fetched
+    /// (0 re-checks the whole overlap on every pass)
+    #[arg(long, value_name = "SLOTS", default_value = "10000", requires = "continuous")]
+    pub dedup_cache_size: usize,
+}
+
+/// Maintenance commands; without one the ETL pipeline runs
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Insert the built-in programs into the program registry (safe to re-run)
+    SeedRegistry,
+
+    /// Fetch only the slots in a range that are missing from the blocks table
+    Backfill {
+        /// First slot of the range
+        #[arg(long, value_name = "SLOT")]
+        start: u64,
+
+        /// Last slot of the range
+        #[arg(long, value_name = "SLOT")]
+        end: u64,
+    },
+
+    /// Re-attempt the slots recorded in the failed_slots table, removing them once loaded
+    RetryFailed,
+
+    /// Compare stored blocks in a range with the chain, exiting non-zero on a mismatch
+    Validate {
+        /// First slot of the range
+        #[arg(long, value_name = "SLOT")]
+        start: u64,
+
+        /// Last slot of the range
+        #[arg(long, value_name = "SLOT")]
+        end: u64,
+
+        /// Only check this many stored blocks, spread evenly over the range (default: all)
+        #[arg(long, value_name = "COUNT")]
+        sample: Option<usize>,
+    },
+
+    /// Classify the stored transactions of a slot range again from their raw_data, updating
+    /// their type and label in place (no RPC; e.g. after a classifier or registry change)
+    Reclassify {
+        /// First slot of the range
+        #[arg(long, value_name = "SLOT")]
+        start: u64,
+
+        /// Last slot of the range
+        #[arg(long, value_name = "SLOT")]
+        end: u64,
+    },
+
+    /// Re-fetch one transaction by signature, classify it and print its details (nothing is
+    /// stored)
+    Inspect {
+        /// Signature of the transaction (base58)
+        #[arg(value_name = "SIGNATURE")]
+        signature: String,
+    },
+
+    /// Print what the database holds: block and transaction totals, the stored slot range and
+    /// its gaps, and the transaction count per type
+    Status,
+
+    /// Check RPC and database connectivity and that the latest slot advances, without running
+    /// the ETL; exits non-zero with a diagnostic when unhealthy
+    Healthcheck {
+        /// File keeping the latest slot between checks
+        #[arg(long, value_name = "PATH", default_value = "/tmp/block-data-fetcher-health")]
+        state_file: PathBuf,
+
+        /// Seconds the latest slot may stay unchanged before the check fails
+        #[arg(long, value_name = "SECONDS", default_value = "60")]
+        max_stall: u64,
+    },
+}
+
+/// Commitment level accepted on the command line
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+/// File format for `--export`
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+}
+
+/// Handling of already stored rows for `--on-conflict`
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    Update,
+    Skip,
+    Error,
+}
+
+/// Console output format for `--format`
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Pretty,
+    Ndjson,
+}
+
+/// Log output format for `--log-format`
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl From<Commitment> for CommitmentConfig {
+    fn from(commitment: Commitment) -> Self {
+        match commitment {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+impl From<OnConflict> for ConflictMode {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Update => ConflictMode::Update,
+            OnConflict::Skip => ConflictMode::Skip,
+            OnConflict::Error => ConflictMode::Error,
+        }
+    }
 }
 
 impl Cli {
@@ -60,17 +454,160 @@ impl Cli {
             }
         }
 
+        // Options from a config file bypass clap's conflict checks
+        if self.start_slot.is_some() && self.start_time.is_some() {
+            anyhow::bail!("--start-slot and --start-time cannot be used together");
+        }
+        if self.end_slot.is_some() && self.end_time.is_some() {
+            anyhow::bail!("--end-slot and --end-time cannot be used together");
+        }
+        if self.num_blocks.is_some() && (self.end_slot.is_some() || self.end_time.is_some()) {
+            anyhow::bail!("--num-blocks cannot be used with --end-slot or --end-time");
+        }
+        if self.address.is_some() && (self.continuous || self.resume) {
+            anyhow::bail!("--address cannot be used with --continuous or --resume");
+        }
+        if self.commit_every.is_some() && self.bulk_load {
+            anyhow::bail!("--commit-every cannot be used with --bulk-load");
+        }
+        if self.no_raw_json && self.compress_raw {
+            anyhow::bail!("--no-raw-json cannot be used with --compress-raw");
+        }
+        if self.commit_every == Some(0) {
+            anyhow::bail!("--commit-every must be greater than 0");
+        }
+        if self.signatures_only {
+            let transaction_details_needed = self.address.is_some() ||
+                self.subscribe ||
+                self.bulk_load ||
+                self.export.is_some() ||
+                self.format == OutputFormat::Ndjson ||
+                self.only_success ||
+                self.only_failed ||
+                !self.programs.is_empty() ||
+                self.verify_tx_count;
+            if transaction_details_needed {
+                anyhow::bail!(
+                    "--signatures-only cannot be used with --address, --subscribe, --bulk-load, --export, \
+                     --format ndjson or the transaction filters (--only-success, --only-failed, --programs, \
+                     --verify-tx-count)"
+                );
+            }
+        }
+
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start > end {
+                anyhow::bail!("Start time ({}) must not be after end time ({})", start, end);
+            }
+        }
+
+        if let Some(Command::Backfill { start, end }) = self.command {
+            if start > end {
+                anyhow::bail!("Backfill start ({}) must be less than or equal to end ({})", start, end);
+            }
+        }
+
+        if let Some(Command::Reclassify { start, end }) = self.command {
+            if start > end {
+                anyhow::bail!("Reclassify start ({}) must be less than or equal to end ({})", start, end);
+            }
+        }
+
+        if let Some(Command::Validate { start, end, sample }) = self.command {
+            if start > end {
+                anyhow::bail!("Validate start ({}) must be less than or equal to end ({})", start, end);
+            }
+            if sample == Some(0) {
+                anyhow::bail!("Validate sample must be greater than 0");
+            }
+        }
+
+        if self.only_success && self.only_failed {
+            anyhow::bail!("--only-success and --only-failed cannot be used together");
+        }
+
+        if let Some(rate) = self.sample_rate {
+            if !(0.0..=1.0).contains(&rate) {
+                anyhow::bail!("Sample rate must be between 0.0 and 1.0, got {}", rate);
+            }
+        }
+
+        if self.max_txs_per_block == Some(0) {
+            anyhow::bail!("Max transactions per block must be greater than 0");
+        }
+
         if self.batch_size == 0 {
             anyhow::bail!("Batch size must be greater than 0");
         }
 
+        if self.adaptive_batching {
+            if !self.target_commit_secs.is_finite() || self.target_commit_secs <= 0.0 {
+                anyhow::bail!("Target commit time must be greater than 0, got {}", self.target_commit_secs);
+            }
+            if self.min_batch_size == 0 || self.min_batch_size > self.max_batch_size {
+                anyhow::bail!(
+                    "Batch size bounds must satisfy 0 < min ({}) <= max ({})",
+                    self.min_batch_size,
+                    self.max_batch_size
+                );
+            }
+        }
+
+        if self.concurrency == 0 {
+            anyhow::bail!("Concurrency must be greater than 0");
+        }
+
+        if self.rpc_batch_size == 0 {
+            anyhow::bail!("RPC batch size must be greater than 0");
+        }
+
+        if self.rps == Some(0) {
+            anyhow::bail!("Requests per second must be greater than 0");
+        }
+
+        if self.http_timeout == 0 {
+            anyhow::bail!("HTTP timeout must be greater than 0");
+        }
+
+        if self.db_max_connections == 0 {
+            anyhow::bail!("Database max connections must be greater than 0");
+        }
+
+        if self.db_acquire_timeout == 0 {
+            anyhow::bail!("Database acquire timeout must be greater than 0");
+        }
+
+        if self.partition_size == 0 {
+            anyhow::bail!("Partition size must be greater than 0");
+        }
+
         if self.max_retries == 0 {
             anyhow::bail!("Max retries must be greater than 0");
         }
 
+        for program_id in &self.programs {
+            program_id
+                .parse::<solana_sdk::pubkey::Pubkey>()
+                .map_err(|_| anyhow::anyhow!("Invalid program ID in --programs: {}", program_id))?;
+        }
+
+        if self.export == Some(ExportFormat::Csv) && self.output.is_none() {
+            anyhow::bail!("--export csv requires --output <PATH>");
+        }
+
         Ok(())
     }
 
+    /// End slot when none is given: the latest slot minus the finality offset
+    pub fn default_end_slot(&self, latest_slot: u64) -> u64 {
+        latest_slot.saturating_sub(self.finality_offset)
+    }
+
+    /// Start slot when none is given: 10 slots before the default end slot
+    pub fn default_start_slot(&self, latest_slot: u64) -> u64 {
+        self.default_end_slot(latest_slot).saturating_sub(10)
+    }
+
     /// Calculate end slot based on start slot and num_blocks
     pub fn calculate_end_slot(&self, start_slot: u64) -> u64 {
         if let Some(num) = self.num_blocks {
@@ -90,36 +627,171 @@ mod tests {
     #[test]
     fn test_calculate_end_slot() {
         let cli = Cli {
+            command: None,
+            config: None,
             start_slot: Some(1000),
             end_slot: None,
             num_blocks: Some(5),
+            start_time: None,
+            end_time: None,
             rpc_url: None,
+            rpc_urls: vec![],
+            commitment: Commitment::Finalized,
+            finality_offset: 20,
             database_url: None,
+            db_max_connections: 5,
+            db_acquire_timeout: 30,
+            partition_size: 10_000_000,
+            address: None,
+            address_limit: 1000,
             batch_size: 10,
+            adaptive_batching: false,
+            target_commit_secs: 2.0,
+            min_batch_size: 1,
+            max_batch_size: 500,
+            concurrency: 1,
+            rpc_batch_size: 1,
+            rps: None,
+            http_timeout: 30,
+            http_pool_size: 64,
+            cache_dir: None,
+            no_cache: false,
             max_retries: 3,
             retry_delay: 2,
+            export: None,
+            output_dir: PathBuf::from("./out"),
+            output: None,
+            format: OutputFormat::Pretty,
+            quiet: false,
+            progress_bar: false,
+            log_format: LogFormat::Pretty,
+            compress_raw: false,
+            no_raw_json: false,
+            bulk_load: false,
+            commit_every: None,
+            signatures_only: false,
+            on_conflict: OnConflict::Update,
+            metrics_port: None,
+            skip_votes: false,
+            only_success: false,
+            only_failed: false,
+            sample_rate: None,
+            sample_seed: 0,
+            programs: vec![],
+            resolve_owners: false,
+            max_txs_per_block: None,
+            truncate_large_blocks: false,
+            skip_existing: false,
+            verify_tx_count: false,
+            dry_run: false,
+            registry_reload_batches: 0,
+            stats_out: None,
+            job_name: "default".to_string(),
+            resume: false,
             continuous: false,
             interval: 10,
+            subscribe: false,
+            ws_url: None,
+            reorg_overlap: 32,
+            dedup_cache_size: 10_000,
         };
 
         assert_eq!(cli.calculate_end_slot(1000), 1004);
+
+        assert_eq!((cli.default_start_slot(1000), cli.default_end_slot(1000)), (970, 980));
+        let cli = Cli { finality_offset: 2, ..cli };
+        assert_eq!((cli.default_start_slot(1000), cli.default_end_slot(1000)), (988, 998));
+        assert_eq!((cli.default_start_slot(5), cli.default_end_slot(5)), (0, 3));
     }
 
     #[test]
     fn test_validation() {
         let cli = Cli {
+            command: None,
+            config: None,
             start_slot: Some(1000),
             end_slot: Some(900),
             num_blocks: None,
+            start_time: None,
+            end_time: None,
             rpc_url: None,
+            rpc_urls: vec![],
+            commitment: Commitment::Finalized,
+            finality_offset: 20,
             database_url: None,
+            db_max_connections: 5,
+            db_acquire_timeout: 30,
+            partition_size: 10_000_000,
+            address: None,
+            address_limit: 1000,
             batch_size: 10,
+            adaptive_batching: false,
+            target_commit_secs: 2.0,
+            min_batch_size: 1,
+            max_batch_size: 500,
+            concurrency: 1,
+            rpc_batch_size: 1,
+            rps: None,
+            http_timeout: 30,
+            http_pool_size: 64,
+            cache_dir: None,
+            no_cache: false,
             max_retries: 3,
             retry_delay: 2,
+            export: None,
+            output_dir: PathBuf::from("./out"),
+            output: None,
+            format: OutputFormat::Pretty,
+            quiet: false,
+            progress_bar: false,
+            log_format: LogFormat::Pretty,
+            compress_raw: false,
+            no_raw_json: false,
+            bulk_load: false,
+            commit_every: None,
+            signatures_only: false,
+            on_conflict: OnConflict::Update,
+            metrics_port: None,
+            skip_votes: false,
+            only_success: false,
+            only_failed: false,
+            sample_rate: None,
+            sample_seed: 0,
+            programs: vec![],
+            resolve_owners: false,
+            max_txs_per_block: None,
+            truncate_large_blocks: false,
+            skip_existing: false,
+            verify_tx_count: false,
+            dry_run: false,
+            registry_reload_batches: 0,
+            stats_out: None,
+            job_name: "default".to_string(),
+            resume: false,
             continuous: false,
             interval: 10,
+            subscribe: false,
+            ws_url: None,
+            reorg_overlap: 32,
+            dedup_cache_size: 10_000,
         };
 
         assert!(cli.validate().is_err());
+
+        let cli = Cli { end_slot: Some(1100), only_success: true, only_failed: true, ..cli };
+        assert!(cli.validate().is_err());
+
+        let cli = Cli { only_failed: false, sample_rate: Some(1.5), ..cli };
+        assert!(cli.validate().is_err());
+
+        let cli = Cli { sample_rate: None, adaptive_batching: true, min_batch_size: 50, max_batch_size: 20, ..cli };
+        assert!(cli.validate().is_err());
+
+        let cli = Cli { adaptive_batching: false, commit_every: Some(0), ..cli };
+        assert!(cli.validate().is_err());
+
+        // Options from a config file bypass clap's conflict checks
+        let cli = Cli { commit_every: None, signatures_only: true, only_success: true, ..cli };
+        assert!(cli.validate().is_err());
     }
 }
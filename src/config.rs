@@ -0,0 +1,240 @@
+/// Config Module
+///
+/// Options read from a TOML file (`--config`). The file takes the same options as the command
+/// line, named after the long flags with underscores (e.g. `rpc_url`, `batch_size`). Values are
+/// layered from highest to lowest precedence: flags given on the command line, the config file,
+/// environment variables (HELIUS_RPC_URL, DATABASE_URL, BLOCK_CACHE_DIR), then the defaults.
+/// Subcommands and their arguments are only taken from the command line.
+use crate::cli::{Cli, Commitment, ExportFormat, LogFormat, OnConflict, OutputFormat};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Contents of a config file; every option is optional
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub start_slot: Option<u64>,
+    pub end_slot: Option<u64>,
+    pub num_blocks: Option<u64>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub rpc_url: Option<String>,
+    pub rpc_urls: Option<Vec<String>>,
+    pub commitment: Option<Commitment>,
+    pub finality_offset: Option<u64>,
+    pub database_url: Option<String>,
+    pub db_max_connections: Option<u32>,
+    pub db_acquire_timeout: Option<u64>,
+    pub partition_size: Option<u64>,
+    pub address: Option<String>,
+    pub address_limit: Option<usize>,
+    pub batch_size: Option<usize>,
+    pub adaptive_batching: Option<bool>,
+    pub target_commit_secs: Option<f64>,
+    pub min_batch_size: Option<usize>,
+    pub max_batch_size: Option<usize>,
+    pub concurrency: Option<usize>,
+    pub rpc_batch_size: Option<usize>,
+    pub rps: Option<u32>,
+    pub http_timeout: Option<u64>,
+    pub http_pool_size: Option<usize>,
+    pub cache_dir: Option<PathBuf>,
+    pub no_cache: Option<bool>,
+    pub max_retries: Option<usize>,
+    pub retry_delay: Option<u64>,
+    pub export: Option<ExportFormat>,
+    pub output_dir: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub format: Option<OutputFormat>,
+    pub quiet: Option<bool>,
+    pub progress_bar: Option<bool>,
+    pub log_format: Option<LogFormat>,
+    pub compress_raw: Option<bool>,
+    pub no_raw_json: Option<bool>,
+    pub commit_every: Option<usize>,
+    pub bulk_load: Option<bool>,
+    pub signatures_only: Option<bool>,
+    pub on_conflict: Option<OnConflict>,
+    pub dry_run: Option<bool>,
+    pub skip_votes: Option<bool>,
+    pub only_success: Option<bool>,
+    pub only_failed: Option<bool>,
+    pub sample_rate: Option<f64>,
+    pub sample_seed: Option<u64>,
+    pub programs: Option<Vec<String>>,
+    pub resolve_owners: Option<bool>,
+    pub max_txs_per_block: Option<usize>,
+    pub truncate_large_blocks: Option<bool>,
+    pub skip_existing: Option<bool>,
+    pub verify_tx_count: Option<bool>,
+    pub registry_reload_batches: Option<usize>,
+    pub metrics_port: Option<u16>,
+    pub stats_out: Option<PathBuf>,
+    pub job_name: Option<String>,
+    pub resume: Option<bool>,
+    pub continuous: Option<bool>,
+    pub interval: Option<u64>,
+    pub subscribe: Option<bool>,
+    pub ws_url: Option<String>,
+    pub reorg_overlap: Option<u64>,
+    pub dedup_cache_size: Option<usize>,
+}
+
+impl ConfigFile {
+    /// Read and parse a config file; unknown keys are rejected so a typo does not go unnoticed
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).context(format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).context(format!("Failed to parse config file {}", path.display()))
+    }
+}
+
+/// Whether the option with this argument ID was given on the command line (not defaulted)
+fn on_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+impl Cli {
+    /// Fill in the options that were not given on the command line from a config file
+    ///
+    /// `matches` are the parsed arguments `self` was built from; they tell flags that were
+    /// passed apart from clap defaults. Environment variables are only read later, when an
+    /// option is still unset, so the file also takes precedence over them.
+    pub fn merge_config(&mut self, config: ConfigFile, matches: &ArgMatches) {
+        macro_rules! merge {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if let Some(value) = config.$field {
+                        if !on_command_line(matches, stringify!($field)) {
+                            self.$field = value.into();
+                        }
+                    }
+                )*
+            };
+        }
+
+        merge!(
+            start_slot,
+            end_slot,
+            num_blocks,
+            start_time,
+            end_time,
+            rpc_url,
+            rpc_urls,
+            commitment,
+            finality_offset,
+            database_url,
+            db_max_connections,
+            db_acquire_timeout,
+            partition_size,
+            address,
+            address_limit,
+            batch_size,
+            adaptive_batching,
+            target_commit_secs,
+            min_batch_size,
+            max_batch_size,
+            concurrency,
+            rpc_batch_size,
+            rps,
+            http_timeout,
+            http_pool_size,
+            cache_dir,
+            no_cache,
+            max_retries,
+            retry_delay,
+            export,
+            output_dir,
+            output,
+            format,
+            quiet,
+            progress_bar,
+            log_format,
+            compress_raw,
+            no_raw_json,
+            commit_every,
+            bulk_load,
+            signatures_only,
+            on_conflict,
+            dry_run,
+            skip_votes,
+            only_success,
+            only_failed,
+            sample_rate,
+            sample_seed,
+            programs,
+            resolve_owners,
+            max_txs_per_block,
+            truncate_large_blocks,
+            skip_existing,
+            verify_tx_count,
+            registry_reload_batches,
+            metrics_port,
+            stats_out,
+            job_name,
+            resume,
+            continuous,
+            interval,
+            subscribe,
+            ws_url,
+            reorg_overlap,
+            dedup_cache_size,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse_with_config(args: &[&str], config: &str) -> Cli {
+        let matches = Cli::command().get_matches_from(args);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        cli.merge_config(toml::from_str(config).unwrap(), &matches);
+        cli
+    }
+
+    #[test]
+    fn test_merge_config() {
+        let config = r#"
+            rpc_url = "https://file.example"
+            batch_size = 50
+            commitment = "confirmed"
+            finality_offset = 4
+            skip_votes = true
+            programs = ["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"]
+            start_time = "2025-10-23T12:00:00Z"
+        "#;
+
+        // The file fills in options that were not passed
+        let cli = parse_with_config(&["block-data-fetcher"], config);
+        assert_eq!(cli.rpc_url.as_deref(), Some("https://file.example"));
+        assert_eq!(cli.batch_size, 50);
+        assert_eq!(cli.commitment, Commitment::Confirmed);
+        assert_eq!(cli.finality_offset, 4);
+        assert!(cli.skip_votes);
+        assert_eq!(cli.programs.len(), 1);
+        assert!(cli.start_time.is_some());
+        // Options missing from the file keep their defaults
+        assert_eq!(cli.concurrency, 1);
+
+        // Flags on the command line win, even when they repeat the default value
+        let cli = parse_with_config(
+            &["block-data-fetcher", "--rpc-url", "https://flag.example", "--batch-size", "10"],
+            config,
+        );
+        assert_eq!(cli.rpc_url.as_deref(), Some("https://flag.example"));
+        assert_eq!(cli.batch_size, 10);
+        assert_eq!(cli.commitment, Commitment::Confirmed);
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_keys() {
+        assert!(toml::from_str::<ConfigFile>("batch_sise = 50").is_err());
+        assert!(toml::from_str::<ConfigFile>("commitment = \"final\"").is_err());
+    }
+}
@@ -4,23 +4,132 @@
 /// - Connection pool management
 /// - Schema migrations
 /// - CRUD operations for blocks, transactions, and instructions
+/// - Slot-range partitions of `blocks` and `transactions`
+/// - Typed aggregate queries (`BlockSummary`, `DbStats`) for dashboards and web backends
+use crate::{
+    etl::{
+        extract::ExtractionStats,
+        load::{ConflictMode, RawDataStorage},
+    },
+    models::{BlockSummary, DbStats},
+};
 use anyhow::{Context, Result};
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres, QueryBuilder};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
+/// Default size of the connection pool
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+
+/// Default time a query waits for a free pooled connection before failing
+///
+/// Without a bound an exhausted pool would make inserts hang; with it they fail with a
+/// pool timeout error that the pipeline retries and reports.
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of slots per partition of `blocks` and `transactions`
+pub const DEFAULT_PARTITION_SIZE: u64 = 10_000_000;
+
+/// Tables range-partitioned by slot, with their partition key
+const PARTITIONED_TABLES: [(&str, &str); 2] = [("blocks", "slot"), ("transactions", "block_slot")];
+
+/// Maximum rows per classification UPDATE statement (5 bind parameters each)
+const CLASSIFICATION_UPDATE_CHUNK: usize = 1000;
+
+/// Advisory lock serializing partition creation across loaders and processes
+const PARTITION_LOCK_ID: i64 = 0x626c_6f63_6b73; // "blocks"
+
+#[derive(Clone)]
 pub struct Database {
     pool: PgPool,
+    partition_size: u64,
+    covered_slots: Arc<Mutex<Vec<(u64, u64)>>>, // Slot ranges known to have partitions in every table
+    on_conflict: ConflictMode,                  // What loads do with rows that are already stored
+    raw_storage: RawDataStorage,                // How raw transaction JSON is stored (`--compress-raw`)
+    commit_every: Option<usize>,                // Commit upsert loads every N transactions (`--commit-every`)
 }
 
 impl Database {
-    /// Create a new database connection pool
+    /// Create a new database connection pool with the default pool size
+    #[allow(dead_code)]
     pub async fn new(database_url: &str) -> Result<Self> {
+        Self::with_pool_options(database_url, DEFAULT_MAX_CONNECTIONS, DEFAULT_ACQUIRE_TIMEOUT).await
+    }
+
+    /// Create a new database connection pool holding up to `max_connections` connections
+    ///
+    /// Raise it together with `--concurrency` so parallel extraction is not throttled by inserts.
+    pub async fn with_pool_options(
+        database_url: &str,
+        max_connections: u32,
+        acquire_timeout: Duration,
+    ) -> Result<Self> {
         let pool = PgPoolOptions::new()
-            .max_connections(5)
+            .max_connections(max_connections)
+            .acquire_timeout(acquire_timeout)
             .connect(database_url)
             .await
             .context("Failed to connect to PostgreSQL database")?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Wrap an existing connection pool
+    #[allow(dead_code)]
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self {
+            pool,
+            partition_size: DEFAULT_PARTITION_SIZE,
+            covered_slots: Arc::default(),
+            on_conflict: ConflictMode::default(),
+            raw_storage: RawDataStorage::default(),
+            commit_every: None,
+        }
+    }
+
+    /// Create new partitions with `partition_size` slots each
+    ///
+    /// Only affects partitions created from now on; existing partitions keep their bounds.
+    pub fn with_partition_size(mut self, partition_size: u64) -> Self {
+        self.partition_size = partition_size;
+        self
+    }
+
+    /// Load already stored rows according to `on_conflict` (overwritten by default)
+    pub fn with_on_conflict(mut self, on_conflict: ConflictMode) -> Self {
+        self.on_conflict = on_conflict;
+        self
+    }
+
+    pub fn on_conflict(&self) -> ConflictMode {
+        self.on_conflict
+    }
+
+    /// Store raw transaction JSON according to `raw_storage` (as queryable JSONB by default)
+    pub fn with_raw_storage(mut self, raw_storage: RawDataStorage) -> Self {
+        self.raw_storage = raw_storage;
+        self
+    }
+
+    pub fn raw_storage(&self) -> RawDataStorage {
+        self.raw_storage
+    }
+
+    /// Split upsert loads into database transactions of at most `commit_every` transactions
+    ///
+    /// A batch is then no longer loaded all-or-nothing, see `batch_insert_blocks_with_transactions`.
+    pub fn with_commit_every(mut self, commit_every: Option<usize>) -> Self {
+        self.commit_every = commit_every;
+        self
+    }
+
+    pub fn commit_every(&self) -> Option<usize> {
+        self.commit_every
     }
 
     /// Get a reference to the connection pool
@@ -43,6 +152,63 @@ impl Database {
         Ok(())
     }
 
+    /// Make sure `blocks` and `transactions` have a partition for `slot`
+    ///
+    /// Creates the partition of the slot's `partition_size` bucket in each table that lacks
+    /// one, narrowed to the gap between existing partitions if they were created with another
+    /// size. Ranges already checked are cached, so calling this for every block is cheap.
+    pub async fn ensure_partition(&self, slot: u64) -> Result<()> {
+        if self.covered_slots.lock().unwrap().iter().any(|&(lower, upper)| (lower..upper).contains(&slot)) {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await.context("Failed to begin partition transaction")?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(PARTITION_LOCK_ID)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to lock partitions")?;
+
+        let mut covered = (0, u64::MAX);
+        for (table, key) in PARTITIONED_TABLES {
+            let partitions = sqlx::query_as::<_, (i64, i64)>(
+                "SELECT m[1]::BIGINT, m[2]::BIGINT
+                 FROM pg_inherits i
+                 JOIN pg_class c ON c.oid = i.inhrelid
+                 CROSS JOIN LATERAL regexp_match(pg_get_expr(c.relpartbound, c.oid),
+                     'FROM \\(''(-?\\d+)''\\) TO \\(''(-?\\d+)''\\)') AS m
+                 WHERE i.inhparent = $1::REGCLASS AND m IS NOT NULL",
+            )
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await
+            .context(format!("Failed to list partitions of {}", table))?;
+            let partitions: Vec<(u64, u64)> =
+                partitions.into_iter().map(|(lower, upper)| (lower.max(0) as u64, upper.max(0) as u64)).collect();
+
+            let range = match partitions.iter().find(|&&(lower, upper)| (lower..upper).contains(&slot)) {
+                Some(&range) => range,
+                None => {
+                    let (lower, upper) = partition_range(slot, self.partition_size, &partitions);
+                    sqlx::query(&format!(
+                        "CREATE TABLE {table}_p{lower} PARTITION OF {table} FOR VALUES FROM ({lower}) TO ({upper})"
+                    ))
+                    .execute(&mut *tx)
+                    .await
+                    .context(format!("Failed to create partition of {} for slot {}", table, slot))?;
+
+                    tracing::info!("Created partition {}_p{} ({} {}-{})", table, lower, key, lower, upper - 1);
+                    (lower, upper)
+                }
+            };
+            covered = (covered.0.max(range.0), covered.1.min(range.1));
+        }
+
+        tx.commit().await.context("Failed to commit partition transaction")?;
+        self.covered_slots.lock().unwrap().push(covered);
+        Ok(())
+    }
+
     /// Load program registry from database
     pub async fn load_program_registry(&self) -> Result<Vec<ProgramInfo>> {
         let programs = sqlx::query_as::<_, ProgramInfo>(
@@ -54,6 +220,566 @@ impl Database {
 
         Ok(programs)
     }
+
+    /// Insert the built-in programs into the program registry
+    ///
+    /// Idempotent: existing entries keep their name and type, only a missing (NULL)
+    /// program_type is filled in. Returns the number of rows inserted or updated.
+    pub async fn seed_program_registry(&self) -> Result<u64> {
+        let programs = crate::etl::transform::BUILTIN_PROGRAMS;
+        let ids: Vec<&str> = programs.iter().map(|(id, _, _)| *id).collect();
+        let names: Vec<&str> = programs.iter().map(|(_, name, _)| *name).collect();
+        let types: Vec<&str> = programs.iter().map(|(_, _, program_type)| *program_type).collect();
+
+        let result = sqlx::query(
+            "INSERT INTO program_registry (program_id, program_name, program_type)
+             SELECT * FROM UNNEST($1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[])
+             ON CONFLICT (program_id) DO UPDATE SET program_type = EXCLUDED.program_type
+             WHERE program_registry.program_type IS NULL",
+        )
+        .bind(&ids)
+        .bind(&names)
+        .bind(&types)
+        .execute(&self.pool)
+        .await
+        .context("Failed to seed program registry")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Slots in `start..=end` with neither a row in `blocks` nor in `skipped_slots`
+    ///
+    /// Slots skipped by their leader only drop out of the result once they are recorded with
+    /// `record_skipped_slots`, so callers should check the result against `getBlocks`.
+    pub async fn find_missing_slots(&self, start: u64, end: u64) -> Result<Vec<u64>> {
+        let slots = sqlx::query_scalar::<_, i64>(
+            "SELECT gs.slot FROM generate_series($1::BIGINT, $2::BIGINT) AS gs(slot)
+             WHERE NOT EXISTS (SELECT 1 FROM blocks b WHERE b.slot = gs.slot)
+               AND NOT EXISTS (SELECT 1 FROM skipped_slots s WHERE s.slot = gs.slot)
+             ORDER BY gs.slot",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context(format!("Failed to find missing slots in {}-{}", start, end))?;
+
+        Ok(slots.into_iter().map(|s| s as u64).collect())
+    }
+
+    /// Slot range `(first, last)` of the stored blocks produced between `start` and `end` (inclusive)
+    ///
+    /// None when no stored block falls in the window. Only stored blocks count, so the range
+    /// may be narrower than the chain's slots for the window; `--start-time` / `--end-time`
+    /// resolve times against the RPC instead.
+    pub async fn blocks_between_times(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Option<(u64, u64)>> {
+        let (first, last) = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+            "SELECT MIN(slot), MAX(slot) FROM blocks WHERE block_time BETWEEN $1 AND $2",
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await
+        .context(format!("Failed to find blocks between {} and {}", start, end))?;
+
+        Ok(first.zip(last).map(|(first, last)| (first as u64, last as u64)))
+    }
+
+    /// Check whether a block is already stored
+    pub async fn block_exists(&self, slot: u64) -> Result<bool> {
+        let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM blocks WHERE slot = $1)")
+            .bind(slot as i64)
+            .fetch_one(&self.pool)
+            .await
+            .context(format!("Failed to check whether block {} exists", slot))?;
+
+        Ok(exists)
+    }
+
+    /// Raw JSON of a stored transaction, whichever way it was stored
+    ///
+    /// Transactions loaded with `--compress-raw` keep it in `raw_data_compressed`; it is
+    /// decompressed here so callers do not need to know. None when the signature is not stored or
+    /// was stored without raw data (`--no-raw-json`).
+    pub async fn load_raw_transaction(&self, signature: &str) -> Result<Option<serde_json::Value>> {
+        let row = sqlx::query_as::<_, (Option<serde_json::Value>, Option<Vec<u8>>)>(
+            "SELECT raw_data, raw_data_compressed FROM transactions WHERE signature = $1 ORDER BY block_slot DESC LIMIT 1",
+        )
+        .bind(signature)
+        .fetch_optional(&self.pool)
+        .await
+        .context(format!("Failed to load raw data of transaction {}", signature))?;
+
+        match row {
+            Some((Some(raw_data), _)) => Ok(Some(raw_data)),
+            Some((None, Some(compressed))) => crate::etl::load::decompress_raw_data(&compressed)
+                .map(Some)
+                .context(format!("Failed to decompress raw data of transaction {}", signature)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Stored transactions in `start..=end` with their raw JSON, in (slot, index) order, one page
+    /// of up to `page_size` rows at a time
+    ///
+    /// Pages are read with keyset pagination on (block_slot, transaction_index), so each page is
+    /// an index range scan however deep into the range it starts, and rows updated between pages
+    /// are neither skipped nor read twice. Raw JSON stored with `--compress-raw` is decompressed
+    /// into `raw_data`.
+    pub fn stream_transactions_raw(
+        &self,
+        start: u64,
+        end: u64,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<Vec<StoredTransaction>>> + '_ {
+        futures::stream::try_unfold(Some((start as i64, -1)), move |cursor| async move {
+            let Some((after_slot, after_index)) = cursor else {
+                return Ok(None);
+            };
+
+            let mut page = sqlx::query_as::<_, StoredTransaction>(
+                "SELECT signature, block_slot, transaction_index, success, fee, transaction_type, transaction_label,
+                     secondary_types, signers, program_ids, log_messages, swap_input_mint, swap_output_mint,
+                     swap_in_amount::TEXT, swap_out_amount::TEXT, raw_data, raw_data_compressed
+                 FROM transactions
+                 WHERE block_slot BETWEEN $1 AND $2 AND (block_slot, transaction_index) > ($1, $3)
+                 ORDER BY block_slot, transaction_index
+                 LIMIT $4",
+            )
+            .bind(after_slot)
+            .bind(end as i64)
+            .bind(after_index)
+            .bind(page_size as i64)
+            .fetch_all(&self.pool)
+            .await
+            .context(format!("Failed to read stored transactions after {}:{}", after_slot, after_index))?;
+            if page.is_empty() {
+                return Ok(None);
+            }
+
+            for transaction in &mut page {
+                if let Some(compressed) = transaction.raw_data_compressed.take() {
+                    transaction.raw_data =
+                        Some(crate::etl::load::decompress_raw_data(&compressed).context(format!(
+                            "Failed to decompress raw data of transaction {}",
+                            transaction.signature
+                        ))?);
+                }
+            }
+
+            let last = page.last().map(|transaction| (transaction.block_slot, transaction.transaction_index));
+            let next = if page.len() < page_size { None } else { last };
+            Ok(Some((page, next)))
+        })
+    }
+
+    /// Overwrite the classification of stored transactions, in one UPDATE per chunk
+    ///
+    /// Returns the number of rows updated.
+    pub async fn update_transaction_classifications(&self, updates: &[ClassificationUpdate]) -> Result<u64> {
+        let mut updated = 0;
+        for chunk in updates.chunks(CLASSIFICATION_UPDATE_CHUNK) {
+            let mut query = QueryBuilder::<Postgres>::new(
+                "UPDATE transactions AS t SET transaction_type = v.transaction_type, \
+                 transaction_label = v.transaction_label, secondary_types = v.secondary_types, processed_at = NOW() \
+                 FROM (",
+            );
+            query.push_values(chunk, |mut row, update| {
+                row.push_bind(&update.signature)
+                    .push_bind(update.block_slot)
+                    .push_bind(update.transaction_type.as_str())
+                    .push_bind(&update.label)
+                    .push_bind(update.secondary_types.iter().map(|t| t.as_str()).collect::<Vec<_>>());
+            });
+            query.push(
+                ") AS v (signature, block_slot, transaction_type, transaction_label, secondary_types) \
+                 WHERE t.signature = v.signature AND t.block_slot = v.block_slot",
+            );
+
+            updated += query
+                .build()
+                .execute(&self.pool)
+                .await
+                .context("Failed to update transaction classifications")?
+                .rows_affected();
+        }
+
+        Ok(updated)
+    }
+
+    /// Delete a stored block with its transactions, instructions, token balances and rewards
+    ///
+    /// Used when a fork orphaned the block. Transactions and block rewards go with the block
+    /// through their `ON DELETE CASCADE` foreign keys; instructions and token balances have no
+    /// foreign key to the partitioned `transactions` table, so they are deleted first, in the same
+    /// database transaction, unless the same signature is also stored in another block. Returns
+    /// whether a block was deleted.
+    pub async fn delete_block(&self, slot: u64) -> Result<bool> {
+        let mut tx = self.pool.begin().await.context("Failed to begin block deletion")?;
+
+        for table in ["instructions", "token_balances"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE transaction_signature IN (
+                     SELECT t.signature FROM transactions t
+                     WHERE t.block_slot = $1
+                       AND NOT EXISTS (
+                           SELECT 1 FROM transactions o WHERE o.signature = t.signature AND o.block_slot <> $1
+                       )
+                 )",
+                table
+            ))
+            .bind(slot as i64)
+            .execute(&mut *tx)
+            .await
+            .context(format!("Failed to delete {} of block {}", table, slot))?;
+        }
+
+        let deleted = sqlx::query("DELETE FROM blocks WHERE slot = $1")
+            .bind(slot as i64)
+            .execute(&mut *tx)
+            .await
+            .context(format!("Failed to delete block {}", slot))?
+            .rows_affected();
+
+        tx.commit().await.context(format!("Failed to commit deletion of block {}", slot))?;
+        Ok(deleted > 0)
+    }
+
+    /// Slots in `start..=end` that are already stored in `blocks`
+    ///
+    /// With `verify_tx_count`, a block only counts when its stored transactions match the
+    /// block's on-chain `transaction_count`, so blocks loaded partially (or before the count
+    /// was recorded) are fetched again.
+    pub async fn find_existing_slots(&self, start: u64, end: u64, verify_tx_count: bool) -> Result<HashSet<u64>> {
+        let slots = sqlx::query_scalar::<_, i64>(
+            "SELECT b.slot FROM blocks b
+             WHERE b.slot BETWEEN $1 AND $2
+               AND (NOT $3 OR b.transaction_count =
+                   (SELECT COUNT(*) FROM transactions t WHERE t.block_slot = b.slot))",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .bind(verify_tx_count)
+        .fetch_all(&self.pool)
+        .await
+        .context(format!("Failed to find existing blocks in {}-{}", start, end))?;
+
+        Ok(slots.into_iter().map(|s| s as u64).collect())
+    }
+
+    /// Summarize the stored transactions of a slot
+    ///
+    /// A slot with no stored transactions (or no stored block) gives an all-zero summary.
+    pub async fn block_summary(&self, slot: u64) -> Result<BlockSummary> {
+        self.range_summary(slot, slot).await
+    }
+
+    /// Summarize the stored transactions of `start..=end`: counts, fees and a per-type breakdown
+    pub async fn range_summary(&self, start: u64, end: u64) -> Result<BlockSummary> {
+        let rows = sqlx::query_as::<_, (String, i64, i64, i64)>(
+            "SELECT COALESCE(transaction_type, 'Unknown'),
+                 COUNT(*),
+                 COUNT(*) FILTER (WHERE success),
+                 COALESCE(SUM(fee), 0)::BIGINT
+             FROM transactions
+             WHERE block_slot BETWEEN $1 AND $2
+             GROUP BY 1",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context(format!("Failed to summarize transactions in {}-{}", start, end))?;
+
+        Ok(BlockSummary::from_type_rows(
+            start,
+            end,
+            rows.into_iter().map(|(transaction_type, count, successful, fees)| {
+                (transaction_type, count as u64, successful as u64, fees as u64)
+            }),
+        ))
+    }
+
+    /// Totals of the stored data: block and transaction counts, the stored slot range, the
+    /// skipped slots inside it and the transaction count per type
+    pub async fn stats(&self) -> Result<DbStats> {
+        let (total_blocks, min_slot, max_slot) =
+            sqlx::query_as::<_, (i64, Option<i64>, Option<i64>)>("SELECT COUNT(*), MIN(slot), MAX(slot) FROM blocks")
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count stored blocks")?;
+
+        let skipped_slots = match (min_slot, max_slot) {
+            (Some(min), Some(max)) => {
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM skipped_slots WHERE slot BETWEEN $1 AND $2")
+                    .bind(min)
+                    .bind(max)
+                    .fetch_one(&self.pool)
+                    .await
+                    .context("Failed to count skipped slots")?
+            }
+            _ => 0,
+        };
+
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            "SELECT COALESCE(transaction_type, 'Unknown'), COUNT(*) FROM transactions GROUP BY 1",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to count stored transactions")?;
+
+        let by_type: std::collections::BTreeMap<String, u64> =
+            rows.into_iter().map(|(transaction_type, count)| (transaction_type, count as u64)).collect();
+        Ok(DbStats {
+            total_blocks: total_blocks as u64,
+            total_transactions: by_type.values().sum(),
+            min_slot: min_slot.map(|slot| slot as u64),
+            max_slot: max_slot.map(|slot| slot as u64),
+            skipped_slots: skipped_slots as u64,
+            by_type,
+        })
+    }
+
+    /// Fill in NULL `block_height`s of stored blocks from their stored neighbours
+    ///
+    /// A block's height is its parent's plus one (skipped slots do not count), so a block whose
+    /// parent has a height gets that height + 1 and a parent whose child has one gets it - 1.
+    /// Repeats until nothing changes, so whole runs of NULLs next to a known height are filled.
+    /// Blocks without a neighbour of known height stay NULL. Returns the number of blocks filled.
+    pub async fn backfill_block_heights(&self) -> Result<u64> {
+        let mut filled = 0;
+        loop {
+            let from_parents = sqlx::query(
+                "UPDATE blocks child SET block_height = parent.block_height + 1
+                 FROM blocks parent
+                 WHERE child.block_height IS NULL
+                   AND parent.slot = child.parent_slot
+                   AND parent.block_height IS NOT NULL",
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to backfill block heights from parent blocks")?
+            .rows_affected();
+
+            let from_children = sqlx::query(
+                "UPDATE blocks parent SET block_height = child.block_height - 1
+                 FROM blocks child
+                 WHERE parent.block_height IS NULL
+                   AND child.parent_slot = parent.slot
+                   AND child.block_height IS NOT NULL",
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to backfill block heights from child blocks")?
+            .rows_affected();
+
+            if from_parents + from_children == 0 {
+                break;
+            }
+            filled += from_parents + from_children;
+        }
+
+        Ok(filled)
+    }
+
+    /// Blocks stored in `start..=end` with their number of stored transactions, in slot order
+    pub async fn load_stored_blocks(&self, start: u64, end: u64) -> Result<Vec<StoredBlock>> {
+        let blocks = sqlx::query_as::<_, StoredBlock>(
+            "SELECT b.slot, b.blockhash, b.transaction_count,
+                 (SELECT COUNT(*) FROM transactions t WHERE t.block_slot = b.slot) AS stored_transactions
+             FROM blocks b
+             WHERE b.slot BETWEEN $1 AND $2
+             ORDER BY b.slot",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context(format!("Failed to load stored blocks in {}-{}", start, end))?;
+
+        Ok(blocks)
+    }
+
+    /// Record slots that were skipped by their leader (no block exists)
+    pub async fn record_skipped_slots(&self, slots: &[u64]) -> Result<()> {
+        let slots: Vec<i64> = slots.iter().map(|&s| s as i64).collect();
+        sqlx::query(
+            "INSERT INTO skipped_slots (slot) SELECT * FROM UNNEST($1::BIGINT[]) ON CONFLICT (slot) DO NOTHING",
+        )
+        .bind(&slots)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record skipped slots")?;
+
+        Ok(())
+    }
+
+    /// Record slots that failed after all retries, for a later `retry-failed` run
+    ///
+    /// `failures` pairs each slot with its error; all of them share the stage and attempt count and
+    /// are written with one statement. A slot that failed before keeps its first failure time; its
+    /// stage and error are replaced with the latest ones and `attempts` is added to the previous
+    /// count.
+    pub async fn record_failed_slots(&self, failures: &[(u64, &str)], stage: &str, attempts: u32) -> Result<()> {
+        let (slots, errors): (Vec<i64>, Vec<&str>) = failures.iter().map(|&(slot, error)| (slot as i64, error)).unzip();
+        sqlx::query(
+            "INSERT INTO failed_slots (slot, stage, error, attempts)
+             SELECT slot, $3, error, $4 FROM UNNEST($1::BIGINT[], $2::TEXT[]) AS failure(slot, error)
+             ON CONFLICT (slot) DO UPDATE SET
+                 stage = EXCLUDED.stage,
+                 error = EXCLUDED.error,
+                 attempts = failed_slots.attempts + EXCLUDED.attempts,
+                 last_failed_at = NOW()",
+        )
+        .bind(&slots)
+        .bind(&errors)
+        .bind(stage)
+        .bind(attempts as i32)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record failed slots")?;
+
+        Ok(())
+    }
+
+    /// Slots recorded by `record_failed_slots`, in slot order
+    pub async fn list_failed_slots(&self) -> Result<Vec<FailedSlot>> {
+        let slots = sqlx::query_as::<_, FailedSlot>(
+            "SELECT slot, stage, error, attempts, last_failed_at FROM failed_slots ORDER BY slot",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list failed slots")?;
+
+        Ok(slots)
+    }
+
+    /// Remove slots from the failed-slot queue once they are loaded (or known to be skipped)
+    pub async fn clear_failed_slots(&self, slots: &[u64]) -> Result<u64> {
+        let slots: Vec<i64> = slots.iter().map(|&s| s as i64).collect();
+        let result = sqlx::query("DELETE FROM failed_slots WHERE slot = ANY($1)")
+            .bind(&slots)
+            .execute(&self.pool)
+            .await
+            .context("Failed to clear failed slots")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Save the last committed slot for a job
+    pub async fn save_checkpoint(&self, job_name: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO checkpoints (job_name, last_slot) VALUES ($1, $2)
+             ON CONFLICT (job_name) DO UPDATE SET last_slot = EXCLUDED.last_slot, updated_at = NOW()",
+        )
+        .bind(job_name)
+        .bind(slot as i64)
+        .execute(&self.pool)
+        .await
+        .context(format!("Failed to save checkpoint for job '{}'", job_name))?;
+
+        Ok(())
+    }
+
+    /// Load the last committed slot for a job, if any
+    pub async fn load_checkpoint(&self, job_name: &str) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>("SELECT last_slot FROM checkpoints WHERE job_name = $1")
+            .bind(job_name)
+            .fetch_optional(&self.pool)
+            .await
+            .context(format!("Failed to load checkpoint for job '{}'", job_name))?;
+
+        Ok(slot.map(|s| s as u64))
+    }
+
+    /// Insert the audit row of a pipeline run that is starting, returning its id
+    pub async fn record_run_start(&self, job_name: &str, start_slot: u64, end_slot: u64) -> Result<i64> {
+        let run_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO processing_runs (job_name, start_slot, end_slot) VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(job_name)
+        .bind(start_slot as i64)
+        .bind(end_slot as i64)
+        .fetch_one(&self.pool)
+        .await
+        .context(format!("Failed to record the start of job '{}'", job_name))?;
+
+        Ok(run_id)
+    }
+
+    /// Complete the audit row of a pipeline run with its outcome
+    pub async fn record_run_finish(&self, run_id: i64, end_slot: u64, outcome: &RunOutcome) -> Result<()> {
+        sqlx::query(
+            "UPDATE processing_runs
+             SET end_slot = $2, finished_at = NOW(), blocks_succeeded = $3, blocks_failed = $4,
+                 transactions_inserted = $5, success_rate = $6
+             WHERE id = $1",
+        )
+        .bind(run_id)
+        .bind(end_slot as i64)
+        .bind(outcome.blocks_succeeded as i64)
+        .bind(outcome.blocks_failed as i64)
+        .bind(outcome.transactions_inserted as i64)
+        .bind(outcome.success_rate)
+        .execute(&self.pool)
+        .await
+        .context(format!("Failed to record the end of run {}", run_id))?;
+
+        Ok(())
+    }
+
+    /// Append the metrics of a committed batch to `metrics_timeseries`
+    pub async fn record_batch_metrics(&self, job_name: &str, metrics: &BatchMetrics) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO metrics_timeseries (
+                job_name, slot_range_start, slot_range_end, blocks_processed, txs_processed,
+                txs_successful, txs_failed, total_fees, avg_fee, avg_priority_fee, vote_count,
+                sol_transfer_count, token_transfer_count, dex_swap_count, nft_operation_count,
+                unknown_count
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
+        )
+        .bind(job_name)
+        .bind(metrics.slot_range_start as i64)
+        .bind(metrics.slot_range_end as i64)
+        .bind(metrics.blocks_processed as i64)
+        .bind(metrics.txs_processed as i64)
+        .bind(metrics.txs_successful as i64)
+        .bind(metrics.txs_failed as i64)
+        .bind(metrics.total_fees as i64)
+        .bind(metrics.avg_fee())
+        .bind(metrics.avg_priority_fee())
+        .bind(metrics.vote_count as i64)
+        .bind(metrics.sol_transfer_count as i64)
+        .bind(metrics.token_transfer_count as i64)
+        .bind(metrics.dex_swap_count as i64)
+        .bind(metrics.nft_operation_count as i64)
+        .bind(metrics.unknown_count as i64)
+        .execute(&self.pool)
+        .await
+        .context(format!(
+            "Failed to record the metrics of slots {}-{}",
+            metrics.slot_range_start, metrics.slot_range_end
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// Bounds `[lower, upper)` of a new partition holding `slot`
+///
+/// The `size`-aligned bucket of the slot, shrunk so it does not overlap the `existing`
+/// partitions, none of which may contain the slot.
+fn partition_range(slot: u64, size: u64, existing: &[(u64, u64)]) -> (u64, u64) {
+    let mut lower = slot - slot % size;
+    let mut upper = lower.saturating_add(size);
+    for &(start, end) in existing {
+        if end <= slot {
+            lower = lower.max(end);
+        } else if start > slot {
+            upper = upper.min(start);
+        }
+    }
+    (lower, upper)
 }
 
 /// Program information from the registry
@@ -63,3 +789,149 @@ pub struct ProgramInfo {
     pub program_name: String,
     pub program_type: Option<String>,
 }
+
+/// A stored block, as compared with the chain by `validate`
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StoredBlock {
+    pub slot: i64,
+    pub blockhash: String,
+    pub transaction_count: Option<i32>, // On-chain count recorded at load time, NULL for older rows
+    pub stored_transactions: i64,       // Rows in `transactions` for the block
+}
+
+/// A stored transaction as read back by `stream_transactions_raw`, e.g. to reclassify it
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StoredTransaction {
+    pub signature: String,
+    pub block_slot: i64,
+    pub transaction_index: i32,
+    pub success: bool,
+    pub fee: i64,
+    pub transaction_type: Option<String>,
+    pub transaction_label: Option<String>,
+    pub secondary_types: Option<Vec<String>>, // NULL for rows loaded before the column
+    pub signers: Option<Vec<String>>,         // NULL for rows loaded before the column
+    pub program_ids: Option<Vec<String>>,     // Top-level and CPI programs
+    pub log_messages: Option<Vec<String>>,    // NULL when the transaction logged nothing
+    pub swap_input_mint: Option<String>,
+    pub swap_output_mint: Option<String>,
+    pub swap_in_amount: Option<String>, // NUMERIC read as text, base units
+    pub swap_out_amount: Option<String>,
+    pub raw_data: Option<serde_json::Value>, // Decompressed from raw_data_compressed when stored compressed
+    pub raw_data_compressed: Option<Vec<u8>>,
+}
+
+/// New classification of a stored transaction, written by `update_transaction_classifications`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassificationUpdate {
+    pub signature: String,
+    pub block_slot: i64,
+    pub transaction_type: crate::models::TransactionType,
+    pub label: String,
+    pub secondary_types: Vec<crate::models::TransactionType>,
+}
+
+/// Outcome of a pipeline run, stored in `processing_runs`
+#[derive(Debug, Clone, Default)]
+pub struct RunOutcome {
+    pub blocks_succeeded: u64,
+    pub blocks_failed: u64,
+    pub transactions_inserted: u64,
+    pub success_rate: f64, // Percentage of attempted blocks that were loaded
+}
+
+/// Throughput and transaction mix of a committed batch, stored in `metrics_timeseries`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchMetrics {
+    pub slot_range_start: u64,
+    pub slot_range_end: u64,
+    pub blocks_processed: u64,
+    pub txs_processed: u64, // Transactions extracted, including votes dropped by `--skip-votes`
+    pub txs_successful: u64,
+    pub txs_failed: u64,
+    pub total_fees: u64,          // Lamports
+    pub total_priority_fees: u64, // Lamports above the base fee
+    pub vote_count: u64,
+    pub sol_transfer_count: u64,
+    pub token_transfer_count: u64,
+    pub dex_swap_count: u64,
+    pub nft_operation_count: u64,
+    pub unknown_count: u64,
+}
+
+impl BatchMetrics {
+    /// Metrics of the batch `start_slot..=end_slot` from the statistics of its extraction
+    pub fn from_extraction(start_slot: u64, end_slot: u64, stats: &ExtractionStats) -> Self {
+        Self {
+            slot_range_start: start_slot,
+            slot_range_end: end_slot,
+            blocks_processed: stats.blocks_fetched,
+            txs_processed: stats.total_transactions,
+            txs_successful: stats.successful_transactions,
+            txs_failed: stats.failed_transactions,
+            total_fees: stats.total_fees,
+            total_priority_fees: stats.total_priority_fees,
+            vote_count: stats.votes,
+            sol_transfer_count: stats.sol_transfers,
+            token_transfer_count: stats.spl_token_transfers,
+            dex_swap_count: stats.dex_swaps,
+            nft_operation_count: stats.nft_operations,
+            unknown_count: stats.unknown_transactions,
+        }
+    }
+
+    /// Mean fee per transaction in lamports, 0 without transactions
+    pub fn avg_fee(&self) -> f64 {
+        self.per_transaction(self.total_fees)
+    }
+
+    /// Mean priority fee per transaction in lamports, 0 without transactions
+    pub fn avg_priority_fee(&self) -> f64 {
+        self.per_transaction(self.total_priority_fees)
+    }
+
+    fn per_transaction(&self, lamports: u64) -> f64 {
+        if self.txs_processed == 0 {
+            0.0
+        } else {
+            lamports as f64 / self.txs_processed as f64
+        }
+    }
+}
+
+/// A slot in the failed-slot queue
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FailedSlot {
+    pub slot: i64,
+    pub stage: String, // Pipeline stage of the latest failure
+    pub error: String, // Error of the latest failure
+    pub attempts: i32, // Attempts across all runs
+    pub last_failed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_range() {
+        assert_eq!(partition_range(25_000_001, 10_000_000, &[]), (20_000_000, 30_000_000));
+        assert_eq!(partition_range(0, 10_000_000, &[]), (0, 10_000_000));
+        // Partitions created with another size narrow the bucket
+        let existing = [(20_000_000, 22_000_000), (28_000_000, 40_000_000)];
+        assert_eq!(partition_range(25_000_001, 10_000_000, &existing), (22_000_000, 28_000_000));
+        assert_eq!(partition_range(19_999_999, 10_000_000, &existing), (10_000_000, 20_000_000));
+    }
+
+    #[test]
+    fn test_batch_metrics_averages() {
+        let metrics =
+            BatchMetrics { txs_processed: 4, total_fees: 30_000, total_priority_fees: 10_000, ..Default::default() };
+        assert_eq!(metrics.avg_fee(), 7_500.0);
+        assert_eq!(metrics.avg_priority_fee(), 2_500.0);
+
+        // A batch of empty blocks averages to 0 rather than NaN
+        let empty = BatchMetrics { blocks_processed: 3, ..Default::default() };
+        assert_eq!((empty.avg_fee(), empty.avg_priority_fee()), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,92 @@
+/// Error Module
+///
+/// Structured errors of the RPC and extract layers. Callers can match on the variant to
+/// decide what to do with a failure (retry a rate limit, move on from a skipped slot)
+/// instead of inspecting messages. The pipeline and `main` keep using `anyhow`, which
+/// `FetcherError` converts into with `?`.
+use solana_client::client_error::ClientError;
+
+/// Result of the RPC and extract layers
+pub type Result<T, E = FetcherError> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetcherError {
+    /// An RPC request or subscription failed: transport, HTTP or JSON-RPC error
+    #[error("{context}: {source}")]
+    RpcError {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The slot has no block available: not produced yet, or no longer served by the node
+    #[error("Block at slot {slot} is not available")]
+    BlockNotAvailable { slot: u64 },
+
+    /// The RPC does not know the transaction: never confirmed, or older than the node's ledger
+    #[error("Transaction {signature} not found: not confirmed, or older than the RPC node's ledger retention")]
+    TransactionNotFound { signature: String },
+
+    /// The leader of the slot did not produce a block
+    #[error("Slot {slot} was skipped")]
+    SlotSkipped { slot: u64 },
+
+    /// Every endpoint answered with HTTP 429
+    #[error("{context}: rate limited by the RPC provider")]
+    RateLimited {
+        context: String,
+        #[source]
+        source: Box<ClientError>,
+    },
+
+    /// A database query failed
+    #[allow(dead_code)]
+    #[error("{context}: {source}")]
+    DatabaseError {
+        context: String,
+        #[source]
+        source: sqlx::Error,
+    },
+
+    /// The block has more transactions than `--max-txs-per-block` allows
+    #[error("Block has {transactions} transactions, more than the limit of {limit}")]
+    TooManyTransactions { transactions: usize, limit: usize },
+
+    /// Data returned by the RPC could not be parsed
+    #[error("{0}")]
+    ParseError(String),
+
+    /// Invalid input, such as a malformed address or an empty slot range
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+impl FetcherError {
+    /// Build an `RpcError` from any error, e.g. a failed WebSocket connection
+    pub fn rpc(context: impl Into<String>, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::RpcError { context: context.into(), source: source.into() }
+    }
+
+    /// Whether the same request may succeed when tried again later
+    ///
+    /// Rate limits and failed requests are retryable. A skipped slot or unavailable block is
+    /// an answer about the chain, and parse or input errors fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RpcError { .. } | Self::RateLimited { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(FetcherError::rpc("Failed to get latest slot", "connection reset").is_retryable());
+        assert!(!FetcherError::SlotSkipped { slot: 42 }.is_retryable());
+        assert!(!FetcherError::BlockNotAvailable { slot: 42 }.is_retryable());
+        assert!(!FetcherError::TooManyTransactions { transactions: 10, limit: 5 }.is_retryable());
+        assert!(!FetcherError::ParseError("Block has no transactions".to_string()).is_retryable());
+        assert_eq!(FetcherError::SlotSkipped { slot: 42 }.to_string(), "Slot 42 was skipped");
+    }
+}
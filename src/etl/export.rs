@@ -0,0 +1,343 @@
+/// Export Module
+///
+/// Writes extracted blocks to files for analysis outside PostgreSQL
+/// (DuckDB, Spark, pandas, spreadsheets, ...). Each row is one transaction.
+use super::{
+    extract::ExtractedBlock,
+    transform::{self, Classifier, ProgramRegistry},
+};
+use crate::models::block_time_to_utc;
+use anyhow::{Context, Result};
+use arrow::{
+    array::{ArrayRef, BooleanArray, ListBuilder, StringArray, StringBuilder, TimestampSecondArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    record_batch::RecordBatch,
+};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::Serialize;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Column names of the flattened CSV export
+const CSV_HEADER: [&str; 11] = [
+    "slot",
+    "block_time",
+    "signature",
+    "success",
+    "fee_lamports",
+    "fee_sol",
+    "transaction_type",
+    "label",
+    "signer",
+    "program_count",
+    "program_names",
+];
+
+/// Where the pipeline exports extracted blocks, in addition to loading them into the database
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum ExportTarget {
+    /// One Parquet file per batch, named after the batch's slot range
+    Parquet { output_dir: PathBuf },
+    /// A single CSV file that every batch is appended to
+    Csv { path: PathBuf },
+}
+
+/// File name for the Parquet export of a slot range
+pub fn parquet_file_name(start_slot: u64, end_slot: u64) -> String {
+    // Zero-padded so files sort by slot
+    format!("transactions_{:012}_{:012}.parquet", start_slot, end_slot)
+}
+
+/// Arrow schema of the transaction export
+fn transaction_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("slot", DataType::UInt64, false),
+        Field::new("block_time", DataType::Timestamp(TimeUnit::Second, Some("UTC".into())), true),
+        Field::new("signature", DataType::Utf8, false),
+        Field::new("success", DataType::Boolean, false),
+        Field::new("fee", DataType::UInt64, false),
+        Field::new("transaction_type", DataType::Utf8, false),
+        Field::new("signer", DataType::Utf8, true),
+        Field::new("program_ids", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
+    ])
+}
+
+/// Write one row per transaction to a Snappy-compressed Parquet file
+///
+/// Extracted transactions carry no type, so the `transaction_type` column is classified here
+/// with `classifier` and `registry`, the same ones the batch is loaded with; that is why this
+/// takes them besides the blocks and the path. The parent directory is created if needed.
+/// Returns the number of rows written.
+pub fn export_blocks_to_parquet(
+    blocks: &[ExtractedBlock],
+    path: &Path,
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+) -> Result<usize> {
+    let rows = blocks.iter().flat_map(|block| block.transactions.iter().map(move |tx| (block, tx)));
+
+    let mut slots = Vec::new();
+    let mut block_times = Vec::new();
+    let mut signatures = Vec::new();
+    let mut successes = Vec::new();
+    let mut fees = Vec::new();
+    let mut transaction_types = Vec::new();
+    let mut signers = Vec::new();
+    let mut program_ids = ListBuilder::new(StringBuilder::new());
+
+    for (block, tx) in rows {
+        slots.push(block.slot);
+        block_times.push(block.block_time);
+        signatures.push(tx.signature.as_str());
+        successes.push(tx.success);
+        fees.push(tx.fee);
+        transaction_types.push(classifier.classify(tx, registry).as_str().to_string());
+        // The fee payer is the first account key and always signs
+        signers.push(transform::extract_account_keys(&tx.raw_json).into_iter().next());
+
+        for program_id in &tx.program_ids {
+            program_ids.values().append_value(program_id);
+        }
+        program_ids.append(true);
+    }
+
+    let row_count = slots.len();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from(slots)),
+        Arc::new(TimestampSecondArray::from(block_times).with_timezone("UTC")),
+        Arc::new(StringArray::from(signatures)),
+        Arc::new(BooleanArray::from(successes)),
+        Arc::new(UInt64Array::from(fees)),
+        Arc::new(StringArray::from(transaction_types)),
+        Arc::new(StringArray::from(signers)),
+        Arc::new(program_ids.finish()),
+    ];
+    let schema = Arc::new(transaction_schema());
+    let batch = RecordBatch::try_new(schema.clone(), columns).context("Failed to build Arrow record batch")?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context(format!("Failed to create export directory {}", parent.display()))?;
+    }
+
+    let file = File::create(path).context(format!("Failed to create {}", path.display()))?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties)).context("Failed to create Parquet writer")?;
+    writer.write(&batch).context("Failed to write Parquet rows")?;
+    writer.close().context(format!("Failed to finish Parquet file {}", path.display()))?;
+
+    tracing::info!("Exported {} transactions to {}", row_count, path.display());
+    Ok(row_count)
+}
+
+/// Write one flattened row per transaction, with a header, as CSV
+///
+/// Blocks without transactions produce no rows. Fields containing commas or quotes
+/// (labels, joined program names) are quoted. Returns the number of rows written.
+pub fn export_transactions_to_csv(
+    blocks: &[ExtractedBlock],
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+    writer: impl Write,
+) -> Result<usize> {
+    write_csv(blocks, registry, classifier, writer, true)
+}
+
+/// Append a batch to a CSV file, writing the header only when the file is new or empty
+pub fn append_transactions_to_csv(
+    blocks: &[ExtractedBlock],
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+    path: &Path,
+) -> Result<usize> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context(format!("Failed to create export directory {}", parent.display()))?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open {}", path.display()))?;
+    let is_empty = file.metadata().context(format!("Failed to read {}", path.display()))?.len() == 0;
+
+    let rows = if is_empty {
+        export_transactions_to_csv(blocks, registry, classifier, file)?
+    } else {
+        write_csv(blocks, registry, classifier, file, false)?
+    };
+    tracing::info!("Exported {} transactions to {}", rows, path.display());
+    Ok(rows)
+}
+
+fn write_csv(
+    blocks: &[ExtractedBlock],
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+    writer: impl Write,
+    header: bool,
+) -> Result<usize> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    if header {
+        csv_writer.write_record(CSV_HEADER).context("Failed to write CSV header")?;
+    }
+
+    let mut rows = 0;
+    for block in blocks {
+        let block_time = block_time_to_utc(block.block_time).map(|t| t.to_rfc3339()).unwrap_or_default();
+
+        for tx in &block.transactions {
+            let details = tx.analyze_with(classifier, registry);
+            let signer = transform::extract_account_keys(&tx.raw_json).into_iter().next().unwrap_or_default();
+            let program_names: Vec<&str> =
+                tx.program_ids.iter().map(|id| registry.get_program_name(id).unwrap_or(id)).collect();
+
+            csv_writer
+                .write_record([
+                    block.slot.to_string(),
+                    block_time.clone(),
+                    tx.signature.clone(),
+                    tx.success.to_string(),
+                    tx.fee.to_string(),
+                    format!("{:.9}", tx.fee as f64 / 1_000_000_000.0),
+                    details.tx_type.as_str().to_string(),
+                    details.label,
+                    signer,
+                    tx.program_ids.len().to_string(),
+                    program_names.join(", "),
+                ])
+                .context(format!("Failed to write CSV row for {}", tx.signature))?;
+            rows += 1;
+        }
+    }
+
+    csv_writer.flush().context("Failed to flush CSV output")?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::etl::{extract::ExtractedTransaction, transform::DefaultClassifier};
+    use arrow::array::{Array, ListArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    fn test_block() -> ExtractedBlock {
+        let tx = ExtractedTransaction {
+            signature: "sig1".to_string(),
+            signers: vec![],
+            version: None,
+            recent_blockhash: None,
+            transaction_index: 0,
+            success: true,
+            error_reason: None,
+            fee: 5000,
+            priority_fee: None,
+            compute_unit_price: None,
+            compute_units: Some(150),
+            num_accounts: 2,
+            num_instructions: 1,
+            num_inner_instructions: 0,
+            max_cpi_depth: Some(0),
+            program_ids: vec!["11111111111111111111111111111111".to_string()],
+            inner_program_ids: vec![],
+            balance_changes: vec![],
+            signer_balance_delta: 0,
+            token_balance_changes: vec![],
+            token_transfer: None,
+            jupiter_swap: None,
+            liquid_staking: None,
+            wrapped_sol: None,
+            log_messages: vec![],
+            logs_truncated: false,
+            raw_json: json!({
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Payer111", "signer": true },
+                        { "pubkey": "Dest111", "signer": false }
+                    ]
+                }
+            }),
+        };
+
+        ExtractedBlock {
+            slot: 100,
+            blockhash: "hash".to_string(),
+            parent_slot: 99,
+            block_time: Some(1_700_000_000),
+            block_height: Some(90),
+            rewards: vec![],
+            transactions: vec![tx],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 1,
+            signatures_only: false,
+        }
+    }
+
+    #[test]
+    fn test_export_blocks_to_parquet() {
+        let path = std::env::temp_dir().join(format!("block-data-fetcher-{}", std::process::id())).join("test.parquet");
+        let registry = ProgramRegistry::from_database(vec![]);
+
+        let rows = export_blocks_to_parquet(&[test_block()], &path, &registry, &DefaultClassifier).unwrap();
+        assert_eq!(rows, 1);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 1);
+        let signer = batch.column_by_name("signer").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(signer.value(0), "Payer111");
+        let programs = batch.column_by_name("program_ids").unwrap().as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(programs.value(0).len(), 1);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_export_transactions_to_csv() {
+        let registry = ProgramRegistry::from_database(vec![crate::db::ProgramInfo {
+            program_id: "11111111111111111111111111111111".to_string(),
+            program_name: "System Program, native".to_string(),
+            program_type: Some("System".to_string()),
+        }]);
+        let empty_block = ExtractedBlock { transactions: vec![], ..test_block() };
+
+        let mut output = Vec::new();
+        let rows = export_transactions_to_csv(&[test_block(), empty_block], &registry, &DefaultClassifier, &mut output)
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].starts_with("100,2023-11-14T22:13:20+00:00,sig1,true,5000,0.000005000,"));
+        assert!(lines[1].ends_with(",Payer111,1,\"System Program, native\""));
+    }
+
+    #[test]
+    fn test_export_transactions_to_csv_without_transactions() {
+        let registry = ProgramRegistry::from_database(vec![]);
+        let empty_block = ExtractedBlock { transactions: vec![], ..test_block() };
+
+        let mut output = Vec::new();
+        let rows = export_transactions_to_csv(&[empty_block], &registry, &DefaultClassifier, &mut output).unwrap();
+        assert_eq!(rows, 0);
+        assert_eq!(String::from_utf8(output).unwrap().trim_end(), CSV_HEADER.join(","));
+    }
+
+    #[test]
+    fn test_parquet_file_name_sorts_by_slot() {
+        assert_eq!(parquet_file_name(99, 108), "transactions_000000000099_000000000108.parquet");
+        assert!(parquet_file_name(99, 108) < parquet_file_name(1_000, 1_009));
+    }
+}
@@ -1,64 +1,227 @@
+use super::parsers::{
+    jupiter::JUPITER_V6, parse_jupiter_swap, parse_liquid_staking, parse_priority_fee, parse_token_balance_changes,
+    parse_token_transfer, parse_wrapped_sol_instruction, token_account_mint, token_account_owner, JupiterSwap,
+    LiquidStaking, PriorityFee, TokenBalanceChange, TokenTransfer, WrappedSolAction, LIQUID_STAKING_PROGRAMS,
+};
 /// Extract Module
 ///
 /// Handles fetching data from the Solana blockchain via RPC and parsing transaction details.
-use anyhow::{Context, Result};
-use solana_transaction_status::{EncodedTransactionWithStatusMeta, UiConfirmedBlock};
-use std::time::Duration;
-use tokio::time::sleep;
+use super::{source::BlockSource, transform::Classifier};
+use crate::{
+    error::{FetcherError, Result},
+    output::console,
+};
+use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use solana_sdk::transaction::TransactionVersion;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta, Rewards, UiConfirmedBlock,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::IsTerminal,
+    time::Duration,
+};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, sleep_until, Instant},
+};
 
 /// Extracted transaction data from a block
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ExtractedTransaction {
     pub signature: String,
+    pub signers: Vec<String>,     // Accounts that signed the transaction, fee payer first
+    pub transaction_index: usize, // Position in the block, kept when other transactions are dropped
+    pub version: Option<u8>,      // Message version: None for legacy transactions, Some(0) for v0
+    pub recent_blockhash: Option<String>, // Blockhash the transaction was signed against (message.recentBlockhash)
     pub success: bool,
-    pub fee: u64,
+    pub error_reason: Option<String>, // Why a failed transaction failed, see `parse_error_reason`
+    pub fee: u64,                     // Total fee from meta.fee: base signature fee plus priority fee
+    pub priority_fee: Option<u64>,    // Lamports paid for SetComputeUnitPrice, None when no price is set
+    pub compute_unit_price: Option<u64>, // Micro-lamports per compute unit
+    pub compute_units: Option<u64>,   // None when the RPC does not report it (older transactions)
     #[allow(dead_code)]
     pub num_accounts: usize,
+    pub num_instructions: usize,       // Top-level instructions of the message
+    pub num_inner_instructions: usize, // Instructions invoked through CPI (meta.innerInstructions)
+    pub max_cpi_depth: Option<u8>,     // Deepest CPI nesting, see `parse_max_cpi_depth`
+    pub program_ids: Vec<String>,      // Program IDs involved in the transaction (top-level and CPI)
+    #[allow(dead_code)]
+    pub inner_program_ids: Vec<String>, // Program IDs invoked via inner instructions (CPI)
+    #[allow(dead_code)]
+    pub balance_changes: Vec<(String, i64)>, // (account, post - pre lamports) in account key order
+    pub signer_balance_delta: i64,     // Fee payer's post - pre lamports, fee included
+    pub token_balance_changes: Vec<TokenBalanceChange>, // Per-owner, per-mint token deltas
+    pub token_transfer: Option<TokenTransfer>, // First top-level SPL token transfer
+    pub jupiter_swap: Option<JupiterSwap>, // Input and output of a Jupiter v6 swap, see `parse_jupiter_swap`
+    pub liquid_staking: Option<LiquidStaking>, // Marinade or stake pool operation, see `parse_liquid_staking`
+    pub wrapped_sol: Option<WrappedSolAction>, // First top-level wSOL wrap or unwrap (syncNative, closeAccount)
+    pub log_messages: Vec<String>,     // meta.logMessages, see `parse_log_messages`
+    pub logs_truncated: bool,          // The RPC cut the logs short ("Log truncated")
+    pub raw_json: serde_json::Value,   // Full transaction JSON for detailed parsing
+}
+
+impl ExtractedTransaction {
+    /// A transaction known only by its signature and position, from a block fetched with
+    /// signatures only (`--signatures-only`); every other field is empty
+    pub fn from_signature(signature: String, transaction_index: usize) -> Self {
+        Self { signature, transaction_index, ..Default::default() }
+    }
+
+    /// Base (signature) fee: the total fee minus the priority fee
+    pub fn base_fee(&self) -> u64 {
+        self.fee.saturating_sub(self.priority_fee.unwrap_or(0))
+    }
+
+    /// Classify the transaction with the built-in rules, see `DefaultClassifier`
+    pub fn classify(&self, registry: &crate::etl::transform::ProgramRegistry) -> crate::models::TransactionType {
+        crate::etl::transform::DefaultClassifier.classify(self, registry)
+    }
+
+    /// Classify the transaction with the built-in rules and extract its details
+    pub fn analyze(
+        &self,
+        registry: &crate::etl::transform::ProgramRegistry,
+    ) -> crate::etl::transform::TransactionDetails {
+        self.analyze_with(&crate::etl::transform::DefaultClassifier, registry)
+    }
+
+    /// Classify the transaction with `classifier` and extract its details (label, amounts, accounts, memo)
+    pub fn analyze_with(
+        &self,
+        classifier: &dyn Classifier,
+        registry: &crate::etl::transform::ProgramRegistry,
+    ) -> crate::etl::transform::TransactionDetails {
+        let mut details = crate::etl::transform::analyze_classified_transaction(
+            classifier.classify(self, registry),
+            &self.program_ids,
+            registry,
+            Some(&self.raw_json),
+            self.wrapped_sol,
+        );
+        details.is_wrapped_sol |= self.token_transfer.as_ref().is_some_and(|transfer| transfer.is_wrapped_sol);
+        if let Some(swap) = &self.jupiter_swap {
+            details.input_mint = Some(swap.input_mint.clone());
+            details.output_mint = Some(swap.output_mint.clone());
+            details.in_amount = Some(swap.in_amount);
+            details.out_amount = Some(swap.out_amount);
+        }
+        // The LST minted or burned, or the lamports of a claim, when no instruction named an amount
+        if let Some(staking) = self.liquid_staking.as_ref().filter(|_| details.amount.is_none()) {
+            details.token_address = staking.lst_mint.clone();
+            details.amount = staking.lst_amount.or(staking.sol_amount);
+        }
+        details
+    }
+
+    /// Net lamport change of the fee payer (account index 0), including the fee
     #[allow(dead_code)]
-    pub num_instructions: usize,
-    pub program_ids: Vec<String>,    // Program IDs involved in the transaction
-    pub raw_json: serde_json::Value, // Full transaction JSON for detailed parsing
+    pub fn fee_payer_lamport_change(&self) -> Option<i64> {
+        self.balance_changes.first().map(|(_, delta)| *delta)
+    }
 }
 
 /// Extracted block data with parsed transactions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExtractedBlock {
     pub slot: u64,
     pub blockhash: String,
     pub parent_slot: u64,
     #[allow(dead_code)]
     pub block_time: Option<i64>,
-    #[allow(dead_code)]
+    // Produced blocks before this one, see `resolve_block_heights`; unlike the slot it skips no numbers
     pub block_height: Option<u64>,
+    pub rewards: Vec<crate::models::Reward>, // Empty when the block was fetched without rewards
     pub transactions: Vec<ExtractedTransaction>,
+    pub filtered_transactions: usize, // Transactions dropped by the program filter (`--programs`)
+    pub truncated_transactions: usize, // Transactions past `--max-txs-per-block` left out of the block
+    pub transaction_count: usize,     // Transactions in the block on chain, including ones not extracted
+    pub signatures_only: bool,        // Fetched without transaction details, see `parse_signatures_block`
 }
 
+/// Number of programs listed in the "Most Active Programs" summary
+const TOP_PROGRAMS: usize = 10;
+
 /// Statistics for a range extraction
 #[derive(Debug, Clone)]
 pub struct ExtractionStats {
     pub blocks_fetched: u64,
     pub blocks_failed: u64,
+    pub failed_slots: Vec<(u64, String)>, // Slots whose block could not be fetched, with the error
+    pub skipped_slots: u64,               // Slots skipped by the leader (no block produced)
+    pub existing_slots: u64,              // Blocks not fetched because they are already stored (`--skip-existing`)
+    pub effective_delay_ms: u64,          // Delay between requests at the end of the range, including 429 backoff
+    pub rate_limited_requests: u64,       // HTTP 429 responses received during the range
     pub total_transactions: u64,
     pub successful_transactions: u64,
     pub failed_transactions: u64,
     pub total_fees: u64,
+    pub total_priority_fees: u64,
+    pub legacy_transactions: u64,    // Transactions with a legacy message
+    pub versioned_transactions: u64, // Transactions with a versioned (v0) message, which can use lookup tables
     // Transaction type counts
     pub sol_transfers: u64,
+    pub account_creations: u64,
     pub spl_token_transfers: u64,
+    pub token_mints: u64,
+    pub token_burns: u64,
     pub dex_swaps: u64,
+    pub liquidity_adds: u64,
+    pub liquidity_removes: u64,
+    pub memos: u64,
+    pub stakes: u64,
+    pub liquid_staking: u64,
+    pub votes: u64,                 // Vote transactions seen, including skipped ones
+    pub votes_skipped: u64,         // Vote transactions dropped by `--skip-votes` (not stored)
+    pub filtered_transactions: u64, // Transactions dropped by `--programs` (not counted anywhere else)
+    pub status_filtered: u64,       // Transactions dropped by `--only-success` / `--only-failed` (still counted)
+    pub sampled_out: u64,           // Transactions left out of the `--sample-rate` sample (still counted)
+    pub oversized_blocks: u64,      // Blocks over `--max-txs-per-block`, truncated or skipped
+    pub unavailable_blocks: u64,    // Blocks the node does not serve (too recent or pruned), skipped without retrying
     pub nft_operations: u64,
     pub program_interactions: u64,
     pub unknown_transactions: u64,
+    pub custom_types: HashMap<String, u64>, // Transactions per user-defined program type (e.g. "Lending")
+    // Transactions touching each program ID (top-level or inner instructions, counted once per transaction)
+    pub program_activity: HashMap<String, u64>,
 }
 
 /// Parse transactions from a block
-pub fn parse_transactions_from_block(block: &UiConfirmedBlock) -> Result<Vec<ExtractedTransaction>> {
-    let transactions = block.transactions.as_ref().context("Block has no transactions")?;
+///
+/// With a program filter, only transactions invoking one of the programs are kept (see
+/// `touches_any_program`). Returns the kept transactions and how many were filtered out.
+///
+/// A block with more transactions than `limit` allows fails with `TooManyTransactions` before
+/// anything is parsed, or has only its first transactions parsed when the limit truncates.
+pub fn parse_transactions_from_block(
+    block: &UiConfirmedBlock,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Result<(Vec<ExtractedTransaction>, usize)> {
+    let transactions =
+        block.transactions.as_ref().ok_or_else(|| FetcherError::ParseError("Block has no transactions".to_string()))?;
+
+    let mut kept = transactions.len();
+    if let Some(limit) = limit.filter(|limit| transactions.len() > limit.max_transactions) {
+        if !limit.truncate {
+            return Err(FetcherError::TooManyTransactions {
+                transactions: transactions.len(),
+                limit: limit.max_transactions,
+            });
+        }
+        kept = limit.max_transactions;
+    }
 
     let mut extracted_transactions = Vec::new();
+    let mut filtered = 0;
 
-    for (index, tx) in transactions.iter().enumerate() {
+    for (index, tx) in transactions.iter().take(kept).enumerate() {
         match parse_single_transaction(tx, index) {
+            Ok(extracted) if program_filter.is_some_and(|programs| !touches_any_program(&extracted, programs)) => {
+                filtered += 1;
+            }
             Ok(extracted) => extracted_transactions.push(extracted),
             Err(e) => {
                 tracing::warn!("Failed to parse transaction at index {}: {}", index, e);
@@ -67,13 +230,18 @@ pub fn parse_transactions_from_block(block: &UiConfirmedBlock) -> Result<Vec<Ext
         }
     }
 
-    Ok(extracted_transactions)
+    Ok((extracted_transactions, filtered))
+}
+
+/// Check whether a transaction invokes one of the programs, at the top level or through CPI
+fn touches_any_program(transaction: &ExtractedTransaction, programs: &HashSet<String>) -> bool {
+    transaction.program_ids.iter().chain(&transaction.inner_program_ids).any(|id| programs.contains(id))
 }
 
 /// Parse a single transaction - simplified for Solana SDK v2.0
-fn parse_single_transaction(tx: &EncodedTransactionWithStatusMeta, _index: usize) -> Result<ExtractedTransaction> {
+fn parse_single_transaction(tx: &EncodedTransactionWithStatusMeta, index: usize) -> Result<ExtractedTransaction> {
     // Extract meta information to determine success and fee
-    let meta = tx.meta.as_ref().context("Transaction has no metadata")?;
+    let meta = tx.meta.as_ref().ok_or_else(|| FetcherError::ParseError("Transaction has no metadata".to_string()))?;
 
     // Determine success (if err is None, transaction succeeded)
     let success = meta.err.is_none();
@@ -83,7 +251,15 @@ fn parse_single_transaction(tx: &EncodedTransactionWithStatusMeta, _index: usize
 
     // For now, we'll extract the signature from the encoded transaction as a JSON string
     // The actual structure varies based on encoding (Json, Base58, Base64)
-    let tx_json = serde_json::to_value(&tx.transaction).context("Failed to serialize transaction to JSON")?;
+    let tx_json = serde_json::to_value(&tx.transaction)
+        .map_err(|e| FetcherError::ParseError(format!("Failed to serialize transaction to JSON: {}", e)))?;
+
+    // Meta as JSON (camelCase keys, e.g. innerInstructions)
+    let meta_json = serde_json::to_value(meta)
+        .map_err(|e| FetcherError::ParseError(format!("Failed to serialize transaction meta to JSON: {}", e)))?;
+
+    // Compute units consumed (absent for older transactions)
+    let compute_units = meta_json.get("computeUnitsConsumed").and_then(|c| c.as_u64());
 
     // Try to extract signature from the JSON structure
     let signature = if let Some(sigs) = tx_json.get("signatures").and_then(|s| s.as_array()) {
@@ -92,66 +268,308 @@ fn parse_single_transaction(tx: &EncodedTransactionWithStatusMeta, _index: usize
         "unknown".to_string()
     };
 
-    // Count accounts from the message structure
-    let num_accounts = if let Some(message) = tx_json.get("message") {
-        message.get("accountKeys").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0)
-    } else {
-        0
+    // Fee payer and any other signers (multisig members, program authorities)
+    let signers = crate::etl::transform::extract_signers(&tx_json);
+
+    // Message version, reported because blocks are requested with maxSupportedTransactionVersion 0
+    let version = match &tx.version {
+        Some(TransactionVersion::Number(version)) => Some(*version),
+        Some(TransactionVersion::Legacy(_)) | None => None,
     };
 
+    // Blockhash the transaction was signed against
+    let recent_blockhash = extract_recent_blockhash(&tx_json);
+
+    // Static account keys plus addresses loaded from Address Lookup Tables (v0 transactions)
+    let account_keys = crate::etl::transform::extract_all_account_keys(&tx_json, &meta_json);
+    let num_accounts = account_keys.len();
+
     // Count instructions from the message structure
-    let num_instructions = if let Some(message) = tx_json.get("message") {
-        message.get("instructions").and_then(|i| i.as_array()).map(|i| i.len()).unwrap_or(0)
-    } else {
-        0
+    let instructions = tx_json.get("message").and_then(|m| m.get("instructions")).and_then(|i| i.as_array());
+    let num_instructions = instructions.map(|i| i.len()).unwrap_or(0);
+
+    // Priority fee from Compute Budget instructions; meta.fee already includes it
+    let PriorityFee { compute_unit_price, priority_fee } =
+        instructions.map(|i| parse_priority_fee(i)).unwrap_or_default();
+
+    // Extract program IDs from top-level and inner (CPI) instructions
+    let mut program_ids = extract_program_ids(&tx_json, &account_keys);
+    let inner_program_ids = extract_inner_program_ids(&meta_json, &account_keys);
+    let num_inner_instructions = count_inner_instructions(&meta_json);
+    let max_cpi_depth = parse_max_cpi_depth(&meta_json);
+    for program_id in &inner_program_ids {
+        if !program_ids.contains(program_id) {
+            program_ids.push(program_id.clone());
+        }
+    }
+
+    // Per-account SOL movement from pre/post balances
+    let balance_changes = extract_balance_changes(&account_keys, &meta_json);
+    let signer_balance_delta = extract_signer_balance_delta(&meta_json);
+    let (log_messages, logs_truncated) = parse_log_messages(&meta_json);
+    let error_reason = parse_error_reason(&meta_json);
+
+    // Per-owner token movement from pre/post token balances
+    let token_balance_changes = parse_token_balance_changes(&meta_json, &account_keys);
+    let token_transfer = extract_token_transfer(&tx_json, &meta_json, &account_keys);
+    let jupiter_swap = program_ids
+        .iter()
+        .any(|id| id == JUPITER_V6)
+        .then(|| parse_jupiter_swap(&meta_json, &account_keys, &log_messages))
+        .flatten();
+    let liquid_staking = program_ids
+        .iter()
+        .any(|id| LIQUID_STAKING_PROGRAMS.contains(&id.as_str()))
+        .then(|| parse_liquid_staking(instructions.map(Vec::as_slice).unwrap_or_default(), &meta_json, &account_keys))
+        .flatten();
+    let wrapped_sol = instructions.and_then(|i| {
+        i.iter().find_map(|instruction| parse_wrapped_sol_instruction(instruction, &meta_json, &account_keys))
+    });
+
+    Ok(ExtractedTransaction {
+        signature,
+        signers,
+        transaction_index: index,
+        version,
+        recent_blockhash,
+        success,
+        error_reason,
+        fee,
+        priority_fee,
+        compute_unit_price,
+        compute_units,
+        num_accounts,
+        num_instructions,
+        num_inner_instructions,
+        max_cpi_depth,
+        program_ids,
+        inner_program_ids,
+        balance_changes,
+        signer_balance_delta,
+        token_balance_changes,
+        token_transfer,
+        jupiter_swap,
+        liquid_staking,
+        wrapped_sol,
+        log_messages,
+        logs_truncated,
+        raw_json: tx_json,
+    })
+}
+
+/// Read `message.recentBlockhash` of a transaction
+///
+/// JsonParsed and compiled (json encoding) messages, legacy or v0, all keep it at the same
+/// place. None for binary encodings, which are not decoded.
+fn extract_recent_blockhash(tx_json: &serde_json::Value) -> Option<String> {
+    tx_json.get("message")?.get("recentBlockhash")?.as_str().map(|hash| hash.to_string())
+}
+
+/// Marker the RPC appends when a transaction's logs exceeded the log size limit
+const LOG_TRUNCATED_MARKER: &str = "Log truncated";
+
+/// Collect the program log lines of a transaction from `meta.logMessages`
+///
+/// Returns the lines and whether they were truncated. Truncated logs are missing their
+/// tail, so classification hints from them are incomplete but still valid.
+fn parse_log_messages(meta_json: &serde_json::Value) -> (Vec<String>, bool) {
+    let log_messages: Vec<String> = meta_json
+        .get("logMessages")
+        .and_then(|l| l.as_array())
+        .map(|lines| lines.iter().filter_map(|line| line.as_str()).map(|line| line.to_string()).collect())
+        .unwrap_or_default();
+    let truncated = log_messages.iter().any(|line| line == LOG_TRUNCATED_MARKER);
+
+    (log_messages, truncated)
+}
+
+/// Describe why a transaction failed, from `meta.err`
+///
+/// An instruction error names the failing top-level instruction and, for a program-defined
+/// error, its custom code in decimal and hex (Anchor error codes start at 6000), e.g.
+/// "Instruction 3 failed: custom program error 6001 (0x1771)". Other errors are named by their
+/// variant, followed by their fields, e.g. "InsufficientFundsForRent (account_index: 2)".
+/// Returns None for successful transactions.
+fn parse_error_reason(meta_json: &serde_json::Value) -> Option<String> {
+    let err = meta_json.get("err").filter(|err| !err.is_null())?;
+
+    match err.get("InstructionError").and_then(|e| e.as_array()).map(Vec::as_slice) {
+        Some([index, cause]) => {
+            let cause = match cause.get("Custom").and_then(|code| code.as_u64()) {
+                Some(code) => format!("custom program error {} ({:#x})", code, code),
+                None => describe_error_variant(cause),
+            };
+            Some(format!("Instruction {} failed: {}", index, cause))
+        }
+        _ => Some(describe_error_variant(err)),
+    }
+}
+
+/// Render a serialized error enum: `"Name"` as is, `{"Name": fields}` as "Name (fields)"
+fn describe_error_variant(err: &serde_json::Value) -> String {
+    let Some((name, fields)) = err.as_object().filter(|variant| variant.len() == 1).and_then(|v| v.iter().next())
+    else {
+        return err.as_str().map(str::to_string).unwrap_or_else(|| err.to_string());
+    };
+
+    let fields = match fields {
+        serde_json::Value::Object(fields) => {
+            fields.iter().map(|(key, value)| format!("{}: {}", key, value)).collect::<Vec<_>>().join(", ")
+        }
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
     };
+    format!("{} ({})", name, fields)
+}
+
+/// Find the first top-level SPL token transfer and fill in its mint, decimals and owners
+///
+/// Plain `transfer` instructions omit the mint and decimals; they are looked up from the
+/// token balances of the source (or destination) account, as are the account owners.
+/// Decimals still missing after that are resolved over RPC by `resolve_token_decimals`,
+/// owners by `resolve_token_owners` with `--resolve-owners`.
+fn extract_token_transfer(
+    tx_json: &serde_json::Value,
+    meta_json: &serde_json::Value,
+    account_keys: &[String],
+) -> Option<TokenTransfer> {
+    let instructions = tx_json.get("message")?.get("instructions")?.as_array()?;
+
+    let mut transfer = instructions
+        .iter()
+        .filter(|instruction| {
+            matches!(instruction.get("program").and_then(|p| p.as_str()), Some("spl-token" | "spl-token-2022"))
+        })
+        .find_map(|instruction| parse_token_transfer(instruction, account_keys))?;
+
+    if transfer.mint.is_none() || transfer.decimals.is_none() {
+        let balance = token_account_mint(meta_json, account_keys, &transfer.source)
+            .or_else(|| token_account_mint(meta_json, account_keys, &transfer.destination));
+        if let Some((mint, decimals)) = balance {
+            if transfer.mint.is_none() {
+                transfer.set_mint(mint);
+            }
+            transfer.decimals.get_or_insert(decimals);
+        }
+    }
+    if transfer.source_owner.is_none() {
+        transfer.source_owner = token_account_owner(meta_json, account_keys, &transfer.source);
+    }
+    transfer.destination_owner = token_account_owner(meta_json, account_keys, &transfer.destination);
+
+    Some(transfer)
+}
+
+/// Look up mint decimals over RPC for token transfers that still lack them
+///
+/// Lookups are cached by the RPC client; a failed lookup leaves the decimals unknown.
+async fn resolve_token_decimals<B: BlockSource + ?Sized>(source: &B, transactions: &mut [ExtractedTransaction]) {
+    for transfer in transactions.iter_mut().filter_map(|tx| tx.token_transfer.as_mut()) {
+        if transfer.decimals.is_some() {
+            continue;
+        }
+        let Some(mint) = transfer.mint.as_deref() else {
+            continue;
+        };
+
+        match source.get_token_decimals(mint).await {
+            Ok(decimals) => transfer.decimals = Some(decimals),
+            Err(e) => tracing::warn!("Failed to resolve decimals for mint {}: {:#}", mint, e),
+        }
+    }
+}
 
-    // Extract program IDs from instructions
-    let program_ids = extract_program_ids(&tx_json);
+/// Look up token account owners over RPC for token transfers that still lack them
+///
+/// Only runs when the source resolves owners (`--resolve-owners`); lookups are cached by the
+/// RPC client and a failed lookup leaves the owner unknown.
+async fn resolve_token_owners<B: BlockSource + ?Sized>(source: &B, transactions: &mut [ExtractedTransaction]) {
+    if !source.resolves_owners() {
+        return;
+    }
+
+    for transfer in transactions.iter_mut().filter_map(|tx| tx.token_transfer.as_mut()) {
+        for (account, owner) in
+            [(&transfer.source, &mut transfer.source_owner), (&transfer.destination, &mut transfer.destination_owner)]
+        {
+            if owner.is_some() {
+                continue;
+            }
+            match source.get_account_owner(account).await {
+                Ok(resolved) => *owner = Some(resolved),
+                Err(e) => tracing::debug!("Failed to resolve the owner of token account {}: {:#}", account, e),
+            }
+        }
+    }
+}
+
+/// Fill in the block height of blocks the RPC returned without one (e.g. very old blocks)
+///
+/// Every produced block has a height one above its parent's: skipped slots advance the slot
+/// but not the height. The parent's height is taken from an earlier block of the same batch,
+/// or else from the parent's header over RPC. Blocks whose parent has no height either keep
+/// None; `Database::backfill_block_heights` can still derive them once neighbours are stored.
+async fn resolve_block_heights<B: BlockSource + ?Sized>(source: &B, mut blocks: Vec<&mut ExtractedBlock>) {
+    blocks.sort_by_key(|block| block.slot);
+    let mut known: HashMap<u64, u64> =
+        blocks.iter().filter_map(|block| Some((block.slot, block.block_height?))).collect();
+
+    for block in blocks.into_iter().filter(|block| block.block_height.is_none()) {
+        let parent_height = match known.get(&block.parent_slot) {
+            Some(&height) => Some(height),
+            None => match source.fetch_block_header(block.parent_slot).await {
+                Ok(parent) => parent.block_height,
+                Err(e) => {
+                    tracing::warn!("Failed to fetch parent {} of block {}: {:#}", block.parent_slot, block.slot, e);
+                    None
+                }
+            },
+        };
 
-    Ok(ExtractedTransaction { signature, success, fee, num_accounts, num_instructions, program_ids, raw_json: tx_json })
+        if let Some(height) = parent_height {
+            block.block_height = Some(height + 1);
+            known.insert(block.slot, height + 1);
+        }
+    }
 }
 
 /// Extract program IDs from transaction JSON
-fn extract_program_ids(tx_json: &serde_json::Value) -> Vec<String> {
+///
+/// `account_keys` must include addresses loaded from lookup tables (see `extract_all_account_keys`),
+/// since compiled instructions can reference a program through one.
+fn extract_program_ids(tx_json: &serde_json::Value, account_keys: &[String]) -> Vec<String> {
     let mut program_ids = Vec::new();
 
     if let Some(message) = tx_json.get("message") {
-        // Get account keys (which include program accounts)
-        let account_keys = message.get("accountKeys").and_then(|a| a.as_array());
-
         // Get instructions
         if let Some(instructions) = message.get("instructions").and_then(|i| i.as_array()) {
             for instruction in instructions {
-                // For JsonParsed encoding, check for programId field first
-                if let Some(program_id) = instruction.get("programId").and_then(|p| p.as_str()) {
-                    if !program_ids.contains(&program_id.to_string()) {
-                        program_ids.push(program_id.to_string());
-                    }
-                }
-                // For parsed instructions, also check the program field (program name like "system", "spl-token")
-                else if let Some(program) = instruction.get("program").and_then(|p| p.as_str()) {
-                    // This is a program name, not ID - we'll skip it for now since we have programId
-                    let program_id = program.to_string();
+                if let Some(program_id) = instruction_program_id(instruction, account_keys) {
                     if !program_ids.contains(&program_id) {
                         program_ids.push(program_id);
                     }
                 }
-                // For compiled instructions, use programIdIndex to look up in accountKeys
-                else if let Some(program_idx) = instruction.get("programIdIndex").and_then(|i| i.as_u64()) {
-                    if let Some(keys) = account_keys {
-                        if let Some(key) = keys.get(program_idx as usize) {
-                            if let Some(pubkey) = key.get("pubkey").and_then(|p| p.as_str()) {
-                                let program_id = pubkey.to_string();
-                                if !program_ids.contains(&program_id) {
-                                    program_ids.push(program_id);
-                                }
-                            } else if let Some(pubkey_str) = key.as_str() {
-                                let program_id = pubkey_str.to_string();
-                                if !program_ids.contains(&program_id) {
-                                    program_ids.push(program_id);
-                                }
-                            }
+            }
+        }
+    }
+
+    program_ids
+}
+
+/// Extract program IDs invoked through cross-program invocation (CPI)
+///
+/// Reads `meta.innerInstructions`, where each entry holds the instructions invoked
+/// by one top-level instruction. Returns deduplicated program IDs in order of appearance.
+fn extract_inner_program_ids(meta_json: &serde_json::Value, account_keys: &[String]) -> Vec<String> {
+    let mut program_ids = Vec::new();
+
+    if let Some(inner_instructions) = meta_json.get("innerInstructions").and_then(|i| i.as_array()) {
+        for inner in inner_instructions {
+            if let Some(instructions) = inner.get("instructions").and_then(|i| i.as_array()) {
+                for instruction in instructions {
+                    if let Some(program_id) = instruction_program_id(instruction, account_keys) {
+                        if !program_ids.contains(&program_id) {
+                            program_ids.push(program_id);
                         }
                     }
                 }
@@ -162,166 +580,1898 @@ fn extract_program_ids(tx_json: &serde_json::Value) -> Vec<String> {
     program_ids
 }
 
+/// Count the instructions invoked through CPI, at every nesting depth
+///
+/// `meta.innerInstructions` lists them flat, grouped by the top-level instruction that made them.
+fn count_inner_instructions(meta_json: &serde_json::Value) -> usize {
+    meta_json
+        .get("innerInstructions")
+        .and_then(|i| i.as_array())
+        .map(|inner_instructions| {
+            inner_instructions
+                .iter()
+                .filter_map(|inner| inner.get("instructions").and_then(|i| i.as_array()))
+                .map(Vec::len)
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Deepest cross-program invocation nesting, from the `stackHeight` of `meta.innerInstructions`
+///
+/// Top-level instructions run at stack height 1, so a program they invoke is at depth 1, a
+/// program that one invokes at depth 2, and so on. 0 when nothing was invoked through CPI. None
+/// when the inner instructions carry no `stackHeight`, which the RPC leaves out for older blocks.
+fn parse_max_cpi_depth(meta_json: &serde_json::Value) -> Option<u8> {
+    let stack_heights: Option<Vec<u64>> = meta_json
+        .get("innerInstructions")
+        .and_then(|i| i.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|inner| inner.get("instructions").and_then(|i| i.as_array()))
+        .flatten()
+        .map(|instruction| instruction.get("stackHeight").and_then(|h| h.as_u64()))
+        .collect();
+
+    let deepest = stack_heights?.into_iter().max().unwrap_or(1);
+    Some(deepest.saturating_sub(1).min(u8::MAX as u64) as u8)
+}
+
+/// Pair each account key with its lamport balance change
+///
+/// Diffs `meta.preBalances` against `meta.postBalances`, which captures SOL movement
+/// regardless of which program moved it. Balances are ordered like the full account key
+/// list, lookup table addresses included.
+fn extract_balance_changes(account_keys: &[String], meta_json: &serde_json::Value) -> Vec<(String, i64)> {
+    let mut changes = Vec::new();
+
+    let pre_balances = meta_json.get("preBalances").and_then(|b| b.as_array());
+    let post_balances = meta_json.get("postBalances").and_then(|b| b.as_array());
+
+    if let (Some(pre), Some(post)) = (pre_balances, post_balances) {
+        for ((account, pre), post) in account_keys.iter().zip(pre).zip(post) {
+            if let (Some(pre), Some(post)) = (pre.as_u64(), post.as_u64()) {
+                changes.push((account.clone(), post as i64 - pre as i64));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Lamport balance change of the fee payer (account index 0)
+///
+/// The fee is deducted from the fee payer whether or not the transaction succeeds, so the
+/// delta includes it: a failed transaction yields `-fee`, a SOL transfer `-(amount + fee)`.
+/// Returns 0 when the balances are missing.
+fn extract_signer_balance_delta(meta_json: &serde_json::Value) -> i64 {
+    let balance = |field: &str| meta_json.get(field).and_then(|b| b.get(0)).and_then(|b| b.as_u64());
+
+    match (balance("preBalances"), balance("postBalances")) {
+        (Some(pre), Some(post)) => post as i64 - pre as i64,
+        _ => 0,
+    }
+}
+
+/// Resolve the program ID of a single instruction
+pub fn instruction_program_id(instruction: &serde_json::Value, account_keys: &[String]) -> Option<String> {
+    // For JsonParsed encoding, check for programId field first
+    if let Some(program_id) = instruction.get("programId").and_then(|p| p.as_str()) {
+        return Some(program_id.to_string());
+    }
+
+    // For parsed instructions, also check the program field (program name like "system", "spl-token")
+    if let Some(program) = instruction.get("program").and_then(|p| p.as_str()) {
+        return Some(program.to_string());
+    }
+
+    // For compiled instructions, use programIdIndex to look up in accountKeys
+    let program_idx = instruction.get("programIdIndex").and_then(|i| i.as_u64())?;
+    account_keys.get(program_idx as usize).cloned()
+}
+
+/// Parse the rewards of a block
+///
+/// Rewards without a type are dropped with a warning: every validator version in use sets it,
+/// and the schema keeps the type as part of a reward's identity.
+fn parse_block_rewards(rewards: Option<&Rewards>) -> Vec<crate::models::Reward> {
+    use crate::models::{Reward, RewardType};
+    use solana_transaction_status::RewardType as UiRewardType;
+
+    rewards
+        .into_iter()
+        .flatten()
+        .filter_map(|reward| {
+            let reward_type = match reward.reward_type {
+                Some(UiRewardType::Fee) => RewardType::Fee,
+                Some(UiRewardType::Rent) => RewardType::Rent,
+                Some(UiRewardType::Staking) => RewardType::Staking,
+                Some(UiRewardType::Voting) => RewardType::Voting,
+                None => {
+                    tracing::warn!("Dropping reward to {} without a reward type", reward.pubkey);
+                    return None;
+                }
+            };
+
+            Some(Reward {
+                pubkey: reward.pubkey.clone(),
+                lamports: reward.lamports,
+                post_balance: reward.post_balance,
+                reward_type,
+                commission: reward.commission,
+            })
+        })
+        .collect()
+}
+
 /// Extract a single block with parsed transactions
-pub async fn extract_block(rpc_client: &crate::rpc::SolanaRpcClient, slot: u64) -> Result<ExtractedBlock> {
+pub async fn extract_block<B: BlockSource + ?Sized>(
+    source: &B,
+    slot: u64,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Result<ExtractedBlock> {
     // Fetch the block from RPC
-    let block = rpc_client.fetch_block(slot).await.context(format!("Failed to fetch block at slot {}", slot))?;
+    let block = source.fetch_block(slot).await?;
+
+    let mut extracted_block = parse_block(slot, block, program_filter, limit)?;
+    resolve_token_decimals(source, &mut extracted_block.transactions).await;
+    resolve_token_owners(source, &mut extracted_block.transactions).await;
+    resolve_block_heights(source, vec![&mut extracted_block]).await;
+
+    Ok(extracted_block)
+}
+
+/// Extract several blocks with one `BlockSource::fetch_blocks` call (a batched `getBlock` request over RPC)
+///
+/// Results keep the order of `slots`. When the batch request itself fails, every slot
+/// reports that failure.
+pub async fn extract_blocks<B: BlockSource + ?Sized>(
+    source: &B,
+    slots: &[u64],
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Vec<(u64, Result<ExtractedBlock>)> {
+    let blocks = match source.fetch_blocks(slots).await {
+        Ok(blocks) => blocks,
+        Err(e) => {
+            let message = e.to_string();
+            return slots
+                .iter()
+                .map(|&slot| {
+                    (slot, Err(FetcherError::rpc(format!("Failed to fetch block at slot {}", slot), message.clone())))
+                })
+                .collect();
+        }
+    };
+
+    let mut extracted = Vec::with_capacity(blocks.len());
+    for (slot, block) in blocks {
+        let block = match block.and_then(|block| parse_block(slot, block, program_filter, limit)) {
+            Ok(mut block) => {
+                resolve_token_decimals(source, &mut block.transactions).await;
+                resolve_token_owners(source, &mut block.transactions).await;
+                Ok(block)
+            }
+            Err(e) => Err(e),
+        };
+        extracted.push((slot, block));
+    }
+    resolve_block_heights(source, extracted.iter_mut().filter_map(|(_, block)| block.as_mut().ok()).collect()).await;
+    extracted
+}
+
+/// Extract a batch of slots: a plain `getBlock` for a single slot, a batch request otherwise
+async fn extract_batch<B: BlockSource + ?Sized>(
+    source: &B,
+    slots: &[u64],
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Vec<(u64, Result<ExtractedBlock>)> {
+    match slots {
+        [slot] => vec![(*slot, extract_block(source, *slot, program_filter, limit).await)],
+        _ => extract_blocks(source, slots, program_filter, limit).await,
+    }
+}
+
+/// Parse a fetched block: metadata, rewards and transactions
+fn parse_block(
+    slot: u64,
+    mut block: UiConfirmedBlock,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Result<ExtractedBlock> {
+    if block.transactions.is_none() {
+        if let Some(signatures) = block.signatures.take() {
+            return parse_signatures_block(slot, block, signatures, limit);
+        }
+    }
 
-    // Parse transactions
-    let transactions = parse_transactions_from_block(&block)?;
+    let (transactions, filtered_transactions) = parse_transactions_from_block(&block, program_filter, limit)?;
+    let transaction_count = block.transactions.as_ref().map_or(0, |txs| txs.len());
+    let truncated_transactions = limit.map_or(0, |limit| transaction_count.saturating_sub(limit.max_transactions));
+    if truncated_transactions > 0 {
+        tracing::warn!(
+            "Block at slot {} has {} transactions, keeping the first {} (--max-txs-per-block)",
+            slot,
+            transaction_count,
+            transaction_count - truncated_transactions
+        );
+    }
 
-    // Extract block metadata
-    let extracted_block = ExtractedBlock {
+    Ok(ExtractedBlock {
         slot,
         blockhash: block.blockhash,
         parent_slot: block.parent_slot,
         block_time: block.block_time,
         block_height: block.block_height,
+        rewards: parse_block_rewards(block.rewards.as_ref()),
         transactions,
-    };
+        filtered_transactions,
+        truncated_transactions,
+        transaction_count,
+        signatures_only: false,
+    })
+}
 
-    Ok(extracted_block)
+/// Parse a block fetched with signatures only (`--signatures-only`)
+///
+/// Each transaction only carries its signature and index in the block (see
+/// `ExtractedTransaction::from_signature`), so a program filter has nothing to match and is not
+/// applied. Blocks above `limit` are skipped or truncated like full blocks.
+fn parse_signatures_block(
+    slot: u64,
+    block: UiConfirmedBlock,
+    signatures: Vec<String>,
+    limit: Option<TransactionLimit>,
+) -> Result<ExtractedBlock> {
+    let transaction_count = signatures.len();
+    let mut kept = transaction_count;
+    if let Some(limit) = limit.filter(|limit| transaction_count > limit.max_transactions) {
+        if !limit.truncate {
+            return Err(FetcherError::TooManyTransactions {
+                transactions: transaction_count,
+                limit: limit.max_transactions,
+            });
+        }
+        kept = limit.max_transactions;
+    }
+
+    let transactions = signatures
+        .into_iter()
+        .take(kept)
+        .enumerate()
+        .map(|(index, signature)| ExtractedTransaction::from_signature(signature, index))
+        .collect();
+
+    Ok(ExtractedBlock {
+        slot,
+        blockhash: block.blockhash,
+        parent_slot: block.parent_slot,
+        block_time: block.block_time,
+        block_height: block.block_height,
+        rewards: vec![],
+        transactions,
+        filtered_transactions: 0,
+        truncated_transactions: transaction_count - kept,
+        transaction_count,
+        signatures_only: true,
+    })
+}
+
+/// Stream new blocks from a WebSocket block subscription, see `SolanaRpcClient::subscribe_blocks`
+///
+/// Each block is parsed like a fetched one. Subscription errors are passed through; the
+/// stream reconnects on its own, so consumers can log them and keep reading.
+pub fn subscribe_extracted_blocks<'a>(
+    rpc_client: &'a crate::rpc::SolanaRpcClient,
+    ws_url: Option<&str>,
+    skip_votes: bool,
+    status_filter: Option<StatusFilter>,
+    sampler: Option<Sampler>,
+    program_filter: Option<&'a HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Result<impl futures::Stream<Item = Result<ExtractedBlock>> + 'a> {
+    let blocks = rpc_client.subscribe_blocks(ws_url)?;
+
+    Ok(blocks.then(move |update| async move {
+        let (slot, block) = update?;
+        let mut extracted_block = parse_block(slot, block, program_filter, limit)
+            .map_err(|e| FetcherError::ParseError(format!("Failed to parse block at slot {}: {}", slot, e)))?;
+        resolve_token_decimals(rpc_client, &mut extracted_block.transactions).await;
+        resolve_token_owners(rpc_client, &mut extracted_block.transactions).await;
+        resolve_block_heights(rpc_client, vec![&mut extracted_block]).await;
+        if skip_votes {
+            drop_vote_transactions(&mut extracted_block);
+        }
+        if let Some(filter) = status_filter {
+            filter.drop_others(&mut extracted_block);
+        }
+        if let Some(sampler) = sampler {
+            sampler.drop_others(&mut extracted_block);
+        }
+
+        Ok(extracted_block)
+    }))
 }
 
 /// Extract a range of blocks with rate limiting and progress tracking
-pub async fn extract_block_range(
-    rpc_client: &crate::rpc::SolanaRpcClient,
+///
+/// Slots in `existing_slots` (blocks already stored, see `--skip-existing`) are not fetched.
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_block_range<B: BlockSource + ?Sized>(
+    source: &B,
     start_slot: u64,
     end_slot: u64,
     rate_limit_ms: u64,
     registry: Option<&crate::etl::transform::ProgramRegistry>,
+    classifier: &dyn Classifier,
+    skip_votes: bool,
+    status_filter: Option<StatusFilter>,
+    sampler: Option<Sampler>,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+    existing_slots: Option<&HashSet<u64>>,
 ) -> Result<(Vec<ExtractedBlock>, ExtractionStats)> {
     if start_slot > end_slot {
-        anyhow::bail!("Start slot {} is greater than end slot {}", start_slot, end_slot);
+        return Err(FetcherError::InvalidInput(format!(
+            "Start slot {} is greater than end slot {}",
+            start_slot, end_slot
+        )));
     }
 
-    let total_blocks = end_slot - start_slot + 1;
-    println!("\n🔄 Starting block range extraction...");
-    println!("   Start slot: {}", format_number(start_slot));
-    println!("   End slot: {}", format_number(end_slot));
-    println!("   Total blocks: {}", format_number(total_blocks));
-    println!("   Rate limit: {}ms between requests", rate_limit_ms);
+    let total_slots = end_slot - start_slot + 1;
 
-    let mut extracted_blocks = Vec::new();
-    let mut stats = ExtractionStats {
-        blocks_fetched: 0,
-        blocks_failed: 0,
-        total_transactions: 0,
-        successful_transactions: 0,
-        failed_transactions: 0,
-        total_fees: 0,
-        sol_transfers: 0,
-        spl_token_transfers: 0,
-        dex_swaps: 0,
-        nft_operations: 0,
-        program_interactions: 0,
-        unknown_transactions: 0,
+    // Only request slots that actually produced a block
+    let mut slots = source.get_blocks(start_slot, end_slot).await?;
+    let total_blocks = slots.len() as u64;
+    let existing = skip_existing_slots(&mut slots, existing_slots);
+
+    console!("\n🔄 Starting block range extraction...");
+    console!("   Start slot: {}", format_number(start_slot));
+    console!("   End slot: {}", format_number(end_slot));
+    console!("   Total blocks: {}", format_number(total_blocks));
+    console!("   Skipped slots: {}", format_number(total_slots - total_blocks));
+    if existing_slots.is_some() {
+        console!("   Already stored: {}", format_number(existing));
+    }
+    console!("   Rate limit: {}ms between requests", rate_limit_ms);
+
+    let (blocks, mut stats) = extract_slots(
+        source,
+        &slots,
+        total_slots - total_blocks,
+        rate_limit_ms,
+        registry,
+        classifier,
+        skip_votes,
+        status_filter,
+        sampler,
+        program_filter,
+        limit,
+    )
+    .await?;
+    stats.existing_slots = existing;
+
+    Ok((blocks, stats))
+}
+
+/// Drop the slots whose blocks are already stored, returning how many were dropped
+fn skip_existing_slots(slots: &mut Vec<u64>, existing_slots: Option<&HashSet<u64>>) -> u64 {
+    let Some(existing_slots) = existing_slots else {
+        return 0;
     };
 
+    let before = slots.len();
+    slots.retain(|slot| !existing_slots.contains(slot));
+    (before - slots.len()) as u64
+}
+
+/// Extract the given slots sequentially, in order
+///
+/// The slots must have produced a block (see `BlockSource::get_blocks`); `skipped_slots`
+/// is only reported in the statistics.
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_slots<B: BlockSource + ?Sized>(
+    source: &B,
+    slots: &[u64],
+    skipped_slots: u64,
+    rate_limit_ms: u64,
+    registry: Option<&crate::etl::transform::ProgramRegistry>,
+    classifier: &dyn Classifier,
+    skip_votes: bool,
+    status_filter: Option<StatusFilter>,
+    sampler: Option<Sampler>,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+) -> Result<(Vec<ExtractedBlock>, ExtractionStats)> {
+    let total_blocks = slots.len() as u64;
+    let mut extracted_blocks = Vec::new();
+    let mut stats = ExtractionStats::new(skipped_slots);
+    let rate_limited_before = rate_limited_count(source);
+
     let start_time = std::time::Instant::now();
+    let mut progress = BlockProgress::new(total_blocks);
 
-    for slot in start_slot..=end_slot {
-        // Progress indicator
-        let progress = stats.blocks_fetched + stats.blocks_failed + 1;
-        if progress.is_multiple_of(10) || progress == total_blocks {
-            let elapsed = start_time.elapsed().as_secs_f64();
-            let blocks_per_sec = progress as f64 / elapsed;
-            let eta_secs = ((total_blocks - progress) as f64 / blocks_per_sec).ceil() as u64;
-
-            println!(
-                "   📊 Progress: {}/{} blocks ({:.1}%) | {:.2} blocks/sec | ETA: {}s",
-                progress,
-                total_blocks,
-                (progress as f64 / total_blocks as f64) * 100.0,
-                blocks_per_sec,
-                eta_secs
-            );
-        }
+    let batches = slots.chunks(source.batch_size());
+    let total_batches = batches.len();
 
-        // Fetch and parse block
-        match extract_block(rpc_client, slot).await {
-            Ok(block) => {
-                // Update statistics
-                stats.blocks_fetched += 1;
-                stats.total_transactions += block.transactions.len() as u64;
+    for (position, batch) in batches.enumerate() {
+        // Fetch and parse the blocks, one request per batch
+        for (slot, result) in extract_batch(source, batch, program_filter, limit).await {
+            progress.inc();
 
-                for tx in &block.transactions {
-                    if tx.success {
-                        stats.successful_transactions += 1;
-                    } else {
-                        stats.failed_transactions += 1;
+            match result {
+                Ok(mut block) => {
+                    crate::metrics::METRICS.record_block_fetched(block.slot);
+                    stats.record_block(&block, registry, classifier);
+                    if skip_votes {
+                        stats.votes_skipped += drop_vote_transactions(&mut block);
                     }
-                    stats.total_fees += tx.fee;
-
-                    // Classify transaction using registry if available, otherwise use legacy method
-                    let tx_type = if let Some(reg) = registry {
-                        crate::etl::transform::classify_transaction_with_registry(&tx.program_ids, reg)
-                    } else {
-                        crate::etl::transform::classify_transaction(&tx.program_ids)
-                    };
-
-                    match tx_type {
-                        crate::models::TransactionType::SolTransfer => stats.sol_transfers += 1,
-                        crate::models::TransactionType::SplTokenTransfer => stats.spl_token_transfers += 1,
-                        crate::models::TransactionType::DexSwap => stats.dex_swaps += 1,
-                        crate::models::TransactionType::NftMint | crate::models::TransactionType::NftTransfer => {
-                            stats.nft_operations += 1
-                        }
-                        crate::models::TransactionType::ProgramInteraction => stats.program_interactions += 1,
-                        crate::models::TransactionType::Unknown => stats.unknown_transactions += 1,
+                    if let Some(filter) = status_filter {
+                        stats.status_filtered += filter.drop_others(&mut block);
+                    }
+                    if let Some(sampler) = sampler {
+                        stats.sampled_out += sampler.drop_others(&mut block);
                     }
+                    extracted_blocks.push(block);
+                }
+                Err(FetcherError::SlotSkipped { .. }) => {
+                    // Listed by getBlocks, but the node no longer has the block (e.g. purged from long-term storage)
+                    tracing::debug!("Slot {} was skipped, nothing to extract", slot);
+                    stats.skipped_slots += 1;
+                }
+                Err(e @ FetcherError::TooManyTransactions { .. }) => {
+                    tracing::warn!("Skipping block at slot {}: {}", slot, e);
+                    stats.oversized_blocks += 1;
+                }
+                Err(e @ FetcherError::BlockNotAvailable { .. }) => {
+                    // Asking again would get the same answer, so the slot is not queued as failed
+                    tracing::warn!("Skipping block at slot {}: {}", slot, e);
+                    stats.unavailable_blocks += 1;
+                }
+                Err(e) => {
+                    crate::metrics::METRICS.record_block_failed();
+                    stats.blocks_failed += 1;
+                    tracing::warn!("Failed to extract block at slot {}: {}", slot, e);
+                    stats.failed_slots.push((slot, format!("{:#}", e)));
                 }
+            }
+        }
+
+        // Rate limiting (skip on last request), backing off further while the provider returns 429s
+        let delay = effective_delay(source, rate_limit_ms);
+        if position + 1 < total_batches && !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+
+    progress.finish();
+    stats.record_throttle(source, rate_limit_ms, rate_limited_before);
+    stats.print_summary(start_time.elapsed().as_secs_f64(), registry);
+
+    Ok((extracted_blocks, stats))
+}
+
+/// Extract a range of blocks concurrently with a bounded number of in-flight requests
+///
+/// Up to `concurrency` blocks are fetched in parallel. Request starts are spaced at least
+/// `rate_limit_ms` apart across all workers so the aggregate request rate stays within RPC quotas,
+/// and further apart while the provider is answering with HTTP 429.
+/// The returned blocks are sorted by slot, matching the ordering of `extract_block_range`.
+/// Slots in `existing_slots` are not fetched.
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_block_range_concurrent<B: BlockSource + ?Sized>(
+    source: &B,
+    start_slot: u64,
+    end_slot: u64,
+    concurrency: usize,
+    rate_limit_ms: u64,
+    registry: Option<&crate::etl::transform::ProgramRegistry>,
+    classifier: &dyn Classifier,
+    skip_votes: bool,
+    status_filter: Option<StatusFilter>,
+    sampler: Option<Sampler>,
+    program_filter: Option<&HashSet<String>>,
+    limit: Option<TransactionLimit>,
+    existing_slots: Option<&HashSet<u64>>,
+) -> Result<(Vec<ExtractedBlock>, ExtractionStats)> {
+    if start_slot > end_slot {
+        return Err(FetcherError::InvalidInput(format!(
+            "Start slot {} is greater than end slot {}",
+            start_slot, end_slot
+        )));
+    }
+
+    let total_slots = end_slot - start_slot + 1;
+
+    // Only request slots that actually produced a block
+    let mut slots = source.get_blocks(start_slot, end_slot).await?;
+    let total_blocks = slots.len() as u64;
+    let existing = skip_existing_slots(&mut slots, existing_slots);
+
+    console!("\n🔄 Starting concurrent block range extraction...");
+    console!("   Start slot: {}", format_number(start_slot));
+    console!("   End slot: {}", format_number(end_slot));
+    console!("   Total blocks: {}", format_number(total_blocks));
+    console!("   Skipped slots: {}", format_number(total_slots - total_blocks));
+    if existing_slots.is_some() {
+        console!("   Already stored: {}", format_number(existing));
+    }
+    console!("   Concurrency: {} requests", concurrency);
+    console!("   Rate limit: {}ms between requests", rate_limit_ms);
+
+    let mut extracted_blocks = Vec::new();
+    let mut stats = ExtractionStats::new(total_slots - total_blocks);
+    stats.existing_slots = existing;
+    let rate_limited_before = rate_limited_count(source);
 
+    // Earliest start of the next request, shared so request starts are spaced out across all workers
+    let next_request = Mutex::new(Instant::now());
+
+    let start_time = std::time::Instant::now();
+    let mut progress = BlockProgress::new(slots.len() as u64);
+
+    let mut results = stream::iter(slots.chunks(source.batch_size()))
+        .map(|batch| {
+            let next_request = &next_request;
+            async move {
+                wait_for_turn(next_request, effective_delay(source, rate_limit_ms)).await;
+                extract_batch(source, batch, program_filter, limit).await
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .flat_map(stream::iter);
+
+    while let Some((slot, result)) = results.next().await {
+        progress.inc();
+
+        match result {
+            Ok(mut block) => {
+                crate::metrics::METRICS.record_block_fetched(block.slot);
+                stats.record_block(&block, registry, classifier);
+                if skip_votes {
+                    stats.votes_skipped += drop_vote_transactions(&mut block);
+                }
+                if let Some(filter) = status_filter {
+                    stats.status_filtered += filter.drop_others(&mut block);
+                }
+                if let Some(sampler) = sampler {
+                    stats.sampled_out += sampler.drop_others(&mut block);
+                }
                 extracted_blocks.push(block);
             }
+            Err(FetcherError::SlotSkipped { .. }) => {
+                // Listed by getBlocks, but the node no longer has the block (e.g. purged from long-term storage)
+                tracing::debug!("Slot {} was skipped, nothing to extract", slot);
+                stats.skipped_slots += 1;
+            }
+            Err(e @ FetcherError::TooManyTransactions { .. }) => {
+                tracing::warn!("Skipping block at slot {}: {}", slot, e);
+                stats.oversized_blocks += 1;
+            }
+            Err(e @ FetcherError::BlockNotAvailable { .. }) => {
+                // Asking again would get the same answer, so the slot is not queued as failed
+                tracing::warn!("Skipping block at slot {}: {}", slot, e);
+                stats.unavailable_blocks += 1;
+            }
             Err(e) => {
+                crate::metrics::METRICS.record_block_failed();
                 stats.blocks_failed += 1;
                 tracing::warn!("Failed to extract block at slot {}: {}", slot, e);
+                stats.failed_slots.push((slot, format!("{:#}", e)));
             }
         }
-
-        // Rate limiting (skip on last block)
-        if slot < end_slot && rate_limit_ms > 0 {
-            sleep(Duration::from_millis(rate_limit_ms)).await;
-        }
     }
 
-    let total_time = start_time.elapsed().as_secs_f64();
-    let avg_blocks_per_sec = stats.blocks_fetched as f64 / total_time;
+    progress.finish();
 
-    println!("\n✅ Block range extraction complete!");
-    println!("   ⏱️  Total time: {:.2}s", total_time);
-    println!("   📦 Blocks fetched: {}", format_number(stats.blocks_fetched));
-    println!("   ❌ Blocks failed: {}", format_number(stats.blocks_failed));
-    println!("   ⚡ Average speed: {:.2} blocks/sec", avg_blocks_per_sec);
-    println!("   📝 Total transactions: {}", format_number(stats.total_transactions));
-    println!("   ✅ Successful: {}", format_number(stats.successful_transactions));
-    println!("   ❌ Failed: {}", format_number(stats.failed_transactions));
-    println!("   💰 Total fees: {} SOL", (stats.total_fees as f64 / 1_000_000_000.0));
+    // Blocks complete out of order, restore slot ordering
+    extracted_blocks.sort_by_key(|b| b.slot);
 
-    // Transaction type breakdown
-    println!("\n📊 Transaction Classification:");
-    println!("   💸 SOL Transfers: {}", format_number(stats.sol_transfers));
-    println!("   🪙  Token Transfers: {}", format_number(stats.spl_token_transfers));
-    println!("   🔄 DEX Swaps: {}", format_number(stats.dex_swaps));
-    println!("   🖼️  NFT Operations: {}", format_number(stats.nft_operations));
-    println!("   ⚙️  Program Interactions: {}", format_number(stats.program_interactions));
-    println!("   ❓ Unknown: {}", format_number(stats.unknown_transactions));
+    stats.record_throttle(source, rate_limit_ms, rate_limited_before);
+    stats.print_summary(start_time.elapsed().as_secs_f64(), registry);
 
     Ok((extracted_blocks, stats))
 }
 
-/// Format a number with thousand separators
-fn format_number(n: u64) -> String {
-    let s = n.to_string();
-    let mut result = String::new();
+/// Remove validator vote transactions from a block, returning how many were removed
+///
+/// Votes are still counted by `record_block` before they are dropped, so the totals
+/// in the summary include them.
+fn drop_vote_transactions(block: &mut ExtractedBlock) -> u64 {
+    let before = block.transactions.len();
+    block.transactions.retain(|tx| !crate::etl::transform::is_vote_transaction(&tx.program_ids));
+    (before - block.transactions.len()) as u64
+}
 
-    for (count, c) in s.chars().rev().enumerate() {
-        if count > 0 && count % 3 == 0 {
-            result.push(',');
-        }
+/// Limit on the transactions of one block (`--max-txs-per-block`)
+///
+/// Guards against pathological blocks: a block with more than `max_transactions` transactions
+/// is skipped, or truncated to its first `max_transactions` transactions, before parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TransactionLimit {
+    pub max_transactions: usize,
+    pub truncate: bool, // Keep the first transactions instead of skipping the block (`--truncate-large-blocks`)
+}
+
+/// Which transactions to keep by execution result (`--only-success` / `--only-failed`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusFilter {
+    Successful,
+    Failed,
+}
+
+impl StatusFilter {
+    /// Whether a transaction passes the filter
+    pub fn keeps(&self, transaction: &ExtractedTransaction) -> bool {
+        match self {
+            Self::Successful => transaction.success,
+            Self::Failed => !transaction.success,
+        }
+    }
+
+    /// Remove the transactions the filter does not keep from a block, returning how many were removed
+    ///
+    /// Like skipped votes, they are counted by `record_block` before they are dropped.
+    fn drop_others(&self, block: &mut ExtractedBlock) -> u64 {
+        let before = block.transactions.len();
+        block.transactions.retain(|tx| self.keeps(tx));
+        (before - block.transactions.len()) as u64
+    }
+}
+
+/// Deterministic random sample of transactions (`--sample-rate`, `--sample-seed`)
+///
+/// Whether a transaction is kept depends only on the seed and its signature, through a fixed
+/// hash mapped to [0, 1): reruns with the same seed keep exactly the same transactions,
+/// whatever the batch size, concurrency or fetch order, and a transaction seen twice (e.g. on
+/// a fork re-check) gets the same answer both times.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Sampler {
+    rate: f64,
+    seed: u64,
+}
+
+impl Sampler {
+    /// Keep each transaction with probability `rate`, clamped to 0.0..=1.0
+    pub fn new(rate: f64, seed: u64) -> Self {
+        Self { rate: rate.clamp(0.0, 1.0), seed }
+    }
+
+    /// Probability of keeping a transaction
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Seed of the sample
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether the transaction with this signature is in the sample
+    pub fn keeps(&self, signature: &str) -> bool {
+        // The top 53 bits give a uniform f64 in [0, 1)
+        let unit = (sample_hash(self.seed, signature.as_bytes()) >> 11) as f64 / (1u64 << 53) as f64;
+        unit < self.rate
+    }
+
+    /// Remove the transactions outside the sample from a block, returning how many were removed
+    ///
+    /// Like skipped votes, they are counted by `record_block` before they are dropped.
+    fn drop_others(&self, block: &mut ExtractedBlock) -> u64 {
+        let before = block.transactions.len();
+        block.transactions.retain(|tx| self.keeps(&tx.signature));
+        (before - block.transactions.len()) as u64
+    }
+}
+
+/// Seeded 64-bit hash that is stable across platforms and Rust versions
+///
+/// FNV-1a over the seed and the bytes, followed by the SplitMix64 finalizer to spread FNV's
+/// weak low bits over the whole word. `std`'s `DefaultHasher` is not guaranteed to stay the
+/// same between releases, which would change the sample.
+fn sample_hash(seed: u64, bytes: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in seed.to_le_bytes().iter().chain(bytes) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// Maximum signatures returned by one `getSignaturesForAddress` call
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+/// Extract the transactions involving one address instead of whole blocks
+///
+/// Pages through `getSignaturesForAddress` (newest first, 1,000 per call) until `limit`
+/// signatures are collected or `min_slot` is passed; signatures above `max_slot` are ignored.
+/// Each transaction is fetched with `getTransaction` and parsed like block transactions. The
+/// transactions are grouped into their blocks, whose metadata and transaction order come from a
+/// signatures-only `getBlock`, so they load into the same tables as a block-range run.
+/// Failed signature lookups are skipped with a warning.
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_transactions_for_address(
+    rpc_client: &crate::rpc::SolanaRpcClient,
+    address: &str,
+    min_slot: Option<u64>,
+    max_slot: Option<u64>,
+    limit: usize,
+    rate_limit_ms: u64,
+    registry: Option<&crate::etl::transform::ProgramRegistry>,
+    classifier: &dyn Classifier,
+    skip_votes: bool,
+    status_filter: Option<StatusFilter>,
+    sampler: Option<Sampler>,
+    program_filter: Option<&HashSet<String>>,
+) -> Result<(Vec<ExtractedBlock>, ExtractionStats)> {
+    console!("\n🔄 Starting address extraction...");
+    console!("   Address: {}", address);
+    console!("   Limit: {} transactions", format_number(limit as u64));
+    console!("   Rate limit: {}ms between requests", rate_limit_ms);
+
+    let start_time = std::time::Instant::now();
+    let rate_limited_before = rpc_client.throttle().rate_limited_count();
+
+    // Collect signatures page by page, newest first
+    let mut signatures_by_slot: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    let mut collected = 0;
+    let mut before: Option<String> = None;
+    'pages: while collected < limit {
+        let page_limit = (limit - collected).min(SIGNATURES_PAGE_LIMIT);
+        let page = rpc_client.get_signatures_for_address(address, before.as_deref(), None, page_limit).await?;
+        let page_len = page.len();
+        before = page.last().map(|status| status.signature.clone());
+
+        for status in page {
+            if min_slot.is_some_and(|min| status.slot < min) {
+                break 'pages;
+            }
+            if max_slot.is_some_and(|max| status.slot > max) {
+                continue;
+            }
+            signatures_by_slot.entry(status.slot).or_default().push(status.signature);
+            collected += 1;
+        }
+
+        if page_len < page_limit {
+            break;
+        }
+        sleep(effective_delay(rpc_client, rate_limit_ms)).await;
+    }
+
+    console!(
+        "   Found {} transactions in {} blocks",
+        format_number(collected as u64),
+        format_number(signatures_by_slot.len() as u64)
+    );
+
+    let mut extracted_blocks = Vec::new();
+    let mut stats = ExtractionStats::new(0);
+
+    for (slot, signatures) in signatures_by_slot {
+        match extract_address_block(rpc_client, slot, &signatures, rate_limit_ms, program_filter).await {
+            Ok(mut block) => {
+                crate::metrics::METRICS.record_block_fetched(block.slot);
+                stats.record_block(&block, registry, classifier);
+                if skip_votes {
+                    stats.votes_skipped += drop_vote_transactions(&mut block);
+                }
+                if let Some(filter) = status_filter {
+                    stats.status_filtered += filter.drop_others(&mut block);
+                }
+                if let Some(sampler) = sampler {
+                    stats.sampled_out += sampler.drop_others(&mut block);
+                }
+                extracted_blocks.push(block);
+            }
+            Err(e) => {
+                crate::metrics::METRICS.record_block_failed();
+                stats.blocks_failed += 1;
+                tracing::warn!("Failed to extract block at slot {}: {}", slot, e);
+                stats.failed_slots.push((slot, format!("{:#}", e)));
+            }
+        }
+    }
+
+    stats.record_throttle(rpc_client, rate_limit_ms, rate_limited_before);
+    stats.print_summary(start_time.elapsed().as_secs_f64(), registry);
+
+    Ok((extracted_blocks, stats))
+}
+
+/// Build a block holding only the given transactions, at their real positions in the block
+async fn extract_address_block(
+    rpc_client: &crate::rpc::SolanaRpcClient,
+    slot: u64,
+    signatures: &[String],
+    rate_limit_ms: u64,
+    program_filter: Option<&HashSet<String>>,
+) -> Result<ExtractedBlock> {
+    let header = rpc_client.fetch_block_signatures(slot).await?;
+    let block_signatures = header.signatures.unwrap_or_default();
+
+    let mut transactions = Vec::new();
+    let mut filtered_transactions = 0;
+    for signature in signatures {
+        sleep(effective_delay(rpc_client, rate_limit_ms)).await;
+
+        let index = block_signatures
+            .iter()
+            .position(|s| s == signature)
+            .ok_or_else(|| FetcherError::ParseError(format!("Signature {} not found in block {}", signature, slot)))?;
+
+        match rpc_client.fetch_transaction(signature).await {
+            Ok(confirmed) => match parse_confirmed_transaction(&confirmed, index) {
+                Ok(extracted) if program_filter.is_some_and(|programs| !touches_any_program(&extracted, programs)) => {
+                    filtered_transactions += 1;
+                }
+                Ok(extracted) => transactions.push(extracted),
+                Err(e) => tracing::warn!("Failed to parse transaction {}: {}", signature, e),
+            },
+            Err(e) => tracing::warn!("Skipping transaction {}: {:#}", signature, e),
+        }
+    }
+    transactions.sort_by_key(|tx| tx.transaction_index);
+    resolve_token_decimals(rpc_client, &mut transactions).await;
+    resolve_token_owners(rpc_client, &mut transactions).await;
+
+    let mut block = ExtractedBlock {
+        slot,
+        blockhash: header.blockhash,
+        parent_slot: header.parent_slot,
+        block_time: header.block_time,
+        block_height: header.block_height,
+        rewards: vec![],
+        transactions,
+        filtered_transactions,
+        truncated_transactions: 0,
+        transaction_count: block_signatures.len(),
+        signatures_only: false,
+    };
+    resolve_block_heights(rpc_client, vec![&mut block]).await;
+
+    Ok(block)
+}
+
+/// A transaction fetched on its own by signature, with the block it landed in
+#[derive(Debug, Clone)]
+pub struct FetchedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: ExtractedTransaction,
+}
+
+/// Parse a transaction returned by `getTransaction`
+///
+/// The response does not say where the transaction sits in its block; `index` is its position
+/// when the caller knows it (e.g. from the block's signatures).
+pub fn parse_confirmed_transaction(
+    confirmed: &EncodedConfirmedTransactionWithStatusMeta,
+    index: usize,
+) -> Result<ExtractedTransaction> {
+    parse_single_transaction(&confirmed.transaction, index)
+}
+
+/// Fetch one transaction by signature and parse it, resolving the decimals of its token transfer
+///
+/// Fails with `FetcherError::TransactionNotFound` when the RPC node does not have it. The
+/// position in the block is not looked up, so `transaction_index` is 0.
+pub async fn extract_transaction(
+    rpc_client: &crate::rpc::SolanaRpcClient,
+    signature: &str,
+) -> Result<FetchedTransaction> {
+    let confirmed = rpc_client.fetch_transaction(signature).await?;
+    let mut transactions = vec![parse_confirmed_transaction(&confirmed, 0)?];
+    resolve_token_decimals(rpc_client, &mut transactions).await;
+    resolve_token_owners(rpc_client, &mut transactions).await;
+
+    Ok(FetchedTransaction {
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        transaction: transactions.remove(0),
+    })
+}
+
+/// Requests the source's throttle has seen rate-limited so far, 0 without a throttle
+fn rate_limited_count<B: BlockSource + ?Sized>(source: &B) -> u64 {
+    source.throttle().map_or(0, |throttle| throttle.rate_limited_count())
+}
+
+/// Delay before the next request: the configured rate limit or the adaptive 429 backoff, whichever is longer
+fn effective_delay<B: BlockSource + ?Sized>(source: &B, rate_limit_ms: u64) -> Duration {
+    let throttle_delay = source.throttle().map_or(Duration::ZERO, |throttle| throttle.current_delay());
+    Duration::from_millis(rate_limit_ms).max(throttle_delay)
+}
+
+/// Wait until this worker may start a request and reserve the next slot `delay` later
+async fn wait_for_turn(next_request: &Mutex<Instant>, delay: Duration) {
+    let start = {
+        let mut next = next_request.lock().await;
+        let start = (*next).max(Instant::now());
+        *next = start + delay;
+        start
+    };
+
+    sleep_until(start).await;
+}
+
+/// Progress of a block extraction, advanced once per fetched, failed or skipped block
+///
+/// With `--progress-bar` and stdout on a terminal, a single `indicatif` line shows the blocks
+/// processed, the rate and the ETA. Otherwise a progress line is printed every 10 blocks and on
+/// the final block, or logged as a `tracing` event when console output is off or stdout is not
+/// a terminal (piped, CI).
+enum BlockProgress {
+    Bar(ProgressBar),
+    Lines { processed: u64, total_blocks: u64, start_time: std::time::Instant, log: bool },
+}
+
+impl BlockProgress {
+    fn new(total_blocks: u64) -> Self {
+        let console = crate::output::console_enabled();
+        let terminal = std::io::stdout().is_terminal();
+
+        if crate::output::progress_bar_enabled() && console && terminal {
+            let bar = ProgressBar::with_draw_target(Some(total_blocks), ProgressDrawTarget::stdout());
+            let style = ProgressStyle::with_template(
+                "   📊 [{elapsed_precise}] {bar:40} {pos}/{len} blocks ({percent}%) | {per_sec} | ETA: {eta}",
+            )
+            .expect("valid progress bar template");
+            bar.set_style(style);
+            bar.enable_steady_tick(Duration::from_millis(200));
+            return Self::Bar(bar);
+        }
+
+        Self::Lines {
+            processed: 0,
+            total_blocks,
+            start_time: std::time::Instant::now(),
+            log: !console || (crate::output::progress_bar_enabled() && !terminal),
+        }
+    }
+
+    fn inc(&mut self) {
+        let (processed, total_blocks, start_time, log) = match self {
+            Self::Bar(bar) => return bar.inc(1),
+            Self::Lines { processed, total_blocks, start_time, log } => (processed, *total_blocks, *start_time, *log),
+        };
+
+        *processed += 1;
+        let progress = *processed;
+        if !progress.is_multiple_of(10) && progress != total_blocks {
+            return;
+        }
+
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let blocks_per_sec = progress as f64 / elapsed;
+        let eta_secs = (total_blocks.saturating_sub(progress) as f64 / blocks_per_sec).ceil() as u64;
+        let percent = (progress as f64 / total_blocks as f64) * 100.0;
+
+        if log {
+            tracing::info!(progress, total_blocks, percent, blocks_per_sec, eta_secs, "Extraction progress");
+            return;
+        }
+        console!(
+            "   📊 Progress: {}/{} blocks ({:.1}%) | {:.2} blocks/sec | ETA: {}s",
+            progress,
+            total_blocks,
+            percent,
+            blocks_per_sec,
+            eta_secs
+        );
+    }
+
+    /// Remove the bar so the summary starts on a clean line
+    fn finish(&self) {
+        if let Self::Bar(bar) = self {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl ExtractionStats {
+    /// Create empty statistics for a range with the given number of skipped slots
+    fn new(skipped_slots: u64) -> Self {
+        Self {
+            blocks_fetched: 0,
+            blocks_failed: 0,
+            failed_slots: Vec::new(),
+            skipped_slots,
+            existing_slots: 0,
+            effective_delay_ms: 0,
+            rate_limited_requests: 0,
+            total_transactions: 0,
+            successful_transactions: 0,
+            failed_transactions: 0,
+            total_fees: 0,
+            total_priority_fees: 0,
+            legacy_transactions: 0,
+            versioned_transactions: 0,
+            sol_transfers: 0,
+            account_creations: 0,
+            spl_token_transfers: 0,
+            token_mints: 0,
+            token_burns: 0,
+            dex_swaps: 0,
+            liquidity_adds: 0,
+            liquidity_removes: 0,
+            memos: 0,
+            stakes: 0,
+            liquid_staking: 0,
+            votes: 0,
+            votes_skipped: 0,
+            filtered_transactions: 0,
+            status_filtered: 0,
+            sampled_out: 0,
+            oversized_blocks: 0,
+            unavailable_blocks: 0,
+            nft_operations: 0,
+            program_interactions: 0,
+            unknown_transactions: 0,
+            custom_types: HashMap::new(),
+            program_activity: HashMap::new(),
+        }
+    }
+
+    /// Update statistics with a successfully extracted block
+    fn record_block(
+        &mut self,
+        block: &ExtractedBlock,
+        registry: Option<&crate::etl::transform::ProgramRegistry>,
+        classifier: &dyn Classifier,
+    ) {
+        self.blocks_fetched += 1;
+        if block.truncated_transactions > 0 {
+            self.oversized_blocks += 1;
+        }
+        self.total_transactions += block.transactions.len() as u64;
+        self.filtered_transactions += block.filtered_transactions as u64;
+        // Nothing but the signature is known of transactions fetched with signatures only
+        if block.signatures_only {
+            return;
+        }
+
+        for tx in &block.transactions {
+            if tx.success {
+                self.successful_transactions += 1;
+            } else {
+                self.failed_transactions += 1;
+            }
+            self.total_fees += tx.fee;
+            self.total_priority_fees += tx.fee - tx.base_fee();
+            if tx.version.is_some() {
+                self.versioned_transactions += 1;
+            } else {
+                self.legacy_transactions += 1;
+            }
+
+            let touched: HashSet<&String> = tx.program_ids.iter().chain(&tx.inner_program_ids).collect();
+            for program_id in touched {
+                *self.program_activity.entry(program_id.clone()).or_insert(0) += 1;
+            }
+
+            // Classify transaction using registry if available, otherwise use legacy method
+            let tx_type = if let Some(reg) = registry {
+                classifier.classify(tx, reg)
+            } else {
+                crate::etl::transform::classify_transaction(&tx.program_ids)
+            };
+
+            match tx_type {
+                crate::models::TransactionType::SolTransfer => self.sol_transfers += 1,
+                crate::models::TransactionType::AccountCreation => self.account_creations += 1,
+                crate::models::TransactionType::SplTokenTransfer => self.spl_token_transfers += 1,
+                crate::models::TransactionType::TokenMint => self.token_mints += 1,
+                crate::models::TransactionType::TokenBurn => self.token_burns += 1,
+                crate::models::TransactionType::DexSwap => self.dex_swaps += 1,
+                crate::models::TransactionType::LiquidityAdd => self.liquidity_adds += 1,
+                crate::models::TransactionType::LiquidityRemove => self.liquidity_removes += 1,
+                crate::models::TransactionType::Memo => self.memos += 1,
+                crate::models::TransactionType::Stake => self.stakes += 1,
+                crate::models::TransactionType::LiquidStaking => self.liquid_staking += 1,
+                crate::models::TransactionType::Vote => self.votes += 1,
+                crate::models::TransactionType::NftMint | crate::models::TransactionType::NftTransfer => {
+                    self.nft_operations += 1
+                }
+                crate::models::TransactionType::ProgramInteraction => self.program_interactions += 1,
+                crate::models::TransactionType::Unknown => self.unknown_transactions += 1,
+                crate::models::TransactionType::Custom(program_type) => {
+                    *self.custom_types.entry(program_type).or_insert(0) += 1
+                }
+            }
+        }
+    }
+
+    /// Record the request delay in effect at the end of the range and the 429s seen during it
+    fn record_throttle<B: BlockSource + ?Sized>(&mut self, source: &B, rate_limit_ms: u64, rate_limited_before: u64) {
+        self.effective_delay_ms = effective_delay(source, rate_limit_ms).as_millis() as u64;
+        self.rate_limited_requests = rate_limited_count(source) - rate_limited_before;
+    }
+
+    /// Transactions kept for loading: those seen, minus skipped votes, status-filtered and sampled-out ones
+    pub fn kept_transactions(&self) -> u64 {
+        self.total_transactions - self.votes_skipped - self.status_filtered - self.sampled_out
+    }
+
+    /// The most active programs, by number of transactions touching them, busiest first
+    ///
+    /// Ties are broken by program ID so the ranking is stable between runs.
+    pub fn top_programs(&self, limit: usize) -> Vec<(&str, u64)> {
+        let mut programs: Vec<(&str, u64)> =
+            self.program_activity.iter().map(|(program_id, count)| (program_id.as_str(), *count)).collect();
+        programs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        programs.truncate(limit);
+        programs
+    }
+
+    /// Print the end-of-range extraction summary
+    fn print_summary(&self, total_time: f64, registry: Option<&crate::etl::transform::ProgramRegistry>) {
+        let avg_blocks_per_sec = self.blocks_fetched as f64 / total_time;
+        crate::metrics::METRICS.set_blocks_per_second(avg_blocks_per_sec);
+
+        console!("\n✅ Block range extraction complete!");
+        console!("   ⏱️  Total time: {:.2}s", total_time);
+        console!("   📦 Blocks fetched: {}", format_number(self.blocks_fetched));
+        console!("   ❌ Blocks failed: {}", format_number(self.blocks_failed));
+        console!("   ⏭️  Skipped slots: {}", format_number(self.skipped_slots));
+        console!("   ⚡ Average speed: {:.2} blocks/sec", avg_blocks_per_sec);
+        console!(
+            "   🐢 Request delay: {}ms ({} rate-limited responses)",
+            self.effective_delay_ms,
+            format_number(self.rate_limited_requests)
+        );
+        console!("   📝 Total transactions: {}", format_number(self.total_transactions));
+        console!("   ✅ Successful: {}", format_number(self.successful_transactions));
+        console!("   ❌ Failed: {}", format_number(self.failed_transactions));
+        if self.filtered_transactions > 0 {
+            console!("   🔎 Filtered out (--programs): {}", format_number(self.filtered_transactions));
+        }
+        if self.status_filtered > 0 {
+            console!(
+                "   🚦 Dropped by status filter: {} (kept {} of {})",
+                format_number(self.status_filtered),
+                format_number(self.kept_transactions()),
+                format_number(self.total_transactions)
+            );
+        }
+        if self.oversized_blocks > 0 {
+            console!("   🐘 Blocks over --max-txs-per-block: {}", format_number(self.oversized_blocks));
+        }
+        if self.unavailable_blocks > 0 {
+            console!("   🕳️  Blocks not available from the node: {}", format_number(self.unavailable_blocks));
+        }
+        if self.sampled_out > 0 {
+            console!(
+                "   🎲 Sampled: kept {} of {} observed ({} left out)",
+                format_number(self.kept_transactions()),
+                format_number(self.total_transactions),
+                format_number(self.sampled_out)
+            );
+        }
+        console!(
+            "   💰 Total fees: {} SOL ({} SOL priority)",
+            (self.total_fees as f64 / 1_000_000_000.0),
+            (self.total_priority_fees as f64 / 1_000_000_000.0)
+        );
+        console!(
+            "   🧾 Legacy / versioned: {} / {}",
+            format_number(self.legacy_transactions),
+            format_number(self.versioned_transactions)
+        );
+
+        // Transaction type breakdown
+        console!("\n📊 Transaction Classification:");
+        console!("   💸 SOL Transfers: {}", format_number(self.sol_transfers));
+        console!("   🆕 Account Creations: {}", format_number(self.account_creations));
+        console!("   🪙  Token Transfers: {}", format_number(self.spl_token_transfers));
+        console!("   🏭 Token Mints/Burns: {} / {}", format_number(self.token_mints), format_number(self.token_burns));
+        console!("   🔄 DEX Swaps: {}", format_number(self.dex_swaps));
+        console!(
+            "   💧 Liquidity Adds/Removes: {} / {}",
+            format_number(self.liquidity_adds),
+            format_number(self.liquidity_removes)
+        );
+        console!("   📝 Memos: {}", format_number(self.memos));
+        console!("   🥩 Stake: {}", format_number(self.stakes));
+        console!("   🌊 Liquid Staking: {}", format_number(self.liquid_staking));
+        console!("   🗳️  Votes: {} ({} skipped)", format_number(self.votes), format_number(self.votes_skipped));
+        console!("   🖼️  NFT Operations: {}", format_number(self.nft_operations));
+        console!("   ⚙️  Program Interactions: {}", format_number(self.program_interactions));
+        console!("   ❓ Unknown: {}", format_number(self.unknown_transactions));
+
+        let mut custom_types: Vec<(&String, &u64)> = self.custom_types.iter().collect();
+        custom_types.sort();
+        for (program_type, count) in custom_types {
+            console!("   🏷️  {}: {}", program_type, format_number(*count));
+        }
+
+        let top_programs = self.top_programs(TOP_PROGRAMS);
+        if !top_programs.is_empty() {
+            console!("\n🏆 Most Active Programs:");
+            for (rank, (program_id, count)) in top_programs.into_iter().enumerate() {
+                let name = registry.and_then(|r| r.get_program_name(program_id)).unwrap_or(program_id);
+                console!("   {:>2}. {}: {} transactions", rank + 1, name, format_number(count));
+            }
+        }
+    }
+}
+
+/// Format a number with thousand separators
+fn format_number(n: u64) -> String {
+    let s = n.to_string();
+    let mut result = String::new();
+
+    for (count, c) in s.chars().rev().enumerate() {
+        if count > 0 && count % 3 == 0 {
+            result.push(',');
+        }
         result.push(c);
     }
 
     result.chars().rev().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::etl::transform::DefaultClassifier;
+
+    /// Block source serving canned blocks, so extraction runs without an RPC
+    #[derive(Default)]
+    struct MockBlockSource {
+        blocks: BTreeMap<u64, UiConfirmedBlock>,
+        failing: HashSet<u64>,     // Produced slots whose fetch fails with an RPC error
+        unavailable: HashSet<u64>, // Produced slots whose block the node does not serve
+    }
+
+    impl MockBlockSource {
+        fn with_block(mut self, slot: u64, transactions: Vec<serde_json::Value>) -> Self {
+            let block = serde_json::from_value(serde_json::json!({
+                "previousBlockhash": format!("hash{}", slot - 1),
+                "blockhash": format!("hash{}", slot),
+                "parentSlot": slot - 1,
+                "transactions": transactions,
+                "rewards": [],
+                "blockTime": 1_700_000_000,
+                "blockHeight": slot
+            }))
+            .unwrap();
+            self.blocks.insert(slot, block);
+            self
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BlockSource for MockBlockSource {
+        async fn fetch_block(&self, slot: u64) -> Result<UiConfirmedBlock> {
+            if self.failing.contains(&slot) {
+                return Err(FetcherError::rpc(format!("Failed to fetch block at slot {}", slot), "connection reset"));
+            }
+            if self.unavailable.contains(&slot) {
+                return Err(FetcherError::BlockNotAvailable { slot });
+            }
+            self.blocks.get(&slot).cloned().ok_or(FetcherError::SlotSkipped { slot })
+        }
+
+        async fn get_latest_slot(&self) -> Result<u64> {
+            Ok(self.blocks.keys().chain(&self.failing).chain(&self.unavailable).max().copied().unwrap_or(0))
+        }
+
+        async fn get_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>> {
+            let mut slots: Vec<u64> =
+                self.blocks.keys().chain(&self.failing).chain(&self.unavailable).copied().collect();
+            slots.retain(|slot| (start_slot..=end_slot).contains(slot));
+            slots.sort_unstable();
+            Ok(slots)
+        }
+    }
+
+    /// A transaction invoking `programs` at the top level, in the RPC's JsonParsed shape
+    fn transaction_json(signature: &str, programs: &[&str], success: bool) -> serde_json::Value {
+        let instructions: Vec<serde_json::Value> = programs
+            .iter()
+            .map(|program| serde_json::json!({ "programId": program, "accounts": [], "data": "" }))
+            .collect();
+        serde_json::json!({
+            "transaction": {
+                "signatures": [signature],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Payer1111111111111111111111111111111111111", "signer": true, "writable": true, "source": "transaction" }
+                    ],
+                    "instructions": instructions,
+                    "recentBlockhash": "1111"
+                }
+            },
+            "meta": {
+                "err": if success { serde_json::Value::Null } else { serde_json::json!({ "InstructionError": [0, { "Custom": 1 }] }) },
+                "status": if success { serde_json::json!({ "Ok": null }) } else { serde_json::json!({ "Err": { "InstructionError": [0, { "Custom": 1 }] } }) },
+                "fee": 5000,
+                "preBalances": [1_000_000],
+                "postBalances": [995_000],
+                "innerInstructions": [],
+                "logMessages": [],
+                "preTokenBalances": [],
+                "postTokenBalances": [],
+                "rewards": []
+            },
+            "version": "legacy"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_extract_block_range_from_block_source() {
+        let registry = crate::etl::transform::ProgramRegistry::from_database(
+            crate::etl::transform::BUILTIN_PROGRAMS
+                .iter()
+                .map(|(program_id, program_name, program_type)| crate::db::ProgramInfo {
+                    program_id: program_id.to_string(),
+                    program_name: program_name.to_string(),
+                    program_type: Some(program_type.to_string()),
+                })
+                .collect(),
+        );
+        let source = MockBlockSource {
+            failing: HashSet::from([13]),
+            unavailable: HashSet::from([14]),
+            ..MockBlockSource::default()
+        }
+        .with_block(
+            10,
+            vec![
+                transaction_json("vote", &["Vote111111111111111111111111111111111111111"], true),
+                transaction_json("memo", &["MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"], true),
+            ],
+        )
+        .with_block(
+            12,
+            vec![
+                transaction_json("transfer", &["11111111111111111111111111111111"], false),
+                transaction_json("swap", &[JUPITER_V6], true),
+            ],
+        );
+
+        // Slot 11 was skipped by its leader, slot 13 fails to fetch, slot 14 is not served
+        let (blocks, stats) = extract_block_range(
+            &source,
+            10,
+            14,
+            0,
+            Some(&registry),
+            &DefaultClassifier,
+            true,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(blocks.iter().map(|b| b.slot).collect::<Vec<_>>(), vec![10, 12]);
+        assert_eq!(blocks[0].transactions.iter().map(|t| t.signature.as_str()).collect::<Vec<_>>(), vec!["memo"]);
+        assert_eq!(blocks[1].block_height, Some(12));
+        assert_eq!((stats.blocks_fetched, stats.blocks_failed, stats.skipped_slots), (2, 1, 1));
+        assert_eq!(stats.failed_slots, vec![(13, stats.failed_slots[0].1.clone())]);
+        assert_eq!(stats.unavailable_blocks, 1);
+        assert_eq!((stats.total_transactions, stats.successful_transactions, stats.failed_transactions), (4, 3, 1));
+        assert_eq!((stats.votes, stats.votes_skipped, stats.memos), (1, 1, 1));
+        assert_eq!((stats.sol_transfers, stats.dex_swaps), (1, 1));
+        assert_eq!(stats.kept_transactions(), 3);
+        assert_eq!(source.get_latest_slot().await.unwrap(), 14);
+    }
+
+    #[tokio::test]
+    async fn test_max_transactions_per_block() {
+        let source = MockBlockSource::default()
+            .with_block(10, vec![transaction_json("small", &["11111111111111111111111111111111"], true)])
+            .with_block(
+                12,
+                vec![
+                    transaction_json("first", &["11111111111111111111111111111111"], true),
+                    transaction_json("second", &["11111111111111111111111111111111"], true),
+                    transaction_json("third", &["11111111111111111111111111111111"], true),
+                ],
+            );
+        let extract = |limit| {
+            extract_block_range(&source, 10, 12, 0, None, &DefaultClassifier, false, None, None, None, limit, None)
+        };
+
+        // Blocks over the limit are skipped, not failed
+        let limit = TransactionLimit { max_transactions: 2, truncate: false };
+        let (blocks, stats) = extract(Some(limit)).await.unwrap();
+        assert_eq!(blocks.iter().map(|b| b.slot).collect::<Vec<_>>(), vec![10]);
+        assert_eq!((stats.oversized_blocks, stats.blocks_failed), (1, 0));
+        assert!(stats.failed_slots.is_empty());
+
+        // Truncated blocks keep their first transactions and the on-chain count
+        let (blocks, stats) = extract(Some(TransactionLimit { truncate: true, ..limit })).await.unwrap();
+        assert_eq!(
+            blocks[1].transactions.iter().map(|t| t.signature.as_str()).collect::<Vec<_>>(),
+            vec!["first", "second"]
+        );
+        assert_eq!((blocks[1].truncated_transactions, blocks[1].transaction_count), (1, 3));
+        assert_eq!((blocks[0].truncated_transactions, stats.oversized_blocks), (0, 1));
+    }
+
+    #[test]
+    fn test_parse_signatures_only_block() {
+        let block: UiConfirmedBlock = serde_json::from_value(serde_json::json!({
+            "previousBlockhash": "hash9",
+            "blockhash": "hash10",
+            "parentSlot": 9,
+            "signatures": ["first", "second", "third"],
+            "blockTime": 1_700_000_000,
+            "blockHeight": 10
+        }))
+        .unwrap();
+        let limit = TransactionLimit { max_transactions: 2, truncate: true };
+
+        let extracted = parse_block(10, block.clone(), None, Some(limit)).unwrap();
+        assert!(extracted.signatures_only);
+        assert_eq!(
+            extracted.transactions.iter().map(|t| (t.signature.as_str(), t.transaction_index)).collect::<Vec<_>>(),
+            vec![("first", 0), ("second", 1)]
+        );
+        assert_eq!((extracted.truncated_transactions, extracted.transaction_count), (1, 3));
+
+        // Nothing is known of the transactions besides their signatures, so none is counted as failed
+        let mut stats = ExtractionStats::new(0);
+        stats.record_block(&extracted, None, &DefaultClassifier);
+        assert_eq!((stats.total_transactions, stats.failed_transactions, stats.unknown_transactions), (2, 0, 0));
+
+        let limit = TransactionLimit { truncate: false, ..limit };
+        assert!(matches!(parse_block(10, block, None, Some(limit)), Err(FetcherError::TooManyTransactions { .. })));
+    }
+
+    #[test]
+    fn test_extract_inner_program_ids() {
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Signer1111111111111111111111111111111111111" },
+                    { "pubkey": "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8" }
+                ],
+                "instructions": [
+                    { "programId": "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "accounts": [], "data": "" }
+                ]
+            }
+        });
+        let meta_json = serde_json::json!({
+            "innerInstructions": [
+                {
+                    "index": 0,
+                    "instructions": [
+                        { "programIdIndex": 1, "accounts": [], "data": "" },
+                        { "program": "spl-token", "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "parsed": {} },
+                        { "programIdIndex": 1, "accounts": [], "data": "" }
+                    ]
+                }
+            ]
+        });
+
+        let account_keys = crate::etl::transform::extract_all_account_keys(&tx_json, &meta_json);
+        assert_eq!(extract_program_ids(&tx_json, &account_keys), vec!["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"]);
+        assert_eq!(
+            extract_inner_program_ids(&meta_json, &account_keys),
+            vec!["675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"]
+        );
+        // Every invocation counts, including repeated programs
+        assert_eq!(count_inner_instructions(&meta_json), 3);
+        assert_eq!(count_inner_instructions(&serde_json::json!({})), 0);
+    }
+
+    #[test]
+    fn test_parse_max_cpi_depth() {
+        let meta_json = serde_json::json!({
+            "innerInstructions": [
+                {
+                    "index": 0,
+                    "instructions": [
+                        { "programIdIndex": 1, "accounts": [], "data": "", "stackHeight": 2 },
+                        { "programIdIndex": 2, "accounts": [], "data": "", "stackHeight": 3 },
+                        { "programIdIndex": 3, "accounts": [], "data": "", "stackHeight": 4 }
+                    ]
+                },
+                {
+                    "index": 1,
+                    "instructions": [{ "programIdIndex": 1, "accounts": [], "data": "", "stackHeight": 2 }]
+                }
+            ]
+        });
+        assert_eq!(parse_max_cpi_depth(&meta_json), Some(3));
+
+        // No CPI at all
+        assert_eq!(parse_max_cpi_depth(&serde_json::json!({ "innerInstructions": [] })), Some(0));
+        assert_eq!(parse_max_cpi_depth(&serde_json::json!({})), Some(0));
+
+        // Older blocks report inner instructions without their stack height
+        let unknown = serde_json::json!({
+            "innerInstructions": [{ "index": 0, "instructions": [{ "programIdIndex": 1, "accounts": [], "data": "" }] }]
+        });
+        assert_eq!(parse_max_cpi_depth(&unknown), None);
+    }
+
+    #[test]
+    fn test_extract_balance_changes() {
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Payer111111111111111111111111111111111111" },
+                    { "pubkey": "Receiver111111111111111111111111111111111" },
+                    { "pubkey": "11111111111111111111111111111111" }
+                ]
+            }
+        });
+        let meta_json = serde_json::json!({
+            "preBalances": [10_000_000, 500, 1],
+            "postBalances": [8_995_000, 1_000_500, 1]
+        });
+
+        let account_keys = crate::etl::transform::extract_all_account_keys(&tx_json, &meta_json);
+        let changes = extract_balance_changes(&account_keys, &meta_json);
+        assert_eq!(
+            changes,
+            vec![
+                ("Payer111111111111111111111111111111111111".to_string(), -1_005_000),
+                ("Receiver111111111111111111111111111111111".to_string(), 1_000_000),
+                ("11111111111111111111111111111111".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_signer_balance_delta() {
+        // Successful transfer of 1,000,000 lamports with a 5,000 lamport fee
+        let transfer = serde_json::json!({
+            "fee": 5000,
+            "preBalances": [10_000_000, 500],
+            "postBalances": [8_995_000, 1_000_500]
+        });
+        assert_eq!(extract_signer_balance_delta(&transfer), -1_005_000);
+
+        // Failed transaction: only the fee is deducted
+        let failed = serde_json::json!({
+            "err": { "InstructionError": [0, { "Custom": 1 }] },
+            "fee": 5000,
+            "preBalances": [10_000_000, 500],
+            "postBalances": [9_995_000, 500]
+        });
+        assert_eq!(extract_signer_balance_delta(&failed), -5000);
+
+        assert_eq!(extract_signer_balance_delta(&serde_json::json!({})), 0);
+    }
+
+    fn test_transaction() -> ExtractedTransaction {
+        ExtractedTransaction {
+            signature: "sig".to_string(),
+            signers: vec![],
+            version: None,
+            recent_blockhash: None,
+            transaction_index: 0,
+            success: true,
+            error_reason: None,
+            fee: 5000,
+            priority_fee: None,
+            compute_unit_price: None,
+            compute_units: None,
+            num_accounts: 2,
+            num_instructions: 1,
+            num_inner_instructions: 0,
+            max_cpi_depth: Some(0),
+            program_ids: vec![],
+            inner_program_ids: vec![],
+            balance_changes: vec![],
+            signer_balance_delta: -5000,
+            token_balance_changes: vec![],
+            token_transfer: None,
+            jupiter_swap: None,
+            liquid_staking: None,
+            wrapped_sol: None,
+            log_messages: vec![],
+            logs_truncated: false,
+            raw_json: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_analyze_with_custom_classifier() {
+        struct LendingClassifier;
+        impl Classifier for LendingClassifier {
+            fn classify(
+                &self,
+                tx: &ExtractedTransaction,
+                registry: &crate::etl::transform::ProgramRegistry,
+            ) -> crate::models::TransactionType {
+                if tx.program_ids.iter().any(|id| id == "MyLending") {
+                    crate::models::TransactionType::Custom("Lending".to_string())
+                } else {
+                    DefaultClassifier.classify(tx, registry)
+                }
+            }
+        }
+
+        let registry = crate::etl::transform::ProgramRegistry::from_database(vec![]);
+        let lending = ExtractedTransaction { program_ids: vec!["MyLending".to_string()], ..test_transaction() };
+        let vote = ExtractedTransaction {
+            program_ids: vec!["Vote111111111111111111111111111111111111111".to_string()],
+            ..test_transaction()
+        };
+
+        assert_eq!(lending.analyze_with(&LendingClassifier, &registry).tx_type.as_str(), "Lending");
+        assert_eq!(lending.analyze(&registry).tx_type, crate::models::TransactionType::Unknown);
+        assert_eq!(vote.analyze_with(&LendingClassifier, &registry).tx_type, crate::models::TransactionType::Vote);
+    }
+
+    #[test]
+    fn test_skip_existing_slots() {
+        let mut slots = vec![10, 11, 13, 14];
+        let existing: HashSet<u64> = [11, 14, 20].into_iter().collect();
+
+        assert_eq!(skip_existing_slots(&mut slots, Some(&existing)), 2);
+        assert_eq!(slots, vec![10, 13]);
+        assert_eq!(skip_existing_slots(&mut slots, None), 0);
+    }
+
+    #[test]
+    fn test_program_activity() {
+        let transaction = |program_ids: &[&str], inner_program_ids: &[&str]| ExtractedTransaction {
+            num_instructions: program_ids.len(),
+            num_inner_instructions: 0,
+            max_cpi_depth: Some(0),
+            program_ids: program_ids.iter().map(|id| id.to_string()).collect(),
+            inner_program_ids: inner_program_ids.iter().map(|id| id.to_string()).collect(),
+            ..test_transaction()
+        };
+        let block = ExtractedBlock {
+            slot: 1,
+            blockhash: "hash".to_string(),
+            parent_slot: 0,
+            block_time: None,
+            block_height: None,
+            rewards: vec![],
+            transactions: vec![
+                transaction(&["dex", "system"], &["token", "token"]),
+                transaction(&["token"], &[]),
+                transaction(&["system"], &["system"]),
+            ],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 3,
+            signatures_only: false,
+        };
+
+        let mut stats = ExtractionStats::new(0);
+        stats.record_block(&block, None, &DefaultClassifier);
+
+        // A program invoked several times by one transaction counts once
+        assert_eq!(stats.program_activity["token"], 2);
+        assert_eq!(stats.program_activity["system"], 2);
+        assert_eq!(stats.top_programs(2), vec![("system", 2), ("token", 2)]);
+        assert_eq!(stats.top_programs(10).len(), 3);
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let mut block = ExtractedBlock {
+            slot: 1,
+            blockhash: "hash".to_string(),
+            parent_slot: 0,
+            block_time: None,
+            block_height: None,
+            rewards: vec![],
+            transactions: vec![
+                test_transaction(),
+                ExtractedTransaction { success: false, ..test_transaction() },
+                test_transaction(),
+            ],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 3,
+            signatures_only: false,
+        };
+
+        let mut stats = ExtractionStats::new(0);
+        stats.record_block(&block, None, &DefaultClassifier);
+        stats.status_filtered += StatusFilter::Failed.drop_others(&mut block);
+
+        assert_eq!(block.transactions.len(), 1);
+        assert!(!block.transactions[0].success);
+        // Dropped transactions are still counted as seen
+        assert_eq!((stats.total_transactions, stats.successful_transactions, stats.kept_transactions()), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_sampler() {
+        let signatures: Vec<String> = (0..10_000).map(|i| format!("signature{}", i)).collect();
+        let kept = |sampler: Sampler| signatures.iter().filter(|s| sampler.keeps(s)).count();
+
+        // Close to the rate, and the same transactions on every run with the same seed
+        let sample = kept(Sampler::new(0.1, 42));
+        assert!((900..1100).contains(&sample), "kept {}", sample);
+        assert_eq!(kept(Sampler::new(0.1, 42)), sample);
+        assert_ne!(
+            signatures.iter().map(|s| Sampler::new(0.1, 42).keeps(s)).collect::<Vec<_>>(),
+            signatures.iter().map(|s| Sampler::new(0.1, 7).keeps(s)).collect::<Vec<_>>()
+        );
+
+        assert_eq!(kept(Sampler::new(0.0, 42)), 0);
+        assert_eq!(kept(Sampler::new(1.0, 42)), signatures.len());
+        // The hash is fixed, so a sample taken today matches one taken after a toolchain upgrade
+        assert_eq!(sample_hash(42, b"signature0"), 0x7273_73ff_3e2f_f0b1);
+    }
+
+    #[test]
+    fn test_touches_any_program() {
+        let transaction = ExtractedTransaction {
+            program_ids: vec!["ComputeBudget111111111111111111111111111111".to_string()],
+            inner_program_ids: vec!["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string()],
+            ..test_transaction()
+        };
+
+        let jupiter: HashSet<String> = ["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string()].into();
+        let stake: HashSet<String> = ["Stake11111111111111111111111111111111111111".to_string()].into();
+        assert!(touches_any_program(&transaction, &jupiter));
+        assert!(!touches_any_program(&transaction, &stake));
+    }
+
+    #[test]
+    fn test_parse_block_rewards() {
+        use solana_transaction_status::{Reward, RewardType};
+
+        let reward = |reward_type, commission| Reward {
+            pubkey: "Vote111111111111111111111111111111111111111".to_string(),
+            lamports: 1_000,
+            post_balance: 5_000,
+            reward_type,
+            commission,
+        };
+        let rewards =
+            vec![reward(Some(RewardType::Fee), None), reward(Some(RewardType::Voting), Some(10)), reward(None, None)];
+
+        let parsed = parse_block_rewards(Some(&rewards));
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].reward_type, crate::models::RewardType::Fee);
+        assert_eq!(parsed[1].reward_type, crate::models::RewardType::Voting);
+        assert_eq!(parsed[1].commission, Some(10));
+        assert!(parse_block_rewards(None).is_empty());
+    }
+
+    #[test]
+    fn test_parse_log_messages() {
+        let meta_json = serde_json::json!({
+            "logMessages": [
+                "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc invoke [1]",
+                "Program log: Instruction: Swap",
+                "Log truncated"
+            ]
+        });
+
+        let (logs, truncated) = parse_log_messages(&meta_json);
+        assert_eq!(logs.len(), 3);
+        assert_eq!(logs[1], "Program log: Instruction: Swap");
+        assert!(truncated);
+
+        let (logs, truncated) = parse_log_messages(&serde_json::json!({ "fee": 5000 }));
+        assert!(logs.is_empty());
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_parse_error_reason() {
+        let reason = |err: serde_json::Value| parse_error_reason(&serde_json::json!({ "err": err }));
+
+        assert_eq!(
+            reason(serde_json::json!({ "InstructionError": [3, { "Custom": 6001 }] })).as_deref(),
+            Some("Instruction 3 failed: custom program error 6001 (0x1771)")
+        );
+        assert_eq!(
+            reason(serde_json::json!({ "InstructionError": [0, "InvalidAccountData"] })).as_deref(),
+            Some("Instruction 0 failed: InvalidAccountData")
+        );
+        assert_eq!(
+            reason(serde_json::json!({ "InstructionError": [1, { "BorshIoError": "Unknown" }] })).as_deref(),
+            Some("Instruction 1 failed: BorshIoError (Unknown)")
+        );
+        assert_eq!(
+            reason(serde_json::json!({ "InsufficientFundsForRent": { "account_index": 2 } })).as_deref(),
+            Some("InsufficientFundsForRent (account_index: 2)")
+        );
+        assert_eq!(reason(serde_json::json!("BlockhashNotFound")).as_deref(), Some("BlockhashNotFound"));
+        assert_eq!(reason(serde_json::Value::Null), None);
+        assert_eq!(parse_error_reason(&serde_json::json!({ "fee": 5000 })), None);
+    }
+
+    #[test]
+    fn test_extract_inner_program_ids_missing() {
+        let meta_json = serde_json::json!({ "fee": 5000 });
+
+        assert!(extract_inner_program_ids(&meta_json, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_program_ids_from_lookup_table() {
+        // Compiled v0 message: the program at index 2 is loaded from a lookup table
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": ["Payer111111111111111111111111111111111111"],
+                "instructions": [{ "programIdIndex": 2, "accounts": [0, 1], "data": "" }]
+            }
+        });
+        let meta_json = serde_json::json!({
+            "loadedAddresses": {
+                "writable": ["Pool1111111111111111111111111111111111111"],
+                "readonly": ["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"]
+            },
+            "preBalances": [10_000, 2_000, 1],
+            "postBalances": [5_000, 7_000, 1]
+        });
+
+        let account_keys = crate::etl::transform::extract_all_account_keys(&tx_json, &meta_json);
+        assert_eq!(extract_program_ids(&tx_json, &account_keys), vec!["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"]);
+        assert_eq!(
+            extract_balance_changes(&account_keys, &meta_json)[1],
+            ("Pool1111111111111111111111111111111111111".to_string(), 5_000)
+        );
+    }
+
+    #[test]
+    fn test_extract_recent_blockhash() {
+        let parsed = transaction_json("sig1", &[], true)["transaction"].clone();
+        assert_eq!(extract_recent_blockhash(&parsed).as_deref(), Some("1111"));
+
+        let compiled = serde_json::json!({
+            "signatures": ["sig2"],
+            "message": {
+                "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": 1 },
+                "accountKeys": ["Payer111111111111111111111111111111111111"],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [],
+                "addressTableLookups": []
+            }
+        });
+        assert_eq!(
+            extract_recent_blockhash(&compiled).as_deref(),
+            Some("EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N")
+        );
+
+        // Binary encodings are a [data, encoding] pair
+        assert_eq!(extract_recent_blockhash(&serde_json::json!(["AQID", "base64"])), None);
+    }
+
+    #[test]
+    fn test_parse_confirmed_transaction() {
+        // getTransaction flattens the transaction next to its slot and block time
+        let mut response = transaction_json("sig7", &["MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"], false);
+        response["slot"] = serde_json::json!(321_000_007);
+        response["blockTime"] = serde_json::json!(1_739_000_000);
+        let confirmed: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(response).unwrap();
+
+        let transaction = parse_confirmed_transaction(&confirmed, 3).unwrap();
+        assert_eq!(confirmed.slot, 321_000_007);
+        assert_eq!((transaction.signature.as_str(), transaction.transaction_index), ("sig7", 3));
+        assert_eq!(transaction.signers, vec!["Payer1111111111111111111111111111111111111"]);
+        assert!(!transaction.success);
+        assert_eq!(transaction.program_ids, vec!["MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"]);
+        assert_eq!(transaction.version, None);
+        assert_eq!(transaction.recent_blockhash.as_deref(), Some("1111"));
+
+        // v0 messages report their version number
+        let mut response = serde_json::to_value(&confirmed).unwrap();
+        response["version"] = serde_json::json!(0);
+        let confirmed: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(response).unwrap();
+        assert_eq!(parse_confirmed_transaction(&confirmed, 3).unwrap().version, Some(0));
+    }
+}
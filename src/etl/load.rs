@@ -1,10 +1,129 @@
 /// Load Module
 ///
 /// Handles storing data into the PostgreSQL database.
-use crate::etl::extract::ExtractedBlock;
-use anyhow::Result;
-use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use crate::{etl::extract::ExtractedBlock, models::block_time_to_utc};
+use anyhow::{Context, Result};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+
+/// Header of a binary COPY stream: signature, flags field and header extension length
+const PGCOPY_HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+
+/// Columns of the transaction rows streamed by `copy_insert_transactions`
+const TRANSACTION_COPY_COLUMNS: i16 = 38;
+
+/// zstd level of compressed raw transaction JSON (`--compress-raw`)
+const RAW_DATA_COMPRESSION_LEVEL: i32 = 3;
+
+/// Maximum instruction rows per INSERT statement (7 bind parameters each, Postgres allows 65,535)
+const INSTRUCTION_INSERT_CHUNK: usize = 1000;
+
+/// Maximum token balance rows per INSERT statement (5 bind parameters each)
+const TOKEN_BALANCE_INSERT_CHUNK: usize = 1000;
+
+/// Maximum reward rows per INSERT statement (6 bind parameters each)
+const REWARD_INSERT_CHUNK: usize = 1000;
+
+/// Maximum signature rows per INSERT statement (3 bind parameters each)
+const SIGNATURE_INSERT_CHUNK: usize = 5000;
+
+/// Columns of `transactions` written by both load paths, in bind order
+const TRANSACTION_COLUMNS: &str = "signature, block_slot, transaction_index, success, fee, transaction_type, \
+     transaction_label, signer, num_accounts, num_instructions, num_inner_instructions, compute_units, priority_fee, \
+     compute_unit_price, memo, token_mint, token_amount, token_decimals, token_program, token_transfer_fee, \
+     swap_input_mint, swap_output_mint, swap_in_amount, swap_out_amount, signer_balance_delta, log_messages, \
+     logs_truncated, program_ids, raw_data, signers, version, recent_blockhash, token_source_owner, \
+     token_destination_owner, raw_data_compressed, secondary_types, error_reason, max_cpi_depth";
+
+/// Assignments of a transaction upsert: every column from the new row
+const TRANSACTION_UPDATE_SET: &str = "block_slot = EXCLUDED.block_slot, \
+     transaction_index = EXCLUDED.transaction_index, \
+     success = EXCLUDED.success, \
+     fee = EXCLUDED.fee, \
+     transaction_type = EXCLUDED.transaction_type, \
+     transaction_label = EXCLUDED.transaction_label, \
+     signer = EXCLUDED.signer, \
+     num_accounts = EXCLUDED.num_accounts, \
+     num_instructions = EXCLUDED.num_instructions, \
+     num_inner_instructions = EXCLUDED.num_inner_instructions, \
+     compute_units = EXCLUDED.compute_units, \
+     priority_fee = EXCLUDED.priority_fee, \
+     compute_unit_price = EXCLUDED.compute_unit_price, \
+     memo = EXCLUDED.memo, \
+     token_mint = EXCLUDED.token_mint, \
+     token_amount = EXCLUDED.token_amount, \
+     token_decimals = EXCLUDED.token_decimals, \
+     token_program = EXCLUDED.token_program, \
+     token_transfer_fee = EXCLUDED.token_transfer_fee, \
+     swap_input_mint = EXCLUDED.swap_input_mint, \
+     swap_output_mint = EXCLUDED.swap_output_mint, \
+     swap_in_amount = EXCLUDED.swap_in_amount, \
+     swap_out_amount = EXCLUDED.swap_out_amount, \
+     signer_balance_delta = EXCLUDED.signer_balance_delta, \
+     log_messages = EXCLUDED.log_messages, \
+     logs_truncated = EXCLUDED.logs_truncated, \
+     program_ids = EXCLUDED.program_ids, \
+     raw_data = EXCLUDED.raw_data, \
+     signers = EXCLUDED.signers, \
+     version = EXCLUDED.version, \
+     recent_blockhash = EXCLUDED.recent_blockhash, \
+     token_source_owner = EXCLUDED.token_source_owner, \
+     token_destination_owner = EXCLUDED.token_destination_owner, \
+     raw_data_compressed = EXCLUDED.raw_data_compressed, \
+     secondary_types = EXCLUDED.secondary_types, \
+     error_reason = EXCLUDED.error_reason, \
+     max_cpi_depth = EXCLUDED.max_cpi_depth, \
+     processed_at = NOW()";
+
+/// How loads store the raw transaction JSON (`--compress-raw`, `--no-raw-json`)
+///
+/// Transactions are parsed and classified from the fetched JSON either way; this only decides
+/// what is persisted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RawDataStorage {
+    /// Queryable JSONB in `raw_data`
+    #[default]
+    Json,
+    /// zstd-compressed in `raw_data_compressed`, `raw_data` NULL (see `compress_raw_data`)
+    Compressed,
+    /// Not stored: both columns NULL, only the parsed and classified columns are kept
+    Omitted,
+}
+
+/// What a load does with rows that are already stored (`--on-conflict`)
+///
+/// Applies to blocks, transactions and their instructions, token balances and rewards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictMode {
+    /// Overwrite the stored row (`ON CONFLICT DO UPDATE`), bumping `processed_at`
+    #[default]
+    Update,
+    /// Keep the stored row untouched (`ON CONFLICT DO NOTHING`), e.g. for backfills overlapping
+    /// ranges that were already loaded
+    Skip,
+    /// No conflict clause: a row that is already stored fails the batch with a unique violation
+    Error,
+}
+
+impl ConflictMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Update => "update",
+            Self::Skip => "skip",
+            Self::Error => "error",
+        }
+    }
+
+    /// Conflict clause of an INSERT into a table with the unique key `target`
+    ///
+    /// `update_set` holds the assignments of the `Update` mode.
+    fn clause(self, target: &str, update_set: &str) -> String {
+        match self {
+            Self::Update => format!(" ON CONFLICT ({}) DO UPDATE SET {}", target, update_set),
+            Self::Skip => format!(" ON CONFLICT ({}) DO NOTHING", target),
+            Self::Error => String::new(),
+        }
+    }
+}
 
 /// Insert a block into the database
 ///
@@ -18,8 +137,7 @@ use sqlx::PgPool;
 /// use `batch_insert_blocks_with_transactions` for better performance.
 #[allow(dead_code)]
 pub async fn insert_block(pool: &PgPool, block: &ExtractedBlock) -> Result<()> {
-    // Convert block_time from Unix timestamp to DateTime if available
-    let block_time: Option<DateTime<Utc>> = block.block_time.and_then(|ts| DateTime::from_timestamp(ts, 0));
+    let block_time = block_time_to_utc(block.block_time);
 
     // Check if parent block exists in database
     let parent_exists = if block.parent_slot == 0 {
@@ -36,21 +154,23 @@ pub async fn insert_block(pool: &PgPool, block: &ExtractedBlock) -> Result<()> {
 
     sqlx::query!(
         r#"
-        INSERT INTO blocks (slot, blockhash, parent_slot, block_time, block_height)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO blocks (slot, blockhash, parent_slot, block_time, block_height, transaction_count)
+        VALUES ($1, $2, $3, $4, $5, $6)
         ON CONFLICT (slot) 
         DO UPDATE SET
             blockhash = EXCLUDED.blockhash,
             parent_slot = EXCLUDED.parent_slot,
             block_time = EXCLUDED.block_time,
-            block_height = EXCLUDED.block_height,
+            block_height = COALESCE(EXCLUDED.block_height, blocks.block_height),
+            transaction_count = EXCLUDED.transaction_count,
             processed_at = NOW()
         "#,
         block.slot as i64,
         block.blockhash,
         parent_slot_value,
         block_time,
-        block.block_height.map(|h| h as i64)
+        block.block_height.map(|h| h as i64),
+        block.transaction_count as i32
     )
     .execute(pool)
     .await?;
@@ -59,85 +179,6 @@ pub async fn insert_block(pool: &PgPool, block: &ExtractedBlock) -> Result<()> {
     Ok(())
 }
 
-/// Insert a transaction into the database with classification
-///
-/// Uses UPSERT logic (ON CONFLICT DO UPDATE) to handle duplicate transactions.
-/// Stores transaction details along with classification labels from the transform phase.
-///
-/// This function is kept for single-transaction operations. For batch operations,
-/// use `batch_insert_blocks_with_transactions` for better performance.
-#[allow(dead_code)]
-pub async fn insert_transaction(
-    pool: &PgPool,
-    block_slot: u64,
-    transaction_index: usize,
-    transaction: &crate::etl::extract::ExtractedTransaction,
-    transaction_type: &crate::models::TransactionType,
-    label: &str,
-) -> Result<()> {
-    // Extract signer (fee payer) from raw_json if available
-    let signer = transaction
-        .raw_json
-        .get("transaction")
-        .and_then(|t| t.get("message"))
-        .and_then(|m| m.get("accountKeys"))
-        .and_then(|keys| keys.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|key| {
-            // Handle both formats: {"pubkey": "..."} and just "string"
-            if let Some(pubkey) = key.get("pubkey") {
-                pubkey.as_str()
-            } else {
-                key.as_str()
-            }
-        })
-        .map(|s| s.to_string());
-
-    sqlx::query!(
-        r#"
-        INSERT INTO transactions (
-            signature, 
-            block_slot, 
-            transaction_index, 
-            success, 
-            fee, 
-            transaction_type, 
-            transaction_label,
-            signer,
-            num_accounts,
-            raw_data
-        )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-        ON CONFLICT (signature) 
-        DO UPDATE SET
-            block_slot = EXCLUDED.block_slot,
-            transaction_index = EXCLUDED.transaction_index,
-            success = EXCLUDED.success,
-            fee = EXCLUDED.fee,
-            transaction_type = EXCLUDED.transaction_type,
-            transaction_label = EXCLUDED.transaction_label,
-            signer = EXCLUDED.signer,
-            num_accounts = EXCLUDED.num_accounts,
-            raw_data = EXCLUDED.raw_data,
-            processed_at = NOW()
-        "#,
-        transaction.signature,
-        block_slot as i64,
-        transaction_index as i32,
-        transaction.success,
-        transaction.fee as i64,
-        transaction_type.as_str(),
-        label,
-        signer,
-        transaction.num_accounts as i32,
-        transaction.raw_json
-    )
-    .execute(pool)
-    .await?;
-
-    Ok(())
-}
-
 /// Batch insert blocks and their transactions within a database transaction
 ///
 /// This function provides atomicity - either all data is inserted or none.
@@ -149,137 +190,142 @@ pub async fn insert_transaction(
 /// Performance: Inserts ~1,000-1,500 transactions/second including classification.
 /// For 10 blocks with ~12,000 transactions, expect ~20-25 seconds total time.
 /// (Most time is spent in transaction classification, not database operations)
+/// For large backfills, `copy_insert_blocks_with_transactions` avoids the per-row round trips.
 ///
-/// Returns the number of (blocks, transactions) inserted.
+/// Instructions and token balance changes of each transaction are inserted into the
+/// `instructions` and `token_balances` tables in the same database transaction.
+///
+/// Rows that are already stored are handled according to `on_conflict`. Blocks are inserted in
+/// slot order, so a parent in the same batch is stored before its child checks for it. Raw
+/// transaction JSON is stored according to `raw_storage`.
+///
+/// With `commit_every`, the database transaction is committed and a new one begun after every
+/// that many transactions, so a batch of huge blocks does not become one giant transaction that
+/// runs into statement timeouts or holds locks for minutes. The batch is then no longer atomic:
+/// when a later part fails, the committed parts stay stored, possibly part of a block. The
+/// pipeline's retry (and a later run) loads the whole batch again, which the upserts make safe
+/// with `ConflictMode::Update` and `ConflictMode::Skip`.
+///
+/// Returns the number of blocks and of transactions inserted (or updated); transactions
+/// skipped with `ConflictMode::Skip` are not counted.
 pub async fn batch_insert_blocks_with_transactions(
     pool: &PgPool,
     blocks: &[ExtractedBlock],
     program_registry: &crate::etl::transform::ProgramRegistry,
+    classifier: &dyn crate::etl::transform::Classifier,
+    on_conflict: ConflictMode,
+    raw_storage: RawDataStorage,
+    commit_every: Option<usize>,
 ) -> Result<(usize, usize)> {
     use crate::etl::transform;
 
+    let insert_transaction = format!(
+        "INSERT INTO transactions ({}) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17::TEXT::NUMERIC, $18, \
+         $19, $20::TEXT::NUMERIC, $21, $22, $23::TEXT::NUMERIC, $24::TEXT::NUMERIC, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37, $38){}",
+        TRANSACTION_COLUMNS,
+        on_conflict.clause("signature, block_slot", TRANSACTION_UPDATE_SET)
+    );
+
     // Start a database transaction
     let mut tx = pool.begin().await?;
 
     let mut blocks_inserted = 0;
     let mut transactions_inserted = 0;
+    let mut uncommitted = 0;
 
-    for block in blocks {
-        // Convert block_time from Unix timestamp to DateTime if available
-        let block_time: Option<DateTime<Utc>> = block.block_time.and_then(|ts| DateTime::from_timestamp(ts, 0));
-
-        // Check if parent block exists in database
-        let parent_exists = if block.parent_slot == 0 {
-            false // Genesis block has no parent
-        } else {
-            let result = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM blocks WHERE slot = $1)")
-                .bind(block.parent_slot as i64)
-                .fetch_one(&mut *tx)
-                .await?;
-            result
-        };
-
-        let parent_slot_value = if parent_exists { Some(block.parent_slot as i64) } else { None };
-
-        // Insert block
-        sqlx::query!(
-            r#"
-            INSERT INTO blocks (slot, blockhash, parent_slot, block_time, block_height)
-            VALUES ($1, $2, $3, $4, $5)
-            ON CONFLICT (slot) 
-            DO UPDATE SET
-                blockhash = EXCLUDED.blockhash,
-                parent_slot = EXCLUDED.parent_slot,
-                block_time = EXCLUDED.block_time,
-                block_height = EXCLUDED.block_height,
-                processed_at = NOW()
-            "#,
-            block.slot as i64,
-            block.blockhash,
-            parent_slot_value,
-            block_time,
-            block.block_height.map(|h| h as i64)
-        )
-        .execute(&mut *tx)
-        .await?;
-
+    for block in in_slot_order(blocks) {
+        upsert_block(&mut tx, block, on_conflict).await?;
+        batch_insert_block_rewards(&mut tx, block.slot, &block.rewards, on_conflict).await?;
         blocks_inserted += 1;
 
-        // Insert all transactions for this block
-        for (tx_index, transaction) in block.transactions.iter().enumerate() {
-            // Classify the transaction
-            let tx_type = transform::classify_transaction_with_registry(&transaction.program_ids, program_registry);
+        let mut instructions = Vec::new();
+        let mut token_balances = Vec::new();
 
-            // Get detailed label
-            let details = transform::analyze_transaction_with_registry(
-                &transaction.program_ids,
-                program_registry,
-                Some(&transaction.raw_json),
-            );
+        // Insert all transactions for this block
+        for transaction in &block.transactions {
+            // Classify the transaction and get its detailed label
+            let details = transaction.analyze_with(classifier, program_registry);
 
-            // Extract signer (fee payer) from raw_json if available
-            let signer = transaction
-                .raw_json
-                .get("transaction")
-                .and_then(|t| t.get("message"))
-                .and_then(|m| m.get("accountKeys"))
-                .and_then(|keys| keys.as_array())
-                .and_then(|arr| arr.first())
-                .and_then(|key| {
-                    // Handle both formats: {"pubkey": "..."} and just "string"
-                    if let Some(pubkey) = key.get("pubkey") {
-                        pubkey.as_str()
-                    } else {
-                        key.as_str()
-                    }
-                })
-                .map(|s| s.to_string());
+            // Fee payer
+            let signer = transaction.signers.first();
+            let (token_mint, token_amount, token_decimals, token_program, token_transfer_fee) =
+                token_transfer_columns(transaction);
+            let (swap_input_mint, swap_output_mint, swap_in_amount, swap_out_amount) = swap_columns(transaction);
+            let log_messages = log_messages_column(transaction);
+            let (raw_data, raw_data_compressed) = raw_data_columns(transaction, raw_storage);
 
             // Insert transaction
-            sqlx::query!(
-                r#"
-                INSERT INTO transactions (
-                    signature, 
-                    block_slot, 
-                    transaction_index, 
-                    success, 
-                    fee, 
-                    transaction_type, 
-                    transaction_label,
-                    signer,
-                    num_accounts,
-                    raw_data
-                )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-                ON CONFLICT (signature) 
-                DO UPDATE SET
-                    block_slot = EXCLUDED.block_slot,
-                    transaction_index = EXCLUDED.transaction_index,
-                    success = EXCLUDED.success,
-                    fee = EXCLUDED.fee,
-                    transaction_type = EXCLUDED.transaction_type,
-                    transaction_label = EXCLUDED.transaction_label,
-                    signer = EXCLUDED.signer,
-                    num_accounts = EXCLUDED.num_accounts,
-                    raw_data = EXCLUDED.raw_data,
-                    processed_at = NOW()
-                "#,
-                transaction.signature,
-                block.slot as i64,
-                tx_index as i32,
-                transaction.success,
-                transaction.fee as i64,
-                tx_type.as_str(),
-                details.label,
-                signer,
-                transaction.num_accounts as i32,
-                transaction.raw_json
-            )
-            .execute(&mut *tx)
-            .await?;
+            let result = sqlx::query(&insert_transaction)
+                .bind(&transaction.signature)
+                .bind(block.slot as i64)
+                .bind(transaction.transaction_index as i32)
+                .bind(transaction.success)
+                .bind(transaction.fee as i64)
+                .bind(details.tx_type.as_str())
+                .bind(&details.label)
+                .bind(signer)
+                .bind(transaction.num_accounts as i32)
+                .bind(transaction.num_instructions as i32)
+                .bind(transaction.num_inner_instructions as i32)
+                .bind(transaction.compute_units.map(|c| c as i64))
+                .bind(transaction.priority_fee.map(|f| f as i64))
+                .bind(transaction.compute_unit_price.map(|p| p as i64))
+                .bind(&details.memo)
+                .bind(token_mint)
+                .bind(token_amount)
+                .bind(token_decimals)
+                .bind(token_program)
+                .bind(token_transfer_fee)
+                .bind(swap_input_mint)
+                .bind(swap_output_mint)
+                .bind(swap_in_amount)
+                .bind(swap_out_amount)
+                .bind(transaction.signer_balance_delta)
+                .bind(log_messages)
+                .bind(transaction.logs_truncated)
+                .bind(&transaction.program_ids)
+                .bind(raw_data)
+                .bind(&transaction.signers)
+                .bind(transaction.version.map(i16::from))
+                .bind(&transaction.recent_blockhash)
+                .bind(transaction.token_transfer.as_ref().and_then(|t| t.source_owner.as_deref()))
+                .bind(transaction.token_transfer.as_ref().and_then(|t| t.destination_owner.as_deref()))
+                .bind(raw_data_compressed)
+                .bind(details.secondary_types.iter().map(crate::models::TransactionType::as_str).collect::<Vec<_>>())
+                .bind(&transaction.error_reason)
+                .bind(transaction.max_cpi_depth.map(i16::from))
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to insert transaction {}", transaction.signature))?;
+
+            transactions_inserted += result.rows_affected() as usize;
+
+            instructions.extend(transform::extract_instructions(
+                &transaction.signature,
+                &transaction.raw_json,
+                program_registry,
+            ));
+            token_balances.extend(
+                transaction.token_balance_changes.iter().map(|change| (transaction.signature.as_str(), change)),
+            );
 
-            transactions_inserted += 1;
+            uncommitted += 1;
+            if commit_every.is_some_and(|limit| uncommitted >= limit) {
+                batch_insert_instructions(&mut tx, &instructions, on_conflict).await?;
+                batch_insert_token_balances(&mut tx, &token_balances, on_conflict).await?;
+                instructions.clear();
+                token_balances.clear();
+                tx.commit().await?;
+                tx = pool.begin().await?;
+                uncommitted = 0;
+            }
         }
+
+        // Instructions and token balances reference their transaction, so insert them after the block's
+        // transactions
+        batch_insert_instructions(&mut tx, &instructions, on_conflict).await?;
+        batch_insert_token_balances(&mut tx, &token_balances, on_conflict).await?;
     }
 
     // Commit the transaction
@@ -288,3 +334,846 @@ pub async fn batch_insert_blocks_with_transactions(
     tracing::info!("Batch inserted {} blocks and {} transactions", blocks_inserted, transactions_inserted);
     Ok((blocks_inserted, transactions_inserted))
 }
+
+/// Bulk load blocks and their transactions using binary COPY
+///
+/// Same result as `batch_insert_blocks_with_transactions`, but all transactions of the batch are
+/// streamed into a staging table with one COPY and merged with a single `INSERT ... SELECT`,
+/// instead of one upsert round trip per transaction. Worth it for large backfill batches; small
+/// batches are faster through the regular upsert path. Rows that are already stored are handled
+/// according to `on_conflict`, and blocks are inserted in slot order and raw JSON stored according
+/// to `raw_storage` like the upsert path.
+///
+/// Returns the number of blocks and of transactions inserted (or updated).
+pub async fn copy_insert_blocks_with_transactions(
+    pool: &PgPool,
+    blocks: &[ExtractedBlock],
+    program_registry: &crate::etl::transform::ProgramRegistry,
+    classifier: &dyn crate::etl::transform::Classifier,
+    on_conflict: ConflictMode,
+    raw_storage: RawDataStorage,
+) -> Result<(usize, usize)> {
+    use crate::etl::transform;
+
+    let mut tx = pool.begin().await?;
+
+    let mut rows = Vec::new();
+    let mut instructions = Vec::new();
+    let mut token_balances = Vec::new();
+
+    for block in in_slot_order(blocks) {
+        upsert_block(&mut tx, block, on_conflict).await?;
+        batch_insert_block_rewards(&mut tx, block.slot, &block.rewards, on_conflict).await?;
+
+        for transaction in &block.transactions {
+            let details = transaction.analyze_with(classifier, program_registry);
+            let (raw_data, raw_data_compressed) = raw_data_columns(transaction, raw_storage);
+
+            rows.push(TransactionRow {
+                signature: &transaction.signature,
+                block_slot: block.slot as i64,
+                transaction_index: transaction.transaction_index as i32,
+                success: transaction.success,
+                fee: transaction.fee as i64,
+                transaction_type: details.tx_type,
+                label: details.label,
+                signer: transaction.signers.first().map(String::as_str),
+                num_accounts: transaction.num_accounts as i32,
+                num_instructions: transaction.num_instructions as i32,
+                num_inner_instructions: transaction.num_inner_instructions as i32,
+                compute_units: transaction.compute_units.map(|c| c as i64),
+                priority_fee: transaction.priority_fee.map(|f| f as i64),
+                compute_unit_price: transaction.compute_unit_price.map(|p| p as i64),
+                memo: details.memo,
+                token_transfer: transaction.token_transfer.as_ref(),
+                jupiter_swap: transaction.jupiter_swap.as_ref(),
+                signer_balance_delta: transaction.signer_balance_delta,
+                log_messages: &transaction.log_messages,
+                logs_truncated: transaction.logs_truncated,
+                program_ids: &transaction.program_ids,
+                raw_data,
+                signers: &transaction.signers,
+                version: transaction.version.map(i16::from),
+                recent_blockhash: transaction.recent_blockhash.as_deref(),
+                raw_data_compressed,
+                secondary_types: details.secondary_types,
+                error_reason: transaction.error_reason.as_deref(),
+                max_cpi_depth: transaction.max_cpi_depth.map(i16::from),
+            });
+
+            instructions.extend(transform::extract_instructions(
+                &transaction.signature,
+                &transaction.raw_json,
+                program_registry,
+            ));
+            token_balances.extend(
+                transaction.token_balance_changes.iter().map(|change| (transaction.signature.as_str(), change)),
+            );
+        }
+    }
+
+    let transactions_inserted = copy_insert_transactions(&mut tx, &rows, on_conflict).await? as usize;
+    batch_insert_instructions(&mut tx, &instructions, on_conflict).await?;
+    batch_insert_token_balances(&mut tx, &token_balances, on_conflict).await?;
+
+    tx.commit().await?;
+
+    tracing::info!("Bulk loaded {} blocks and {} transactions", blocks.len(), transactions_inserted);
+    Ok((blocks.len(), transactions_inserted))
+}
+
+/// Load blocks and the signatures of their transactions (`--signatures-only`)
+///
+/// For blocks fetched without transaction details: the blocks go to `blocks` as usual and each
+/// transaction's signature and index to `transaction_signatures`, since without status, fee or
+/// accounts there is nothing to store in `transactions`. No transaction is classified. Rows that
+/// are already stored are handled according to `on_conflict`.
+///
+/// Returns the number of blocks and of signatures inserted (or updated).
+pub async fn batch_insert_block_signatures(
+    pool: &PgPool,
+    blocks: &[ExtractedBlock],
+    on_conflict: ConflictMode,
+) -> Result<(usize, usize)> {
+    let mut tx = pool.begin().await?;
+    let mut signatures_inserted = 0;
+
+    for block in in_slot_order(blocks) {
+        upsert_block(&mut tx, block, on_conflict).await?;
+
+        for chunk in block.transactions.chunks(SIGNATURE_INSERT_CHUNK) {
+            let mut query = QueryBuilder::<Postgres>::new(
+                "INSERT INTO transaction_signatures (signature, block_slot, transaction_index) ",
+            );
+
+            query.push_values(chunk, |mut row, transaction| {
+                row.push_bind(&transaction.signature)
+                    .push_bind(block.slot as i64)
+                    .push_bind(transaction.transaction_index as i32);
+            });
+
+            query.push(on_conflict.clause(
+                "signature, block_slot",
+                "transaction_index = EXCLUDED.transaction_index, \
+                 processed_at = NOW()",
+            ));
+
+            let result = query
+                .build()
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to insert signatures of block {}", block.slot))?;
+            signatures_inserted += result.rows_affected() as usize;
+        }
+    }
+
+    tx.commit().await?;
+
+    tracing::info!("Inserted {} blocks and {} transaction signatures", blocks.len(), signatures_inserted);
+    Ok((blocks.len(), signatures_inserted))
+}
+
+/// Transaction columns written by the COPY path, in staging table order
+struct TransactionRow<'a> {
+    signature: &'a str,
+    block_slot: i64,
+    transaction_index: i32,
+    success: bool,
+    fee: i64,
+    transaction_type: crate::models::TransactionType,
+    label: String,
+    signer: Option<&'a str>,
+    num_accounts: i32,
+    num_instructions: i32,
+    num_inner_instructions: i32,
+    compute_units: Option<i64>,
+    priority_fee: Option<i64>,
+    compute_unit_price: Option<i64>,
+    memo: Option<String>,
+    token_transfer: Option<&'a crate::etl::parsers::TokenTransfer>,
+    jupiter_swap: Option<&'a crate::etl::parsers::JupiterSwap>,
+    signer_balance_delta: i64,
+    log_messages: &'a [String],
+    logs_truncated: bool,
+    program_ids: &'a [String],
+    raw_data: Option<&'a serde_json::Value>,
+    signers: &'a [String],
+    version: Option<i16>,
+    recent_blockhash: Option<&'a str>,
+    raw_data_compressed: Option<Vec<u8>>,
+    secondary_types: Vec<crate::models::TransactionType>,
+    error_reason: Option<&'a str>,
+    max_cpi_depth: Option<i16>,
+}
+
+/// Stream transaction rows into a staging table with binary COPY, then merge them into `transactions`
+///
+/// Must run inside a database transaction: the staging table is dropped on commit. The referenced
+/// blocks must already exist. When a signature appears more than once, the last row wins, as with
+/// the per-row upsert. Returns the number of transactions inserted or updated.
+async fn copy_insert_transactions(
+    conn: &mut PgConnection,
+    rows: &[TransactionRow<'_>],
+    on_conflict: ConflictMode,
+) -> Result<u64> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    sqlx::query(
+        "CREATE TEMP TABLE transactions_staging (
+            signature TEXT,
+            block_slot BIGINT,
+            transaction_index INTEGER,
+            success BOOLEAN,
+            fee BIGINT,
+            transaction_type TEXT,
+            transaction_label TEXT,
+            signer TEXT,
+            num_accounts INTEGER,
+            num_instructions INTEGER,
+            num_inner_instructions INTEGER,
+            compute_units BIGINT,
+            priority_fee BIGINT,
+            compute_unit_price BIGINT,
+            memo TEXT,
+            token_mint TEXT,
+            token_amount TEXT,
+            token_decimals SMALLINT,
+            token_program TEXT,
+            token_transfer_fee TEXT,
+            swap_input_mint TEXT,
+            swap_output_mint TEXT,
+            swap_in_amount TEXT,
+            swap_out_amount TEXT,
+            signer_balance_delta BIGINT,
+            log_messages JSONB,
+            logs_truncated BOOLEAN,
+            program_ids JSONB,
+            raw_data JSONB,
+            signers JSONB,
+            version SMALLINT,
+            recent_blockhash TEXT,
+            token_source_owner TEXT,
+            token_destination_owner TEXT,
+            raw_data_compressed BYTEA,
+            secondary_types JSONB,
+            error_reason TEXT,
+            max_cpi_depth SMALLINT
+        ) ON COMMIT DROP",
+    )
+    .execute(&mut *conn)
+    .await
+    .context("Failed to create transaction staging table")?;
+
+    let mut copy = conn
+        .copy_in_raw("COPY transactions_staging FROM STDIN WITH (FORMAT binary)")
+        .await
+        .context("Failed to start COPY into transaction staging table")?;
+    if let Err(e) = copy.send(encode_transaction_rows(rows)).await {
+        copy.abort("failed to send transaction rows").await.ok();
+        return Err(e).context("Failed to stream transactions to staging table");
+    }
+    copy.finish().await.context("Failed to finish COPY into transaction staging table")?;
+
+    let merge = format!(
+        "INSERT INTO transactions ({})
+        SELECT DISTINCT ON (signature)
+            signature, block_slot, transaction_index, success, fee, transaction_type, transaction_label,
+            signer, num_accounts, num_instructions, num_inner_instructions, compute_units, priority_fee,
+            compute_unit_price, memo, token_mint, token_amount::NUMERIC, token_decimals, token_program, token_transfer_fee::NUMERIC, swap_input_mint,
+            swap_output_mint, swap_in_amount::NUMERIC, swap_out_amount::NUMERIC, signer_balance_delta,
+            CASE WHEN log_messages IS NULL THEN NULL ELSE ARRAY(SELECT jsonb_array_elements_text(log_messages)) END,
+            logs_truncated, ARRAY(SELECT jsonb_array_elements_text(program_ids)), raw_data,
+            ARRAY(SELECT jsonb_array_elements_text(signers)), version, recent_blockhash,
+            token_source_owner, token_destination_owner, raw_data_compressed,
+            ARRAY(SELECT jsonb_array_elements_text(secondary_types)), error_reason, max_cpi_depth
+        FROM transactions_staging
+        ORDER BY signature, block_slot DESC, transaction_index DESC{}",
+        TRANSACTION_COLUMNS,
+        on_conflict.clause("signature, block_slot", TRANSACTION_UPDATE_SET)
+    );
+    let result = sqlx::query(&merge).execute(&mut *conn).await.context("Failed to merge staged transactions")?;
+
+    tracing::debug!("Merged {} staged transactions", result.rows_affected());
+    Ok(result.rows_affected())
+}
+
+/// Encode transaction rows in the PostgreSQL binary COPY format
+///
+/// Each field is a big-endian i32 length (-1 for NULL) followed by the value in the type's
+/// binary send format. JSONB values are prefixed with the format version byte.
+fn encode_transaction_rows(rows: &[TransactionRow<'_>]) -> Vec<u8> {
+    fn field(buf: &mut Vec<u8>, value: Option<&[u8]>) {
+        match value {
+            Some(bytes) => {
+                buf.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+                buf.extend_from_slice(bytes);
+            }
+            None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+
+    fn jsonb(value: &serde_json::Value) -> Vec<u8> {
+        let mut bytes = vec![1u8];
+        bytes.extend_from_slice(value.to_string().as_bytes());
+        bytes
+    }
+
+    let mut buf = PGCOPY_HEADER.to_vec();
+
+    for row in rows {
+        let raw_data = row.raw_data.map(jsonb);
+        let token_mint = row.token_transfer.and_then(|t| t.mint.as_deref());
+        let token_amount = row.token_transfer.map(|t| t.amount.to_string());
+        let token_decimals = row.token_transfer.and_then(|t| t.decimals).map(i16::from);
+        let token_program = row.token_transfer.map(|t| t.token_program.as_str());
+        let token_transfer_fee = row.token_transfer.and_then(|t| t.transfer_fee).map(|fee| fee.to_string());
+        let swap_in_amount = row.jupiter_swap.map(|swap| swap.in_amount.to_string());
+        let swap_out_amount = row.jupiter_swap.map(|swap| swap.out_amount.to_string());
+        // TEXT[] columns are staged as JSONB arrays, which are simpler to encode than the binary array format
+        let log_messages = (!row.log_messages.is_empty()).then(|| jsonb(&serde_json::Value::from(row.log_messages)));
+        let program_ids = jsonb(&serde_json::Value::from(row.program_ids));
+        let signers = jsonb(&serde_json::Value::from(row.signers));
+        let secondary_types =
+            jsonb(&serde_json::Value::from(row.secondary_types.iter().map(|t| t.as_str()).collect::<Vec<_>>()));
+
+        buf.extend_from_slice(&TRANSACTION_COPY_COLUMNS.to_be_bytes());
+        field(&mut buf, Some(row.signature.as_bytes()));
+        field(&mut buf, Some(&row.block_slot.to_be_bytes()));
+        field(&mut buf, Some(&row.transaction_index.to_be_bytes()));
+        field(&mut buf, Some(&[row.success as u8]));
+        field(&mut buf, Some(&row.fee.to_be_bytes()));
+        field(&mut buf, Some(row.transaction_type.as_str().as_bytes()));
+        field(&mut buf, Some(row.label.as_bytes()));
+        field(&mut buf, row.signer.map(str::as_bytes));
+        field(&mut buf, Some(&row.num_accounts.to_be_bytes()));
+        field(&mut buf, Some(&row.num_instructions.to_be_bytes()));
+        field(&mut buf, Some(&row.num_inner_instructions.to_be_bytes()));
+        field(&mut buf, row.compute_units.map(i64::to_be_bytes).as_ref().map(|b| &b[..]));
+        field(&mut buf, row.priority_fee.map(i64::to_be_bytes).as_ref().map(|b| &b[..]));
+        field(&mut buf, row.compute_unit_price.map(i64::to_be_bytes).as_ref().map(|b| &b[..]));
+        field(&mut buf, row.memo.as_deref().map(str::as_bytes));
+        field(&mut buf, token_mint.map(str::as_bytes));
+        field(&mut buf, token_amount.as_deref().map(str::as_bytes));
+        field(&mut buf, token_decimals.map(i16::to_be_bytes).as_ref().map(|b| &b[..]));
+        field(&mut buf, token_program.map(str::as_bytes));
+        field(&mut buf, token_transfer_fee.as_deref().map(str::as_bytes));
+        field(&mut buf, row.jupiter_swap.map(|swap| swap.input_mint.as_bytes()));
+        field(&mut buf, row.jupiter_swap.map(|swap| swap.output_mint.as_bytes()));
+        field(&mut buf, swap_in_amount.as_deref().map(str::as_bytes));
+        field(&mut buf, swap_out_amount.as_deref().map(str::as_bytes));
+        field(&mut buf, Some(&row.signer_balance_delta.to_be_bytes()));
+        field(&mut buf, log_messages.as_deref());
+        field(&mut buf, Some(&[row.logs_truncated as u8]));
+        field(&mut buf, Some(&program_ids));
+        field(&mut buf, raw_data.as_deref());
+        field(&mut buf, Some(&signers));
+        field(&mut buf, row.version.map(i16::to_be_bytes).as_ref().map(|b| &b[..]));
+        field(&mut buf, row.recent_blockhash.map(str::as_bytes));
+        field(&mut buf, row.token_transfer.and_then(|t| t.source_owner.as_deref()).map(str::as_bytes));
+        field(&mut buf, row.token_transfer.and_then(|t| t.destination_owner.as_deref()).map(str::as_bytes));
+        field(&mut buf, row.raw_data_compressed.as_deref());
+        field(&mut buf, Some(&secondary_types));
+        field(&mut buf, row.error_reason.map(str::as_bytes));
+        field(&mut buf, row.max_cpi_depth.map(i16::to_be_bytes).as_ref().map(|b| &b[..]));
+    }
+
+    // File trailer
+    buf.extend_from_slice(&(-1i16).to_be_bytes());
+    buf
+}
+
+/// Blocks of a batch sorted by slot, ascending
+///
+/// `upsert_block` only links a block to a parent that is already stored, so parents must come
+/// first. Blocks with the same slot keep their batch order.
+fn in_slot_order(blocks: &[ExtractedBlock]) -> Vec<&ExtractedBlock> {
+    let mut ordered: Vec<&ExtractedBlock> = blocks.iter().collect();
+    ordered.sort_by_key(|block| block.slot);
+    ordered
+}
+
+/// Insert a block row, storing a NULL parent_slot when the parent block is not in the database
+/// (avoids foreign key violations at the start of a range)
+async fn upsert_block(conn: &mut PgConnection, block: &ExtractedBlock, on_conflict: ConflictMode) -> Result<()> {
+    let block_time = block_time_to_utc(block.block_time);
+
+    // Check if parent block exists in database
+    let parent_exists = if block.parent_slot == 0 {
+        false // Genesis block has no parent
+    } else {
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM blocks WHERE slot = $1)")
+            .bind(block.parent_slot as i64)
+            .fetch_one(&mut *conn)
+            .await?
+    };
+
+    let parent_slot_value = if parent_exists { Some(block.parent_slot as i64) } else { None };
+
+    let insert = format!(
+        "INSERT INTO blocks (slot, blockhash, parent_slot, block_time, block_height, transaction_count) \
+         VALUES ($1, $2, $3, $4, $5, $6){}",
+        on_conflict.clause(
+            "slot",
+            "blockhash = EXCLUDED.blockhash, \
+             parent_slot = EXCLUDED.parent_slot, \
+             block_time = EXCLUDED.block_time, \
+             block_height = COALESCE(EXCLUDED.block_height, blocks.block_height), \
+             transaction_count = EXCLUDED.transaction_count, \
+             processed_at = NOW()",
+        )
+    );
+    sqlx::query(&insert)
+        .bind(block.slot as i64)
+        .bind(&block.blockhash)
+        .bind(parent_slot_value)
+        .bind(block_time)
+        .bind(block.block_height.map(|h| h as i64))
+        .bind(block.transaction_count as i32)
+        .execute(&mut *conn)
+        .await
+        .context(format!("Failed to insert block {}", block.slot))?;
+
+    Ok(())
+}
+
+/// Token transfer columns (mint, raw amount as text, decimals, token program, transfer fee as text) of a transaction
+///
+/// Amounts are bound as text and cast to NUMERIC in SQL, since they can exceed BIGINT.
+#[allow(clippy::type_complexity)]
+fn token_transfer_columns(
+    transaction: &crate::etl::extract::ExtractedTransaction,
+) -> (Option<String>, Option<String>, Option<i16>, Option<&'static str>, Option<String>) {
+    match &transaction.token_transfer {
+        Some(transfer) => (
+            transfer.mint.clone(),
+            Some(transfer.amount.to_string()),
+            transfer.decimals.map(i16::from),
+            Some(transfer.token_program.as_str()),
+            transfer.transfer_fee.map(|fee| fee.to_string()),
+        ),
+        None => (None, None, None, None, None),
+    }
+}
+
+/// Jupiter swap columns (input mint, output mint, amounts in and out as text) of a transaction
+fn swap_columns(
+    transaction: &crate::etl::extract::ExtractedTransaction,
+) -> (Option<&str>, Option<&str>, Option<String>, Option<String>) {
+    match &transaction.jupiter_swap {
+        Some(swap) => (
+            Some(swap.input_mint.as_str()),
+            Some(swap.output_mint.as_str()),
+            Some(swap.in_amount.to_string()),
+            Some(swap.out_amount.to_string()),
+        ),
+        None => (None, None, None, None),
+    }
+}
+
+/// Raw JSON columns (`raw_data`, `raw_data_compressed`) of a transaction: at most one is set
+fn raw_data_columns(
+    transaction: &crate::etl::extract::ExtractedTransaction,
+    raw_storage: RawDataStorage,
+) -> (Option<&serde_json::Value>, Option<Vec<u8>>) {
+    match raw_storage {
+        RawDataStorage::Json => (Some(&transaction.raw_json), None),
+        RawDataStorage::Compressed => (None, Some(compress_raw_data(&transaction.raw_json))),
+        RawDataStorage::Omitted => (None, None),
+    }
+}
+
+/// Compress raw transaction JSON for `raw_data_compressed`
+pub fn compress_raw_data(raw_data: &serde_json::Value) -> Vec<u8> {
+    zstd::bulk::compress(raw_data.to_string().as_bytes(), RAW_DATA_COMPRESSION_LEVEL)
+        .expect("compressing an in-memory buffer does not fail")
+}
+
+/// Decompress a `raw_data_compressed` value back into the transaction JSON
+pub fn decompress_raw_data(compressed: &[u8]) -> Result<serde_json::Value> {
+    let json = zstd::stream::decode_all(compressed).context("Invalid zstd data")?;
+    serde_json::from_slice(&json).context("Invalid transaction JSON")
+}
+
+/// Program log lines of a transaction, NULL when it logged nothing
+fn log_messages_column(transaction: &crate::etl::extract::ExtractedTransaction) -> Option<&[String]> {
+    (!transaction.log_messages.is_empty()).then_some(transaction.log_messages.as_slice())
+}
+
+/// Batch insert parsed instructions
+///
+/// Uses multi-row INSERT statements with UPSERT logic on (transaction_signature, instruction_index),
+/// so re-processing a block replaces its instruction rows (unless `on_conflict` says otherwise).
+/// The referenced transactions must already exist. Returns the number of instructions inserted.
+pub async fn batch_insert_instructions(
+    conn: &mut PgConnection,
+    instructions: &[crate::models::Instruction],
+    on_conflict: ConflictMode,
+) -> Result<usize> {
+    for chunk in instructions.chunks(INSTRUCTION_INSERT_CHUNK) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO instructions (transaction_signature, instruction_index, program_id, program_name, \
+             instruction_type, accounts, num_accounts) ",
+        );
+
+        query.push_values(chunk, |mut row, instruction| {
+            row.push_bind(&instruction.transaction_signature)
+                .push_bind(instruction.instruction_index)
+                .push_bind(&instruction.program_id)
+                .push_bind(&instruction.program_name)
+                .push_bind(&instruction.instruction_type)
+                .push_bind(&instruction.accounts)
+                .push_bind(instruction.accounts.len() as i32);
+        });
+
+        query.push(on_conflict.clause(
+            "transaction_signature, instruction_index",
+            "program_id = EXCLUDED.program_id, \
+             program_name = EXCLUDED.program_name, \
+             instruction_type = EXCLUDED.instruction_type, \
+             accounts = EXCLUDED.accounts, \
+             num_accounts = EXCLUDED.num_accounts, \
+             processed_at = NOW()",
+        ));
+
+        query.build().execute(&mut *conn).await?;
+    }
+
+    tracing::debug!("Inserted {} instructions", instructions.len());
+    Ok(instructions.len())
+}
+
+/// Batch insert token balance changes as (transaction signature, change) pairs
+///
+/// Uses UPSERT logic on (transaction_signature, owner, mint), see `ConflictMode`. The referenced
+/// transactions must already exist. Returns the number of rows inserted.
+pub async fn batch_insert_token_balances(
+    conn: &mut PgConnection,
+    changes: &[(&str, &crate::etl::parsers::TokenBalanceChange)],
+    on_conflict: ConflictMode,
+) -> Result<usize> {
+    for chunk in changes.chunks(TOKEN_BALANCE_INSERT_CHUNK) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO token_balances (transaction_signature, owner, mint, decimals, amount_delta) ",
+        );
+
+        query.push_values(chunk, |mut row, (signature, change)| {
+            row.push_bind(*signature)
+                .push_bind(&change.owner)
+                .push_bind(&change.mint)
+                .push_bind(change.decimals as i16)
+                .push_bind(change.amount_delta.to_string())
+                .push_unseparated("::NUMERIC");
+        });
+
+        query.push(on_conflict.clause(
+            "transaction_signature, owner, mint",
+            "decimals = EXCLUDED.decimals, \
+             amount_delta = EXCLUDED.amount_delta, \
+             processed_at = NOW()",
+        ));
+
+        query.build().execute(&mut *conn).await?;
+    }
+
+    tracing::debug!("Inserted {} token balance changes", changes.len());
+    Ok(changes.len())
+}
+
+/// Batch insert the rewards of a block
+///
+/// Uses UPSERT logic on (block_slot, pubkey, reward_type), see `ConflictMode`. The block must
+/// already exist. Returns the number of rows inserted.
+pub async fn batch_insert_block_rewards(
+    conn: &mut PgConnection,
+    block_slot: u64,
+    rewards: &[crate::models::Reward],
+    on_conflict: ConflictMode,
+) -> Result<usize> {
+    for chunk in rewards.chunks(REWARD_INSERT_CHUNK) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO block_rewards (block_slot, pubkey, lamports, post_balance, reward_type, commission) ",
+        );
+
+        query.push_values(chunk, |mut row, reward| {
+            row.push_bind(block_slot as i64)
+                .push_bind(&reward.pubkey)
+                .push_bind(reward.lamports)
+                .push_bind(reward.post_balance as i64)
+                .push_bind(reward.reward_type.as_str())
+                .push_bind(reward.commission.map(i16::from));
+        });
+
+        query.push(on_conflict.clause(
+            "block_slot, pubkey, reward_type",
+            "lamports = EXCLUDED.lamports, \
+             post_balance = EXCLUDED.post_balance, \
+             commission = EXCLUDED.commission, \
+             processed_at = NOW()",
+        ));
+
+        query.build().execute(&mut *conn).await.context(format!("Failed to insert rewards of block {}", block_slot))?;
+    }
+
+    tracing::debug!("Inserted {} rewards for block {}", rewards.len(), block_slot);
+    Ok(rewards.len())
+}
+
+/// Check whether a failed load is worth retrying
+///
+/// Connection drops, pool timeouts, deadlocks and serialization failures are transient, so
+/// the same batch can succeed on a later attempt. Constraint violations, bad data and
+/// decoding errors fail the same way every time. Errors that did not come from the
+/// database (e.g. a file export) are treated as transient.
+pub fn is_retryable_load_error(error: &anyhow::Error) -> bool {
+    let Some(error) = error.chain().find_map(|cause| cause.downcast_ref::<sqlx::Error>()) else {
+        return true;
+    };
+
+    match error {
+        sqlx::Error::Database(db_error) => {
+            if db_error.is_unique_violation() || db_error.is_foreign_key_violation() || db_error.is_check_violation() {
+                return false;
+            }
+            db_error.code().is_some_and(|code| is_transient_sqlstate(&code))
+        }
+        sqlx::Error::Io(_) |
+        sqlx::Error::Tls(_) |
+        sqlx::Error::Protocol(_) |
+        sqlx::Error::PoolTimedOut |
+        sqlx::Error::WorkerCrashed |
+        sqlx::Error::BeginFailed => true,
+        _ => false,
+    }
+}
+
+/// SQLSTATE codes of transient failures: connection exceptions (class 08), transaction
+/// rollbacks such as serialization failures and deadlocks (class 40), insufficient
+/// resources (class 53) and server shutdowns (57P01-57P03)
+fn is_transient_sqlstate(code: &str) -> bool {
+    code.starts_with("08") ||
+        code.starts_with("40") ||
+        code.starts_with("53") ||
+        matches!(code, "57P01" | "57P02" | "57P03")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TransactionType;
+
+    fn block(slot: u64, parent_slot: u64) -> ExtractedBlock {
+        ExtractedBlock {
+            slot,
+            blockhash: format!("hash{}", slot),
+            parent_slot,
+            block_time: None,
+            block_height: None,
+            rewards: vec![],
+            transactions: vec![],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 0,
+            signatures_only: false,
+        }
+    }
+
+    #[test]
+    fn test_in_slot_order() {
+        let blocks = vec![block(12, 11), block(10, 9), block(11, 10)];
+        assert_eq!(in_slot_order(&blocks).iter().map(|b| b.slot).collect::<Vec<_>>(), vec![10, 11, 12]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL database in DATABASE_URL"]
+    async fn test_batch_insert_links_parent_in_same_batch() {
+        let database = crate::db::Database::new(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        database.migrate().await.unwrap();
+        let (parent, child) = (999_000_000_u64, 999_000_001_u64);
+        database.ensure_partition(parent).await.unwrap();
+        let cleanup = "DELETE FROM blocks WHERE slot IN ($1, $2)";
+        sqlx::query(cleanup).bind(parent as i64).bind(child as i64).execute(database.pool()).await.unwrap();
+
+        // The child comes first in the batch
+        let blocks = vec![block(child, parent), block(parent, parent - 1)];
+        let registry = crate::etl::transform::ProgramRegistry::from_database(vec![]);
+        batch_insert_blocks_with_transactions(
+            database.pool(),
+            &blocks,
+            &registry,
+            &crate::etl::transform::DefaultClassifier,
+            ConflictMode::Update,
+            RawDataStorage::Json,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let parent_slot: Option<i64> = sqlx::query_scalar("SELECT parent_slot FROM blocks WHERE slot = $1")
+            .bind(child as i64)
+            .fetch_one(database.pool())
+            .await
+            .unwrap();
+        sqlx::query(cleanup).bind(parent as i64).bind(child as i64).execute(database.pool()).await.unwrap();
+        assert_eq!(parent_slot, Some(parent as i64));
+    }
+
+    #[test]
+    fn test_encode_transaction_rows() {
+        let raw_data = serde_json::json!({ "message": {} });
+        let row = TransactionRow {
+            signature: "sig1",
+            block_slot: 100,
+            transaction_index: 0,
+            success: true,
+            fee: 5000,
+            transaction_type: TransactionType::SolTransfer,
+            label: "SOL Transfer".to_string(),
+            signer: None,
+            num_accounts: 2,
+            num_instructions: 1,
+            num_inner_instructions: 0,
+            compute_units: Some(150),
+            priority_fee: None,
+            compute_unit_price: None,
+            memo: None,
+            token_transfer: None,
+            jupiter_swap: None,
+            signer_balance_delta: -5000,
+            log_messages: &[],
+            logs_truncated: false,
+            program_ids: &[],
+            raw_data: Some(&raw_data),
+            signers: &[],
+            version: Some(0),
+            recent_blockhash: Some("1111"),
+            raw_data_compressed: None,
+            secondary_types: vec![TransactionType::AccountCreation],
+            error_reason: None,
+            max_cpi_depth: Some(1),
+        };
+
+        let encoded = encode_transaction_rows(&[row]);
+        assert!(encoded.starts_with(PGCOPY_HEADER));
+        assert!(encoded.ends_with(&(-1i16).to_be_bytes()));
+
+        let tuple = &encoded[PGCOPY_HEADER.len()..];
+        assert_eq!(&tuple[..2], &TRANSACTION_COPY_COLUMNS.to_be_bytes());
+        // First field: signature length then bytes
+        assert_eq!(&tuple[2..6], &4i32.to_be_bytes());
+        assert_eq!(&tuple[6..10], b"sig1");
+        // JSONB is sent with the version 1 prefix
+        assert!(tuple.windows(2).any(|w| w == [1, b'{']));
+    }
+
+    #[test]
+    fn test_raw_data_columns() {
+        let transaction = crate::etl::extract::ExtractedTransaction {
+            raw_json: serde_json::json!({ "signatures": ["sig1"] }),
+            ..Default::default()
+        };
+
+        let (raw_data, compressed) = raw_data_columns(&transaction, RawDataStorage::Json);
+        assert_eq!((raw_data, compressed), (Some(&transaction.raw_json), None));
+        let (raw_data, compressed) = raw_data_columns(&transaction, RawDataStorage::Compressed);
+        assert!(raw_data.is_none());
+        assert_eq!(decompress_raw_data(&compressed.unwrap()).unwrap(), transaction.raw_json);
+        assert_eq!(raw_data_columns(&transaction, RawDataStorage::Omitted), (None, None));
+    }
+
+    #[test]
+    fn test_raw_data_compression_round_trip() {
+        let raw_data = serde_json::json!({
+            "message": { "accountKeys": ["Signer1111", "11111111111111111111111111111111"], "instructions": [] },
+            "signatures": ["sig1"]
+        });
+
+        let compressed = compress_raw_data(&raw_data);
+        assert_eq!(decompress_raw_data(&compressed).unwrap(), raw_data);
+        assert!(decompress_raw_data(b"not zstd").is_err());
+    }
+
+    /// Database error with a fixed SQLSTATE, standing in for a Postgres error
+    #[derive(Debug)]
+    struct MockDatabaseError {
+        code: &'static str,
+    }
+
+    impl std::fmt::Display for MockDatabaseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "database error {}", self.code)
+        }
+    }
+
+    impl std::error::Error for MockDatabaseError {}
+
+    impl sqlx::error::DatabaseError for MockDatabaseError {
+        fn message(&self) -> &str {
+            "mock"
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some(self.code.into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            match self.code {
+                "23505" => sqlx::error::ErrorKind::UniqueViolation,
+                "23503" => sqlx::error::ErrorKind::ForeignKeyViolation,
+                "23514" => sqlx::error::ErrorKind::CheckViolation,
+                _ => sqlx::error::ErrorKind::Other,
+            }
+        }
+    }
+
+    fn database_error(code: &'static str) -> anyhow::Error {
+        anyhow::Error::new(sqlx::Error::Database(Box::new(MockDatabaseError { code })))
+            .context("Failed to insert transaction")
+    }
+
+    #[test]
+    fn test_conflict_clause() {
+        let update_set = "lamports = EXCLUDED.lamports";
+        assert_eq!(
+            ConflictMode::Update.clause("block_slot, pubkey", update_set),
+            " ON CONFLICT (block_slot, pubkey) DO UPDATE SET lamports = EXCLUDED.lamports"
+        );
+        assert_eq!(
+            ConflictMode::Skip.clause("block_slot, pubkey", update_set),
+            " ON CONFLICT (block_slot, pubkey) DO NOTHING"
+        );
+        assert_eq!(ConflictMode::Error.clause("block_slot, pubkey", update_set), "");
+        assert_eq!(ConflictMode::default(), ConflictMode::Update);
+    }
+
+    #[test]
+    fn test_is_retryable_load_error() {
+        // Deadlock, serialization failure, connection failure
+        assert!(is_retryable_load_error(&database_error("40P01")));
+        assert!(is_retryable_load_error(&database_error("40001")));
+        assert!(is_retryable_load_error(&database_error("08006")));
+        assert!(is_retryable_load_error(&anyhow::Error::new(sqlx::Error::PoolTimedOut)));
+
+        // Unique violation, invalid JSON, value too long
+        assert!(!is_retryable_load_error(&database_error("23505")));
+        assert!(!is_retryable_load_error(&database_error("22P02")));
+        assert!(!is_retryable_load_error(&database_error("22001")));
+        assert!(!is_retryable_load_error(&anyhow::Error::new(sqlx::Error::ColumnNotFound("slot".to_string()))));
+
+        // Not a database error
+        assert!(is_retryable_load_error(&anyhow::anyhow!("connection reset")));
+    }
+}
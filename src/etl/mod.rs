@@ -4,9 +4,13 @@
 /// - Extract: Fetch block and transaction data from Solana RPC
 /// - Transform: Parse and classify transactions
 /// - Load: Store structured data in PostgreSQL database
+/// - Export: Optionally write the same data to files
+pub mod export;
 pub mod extract;
 pub mod load;
 pub mod parsers;
+pub mod sink;
+pub mod source;
 pub mod transform;
 
 use crate::{db::Database, rpc::SolanaRpcClient};
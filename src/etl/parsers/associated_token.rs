@@ -0,0 +1,135 @@
+/// Associated Token Account Program Instruction Parser
+///
+/// Parses instructions from the Associated Token Account program
+/// (ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL), which creates the canonical token account of
+/// a wallet for a mint. Creating the recipient's account is usually the first instruction of a
+/// token transfer to a new holder, and the whole transaction when a wallet sets up a token.
+///
+/// Associated Token Account program ID
+pub const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+/// Details of a `create` or `createIdempotent` instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssociatedAccountCreation {
+    /// The associated token account being created
+    pub account: String,
+    /// Wallet that owns the new account
+    pub owner: String,
+    pub mint: String,
+    /// Account paying the rent of the new account
+    pub source: String,
+    /// `createIdempotent` succeeds without changes when the account already exists
+    pub idempotent: bool,
+}
+
+/// Parse an Associated Token Account program `create` or `createIdempotent` instruction
+///
+/// JsonParsed instructions name the accounts in `parsed.info`. Instructions the RPC could not
+/// parse list account indexes in the program's fixed order (funding account, associated account,
+/// wallet, mint, ...) and carry the instruction in their data: empty or 0 for `create`, 1 for
+/// `createIdempotent`.
+///
+/// Returns None for other instructions (e.g., `recoverNested`) and other programs.
+pub fn parse_associated_account_creation(
+    instruction: &serde_json::Value,
+    account_keys: &[String],
+) -> Option<AssociatedAccountCreation> {
+    let program_id = crate::etl::extract::instruction_program_id(instruction, account_keys)?;
+    if program_id != ASSOCIATED_TOKEN_PROGRAM {
+        return None;
+    }
+
+    if let Some(parsed) = instruction.get("parsed") {
+        let idempotent = match parsed.get("type").and_then(|t| t.as_str())? {
+            "create" => false,
+            "createIdempotent" => true,
+            _ => return None,
+        };
+        let info = parsed.get("info")?;
+        let field = |name: &str| info.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        return Some(AssociatedAccountCreation {
+            account: field("account")?,
+            owner: field("wallet")?,
+            mint: field("mint")?,
+            source: field("source")?,
+            idempotent,
+        });
+    }
+
+    let data = bs58::decode(instruction.get("data")?.as_str()?).into_vec().ok()?;
+    let idempotent = match data.first() {
+        None | Some(0) => false,
+        Some(1) => true,
+        Some(_) => return None,
+    };
+
+    let accounts = instruction.get("accounts")?.as_array()?;
+    let account = |position: usize| {
+        let index = accounts.get(position)?.as_u64()?;
+        account_keys.get(index as usize).cloned()
+    };
+
+    Some(AssociatedAccountCreation {
+        source: account(0)?,
+        account: account(1)?,
+        owner: account(2)?,
+        mint: account(3)?,
+        idempotent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_associated_account_creation() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "createIdempotent",
+                "info": {
+                    "account": "NewAta111",
+                    "mint": "Mint111",
+                    "source": "Payer111",
+                    "systemProgram": "11111111111111111111111111111111",
+                    "tokenProgram": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "wallet": "Wallet111"
+                }
+            },
+            "program": "spl-associated-token-account",
+            "programId": ASSOCIATED_TOKEN_PROGRAM
+        });
+
+        let creation = parse_associated_account_creation(&instruction, &[]).unwrap();
+        assert_eq!(
+            creation,
+            AssociatedAccountCreation {
+                account: "NewAta111".to_string(),
+                owner: "Wallet111".to_string(),
+                mint: "Mint111".to_string(),
+                source: "Payer111".to_string(),
+                idempotent: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_compiled_associated_account_creation() {
+        let account_keys: Vec<String> = ["Payer111", "NewAta111", "Wallet111", "Mint111", ASSOCIATED_TOKEN_PROGRAM]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let instruction = serde_json::json!({ "programIdIndex": 4, "accounts": [0, 1, 2, 3], "data": "" });
+
+        let creation = parse_associated_account_creation(&instruction, &account_keys).unwrap();
+        assert_eq!(
+            (creation.owner.as_str(), creation.mint.as_str(), creation.idempotent),
+            ("Wallet111", "Mint111", false)
+        );
+
+        // recoverNested is not a creation
+        let recover = serde_json::json!({ "programIdIndex": 4, "accounts": [0, 1, 2, 3], "data": "3" });
+        assert!(parse_associated_account_creation(&recover, &account_keys).is_none());
+    }
+}
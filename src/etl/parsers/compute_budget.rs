@@ -0,0 +1,157 @@
+/// Compute Budget Instruction Parser
+///
+/// Parses instructions from the Compute Budget program (ComputeBudget111111111111111111111111111111).
+/// The RPC has no JsonParsed support for this program, so instructions arrive as raw base58
+/// `data`: a one-byte discriminator followed by little-endian arguments.
+///
+/// Compute Budget program ID
+pub const COMPUTE_BUDGET_PROGRAM: &str = "ComputeBudget111111111111111111111111111111";
+
+/// Compute units allotted per instruction when no `SetComputeUnitLimit` is present
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// Maximum compute units a transaction can request
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Micro-lamports per lamport
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// A decoded Compute Budget instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBudgetInstruction {
+    RequestHeapFrame(u32),
+    SetComputeUnitLimit(u32),
+    /// Price in micro-lamports per compute unit
+    SetComputeUnitPrice(u64),
+    SetLoadedAccountsDataSizeLimit(u32),
+}
+
+/// Decode a Compute Budget instruction from its raw base58 data
+///
+/// Returns None for other programs, the deprecated `RequestUnits` instruction and malformed data.
+pub fn parse_compute_budget(instruction: &serde_json::Value) -> Option<ComputeBudgetInstruction> {
+    if instruction.get("programId").and_then(|p| p.as_str()) != Some(COMPUTE_BUDGET_PROGRAM) {
+        return None;
+    }
+
+    let data = bs58::decode(instruction.get("data")?.as_str()?).into_vec().ok()?;
+    let (discriminator, args) = data.split_first()?;
+
+    match discriminator {
+        1 => Some(ComputeBudgetInstruction::RequestHeapFrame(u32::from_le_bytes(args.get(..4)?.try_into().ok()?))),
+        2 => Some(ComputeBudgetInstruction::SetComputeUnitLimit(u32::from_le_bytes(args.get(..4)?.try_into().ok()?))),
+        3 => Some(ComputeBudgetInstruction::SetComputeUnitPrice(u64::from_le_bytes(args.get(..8)?.try_into().ok()?))),
+        4 => Some(ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(u32::from_le_bytes(
+            args.get(..4)?.try_into().ok()?,
+        ))),
+        _ => None,
+    }
+}
+
+/// Priority fee settings of a transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriorityFee {
+    /// Price in micro-lamports per compute unit, None when the transaction sets no price
+    pub compute_unit_price: Option<u64>,
+    /// Priority fee in lamports (the part of `meta.fee` above the base signature fee)
+    pub priority_fee: Option<u64>,
+}
+
+/// Compute the priority fee of a transaction from its top-level instructions
+///
+/// The fee is `ceil(price * unit_limit / 1_000_000)` lamports. When the transaction does not
+/// set a unit limit, the runtime default of 200,000 units per non-compute-budget instruction
+/// (capped at 1,400,000) is used. Transactions without `SetComputeUnitPrice` pay no priority
+/// fee and both fields are None.
+pub fn parse_priority_fee(instructions: &[serde_json::Value]) -> PriorityFee {
+    let mut unit_limit = None;
+    let mut unit_price = None;
+
+    for instruction in instructions {
+        match parse_compute_budget(instruction) {
+            Some(ComputeBudgetInstruction::SetComputeUnitLimit(limit)) => unit_limit = Some(limit as u64),
+            Some(ComputeBudgetInstruction::SetComputeUnitPrice(price)) => unit_price = Some(price),
+            _ => {}
+        }
+    }
+
+    let Some(price) = unit_price else {
+        return PriorityFee::default();
+    };
+
+    let unit_limit = unit_limit.unwrap_or_else(|| {
+        let counted = instructions.iter().filter(|ix| !is_compute_budget(ix)).count() as u64;
+        (counted * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT).min(MAX_COMPUTE_UNIT_LIMIT)
+    });
+
+    let micro_lamports = price as u128 * unit_limit.min(MAX_COMPUTE_UNIT_LIMIT) as u128;
+    let priority_fee = micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+
+    PriorityFee { compute_unit_price: Some(price), priority_fee: u64::try_from(priority_fee).ok() }
+}
+
+/// Compute Budget instructions do not receive the default per-instruction allotment
+fn is_compute_budget(instruction: &serde_json::Value) -> bool {
+    instruction.get("programId").and_then(|p| p.as_str()) == Some(COMPUTE_BUDGET_PROGRAM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compute_budget_instruction(data: &[u8]) -> serde_json::Value {
+        serde_json::json!({
+            "accounts": [],
+            "data": bs58::encode(data).into_string(),
+            "programId": COMPUTE_BUDGET_PROGRAM,
+            "stackHeight": null
+        })
+    }
+
+    fn set_unit_limit(limit: u32) -> serde_json::Value {
+        let mut data = vec![2];
+        data.extend_from_slice(&limit.to_le_bytes());
+        compute_budget_instruction(&data)
+    }
+
+    fn set_unit_price(price: u64) -> serde_json::Value {
+        let mut data = vec![3];
+        data.extend_from_slice(&price.to_le_bytes());
+        compute_budget_instruction(&data)
+    }
+
+    #[test]
+    fn test_parse_compute_budget() {
+        assert_eq!(
+            parse_compute_budget(&set_unit_limit(300_000)),
+            Some(ComputeBudgetInstruction::SetComputeUnitLimit(300_000))
+        );
+        assert_eq!(
+            parse_compute_budget(&set_unit_price(50_000)),
+            Some(ComputeBudgetInstruction::SetComputeUnitPrice(50_000))
+        );
+        assert_eq!(parse_compute_budget(&compute_budget_instruction(&[3, 1])), None);
+    }
+
+    #[test]
+    fn test_parse_priority_fee() {
+        // 300,000 units at 50,001 micro-lamports = 15,000.3 lamports, rounded up
+        let fee = parse_priority_fee(&[set_unit_limit(300_000), set_unit_price(50_001)]);
+        assert_eq!(fee, PriorityFee { compute_unit_price: Some(50_001), priority_fee: Some(15_001) });
+    }
+
+    #[test]
+    fn test_parse_priority_fee_default_limit() {
+        let transfer = serde_json::json!({
+            "parsed": { "type": "transfer", "info": {} },
+            "program": "system",
+            "programId": "11111111111111111111111111111111"
+        });
+
+        // One counted instruction gets the default 200,000 units
+        let fee = parse_priority_fee(&[set_unit_price(1_000_000), transfer]);
+        assert_eq!(fee.priority_fee, Some(200_000));
+
+        assert_eq!(parse_priority_fee(&[set_unit_limit(300_000)]), PriorityFee::default());
+    }
+}
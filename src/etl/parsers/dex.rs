@@ -0,0 +1,123 @@
+/// DEX Liquidity Instruction Parser
+///
+/// Recognizes liquidity deposits and withdrawals of Raydium AMM v4, Raydium CLMM and Orca
+/// Whirlpool. None of them has JsonParsed support, so instructions arrive as raw base58 `data`.
+/// Raydium AMM v4 starts its data with a one-byte instruction tag; the two Anchor programs
+/// start it with an 8-byte discriminator (`sha256("global:<instruction>")[..8]`).
+///
+/// Raydium AMM v4 program ID
+const RAYDIUM_AMM_V4: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+/// Raydium CLMM program ID
+const RAYDIUM_CLMM: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
+
+/// Orca Whirlpool program ID
+const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Raydium AMM v4 `Deposit` and `Withdraw` instruction tags
+const RAYDIUM_AMM_DEPOSIT: u8 = 3;
+const RAYDIUM_AMM_WITHDRAW: u8 = 4;
+
+/// Anchor discriminators of `increase_liquidity` and `increase_liquidity_v2`
+/// (the instruction names are shared by Raydium CLMM and Orca Whirlpool)
+const INCREASE_LIQUIDITY: [[u8; 8]; 2] = [[46, 156, 243, 118, 13, 205, 251, 178], [133, 29, 89, 223, 69, 238, 176, 10]];
+
+/// Anchor discriminators of `decrease_liquidity` and `decrease_liquidity_v2`
+const DECREASE_LIQUIDITY: [[u8; 8]; 2] = [[160, 38, 208, 111, 104, 91, 44, 1], [58, 127, 188, 62, 79, 82, 196, 96]];
+
+/// Direction of a liquidity operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityAction {
+    Add,
+    Remove,
+}
+
+/// Decode a liquidity deposit or withdrawal from a raw DEX instruction
+///
+/// Returns None for swaps, other instructions, other programs and malformed data.
+pub fn parse_liquidity_instruction(instruction: &serde_json::Value) -> Option<LiquidityAction> {
+    let program_id = instruction.get("programId")?.as_str()?;
+    let data = bs58::decode(instruction.get("data")?.as_str()?).into_vec().ok()?;
+
+    match program_id {
+        RAYDIUM_AMM_V4 => match *data.first()? {
+            RAYDIUM_AMM_DEPOSIT => Some(LiquidityAction::Add),
+            RAYDIUM_AMM_WITHDRAW => Some(LiquidityAction::Remove),
+            _ => None,
+        },
+        RAYDIUM_CLMM | ORCA_WHIRLPOOL => {
+            let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+            if INCREASE_LIQUIDITY.contains(&discriminator) {
+                Some(LiquidityAction::Add)
+            } else if DECREASE_LIQUIDITY.contains(&discriminator) {
+                Some(LiquidityAction::Remove)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Find a liquidity operation in the Anchor instruction names a transaction logged
+///
+/// Catches operations reached through CPI (e.g. a vault depositing into a Whirlpool),
+/// which are not visible in the top-level instructions.
+pub fn parse_liquidity_log(log_messages: &[String]) -> Option<LiquidityAction> {
+    log_messages.iter().filter_map(|line| line.strip_prefix("Program log: Instruction: ")).find_map(|instruction| {
+        match instruction.trim() {
+            "IncreaseLiquidity" | "IncreaseLiquidityV2" => Some(LiquidityAction::Add),
+            "DecreaseLiquidity" | "DecreaseLiquidityV2" => Some(LiquidityAction::Remove),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn instruction(program_id: &str, data: &[u8]) -> serde_json::Value {
+        json!({
+            "programId": program_id,
+            "accounts": [],
+            "data": bs58::encode(data).into_string(),
+        })
+    }
+
+    #[test]
+    fn test_parse_raydium_amm_liquidity() {
+        let deposit = instruction(RAYDIUM_AMM_V4, &[3, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(parse_liquidity_instruction(&deposit), Some(LiquidityAction::Add));
+
+        let withdraw = instruction(RAYDIUM_AMM_V4, &[4, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(parse_liquidity_instruction(&withdraw), Some(LiquidityAction::Remove));
+
+        // SwapBaseIn
+        let swap = instruction(RAYDIUM_AMM_V4, &[9, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(parse_liquidity_instruction(&swap), None);
+    }
+
+    #[test]
+    fn test_parse_anchor_liquidity() {
+        let increase = instruction(ORCA_WHIRLPOOL, &[46, 156, 243, 118, 13, 205, 251, 178, 0, 0]);
+        assert_eq!(parse_liquidity_instruction(&increase), Some(LiquidityAction::Add));
+
+        let decrease_v2 = instruction(RAYDIUM_CLMM, &[58, 127, 188, 62, 79, 82, 196, 96]);
+        assert_eq!(parse_liquidity_instruction(&decrease_v2), Some(LiquidityAction::Remove));
+
+        // Too short for a discriminator
+        assert_eq!(parse_liquidity_instruction(&instruction(ORCA_WHIRLPOOL, &[46, 156])), None);
+    }
+
+    #[test]
+    fn test_parse_liquidity_log() {
+        let logs = vec![
+            "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc invoke [2]".to_string(),
+            "Program log: Instruction: DecreaseLiquidityV2".to_string(),
+        ];
+        assert_eq!(parse_liquidity_log(&logs), Some(LiquidityAction::Remove));
+        assert_eq!(parse_liquidity_log(&["Program log: Instruction: Swap".to_string()]), None);
+    }
+}
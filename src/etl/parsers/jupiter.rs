@@ -0,0 +1,257 @@
+/// Jupiter Swap Parser
+///
+/// Extracts the input and output side of Jupiter v6 aggregator swaps (`Route`,
+/// `SharedAccountsRoute` and their exact-out and token-ledger variants). Every hop of a route
+/// emits an Anchor `SwapEvent` with the AMM, input mint and amount, and output mint and amount:
+/// current program versions through a self-CPI (`emit_cpi!`, an inner instruction), older ones
+/// as a `Program data:` log line. When no event can be read, e.g. because the logs were
+/// truncated, the swap is inferred from the signer's balance changes instead.
+use super::{token::WRAPPED_SOL_MINT, token_balance::parse_token_balance_changes};
+use base64::Engine;
+use serde::Serialize;
+
+/// Jupiter Aggregator v6 program ID
+pub const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+
+/// Prefix of Anchor `emit_cpi!` instruction data (`sha256("anchor:event")[..8]`, little endian)
+const EVENT_IX_TAG: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// Anchor discriminator of `SwapEvent` (`sha256("event:SwapEvent")[..8]`)
+const SWAP_EVENT: [u8; 8] = [64, 198, 205, 232, 38, 8, 113, 226];
+
+/// `SwapEvent` body: amm, input mint, input amount, output mint, output amount
+const SWAP_EVENT_LEN: usize = 32 + 32 + 8 + 32 + 8;
+
+/// Input and output of a Jupiter swap, in token base units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JupiterSwap {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub inferred: bool, // Inferred from the signer's balance changes, not read from swap events
+}
+
+/// One hop of a route, as emitted by `SwapEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SwapEvent {
+    input_mint: String,
+    input_amount: u64,
+    output_mint: String,
+    output_amount: u64,
+}
+
+/// Parse the swap of a transaction that invoked Jupiter v6
+///
+/// Reads the `SwapEvent`s of the route: the input mint is the first hop's input and the output
+/// mint the last hop's output; amounts are summed over the hops leaving the input mint and
+/// arriving at the output mint, so split routes count every leg. Without events, the signer's
+/// largest token decrease and increase (or its lamport change, fee excluded, for native SOL)
+/// give an inferred swap. Returns None when neither side can be determined.
+pub fn parse_jupiter_swap(
+    meta: &serde_json::Value,
+    account_keys: &[String],
+    log_messages: &[String],
+) -> Option<JupiterSwap> {
+    let mut events = inner_instruction_events(meta, account_keys);
+    if events.is_empty() {
+        events = log_events(log_messages);
+    }
+
+    match (events.first(), events.last()) {
+        (Some(first), Some(last)) => {
+            let in_amount = events.iter().filter(|e| e.input_mint == first.input_mint).map(|e| e.input_amount).sum();
+            let out_amount = events.iter().filter(|e| e.output_mint == last.output_mint).map(|e| e.output_amount).sum();
+            Some(JupiterSwap {
+                input_mint: first.input_mint.clone(),
+                output_mint: last.output_mint.clone(),
+                in_amount,
+                out_amount,
+                inferred: false,
+            })
+        }
+        _ => infer_from_balances(meta, account_keys),
+    }
+}
+
+/// `SwapEvent`s emitted through self-CPI, in execution order
+fn inner_instruction_events(meta: &serde_json::Value, account_keys: &[String]) -> Vec<SwapEvent> {
+    let Some(inner_instructions) = meta.get("innerInstructions").and_then(|i| i.as_array()) else {
+        return Vec::new();
+    };
+
+    inner_instructions
+        .iter()
+        .filter_map(|inner| inner.get("instructions").and_then(|i| i.as_array()))
+        .flatten()
+        .filter(|instruction| {
+            let program_id = instruction.get("programId").and_then(|p| p.as_str()).or_else(|| {
+                let index = instruction.get("programIdIndex").and_then(|i| i.as_u64())?;
+                account_keys.get(index as usize).map(String::as_str)
+            });
+            program_id == Some(JUPITER_V6)
+        })
+        .filter_map(|instruction| bs58::decode(instruction.get("data")?.as_str()?).into_vec().ok())
+        .filter_map(|data| decode_swap_event(data.strip_prefix(&EVENT_IX_TAG)?))
+        .collect()
+}
+
+/// `SwapEvent`s emitted as `Program data:` log lines by older program versions
+fn log_events(log_messages: &[String]) -> Vec<SwapEvent> {
+    log_messages
+        .iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data.trim()).ok())
+        .filter_map(|data| decode_swap_event(&data))
+        .collect()
+}
+
+/// Decode a `SwapEvent` from its discriminator and Borsh body
+fn decode_swap_event(data: &[u8]) -> Option<SwapEvent> {
+    let body = data.strip_prefix(&SWAP_EVENT)?;
+    if body.len() < SWAP_EVENT_LEN {
+        return None;
+    }
+
+    let pubkey = |offset: usize| bs58::encode(&body[offset..offset + 32]).into_string();
+    let amount = |offset: usize| u64::from_le_bytes(body[offset..offset + 8].try_into().unwrap());
+
+    // body[0..32] is the AMM that executed the hop
+    Some(SwapEvent {
+        input_mint: pubkey(32),
+        input_amount: amount(64),
+        output_mint: pubkey(72),
+        output_amount: amount(104),
+    })
+}
+
+/// Infer a swap from the signer's token balance changes and lamport change
+///
+/// The input is the mint the signer lost the most of and the output the mint it gained the
+/// most of. A side without a token change is native SOL when the signer's lamports (fee
+/// excluded) moved that way; account rent paid or refunded in the same transaction is included.
+fn infer_from_balances(meta: &serde_json::Value, account_keys: &[String]) -> Option<JupiterSwap> {
+    let signer = account_keys.first()?;
+    let changes = parse_token_balance_changes(meta, account_keys);
+    let signer_changes = changes.iter().filter(|change| &change.owner == signer);
+
+    let balance = |field: &str| meta.get(field).and_then(|b| b.get(0)).and_then(|b| b.as_i64());
+    let fee = meta.get("fee").and_then(|f| f.as_i64()).unwrap_or(0);
+    let lamports = match (balance("preBalances"), balance("postBalances")) {
+        (Some(pre), Some(post)) => post - pre + fee,
+        _ => 0,
+    };
+
+    let input = signer_changes
+        .clone()
+        .filter(|change| change.amount_delta < 0)
+        .min_by_key(|change| change.amount_delta)
+        .map(|change| (change.mint.clone(), change.amount_delta.unsigned_abs()))
+        .or_else(|| (lamports < 0).then(|| (WRAPPED_SOL_MINT.to_string(), lamports.unsigned_abs() as u128)))?;
+    let output = signer_changes
+        .filter(|change| change.amount_delta > 0)
+        .max_by_key(|change| change.amount_delta)
+        .map(|change| (change.mint.clone(), change.amount_delta as u128))
+        .or_else(|| (lamports > 0).then(|| (WRAPPED_SOL_MINT.to_string(), lamports as u128)))?;
+
+    Some(JupiterSwap {
+        input_mint: input.0,
+        output_mint: output.0,
+        in_amount: u64::try_from(input.1).ok()?,
+        out_amount: u64::try_from(output.1).ok()?,
+        inferred: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn swap_event(input_mint: [u8; 32], input_amount: u64, output_mint: [u8; 32], output_amount: u64) -> Vec<u8> {
+        let mut data = SWAP_EVENT.to_vec();
+        data.extend_from_slice(&[9; 32]); // amm
+        data.extend_from_slice(&input_mint);
+        data.extend_from_slice(&input_amount.to_le_bytes());
+        data.extend_from_slice(&output_mint);
+        data.extend_from_slice(&output_amount.to_le_bytes());
+        data
+    }
+
+    fn mint(byte: u8) -> String {
+        bs58::encode([byte; 32]).into_string()
+    }
+
+    #[test]
+    fn test_parse_jupiter_swap_from_events() {
+        // Two-hop route A -> B -> C, with the second hop reached through an inner instruction
+        // that only names its program by index
+        let hop = |data: Vec<u8>| bs58::encode([EVENT_IX_TAG.to_vec(), data].concat()).into_string();
+        let meta = json!({
+            "innerInstructions": [{
+                "index": 0,
+                "instructions": [
+                    { "programId": JUPITER_V6, "accounts": [], "data": hop(swap_event([1; 32], 1_000, [2; 32], 500)) },
+                    { "programIdIndex": 1, "accounts": [], "data": hop(swap_event([2; 32], 500, [3; 32], 42)) },
+                    { "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "parsed": {} }
+                ]
+            }]
+        });
+        let account_keys = vec!["Signer".to_string(), JUPITER_V6.to_string()];
+
+        let swap = parse_jupiter_swap(&meta, &account_keys, &[]).unwrap();
+        assert_eq!(
+            swap,
+            JupiterSwap {
+                input_mint: mint(1),
+                output_mint: mint(3),
+                in_amount: 1_000,
+                out_amount: 42,
+                inferred: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_jupiter_swap_from_log_events() {
+        let data = base64::engine::general_purpose::STANDARD.encode(swap_event([1; 32], 7, [2; 32], 3));
+        let logs = vec!["Program log: Instruction: SharedAccountsRoute".to_string(), format!("Program data: {}", data)];
+
+        let swap = parse_jupiter_swap(&json!({}), &[], &logs).unwrap();
+        assert_eq!((swap.in_amount, swap.out_amount, swap.inferred), (7, 3, false));
+    }
+
+    #[test]
+    fn test_infer_jupiter_swap_from_balances() {
+        // Logs truncated before any event: the signer sold SOL for USDC
+        let meta = json!({
+            "fee": 5_000,
+            "preBalances": [10_000_000_000u64, 0],
+            "postBalances": [8_999_995_000u64, 0],
+            "preTokenBalances": [],
+            "postTokenBalances": [{
+                "accountIndex": 1,
+                "mint": "USDC",
+                "owner": "Signer",
+                "uiTokenAmount": { "amount": "150000000", "decimals": 6 }
+            }]
+        });
+        let account_keys = vec!["Signer".to_string(), "SignerUsdcAccount".to_string()];
+        let logs = vec!["Log truncated".to_string()];
+
+        let swap = parse_jupiter_swap(&meta, &account_keys, &logs).unwrap();
+        assert_eq!(
+            swap,
+            JupiterSwap {
+                input_mint: WRAPPED_SOL_MINT.to_string(),
+                output_mint: "USDC".to_string(),
+                in_amount: 1_000_000_000,
+                out_amount: 150_000_000,
+                inferred: true,
+            }
+        );
+
+        // Nothing moved: no swap
+        assert_eq!(parse_jupiter_swap(&json!({ "fee": 5_000 }), &account_keys, &[]), None);
+    }
+}
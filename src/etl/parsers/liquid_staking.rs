@@ -0,0 +1,204 @@
+/// Liquid Staking Parser
+///
+/// Parses deposits into and withdrawals from liquid staking pools, which stake SOL on the
+/// depositor's behalf and mint a liquid staking token (LST) in return: Marinade
+/// (MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD, mSOL) and pools run on the SPL Stake Pool
+/// program (SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy), such as Jito's JitoSOL and BlazeStake's
+/// bSOL. Neither program is parsed by the RPC, so the action is read from the instruction data
+/// and the amounts from the signer's balance changes.
+use super::{token::WRAPPED_SOL_MINT, token_balance::parse_token_balance_changes};
+use serde::Serialize;
+
+/// Marinade Finance liquid staking program ID
+pub const MARINADE_PROGRAM: &str = "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD";
+
+/// SPL Stake Pool program ID, shared by most stake pools (Jito, BlazeStake, ...)
+pub const SPL_STAKE_POOL_PROGRAM: &str = "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy";
+
+/// Programs whose transactions are liquid staking operations
+pub const LIQUID_STAKING_PROGRAMS: &[&str] = &[MARINADE_PROGRAM, SPL_STAKE_POOL_PROGRAM];
+
+/// Anchor discriminators (`sha256("global:<name>")[..8]`) of the Marinade instructions we track
+const MARINADE_DEPOSIT: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+const MARINADE_DEPOSIT_STAKE_ACCOUNT: [u8; 8] = [110, 130, 115, 41, 164, 102, 2, 59];
+const MARINADE_LIQUID_UNSTAKE: [u8; 8] = [30, 30, 119, 240, 191, 227, 12, 16];
+const MARINADE_ORDER_UNSTAKE: [u8; 8] = [97, 167, 144, 107, 117, 190, 128, 36];
+const MARINADE_CLAIM: [u8; 8] = [62, 198, 214, 193, 213, 159, 108, 210];
+
+/// Liquid staking action of a parsed instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidStakingAction {
+    Deposit,  // SOL or a stake account in, LST out
+    Withdraw, // LST in, SOL, a stake account or an unstake ticket out
+    Claim,    // SOL out for a matured Marinade unstake ticket
+}
+
+impl LiquidStakingAction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::Withdraw => "withdraw",
+            Self::Claim => "claim",
+        }
+    }
+}
+
+/// A liquid staking operation and the amounts the signer moved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LiquidStaking {
+    pub action: LiquidStakingAction,
+    pub lst_mint: Option<String>, // Mint of the liquid staking token minted or burned
+    pub lst_amount: Option<u64>,  // LST base units minted to or burned from the signer
+    pub sol_amount: Option<u64>,  // Lamports the signer paid or received, fee excluded
+}
+
+/// Parse the action of a Marinade or SPL Stake Pool instruction
+///
+/// Marinade is an Anchor program, its instructions start with an 8-byte discriminator. SPL
+/// Stake Pool instructions start with a one-byte index: DepositStake (9), DepositSol (14) and
+/// their slippage variants (23, 25) deposit; WithdrawStake (10), WithdrawSol (16) and their
+/// slippage variants (24, 26) withdraw.
+///
+/// Returns None for other instructions (e.g., pool maintenance) and other programs.
+pub fn parse_liquid_staking_instruction(
+    instruction: &serde_json::Value,
+    account_keys: &[String],
+) -> Option<LiquidStakingAction> {
+    let program_id = crate::etl::extract::instruction_program_id(instruction, account_keys)?;
+    if !LIQUID_STAKING_PROGRAMS.contains(&program_id.as_str()) {
+        return None;
+    }
+    let data = bs58::decode(instruction.get("data")?.as_str()?).into_vec().ok()?;
+
+    match program_id.as_str() {
+        MARINADE_PROGRAM => match <[u8; 8]>::try_from(data.get(..8)?).ok()? {
+            MARINADE_DEPOSIT | MARINADE_DEPOSIT_STAKE_ACCOUNT => Some(LiquidStakingAction::Deposit),
+            MARINADE_LIQUID_UNSTAKE | MARINADE_ORDER_UNSTAKE => Some(LiquidStakingAction::Withdraw),
+            MARINADE_CLAIM => Some(LiquidStakingAction::Claim),
+            _ => None,
+        },
+        SPL_STAKE_POOL_PROGRAM => match data.first()? {
+            9 | 14 | 23 | 25 => Some(LiquidStakingAction::Deposit),
+            10 | 16 | 24 | 26 => Some(LiquidStakingAction::Withdraw),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parse the liquid staking operation of a transaction
+///
+/// The action is that of the first top-level liquid staking instruction (a CPI from an
+/// aggregator is not counted). The LST amount is the signer's largest token change other than
+/// wrapped SOL, and the SOL amount its lamport change with the fee added back; rent of an
+/// account created in the same transaction, e.g. the signer's LST account, is included.
+/// Returns None when no instruction deposits, withdraws or claims.
+pub fn parse_liquid_staking(
+    instructions: &[serde_json::Value],
+    meta: &serde_json::Value,
+    account_keys: &[String],
+) -> Option<LiquidStaking> {
+    let action =
+        instructions.iter().find_map(|instruction| parse_liquid_staking_instruction(instruction, account_keys))?;
+
+    let signer = account_keys.first()?;
+    let lst_change = parse_token_balance_changes(meta, account_keys)
+        .into_iter()
+        .filter(|change| &change.owner == signer && change.mint != WRAPPED_SOL_MINT)
+        .max_by_key(|change| change.amount_delta.unsigned_abs());
+
+    let balance = |field: &str| meta.get(field).and_then(|b| b.get(0)).and_then(|b| b.as_i64());
+    let fee = meta.get("fee").and_then(|f| f.as_i64()).unwrap_or(0);
+    let lamports = match (balance("preBalances"), balance("postBalances")) {
+        (Some(pre), Some(post)) => post - pre + fee,
+        _ => 0,
+    };
+
+    Some(LiquidStaking {
+        action,
+        lst_amount: lst_change.as_ref().and_then(|change| u64::try_from(change.amount_delta.unsigned_abs()).ok()),
+        lst_mint: lst_change.map(|change| change.mint),
+        sol_amount: (lamports != 0).then(|| lamports.unsigned_abs()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MSOL_MINT: &str = "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So";
+
+    #[test]
+    fn test_parse_marinade_deposit() {
+        // Deposit 2 SOL for mSOL; the signer also paid the 5000 lamport fee
+        let account_keys: Vec<String> = ["Signer111", "SignerMsol111", MARINADE_PROGRAM].map(String::from).to_vec();
+        let mut data = MARINADE_DEPOSIT.to_vec();
+        data.extend_from_slice(&2_000_000_000u64.to_le_bytes());
+        let instructions = vec![json!({
+            "programId": MARINADE_PROGRAM,
+            "accounts": ["Signer111", "SignerMsol111"],
+            "data": bs58::encode(data).into_string()
+        })];
+        let meta = json!({
+            "fee": 5000,
+            "preBalances": [10_000_000_000i64, 2_039_280, 1],
+            "postBalances": [7_999_995_000i64, 2_039_280, 1],
+            "preTokenBalances": [
+                { "accountIndex": 1, "mint": MSOL_MINT, "owner": "Signer111",
+                  "uiTokenAmount": { "amount": "0", "decimals": 9 } }
+            ],
+            "postTokenBalances": [
+                { "accountIndex": 1, "mint": MSOL_MINT, "owner": "Signer111",
+                  "uiTokenAmount": { "amount": "1650000000", "decimals": 9 } }
+            ]
+        });
+
+        let staking = parse_liquid_staking(&instructions, &meta, &account_keys).unwrap();
+        assert_eq!(
+            staking,
+            LiquidStaking {
+                action: LiquidStakingAction::Deposit,
+                lst_mint: Some(MSOL_MINT.to_string()),
+                lst_amount: Some(1_650_000_000),
+                sol_amount: Some(2_000_000_000),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_stake_pool_instructions() {
+        let account_keys: Vec<String> = ["Signer111", SPL_STAKE_POOL_PROGRAM].map(String::from).to_vec();
+        let instruction =
+            |data: &[u8]| json!({ "programIdIndex": 1, "accounts": [0], "data": bs58::encode(data).into_string() });
+
+        let deposit_sol = instruction(&[14, 0, 202, 154, 59, 0, 0, 0, 0]);
+        assert_eq!(parse_liquid_staking_instruction(&deposit_sol, &account_keys), Some(LiquidStakingAction::Deposit));
+        let withdraw_stake = instruction(&[10, 0, 202, 154, 59, 0, 0, 0, 0]);
+        assert_eq!(
+            parse_liquid_staking_instruction(&withdraw_stake, &account_keys),
+            Some(LiquidStakingAction::Withdraw)
+        );
+
+        // UpdateValidatorListBalance is pool maintenance, not a user operation
+        assert!(parse_liquid_staking_instruction(&instruction(&[6, 0, 0, 0, 0, 0]), &account_keys).is_none());
+        assert!(parse_liquid_staking(&[instruction(&[6])], &json!({}), &account_keys).is_none());
+    }
+
+    #[test]
+    fn test_parse_marinade_claim() {
+        // Claiming a ticket pays out SOL and moves no mSOL
+        let account_keys: Vec<String> = ["Signer111", MARINADE_PROGRAM].map(String::from).to_vec();
+        let instructions = vec![json!({
+            "programId": MARINADE_PROGRAM,
+            "accounts": ["Signer111"],
+            "data": bs58::encode(MARINADE_CLAIM).into_string()
+        })];
+        let meta = json!({ "fee": 5000, "preBalances": [1_000_000, 1], "postBalances": [1_501_000_000, 1] });
+
+        let staking = parse_liquid_staking(&instructions, &meta, &account_keys).unwrap();
+        assert_eq!(staking.action, LiquidStakingAction::Claim);
+        assert_eq!((staking.lst_mint, staking.lst_amount, staking.sol_amount), (None, None, Some(1_500_005_000)));
+    }
+}
@@ -0,0 +1,77 @@
+/// Memo Program Instruction Parser
+///
+/// Parses instructions from the SPL Memo program (MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr)
+/// and its legacy v1 deployment. Memos often carry off-chain context such as exchange
+/// withdrawal IDs or payment references.
+///
+/// SPL Memo program v2
+pub const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+/// Legacy SPL Memo program v1
+pub const MEMO_V1_PROGRAM: &str = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo";
+
+/// Check whether a program ID is one of the memo programs
+pub fn is_memo_program(program_id: &str) -> bool {
+    program_id == MEMO_PROGRAM || program_id == MEMO_V1_PROGRAM
+}
+
+/// Parse the memo text of a Memo program instruction
+///
+/// With JsonParsed encoding the RPC returns a valid UTF-8 memo directly as the `parsed`
+/// string. Memos that are not valid UTF-8 cannot be parsed by the RPC and arrive as raw
+/// base58 `data` instead; those bytes are decoded lossily (invalid sequences become U+FFFD).
+///
+/// Returns None if the instruction does not belong to a memo program.
+pub fn parse_memo(instruction: &serde_json::Value) -> Option<String> {
+    let program_id = instruction.get("programId").and_then(|p| p.as_str())?;
+    if !is_memo_program(program_id) {
+        return None;
+    }
+
+    if let Some(memo) = instruction.get("parsed").and_then(|p| p.as_str()) {
+        return Some(memo.to_string());
+    }
+
+    let data = instruction.get("data").and_then(|d| d.as_str())?;
+    let bytes = bs58::decode(data).into_vec().ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_memo() {
+        let instruction = serde_json::json!({
+            "parsed": "withdrawal 8f3a, 42",
+            "program": "spl-memo",
+            "programId": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+            "stackHeight": null
+        });
+
+        assert_eq!(parse_memo(&instruction).as_deref(), Some("withdrawal 8f3a, 42"));
+    }
+
+    #[test]
+    fn test_parse_memo_non_utf8() {
+        let instruction = serde_json::json!({
+            "accounts": [],
+            "data": bs58::encode([b'h', b'i', 0xff]).into_string(),
+            "programId": "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+        });
+
+        assert_eq!(parse_memo(&instruction).as_deref(), Some("hi\u{fffd}"));
+    }
+
+    #[test]
+    fn test_parse_memo_other_program() {
+        let instruction = serde_json::json!({
+            "parsed": { "type": "transfer", "info": {} },
+            "program": "system",
+            "programId": "11111111111111111111111111111111"
+        });
+
+        assert!(parse_memo(&instruction).is_none());
+    }
+}
@@ -2,9 +2,30 @@
 ///
 /// Contains instruction parsers for different Solana programs.
 /// Each parser extracts specific data from transaction instructions.
+pub mod associated_token;
+pub mod compute_budget;
+pub mod dex;
+pub mod jupiter;
+pub mod liquid_staking;
+pub mod memo;
+pub mod stake;
 pub mod system;
 pub mod token;
+pub mod token_balance;
 
 // Re-export commonly used parsers
-pub use system::parse_system_transfer;
-pub use token::parse_token_transfer;
+pub use associated_token::{parse_associated_account_creation, AssociatedAccountCreation, ASSOCIATED_TOKEN_PROGRAM};
+pub use compute_budget::{parse_priority_fee, PriorityFee};
+pub use dex::{parse_liquidity_instruction, parse_liquidity_log, LiquidityAction};
+pub use jupiter::{parse_jupiter_swap, JupiterSwap};
+pub use liquid_staking::{
+    parse_liquid_staking, parse_liquid_staking_instruction, LiquidStaking, LiquidStakingAction, LIQUID_STAKING_PROGRAMS,
+};
+pub use memo::{is_memo_program, parse_memo};
+pub use stake::{parse_stake, StakeAction};
+pub use system::{parse_system_instruction, SystemInstruction};
+pub use token::{
+    parse_token_burn, parse_token_mint, parse_token_transfer, parse_wrapped_sol_instruction, TokenProgram,
+    TokenTransfer, WrappedSolAction, WRAPPED_SOL_MINT,
+};
+pub use token_balance::{parse_token_balance_changes, token_account_mint, token_account_owner, TokenBalanceChange};
@@ -0,0 +1,124 @@
+/// Stake Program Instruction Parser
+///
+/// Parses instructions from the native Stake Program (Stake11111111111111111111111111111111111111).
+/// Handles delegation to a validator, deactivation and withdrawal of stake.
+///
+/// Stake lifecycle action of a parsed instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakeAction {
+    Delegate,
+    Deactivate,
+    Withdraw,
+}
+
+impl StakeAction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Delegate => "delegate",
+            Self::Deactivate => "deactivate",
+            Self::Withdraw => "withdraw",
+        }
+    }
+}
+
+/// Details of a delegate, deactivate or withdraw instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakeInstruction {
+    pub action: StakeAction,
+    pub stake_account: String,
+    /// Validator vote account (delegate only)
+    pub vote_account: Option<String>,
+    /// Withdrawal destination (withdraw only)
+    pub destination: Option<String>,
+    /// Lamports withdrawn (withdraw only; a delegation always covers the whole stake account)
+    pub lamports: Option<u64>,
+}
+
+/// Parse Stake Program instructions for delegations, deactivations and withdrawals
+///
+/// Extracts from JsonParsed instruction data:
+/// - delegate: stake account and validator vote account
+/// - deactivate: stake account
+/// - withdraw: stake account, destination and amount in lamports
+///
+/// Returns None for other stake instructions (e.g., initialize, split, merge, authorize).
+pub fn parse_stake(instruction: &serde_json::Value, _account_keys: &[String]) -> Option<StakeInstruction> {
+    let parsed = instruction.get("parsed")?;
+    let info = parsed.get("info")?;
+
+    let action = match parsed.get("type").and_then(|t| t.as_str())? {
+        "delegate" => StakeAction::Delegate,
+        "deactivate" => StakeAction::Deactivate,
+        "withdraw" => StakeAction::Withdraw,
+        _ => return None,
+    };
+
+    let stake_account = info.get("stakeAccount").and_then(|v| v.as_str())?.to_string();
+    let vote_account = info.get("voteAccount").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let destination = info.get("destination").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let lamports = info.get("lamports").and_then(|v| v.as_u64());
+
+    Some(StakeInstruction { action, stake_account, vote_account, destination, lamports })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stake_delegate() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "delegate",
+                "info": {
+                    "stakeAccount": "StakeAcct111",
+                    "voteAccount": "VoteAcct111",
+                    "stakeAuthority": "Authority111"
+                }
+            },
+            "program": "stake",
+            "programId": "Stake11111111111111111111111111111111111111"
+        });
+
+        let result = parse_stake(&instruction, &[]).unwrap();
+        assert_eq!(result.action, StakeAction::Delegate);
+        assert_eq!(result.stake_account, "StakeAcct111");
+        assert_eq!(result.vote_account.as_deref(), Some("VoteAcct111"));
+        assert_eq!(result.lamports, None);
+    }
+
+    #[test]
+    fn test_parse_stake_withdraw() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "withdraw",
+                "info": {
+                    "stakeAccount": "StakeAcct111",
+                    "destination": "Wallet111",
+                    "withdrawAuthority": "Authority111",
+                    "lamports": 2_500_000_000u64
+                }
+            },
+            "program": "stake",
+            "programId": "Stake11111111111111111111111111111111111111"
+        });
+
+        let result = parse_stake(&instruction, &[]).unwrap();
+        assert_eq!(result.action, StakeAction::Withdraw);
+        assert_eq!(result.destination.as_deref(), Some("Wallet111"));
+        assert_eq!(result.lamports, Some(2_500_000_000));
+    }
+
+    #[test]
+    fn test_parse_stake_other_instruction() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "initialize",
+                "info": { "stakeAccount": "StakeAcct111" }
+            },
+            "program": "stake"
+        });
+
+        assert!(parse_stake(&instruction, &[]).is_none());
+    }
+}
@@ -3,52 +3,82 @@
 /// Parses instructions from the Solana System Program (11111111111111111111111111111111).
 /// Handles transfer, createAccount, and other system-level operations.
 ///
-/// Parse System Program instructions for SOL transfers
+/// A parsed System Program instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemInstruction {
+    /// SOL transfer between two accounts
+    Transfer { lamports: u64, source: String, destination: String },
+    /// `createAccount` or `createAccountWithSeed`: a new account funded by `source`
+    CreateAccount { source: String, new_account: String, owner: String, lamports: u64, space: u64 },
+    /// `allocate`: space reserved for an existing account, no lamports move
+    Allocate { account: String, space: u64 },
+}
+
+/// Parse System Program instructions for transfers, account creations and allocations
 ///
-/// Extracts transfer details from JsonParsed instruction data:
-/// - Amount in lamports
-/// - Source account (from)
-/// - Destination account (to)
+/// Extracts from JsonParsed instruction data:
+/// - transfer: amount in lamports, source (from) and destination (to)
+/// - createAccount / createAccountWithSeed: funding account, new account, owner program, lamports funded (usually the
+///   rent-exempt minimum) and space
+/// - allocate: account and space
 ///
-/// Returns None if the instruction is not a transfer (e.g., advanceNonce, createAccount).
-pub fn parse_system_transfer(
+/// Returns None for other system instructions (e.g., advanceNonce, assign).
+pub fn parse_system_instruction(
     instruction: &serde_json::Value,
     _account_keys: &[String],
-) -> Option<(u64, String, String)> {
+) -> Option<SystemInstruction> {
     // Debug: Print the instruction structure
     if std::env::var("DEBUG_TX").is_ok() {
-        eprintln!("=== Parsing System Transfer ===");
+        eprintln!("=== Parsing System Instruction ===");
         eprintln!("Instruction: {}", serde_json::to_string_pretty(instruction).unwrap_or_default());
     }
 
-    // System Program transfer instruction structure with JsonParsed encoding:
-    // - parsed/info/lamports contains the amount
-    // - parsed/info/source is the "from" account
-    // - parsed/info/destination is the "to" account
-    // - parsed/type should be "transfer"
-
-    if let Some(parsed) = instruction.get("parsed") {
-        // Check if this is a transfer instruction
-        if let Some(inst_type) = parsed.get("type").and_then(|t| t.as_str()) {
-            if inst_type != "transfer" {
-                return None; // Not a transfer, maybe advanceNonce or other system instruction
-            }
-        }
-
-        if let Some(info) = parsed.get("info") {
-            let amount = info.get("lamports").and_then(|v| v.as_u64())?;
-            let from_account = info.get("source").and_then(|v| v.as_str())?.to_string();
-            let to_account = info.get("destination").and_then(|v| v.as_str())?.to_string();
+    let parsed = instruction.get("parsed")?;
+    let info = parsed.get("info")?;
+    let string = |key: &str| info.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let number = |key: &str| info.get(key).and_then(|v| v.as_u64());
 
-            if std::env::var("DEBUG_TX").is_ok() {
-                eprintln!("✅ Parsed: amount={}, from={}, to={}", amount, from_account, to_account);
-            }
+    let parsed_instruction = match parsed.get("type").and_then(|t| t.as_str())? {
+        "transfer" => SystemInstruction::Transfer {
+            lamports: number("lamports")?,
+            source: string("source")?,
+            destination: string("destination")?,
+        },
+        "createAccount" | "createAccountWithSeed" => SystemInstruction::CreateAccount {
+            source: string("source")?,
+            new_account: string("newAccount")?,
+            owner: string("owner")?,
+            lamports: number("lamports")?,
+            space: number("space")?,
+        },
+        "allocate" => SystemInstruction::Allocate { account: string("account")?, space: number("space")? },
+        _ => return None, // Not handled, maybe advanceNonce or assign
+    };
 
-            return Some((amount, from_account, to_account));
-        }
+    if std::env::var("DEBUG_TX").is_ok() {
+        eprintln!("✅ Parsed: {:?}", parsed_instruction);
     }
 
-    None
+    Some(parsed_instruction)
+}
+
+/// Parse System Program instructions for SOL transfers
+///
+/// Extracts transfer details from JsonParsed instruction data:
+/// - Amount in lamports
+/// - Source account (from)
+/// - Destination account (to)
+///
+/// Returns None if the instruction is not a transfer (e.g., advanceNonce, createAccount).
+#[allow(dead_code)]
+pub fn parse_system_transfer(
+    instruction: &serde_json::Value,
+    account_keys: &[String],
+) -> Option<(u64, String, String)> {
+    match parse_system_instruction(instruction, account_keys)? {
+        SystemInstruction::Transfer { lamports, source, destination } => Some((lamports, source, destination)),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -95,4 +125,53 @@ mod tests {
         let result = parse_system_transfer(&instruction, &[]);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_parse_create_account() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "createAccountWithSeed",
+                "info": {
+                    "source": "Payer111",
+                    "newAccount": "NewAcct111",
+                    "base": "Payer111",
+                    "seed": "stake:0",
+                    "lamports": 2_039_280,
+                    "space": 165,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                }
+            },
+            "program": "system",
+            "programId": "11111111111111111111111111111111"
+        });
+
+        assert_eq!(
+            parse_system_instruction(&instruction, &[]),
+            Some(SystemInstruction::CreateAccount {
+                source: "Payer111".to_string(),
+                new_account: "NewAcct111".to_string(),
+                owner: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
+                lamports: 2_039_280,
+                space: 165,
+            })
+        );
+        // Funding a new account is not a transfer
+        assert!(parse_system_transfer(&instruction, &[]).is_none());
+    }
+
+    #[test]
+    fn test_parse_allocate() {
+        let instruction = serde_json::json!({
+            "parsed": {
+                "type": "allocate",
+                "info": { "account": "Acct111", "space": 200 }
+            },
+            "program": "system"
+        });
+
+        assert_eq!(
+            parse_system_instruction(&instruction, &[]),
+            Some(SystemInstruction::Allocate { account: "Acct111".to_string(), space: 200 })
+        );
+    }
 }
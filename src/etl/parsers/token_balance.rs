@@ -0,0 +1,204 @@
+/// Token Balance Parser
+///
+/// Parses `meta.preTokenBalances` and `meta.postTokenBalances` from transaction metadata.
+/// These arrays are the authoritative record of token movement, covering swaps, burns,
+/// and CPI transfers that instruction-level parsers cannot see.
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Net token movement for one owner and mint within a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenBalanceChange {
+    /// Wallet owning the token account (falls back to the token account address when the RPC omits it)
+    pub owner: String,
+    pub mint: String,
+    pub decimals: u8,
+    /// Post minus pre balance in base units
+    pub amount_delta: i128,
+}
+
+/// A single entry of a pre/post token balance array
+struct TokenBalanceEntry {
+    owner: Option<String>,
+    mint: String,
+    decimals: u8,
+    amount: u128,
+}
+
+/// Parse pre/post token balances into per-owner, per-mint deltas
+///
+/// Token accounts are matched by `accountIndex`. An account missing from one side
+/// (created or closed in this transaction) is treated as a zero balance. Deltas that
+/// net to zero are omitted. Results are ordered by (owner, mint).
+pub fn parse_token_balance_changes(meta: &serde_json::Value, account_keys: &[String]) -> Vec<TokenBalanceChange> {
+    let pre = parse_token_balances(meta.get("preTokenBalances"));
+    let post = parse_token_balances(meta.get("postTokenBalances"));
+
+    let mut account_indexes: Vec<&u64> = pre.keys().chain(post.keys()).collect();
+    account_indexes.sort();
+    account_indexes.dedup();
+
+    let mut deltas: BTreeMap<(String, String), (u8, i128)> = BTreeMap::new();
+
+    for account_index in account_indexes {
+        let pre_entry = pre.get(account_index);
+        let post_entry = post.get(account_index);
+        let Some(entry) = post_entry.or(pre_entry) else {
+            continue;
+        };
+
+        let owner = entry
+            .owner
+            .clone()
+            .or_else(|| account_keys.get(*account_index as usize).cloned())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let delta =
+            post_entry.map(|e| e.amount as i128).unwrap_or(0) - pre_entry.map(|e| e.amount as i128).unwrap_or(0);
+
+        let total = deltas.entry((owner, entry.mint.clone())).or_insert((entry.decimals, 0));
+        total.1 += delta;
+    }
+
+    deltas
+        .into_iter()
+        .filter(|(_, (_, delta))| *delta != 0)
+        .map(|((owner, mint), (decimals, amount_delta))| TokenBalanceChange { owner, mint, decimals, amount_delta })
+        .collect()
+}
+
+/// Look up the mint and decimals of a token account from the pre/post token balances
+///
+/// Plain `transfer` instructions only name the token accounts; the balance arrays
+/// record which mint each account holds. Returns None when the account has no entry.
+pub fn token_account_mint(
+    meta: &serde_json::Value,
+    account_keys: &[String],
+    token_account: &str,
+) -> Option<(String, u8)> {
+    let account_index = account_keys.iter().position(|key| key == token_account)? as u64;
+
+    let post = parse_token_balances(meta.get("postTokenBalances"));
+    let pre = parse_token_balances(meta.get("preTokenBalances"));
+    post.get(&account_index).or_else(|| pre.get(&account_index)).map(|entry| (entry.mint.clone(), entry.decimals))
+}
+
+/// Look up the wallet owning a token account from the pre/post token balances
+///
+/// Returns None when the account has no entry or the RPC omitted the owner (older blocks).
+pub fn token_account_owner(meta: &serde_json::Value, account_keys: &[String], token_account: &str) -> Option<String> {
+    let account_index = account_keys.iter().position(|key| key == token_account)? as u64;
+
+    let post = parse_token_balances(meta.get("postTokenBalances"));
+    let pre = parse_token_balances(meta.get("preTokenBalances"));
+    post.get(&account_index).and_then(|entry| entry.owner.clone()).or_else(|| pre.get(&account_index)?.owner.clone())
+}
+
+/// Index a token balance array by account index
+fn parse_token_balances(balances: Option<&serde_json::Value>) -> HashMap<u64, TokenBalanceEntry> {
+    let mut entries = HashMap::new();
+
+    if let Some(balances) = balances.and_then(|b| b.as_array()) {
+        for balance in balances {
+            let Some(account_index) = balance.get("accountIndex").and_then(|i| i.as_u64()) else {
+                continue;
+            };
+            let Some(mint) = balance.get("mint").and_then(|m| m.as_str()) else {
+                continue;
+            };
+
+            let ui_token_amount = balance.get("uiTokenAmount");
+            let amount = ui_token_amount
+                .and_then(|a| a.get("amount"))
+                .and_then(|a| a.as_str())
+                .and_then(|a| a.parse::<u128>().ok())
+                .unwrap_or(0);
+            let decimals = ui_token_amount.and_then(|a| a.get("decimals")).and_then(|d| d.as_u64()).unwrap_or(0) as u8;
+            let owner = balance.get("owner").and_then(|o| o.as_str()).map(|o| o.to_string());
+
+            entries.insert(account_index, TokenBalanceEntry { owner, mint: mint.to_string(), decimals, amount });
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(account_index: u64, owner: &str, mint: &str, amount: &str) -> serde_json::Value {
+        serde_json::json!({
+            "accountIndex": account_index,
+            "mint": mint,
+            "owner": owner,
+            "uiTokenAmount": { "amount": amount, "decimals": 6 }
+        })
+    }
+
+    #[test]
+    fn test_parse_token_balance_changes() {
+        let meta = serde_json::json!({
+            "preTokenBalances": [
+                balance(1, "Alice", "USDC", "5000000"),
+                balance(2, "Bob", "USDC", "0")
+            ],
+            "postTokenBalances": [
+                balance(1, "Alice", "USDC", "3000000"),
+                balance(2, "Bob", "USDC", "2000000")
+            ]
+        });
+
+        let changes = parse_token_balance_changes(&meta, &[]);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].owner, "Alice");
+        assert_eq!(changes[0].amount_delta, -2_000_000);
+        assert_eq!(changes[1].owner, "Bob");
+        assert_eq!(changes[1].amount_delta, 2_000_000);
+        assert_eq!(changes[1].decimals, 6);
+    }
+
+    #[test]
+    fn test_parse_token_balance_changes_closed_account() {
+        // Account 3 is closed (burned out), so it only appears in preTokenBalances
+        let meta = serde_json::json!({
+            "preTokenBalances": [balance(3, "Carol", "BONK", "42")],
+            "postTokenBalances": []
+        });
+
+        let changes = parse_token_balance_changes(&meta, &[]);
+        assert_eq!(
+            changes,
+            vec![TokenBalanceChange {
+                owner: "Carol".to_string(),
+                mint: "BONK".to_string(),
+                decimals: 6,
+                amount_delta: -42
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_token_balance_changes_unchanged() {
+        let meta = serde_json::json!({
+            "preTokenBalances": [balance(1, "Alice", "USDC", "100")],
+            "postTokenBalances": [balance(1, "Alice", "USDC", "100")]
+        });
+
+        assert!(parse_token_balance_changes(&meta, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_token_account_mint() {
+        let meta = serde_json::json!({
+            "preTokenBalances": [balance(1, "Alice", "USDC", "5000000")],
+            "postTokenBalances": []
+        });
+        let account_keys = vec!["Payer".to_string(), "AliceTokenAccount".to_string()];
+
+        assert_eq!(token_account_mint(&meta, &account_keys, "AliceTokenAccount"), Some(("USDC".to_string(), 6)));
+        assert_eq!(token_account_mint(&meta, &account_keys, "Payer"), None);
+        assert_eq!(token_account_owner(&meta, &account_keys, "AliceTokenAccount").as_deref(), Some("Alice"));
+        assert_eq!(token_account_owner(&meta, &account_keys, "Payer"), None);
+    }
+}
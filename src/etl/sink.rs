@@ -0,0 +1,122 @@
+/// Sink Module
+///
+/// Destinations for extracted blocks. The pipeline loads every batch through a `BlockSink`,
+/// so the storage backend can be swapped (or replaced by a mock in tests) without touching
+/// the extract and retry logic.
+use crate::{
+    db::Database,
+    etl::{
+        extract::ExtractedBlock,
+        load,
+        transform::{Classifier, ProgramRegistry},
+    },
+};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// A destination that batches of blocks are loaded into
+#[async_trait]
+pub trait BlockSink: Send + Sync {
+    /// Load a batch of blocks and their transactions
+    ///
+    /// Returns the number of (blocks, transactions) loaded. A failed batch is retried by the
+    /// pipeline, so loading the same batch twice must be safe.
+    async fn load_blocks(
+        &self,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+        classifier: &dyn Classifier,
+    ) -> Result<(usize, usize)>;
+}
+
+/// PostgreSQL with per-row upserts, one database transaction per batch
+///
+/// Rows that are already stored are handled according to `Database::on_conflict`, and raw
+/// transaction JSON is stored according to `Database::raw_storage`. With `Database::commit_every`,
+/// large batches are committed in several database transactions.
+#[async_trait]
+impl BlockSink for Database {
+    async fn load_blocks(
+        &self,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+        classifier: &dyn Classifier,
+    ) -> Result<(usize, usize)> {
+        for block in blocks {
+            self.ensure_partition(block.slot).await?;
+        }
+        load::batch_insert_blocks_with_transactions(
+            self.pool(),
+            blocks,
+            registry,
+            classifier,
+            self.on_conflict(),
+            self.raw_storage(),
+            self.commit_every(),
+        )
+        .await
+    }
+}
+
+/// PostgreSQL with binary COPY and one merge per batch (`--bulk-load`)
+pub struct BulkLoadSink {
+    database: Database,
+}
+
+impl BulkLoadSink {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl BlockSink for BulkLoadSink {
+    async fn load_blocks(
+        &self,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+        classifier: &dyn Classifier,
+    ) -> Result<(usize, usize)> {
+        for block in blocks {
+            self.database.ensure_partition(block.slot).await?;
+        }
+        load::copy_insert_blocks_with_transactions(
+            self.database.pool(),
+            blocks,
+            registry,
+            classifier,
+            self.database.on_conflict(),
+            self.database.raw_storage(),
+        )
+        .await
+    }
+}
+
+/// PostgreSQL signature index for blocks fetched with signatures only (`--signatures-only`)
+///
+/// Stores blocks and `transaction_signatures` rows, see `load::batch_insert_block_signatures`;
+/// the registry and classifier are not used.
+pub struct SignatureSink {
+    database: Database,
+}
+
+impl SignatureSink {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl BlockSink for SignatureSink {
+    async fn load_blocks(
+        &self,
+        blocks: &[ExtractedBlock],
+        _registry: &ProgramRegistry,
+        _classifier: &dyn Classifier,
+    ) -> Result<(usize, usize)> {
+        for block in blocks {
+            self.database.ensure_partition(block.slot).await?;
+        }
+        load::batch_insert_block_signatures(self.database.pool(), blocks, self.database.on_conflict()).await
+    }
+}
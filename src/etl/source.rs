@@ -0,0 +1,111 @@
+/// Source Module
+///
+/// Where extracted blocks come from. The extract functions fetch through a `BlockSource`, so
+/// the extract and classification logic can run against canned blocks in tests instead of a
+/// live RPC. `SolanaRpcClient` is the production source.
+use crate::{
+    error::{FetcherError, Result},
+    rpc::{throttle::AdaptiveThrottle, SolanaRpcClient},
+};
+use async_trait::async_trait;
+use solana_transaction_status::UiConfirmedBlock;
+
+/// A source of confirmed blocks
+///
+/// Only `fetch_block`, `get_latest_slot` and `get_blocks` are required. The other methods
+/// default to what a source without batching, mint or owner lookups or a throttle can offer.
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    /// Fetch the full block of `slot`
+    async fn fetch_block(&self, slot: u64) -> Result<UiConfirmedBlock>;
+
+    /// The latest slot at the source's commitment
+    async fn get_latest_slot(&self) -> Result<u64>;
+
+    /// Slots of `start_slot..=end_slot` that produced a block, ascending
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>>;
+
+    /// Fetch several blocks, one result per slot in the order of `slots`
+    ///
+    /// An `Err` fails every slot, e.g. a batch request that could not be sent.
+    async fn fetch_blocks(&self, slots: &[u64]) -> Result<Vec<(u64, Result<UiConfirmedBlock>)>> {
+        let mut blocks = Vec::with_capacity(slots.len());
+        for &slot in slots {
+            blocks.push((slot, self.fetch_block(slot).await));
+        }
+        Ok(blocks)
+    }
+
+    /// The block of `slot` without transaction details, used to fill in missing block heights
+    async fn fetch_block_header(&self, slot: u64) -> Result<UiConfirmedBlock> {
+        self.fetch_block(slot).await
+    }
+
+    /// Decimals of a mint, for token transfers whose balances do not carry them
+    async fn get_token_decimals(&self, mint: &str) -> Result<u8> {
+        Err(FetcherError::InvalidInput(format!("Decimals of mint {} are not available from this source", mint)))
+    }
+
+    /// Wallet owning a token account, for token transfers whose balances do not name it
+    async fn get_account_owner(&self, token_account: &str) -> Result<String> {
+        Err(FetcherError::InvalidInput(format!("Owner of {} is not available from this source", token_account)))
+    }
+
+    /// Whether token account owners missing from transfers are looked up with `get_account_owner`
+    fn resolves_owners(&self) -> bool {
+        false
+    }
+
+    /// Blocks to fetch per `fetch_blocks` call (1 = one `fetch_block` per slot)
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    /// Client-side throttle slowing requests down while the source rate-limits, if any
+    fn throttle(&self) -> Option<&AdaptiveThrottle> {
+        None
+    }
+}
+
+#[async_trait]
+impl BlockSource for SolanaRpcClient {
+    async fn fetch_block(&self, slot: u64) -> Result<UiConfirmedBlock> {
+        SolanaRpcClient::fetch_block(self, slot).await
+    }
+
+    async fn get_latest_slot(&self) -> Result<u64> {
+        SolanaRpcClient::get_latest_slot(self).await
+    }
+
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>> {
+        SolanaRpcClient::get_blocks(self, start_slot, end_slot).await
+    }
+
+    async fn fetch_blocks(&self, slots: &[u64]) -> Result<Vec<(u64, Result<UiConfirmedBlock>)>> {
+        SolanaRpcClient::fetch_blocks(self, slots).await
+    }
+
+    async fn fetch_block_header(&self, slot: u64) -> Result<UiConfirmedBlock> {
+        self.fetch_block_signatures(slot).await
+    }
+
+    async fn get_token_decimals(&self, mint: &str) -> Result<u8> {
+        SolanaRpcClient::get_token_decimals(self, mint).await
+    }
+
+    async fn get_account_owner(&self, token_account: &str) -> Result<String> {
+        SolanaRpcClient::get_account_owner(self, token_account).await
+    }
+
+    fn resolves_owners(&self) -> bool {
+        SolanaRpcClient::resolves_owners(self)
+    }
+
+    fn batch_size(&self) -> usize {
+        SolanaRpcClient::batch_size(self)
+    }
+
+    fn throttle(&self) -> Option<&AdaptiveThrottle> {
+        Some(SolanaRpcClient::throttle(self))
+    }
+}
@@ -1,12 +1,20 @@
 /// Transform Module
 ///
 /// Handles parsing and classification of transaction data.
+use super::extract::ExtractedTransaction;
 use crate::models::TransactionType;
 use anyhow::Result;
 use std::collections::HashMap;
 
 // Import parsers
-use super::parsers::{parse_system_transfer, parse_token_transfer};
+use super::parsers::{
+    is_memo_program,
+    liquid_staking::{MARINADE_PROGRAM, SPL_STAKE_POOL_PROGRAM},
+    parse_associated_account_creation, parse_liquid_staking_instruction, parse_liquidity_instruction,
+    parse_liquidity_log, parse_memo, parse_stake, parse_system_instruction, parse_token_burn, parse_token_mint,
+    parse_token_transfer, LiquidStakingAction, LiquidityAction, StakeAction, SystemInstruction, TokenProgram,
+    WrappedSolAction, ASSOCIATED_TOKEN_PROGRAM, WRAPPED_SOL_MINT,
+};
 
 /// Program registry for transaction classification
 #[derive(Debug, Clone)]
@@ -55,18 +63,40 @@ impl ProgramRegistry {
         self.get_program_type(program_id).map(|t| t == "Token").unwrap_or(false)
     }
 
+    /// Check if a program is the native Stake Program
+    pub fn is_stake(&self, program_id: &str) -> bool {
+        self.get_program_type(program_id).map(|t| t == "Stake").unwrap_or(false)
+    }
+
+    /// Check if a program is a liquid staking pool (Marinade, SPL Stake Pool)
+    pub fn is_liquid_staking(&self, program_id: &str) -> bool {
+        self.get_program_type(program_id).map(|t| t == "LiquidStaking").unwrap_or(false)
+    }
+
     /// Check if a program is System
     pub fn is_system(&self, program_id: &str) -> bool {
         self.get_program_type(program_id).map(|t| t == "System").unwrap_or(false)
     }
+
+    /// User-defined program type, e.g. "Lending" for a registry entry an analyst added
+    ///
+    /// None for the types the classifier handles itself (see `BUILTIN_PROGRAM_TYPES`) and
+    /// for programs without a type.
+    pub fn custom_type(&self, program_id: &str) -> Option<&str> {
+        self.get_program_type(program_id).filter(|t| !BUILTIN_PROGRAM_TYPES.contains(t))
+    }
 }
 
+/// Registry program types with a dedicated classification rule; every other type is custom
+const BUILTIN_PROGRAM_TYPES: &[&str] = &["System", "Token", "Stake", "LiquidStaking", "DEX", "NFT", "Unknown"];
+
 // Known Solana program IDs (fallback if database is not available)
 const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
 const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
-#[allow(dead_code)]
-const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const COMPUTE_BUDGET_PROGRAM: &str = "ComputeBudget111111111111111111111111111111";
+const STAKE_PROGRAM: &str = "Stake11111111111111111111111111111111111111";
+const VOTE_PROGRAM: &str = "Vote111111111111111111111111111111111111111";
 
 // DEX programs
 const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
@@ -78,11 +108,88 @@ const RAYDIUM_CLMM: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
 const METAPLEX_TOKEN_METADATA: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 const MAGIC_EDEN_V2: &str = "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K";
 
+/// Built-in programs as (program_id, program_name, program_type), used to seed an empty registry
+pub const BUILTIN_PROGRAMS: &[(&str, &str, &str)] = &[
+    (SYSTEM_PROGRAM, "System Program", "System"),
+    (COMPUTE_BUDGET_PROGRAM, "Compute Budget Program", "System"),
+    (STAKE_PROGRAM, "Stake Program", "Stake"),
+    (MARINADE_PROGRAM, "Marinade Finance", "LiquidStaking"),
+    (SPL_STAKE_POOL_PROGRAM, "SPL Stake Pool", "LiquidStaking"),
+    (TOKEN_PROGRAM, "Token Program", "Token"),
+    (TOKEN_2022_PROGRAM, "Token-2022 Program", "Token"),
+    (ASSOCIATED_TOKEN_PROGRAM, "Associated Token Program", "Token"),
+    (JUPITER_V6, "Jupiter Aggregator v6", "DEX"),
+    (ORCA_WHIRLPOOL, "Orca Whirlpool", "DEX"),
+    (RAYDIUM_AMM_V4, "Raydium AMM v4", "DEX"),
+    (RAYDIUM_CLMM, "Raydium CLMM", "DEX"),
+    (METAPLEX_TOKEN_METADATA, "Metaplex Token Metadata", "NFT"),
+    (MAGIC_EDEN_V2, "Magic Eden v2", "NFT"),
+];
+
+/// Transaction classification rules
+///
+/// The pipeline classifies every transaction through a `Classifier` (see
+/// `Pipeline::with_classifier`), so rules for programs the built-in logic does not know can be
+/// added without forking the crate. A custom classifier usually recognizes its own programs and
+/// falls back to `DefaultClassifier` for everything else.
+pub trait Classifier: Send + Sync {
+    fn classify(&self, tx: &ExtractedTransaction, registry: &ProgramRegistry) -> TransactionType;
+}
+
+/// The built-in rules: the primary type of `classify_transaction_with_registry`, upgraded by
+/// the program logs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultClassifier;
+
+impl Classifier for DefaultClassifier {
+    fn classify(&self, tx: &ExtractedTransaction, registry: &ProgramRegistry) -> TransactionType {
+        let tx_type = primary_transaction_type(&tx.program_ids, registry, Some(&tx.raw_json));
+        classify_with_logs(tx_type, &tx.log_messages)
+    }
+}
+
+/// The type of a transaction and the other types it also matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub primary: TransactionType,
+    pub secondary_types: Vec<TransactionType>, // Highest priority first, see `secondary_transaction_types`
+}
+
 /// Classify a transaction using the program registry
-pub fn classify_transaction_with_registry(program_ids: &[String], registry: &ProgramRegistry) -> TransactionType {
-    // Check for DEX interactions (swaps) using registry
+///
+/// A transaction often matches several types, e.g. a swap that first creates the associated
+/// token account receiving the output. The primary type is decided by priority: votes, then
+/// swaps and liquidity operations, NFT operations, liquid staking, user-defined types, token
+/// operations, stake, memos and finally system operations. The other types the transaction shows evidence
+/// of are kept as secondary types.
+pub fn classify_transaction_with_registry(
+    program_ids: &[String],
+    registry: &ProgramRegistry,
+    tx_json: Option<&serde_json::Value>,
+) -> Classification {
+    let primary = primary_transaction_type(program_ids, registry, tx_json);
+    let secondary_types = secondary_transaction_types(&primary, program_ids, registry, tx_json);
+    Classification { primary, secondary_types }
+}
+
+/// The highest-priority type of a transaction
+///
+/// With the transaction JSON, token transactions are told apart by their parsed
+/// instruction type (transfer, mint or burn); without it they count as transfers.
+fn primary_transaction_type(
+    program_ids: &[String],
+    registry: &ProgramRegistry,
+    tx_json: Option<&serde_json::Value>,
+) -> TransactionType {
+    // Validator votes make up most of the chain's traffic, check them first
+    if is_vote_transaction(program_ids) {
+        return TransactionType::Vote;
+    }
+
+    // Check for DEX interactions using registry: liquidity deposits/withdrawals when the
+    // instruction data says so, swaps otherwise
     if program_ids.iter().any(|id| registry.is_dex(id)) {
-        return TransactionType::DexSwap;
+        return tx_json.and_then(classify_liquidity_instructions).unwrap_or(TransactionType::DexSwap);
     }
 
     // Check for NFT operations using registry
@@ -90,8 +197,30 @@ pub fn classify_transaction_with_registry(program_ids: &[String], registry: &Pro
         return TransactionType::NftMint; // Generic NFT operation
     }
 
-    // Check for SPL Token transfers using registry
-    if program_ids.iter().any(|id| registry.is_token(id)) {
+    // Liquid staking pools mint and burn their token and move stake, check them before the
+    // token and stake programs they call
+    if program_ids.iter().any(|id| registry.is_liquid_staking(id)) {
+        return TransactionType::LiquidStaking;
+    }
+
+    // Programs with a user-defined type (e.g. "Lending") take precedence over the token and
+    // system programs they usually call
+    if let Some(custom_type) = program_ids.iter().find_map(|id| registry.custom_type(id)) {
+        return TransactionType::Custom(custom_type.to_string());
+    }
+
+    // Check for SPL Token transfers using registry; the Associated Token Account program is
+    // recognized even when the registry was not seeded
+    if program_ids.iter().any(|id| registry.is_token(id) || id == ASSOCIATED_TOKEN_PROGRAM) {
+        if let Some(tx_type) = tx_json.and_then(classify_token_instructions) {
+            return tx_type;
+        }
+
+        // Only setting up a token account, e.g. before receiving a token for the first time
+        if tx_json.is_some_and(creates_associated_account) {
+            return TransactionType::AccountCreation;
+        }
+
         // System program + Token program often indicates account creation or wrapped SOL
         if program_ids.iter().any(|id| registry.is_system(id)) {
             return TransactionType::SplTokenTransfer;
@@ -99,8 +228,21 @@ pub fn classify_transaction_with_registry(program_ids: &[String], registry: &Pro
         return TransactionType::SplTokenTransfer;
     }
 
-    // Check for pure SOL transfers (System Program only)
+    // Check for native staking (delegate, deactivate, withdraw) using registry
+    if program_ids.iter().any(|id| registry.is_stake(id)) {
+        return TransactionType::Stake;
+    }
+
+    if is_memo_only(program_ids) {
+        return TransactionType::Memo;
+    }
+
+    // Check for pure SOL transfers (System Program only), telling account creations apart
+    // when the instructions are available: their lamports fund a new account, usually for rent
     if program_ids.len() == 1 && registry.is_system(&program_ids[0]) {
+        if tx_json.is_some_and(creates_account) {
+            return TransactionType::AccountCreation;
+        }
         return TransactionType::SolTransfer;
     }
 
@@ -113,8 +255,176 @@ pub fn classify_transaction_with_registry(program_ids: &[String], registry: &Pro
     TransactionType::Unknown
 }
 
+/// Types other than `primary` that a transaction matches, highest priority first
+///
+/// Only types with evidence in the transaction count: a program of the type is invoked at the
+/// top level, or (with the transaction JSON) a top-level instruction performs the operation,
+/// such as a token transfer, an associated token account creation or a SOL transfer. The DEX
+/// types are alternatives to each other, so a liquidity operation has no secondary swap.
+pub fn secondary_transaction_types(
+    primary: &TransactionType,
+    program_ids: &[String],
+    registry: &ProgramRegistry,
+    tx_json: Option<&serde_json::Value>,
+) -> Vec<TransactionType> {
+    let is_dex_type = |tx_type: &TransactionType| {
+        matches!(tx_type, TransactionType::DexSwap | TransactionType::LiquidityAdd | TransactionType::LiquidityRemove)
+    };
+
+    let mut candidates = Vec::new();
+    if program_ids.iter().any(|id| registry.is_dex(id)) {
+        candidates.push(tx_json.and_then(classify_liquidity_instructions).unwrap_or(TransactionType::DexSwap));
+    }
+    if program_ids.iter().any(|id| registry.is_nft(id)) {
+        candidates.push(TransactionType::NftMint);
+    }
+    if program_ids.iter().any(|id| registry.is_liquid_staking(id)) {
+        candidates.push(TransactionType::LiquidStaking);
+    }
+    if let Some(custom_type) = program_ids.iter().find_map(|id| registry.custom_type(id)) {
+        candidates.push(TransactionType::Custom(custom_type.to_string()));
+    }
+    if let Some(json) = tx_json {
+        candidates.extend(classify_token_instructions(json));
+        if creates_associated_account(json) {
+            candidates.push(TransactionType::AccountCreation);
+        }
+    }
+    if program_ids.iter().any(|id| registry.is_stake(id)) {
+        candidates.push(TransactionType::Stake);
+    }
+    if program_ids.iter().any(|id| is_memo_program(id)) {
+        candidates.push(TransactionType::Memo);
+    }
+    if let Some(json) = tx_json {
+        if creates_account(json) {
+            candidates.push(TransactionType::AccountCreation);
+        }
+        if transfers_sol(json) {
+            candidates.push(TransactionType::SolTransfer);
+        }
+    }
+
+    let mut secondary_types: Vec<TransactionType> = Vec::new();
+    for candidate in candidates {
+        if candidate != *primary &&
+            !(is_dex_type(primary) && is_dex_type(&candidate)) &&
+            !secondary_types.contains(&candidate)
+        {
+            secondary_types.push(candidate);
+        }
+    }
+    secondary_types
+}
+
+/// Check whether a transaction transfers SOL with a top-level System Program instruction
+fn transfers_sol(tx_json: &serde_json::Value) -> bool {
+    let Some(instructions) = tx_json.get("message").and_then(|m| m.get("instructions")).and_then(|i| i.as_array())
+    else {
+        return false;
+    };
+
+    instructions.iter().any(|instruction| {
+        matches!(parse_system_instruction(instruction, &[]), Some(SystemInstruction::Transfer { .. }))
+    })
+}
+
+/// Check whether a transaction creates or allocates an account with a top-level System Program instruction
+fn creates_account(tx_json: &serde_json::Value) -> bool {
+    let Some(instructions) = tx_json.get("message").and_then(|m| m.get("instructions")).and_then(|i| i.as_array())
+    else {
+        return false;
+    };
+
+    instructions.iter().any(|instruction| {
+        matches!(
+            parse_system_instruction(instruction, &[]),
+            Some(SystemInstruction::CreateAccount { .. } | SystemInstruction::Allocate { .. })
+        )
+    })
+}
+
+/// Check whether a transaction creates an associated token account with a top-level instruction
+fn creates_associated_account(tx_json: &serde_json::Value) -> bool {
+    let Some(instructions) = tx_json.get("message").and_then(|m| m.get("instructions")).and_then(|i| i.as_array())
+    else {
+        return false;
+    };
+
+    let account_keys = extract_account_keys(tx_json);
+    instructions.iter().any(|instruction| parse_associated_account_creation(instruction, &account_keys).is_some())
+}
+
+/// Classify a token transaction by its first top-level transfer, mint or burn instruction
+fn classify_token_instructions(tx_json: &serde_json::Value) -> Option<TransactionType> {
+    let instructions = tx_json.get("message")?.get("instructions")?.as_array()?;
+
+    instructions
+        .iter()
+        .filter(|instruction| {
+            matches!(instruction.get("program").and_then(|p| p.as_str()), Some("spl-token" | "spl-token-2022"))
+        })
+        .find_map(|instruction| {
+            if parse_token_transfer(instruction, &[]).is_some() {
+                Some(TransactionType::SplTokenTransfer)
+            } else if parse_token_mint(instruction, &[]).is_some() {
+                Some(TransactionType::TokenMint)
+            } else if parse_token_burn(instruction, &[]).is_some() {
+                Some(TransactionType::TokenBurn)
+            } else {
+                None
+            }
+        })
+}
+
+/// Classify a DEX transaction by its first top-level liquidity deposit or withdrawal
+fn classify_liquidity_instructions(tx_json: &serde_json::Value) -> Option<TransactionType> {
+    let instructions = tx_json.get("message")?.get("instructions")?.as_array()?;
+
+    instructions.iter().find_map(parse_liquidity_instruction).map(liquidity_transaction_type)
+}
+
+fn liquidity_transaction_type(action: LiquidityAction) -> TransactionType {
+    match action {
+        LiquidityAction::Add => TransactionType::LiquidityAdd,
+        LiquidityAction::Remove => TransactionType::LiquidityRemove,
+    }
+}
+
+/// Check whether a transaction is a validator vote (only invokes the Vote program,
+/// optionally with compute budget instructions)
+pub fn is_vote_transaction(program_ids: &[String]) -> bool {
+    program_ids.iter().any(|id| id == VOTE_PROGRAM) &&
+        program_ids.iter().all(|id| id == VOTE_PROGRAM || id == COMPUTE_BUDGET_PROGRAM)
+}
+
+/// Check whether a transaction only posts a memo (optionally with compute budget instructions)
+fn is_memo_only(program_ids: &[String]) -> bool {
+    program_ids.iter().any(|id| is_memo_program(id)) &&
+        program_ids.iter().all(|id| is_memo_program(id) || id == COMPUTE_BUDGET_PROGRAM)
+}
+
+/// Extract the memo of a transaction from its top-level instructions
+///
+/// When a transaction carries several memo instructions, their texts are concatenated
+/// in instruction order, separated by newlines. Returns None when there is no memo.
+pub fn extract_memo(tx_json: &serde_json::Value) -> Option<String> {
+    let instructions = tx_json.get("message")?.get("instructions")?.as_array()?;
+    let memos: Vec<String> = instructions.iter().filter_map(parse_memo).collect();
+
+    if memos.is_empty() {
+        None
+    } else {
+        Some(memos.join("\n"))
+    }
+}
+
 /// Classify a transaction based on its program IDs (without registry - legacy)
 pub fn classify_transaction(program_ids: &[String]) -> TransactionType {
+    if is_vote_transaction(program_ids) {
+        return TransactionType::Vote;
+    }
+
     // Check for DEX interactions (swaps)
     if program_ids
         .iter()
@@ -130,6 +440,11 @@ pub fn classify_transaction(program_ids: &[String]) -> TransactionType {
         return TransactionType::NftMint; // Generic NFT operation
     }
 
+    // Check for liquid staking pools
+    if program_ids.iter().any(|id| id == MARINADE_PROGRAM || id == SPL_STAKE_POOL_PROGRAM) {
+        return TransactionType::LiquidStaking;
+    }
+
     // Check for SPL Token transfers
     if program_ids.iter().any(|id| id == TOKEN_PROGRAM || id == TOKEN_2022_PROGRAM) {
         // This could be a token transfer, but we'd need to parse instructions to be sure
@@ -141,6 +456,15 @@ pub fn classify_transaction(program_ids: &[String]) -> TransactionType {
         return TransactionType::SplTokenTransfer;
     }
 
+    // Check for native staking
+    if program_ids.iter().any(|id| id == STAKE_PROGRAM) {
+        return TransactionType::Stake;
+    }
+
+    if is_memo_only(program_ids) {
+        return TransactionType::Memo;
+    }
+
     // Check for pure SOL transfers (System Program only or primarily)
     if program_ids.len() == 1 && program_ids[0] == SYSTEM_PROGRAM {
         return TransactionType::SolTransfer;
@@ -229,7 +553,7 @@ pub fn parse_instruction_data(_data: &[u8]) -> Result<serde_json::Value> {
 }
 
 /// Extract account keys from transaction JSON
-fn extract_account_keys(tx_json: &serde_json::Value) -> Vec<String> {
+pub fn extract_account_keys(tx_json: &serde_json::Value) -> Vec<String> {
     let mut keys = Vec::new();
 
     if let Some(message) = tx_json.get("message") {
@@ -247,46 +571,233 @@ fn extract_account_keys(tx_json: &serde_json::Value) -> Vec<String> {
     keys
 }
 
+/// Extract the signers of a transaction, fee payer first
+///
+/// JsonParsed messages flag each account key with `signer`. Compiled messages list the signers
+/// first, `message.header.numRequiredSignatures` of them.
+pub fn extract_signers(tx_json: &serde_json::Value) -> Vec<String> {
+    let Some(message) = tx_json.get("message") else {
+        return Vec::new();
+    };
+    let Some(account_keys) = message.get("accountKeys").and_then(|a| a.as_array()) else {
+        return Vec::new();
+    };
+
+    let num_required_signatures =
+        message.get("header").and_then(|h| h.get("numRequiredSignatures")).and_then(|n| n.as_u64()).map(|n| n as usize);
+
+    account_keys
+        .iter()
+        .enumerate()
+        .filter(|(index, key)| match key.get("signer").and_then(|s| s.as_bool()) {
+            Some(signer) => signer,
+            None => num_required_signatures.is_some_and(|n| *index < n),
+        })
+        .filter_map(|(_, key)| key.get("pubkey").and_then(|p| p.as_str()).or_else(|| key.as_str()))
+        .map(|pubkey| pubkey.to_string())
+        .collect()
+}
+
+/// Extract the account keys of a transaction including addresses loaded from Address Lookup Tables
+///
+/// v0 transactions reference some accounts through lookup tables, resolved in
+/// `meta.loadedAddresses`. They follow the static keys (writable, then readonly), which is the
+/// order used by account indexes and `meta.preBalances`. JsonParsed messages already list them
+/// in `accountKeys`, so addresses that are present are not appended twice.
+pub fn extract_all_account_keys(tx_json: &serde_json::Value, meta_json: &serde_json::Value) -> Vec<String> {
+    let mut keys = extract_account_keys(tx_json);
+
+    if let Some(loaded) = meta_json.get("loadedAddresses") {
+        for group in ["writable", "readonly"] {
+            let addresses = loaded.get(group).and_then(|a| a.as_array()).into_iter().flatten();
+            for address in addresses.filter_map(|a| a.as_str()) {
+                if !keys.iter().any(|key| key == address) {
+                    keys.push(address.to_string());
+                }
+            }
+        }
+    }
+
+    keys
+}
+
+/// Extract the top-level instructions of a transaction for the instructions table
+///
+/// Program names are resolved through the registry. Parsed instructions carry their
+/// `parsed.type` as the instruction type and the account keys referenced by `parsed.info`;
+/// compiled (non-parsed) instructions get a row with a null instruction type.
+pub fn extract_instructions(
+    signature: &str,
+    tx_json: &serde_json::Value,
+    registry: &ProgramRegistry,
+) -> Vec<crate::models::Instruction> {
+    let mut extracted = Vec::new();
+
+    let account_keys = extract_account_keys(tx_json);
+
+    let Some(instructions) = tx_json.get("message").and_then(|m| m.get("instructions")).and_then(|i| i.as_array())
+    else {
+        return extracted;
+    };
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        let Some(program_id) = super::extract::instruction_program_id(instruction, &account_keys) else {
+            continue;
+        };
+
+        let instruction_type =
+            instruction.get("parsed").and_then(|p| p.get("type")).and_then(|t| t.as_str()).map(|t| t.to_string());
+
+        let accounts = if let Some(accounts) = instruction.get("accounts").and_then(|a| a.as_array()) {
+            // Partially decoded instructions list pubkeys, compiled ones list indexes into accountKeys
+            accounts
+                .iter()
+                .filter_map(|a| {
+                    a.as_str()
+                        .map(|s| s.to_string())
+                        .or_else(|| a.as_u64().and_then(|i| account_keys.get(i as usize).cloned()))
+                })
+                .collect()
+        } else if let Some(info) = instruction.get("parsed").and_then(|p| p.get("info")).and_then(|i| i.as_object()) {
+            // Parsed instructions name their accounts inside `info`
+            let mut accounts: Vec<String> = Vec::new();
+            for value in info.values() {
+                if let Some(key) = value.as_str() {
+                    if account_keys.iter().any(|k| k == key) && !accounts.iter().any(|a| a == key) {
+                        accounts.push(key.to_string());
+                    }
+                }
+            }
+            accounts
+        } else {
+            Vec::new()
+        };
+
+        extracted.push(crate::models::Instruction {
+            transaction_signature: signature.to_string(),
+            instruction_index: index as i32,
+            program_name: registry.get_program_name(&program_id).map(|s| s.to_string()),
+            program_id,
+            instruction_type,
+            accounts,
+        });
+    }
+
+    extracted
+}
+
 /// Extract detailed information from a transaction for better classification
 #[derive(Debug, Clone)]
 pub struct TransactionDetails {
-    #[allow(dead_code)]
     pub tx_type: TransactionType,
+    pub secondary_types: Vec<TransactionType>, // See `secondary_transaction_types`
     pub label: String,
     #[allow(dead_code)]
     pub amount: Option<u64>,
     #[allow(dead_code)]
     pub token_address: Option<String>,
     #[allow(dead_code)]
+    pub ui_amount: Option<f64>, // Token amount scaled by the mint decimals, when they are known
+    #[allow(dead_code)]
     pub from_account: Option<String>,
     #[allow(dead_code)]
     pub to_account: Option<String>,
     #[allow(dead_code)]
     pub program_names: Vec<String>,
+    #[allow(dead_code)]
+    pub stake_action: Option<StakeAction>, // Delegate, deactivate or withdraw for stake transactions
+    #[allow(dead_code)]
+    pub liquid_staking_action: Option<LiquidStakingAction>, // Deposit, withdraw or claim for liquid staking
+    #[allow(dead_code)]
+    pub account_owner: Option<String>, // Owner program of the account created by a System Program createAccount
+    #[allow(dead_code)]
+    pub token_account_owner: Option<String>, // Wallet owning the associated token account created by the transaction
+    #[allow(dead_code)]
+    pub token_program: Option<TokenProgram>, // Classic SPL Token or Token-2022, for token transfers
+    #[allow(dead_code)]
+    pub transfer_fee: Option<u64>, // Fee withheld by a Token-2022 transfer-fee mint, in token base units
+    pub wrapped_sol: Option<WrappedSolAction>, // SOL wrapped into or unwrapped from wSOL
+    pub is_wrapped_sol: bool,                  // The transfer moves wSOL, or the transaction wraps or unwraps SOL
+    pub input_mint: Option<String>,            // Mint sold in a Jupiter swap (wrapped SOL for native SOL)
+    pub output_mint: Option<String>,           // Mint bought in a Jupiter swap
+    pub in_amount: Option<u64>,                // Amount sold, in input mint base units
+    pub out_amount: Option<u64>,               // Amount bought, in output mint base units
+    pub memo: Option<String>,                  // Concatenated memo text, see `extract_memo`
+}
+
+/// Upgrade a vague classification using tell-tale program log lines
+///
+/// Only `Unknown` and `ProgramInteraction` are upgraded: programs outside the registry often
+/// log their Anchor instruction name (e.g. "Program log: Instruction: Swap"), which is a
+/// better signal than nothing, but never better than a program ID match. A `DexSwap` is
+/// narrowed to a liquidity operation when a pool logged one through CPI.
+pub fn classify_with_logs(tx_type: TransactionType, log_messages: &[String]) -> TransactionType {
+    if let Some(action) = parse_liquidity_log(log_messages) {
+        if matches!(tx_type, TransactionType::DexSwap | TransactionType::Unknown | TransactionType::ProgramInteraction)
+        {
+            return liquidity_transaction_type(action);
+        }
+    }
+
+    if !matches!(tx_type, TransactionType::Unknown | TransactionType::ProgramInteraction) {
+        return tx_type;
+    }
+
+    let logged_instructions =
+        log_messages.iter().filter_map(|line| line.strip_prefix("Program log: Instruction: ")).map(str::trim);
+
+    for instruction in logged_instructions {
+        if instruction.contains("Swap") {
+            return TransactionType::DexSwap;
+        } else if instruction.starts_with("Mint") {
+            return TransactionType::TokenMint;
+        } else if instruction.starts_with("Burn") {
+            return TransactionType::TokenBurn;
+        }
+    }
+
+    tx_type
 }
 
 /// Analyze transaction with detailed extraction
+#[allow(dead_code)]
 pub fn analyze_transaction_with_registry(
     program_ids: &[String],
     registry: &ProgramRegistry,
     tx_json: Option<&serde_json::Value>,
 ) -> TransactionDetails {
-    let tx_type = classify_transaction_with_registry(program_ids, registry);
+    let tx_type = primary_transaction_type(program_ids, registry, tx_json);
+    analyze_classified_transaction(tx_type, program_ids, registry, tx_json, None)
+}
 
+/// Extract transaction details for an already classified transaction
+///
+/// `wrapped_sol` is the wrap or unwrap found during extraction: recognizing an unwrap needs
+/// the token balances, which `tx_json` does not carry.
+pub fn analyze_classified_transaction(
+    tx_type: TransactionType,
+    program_ids: &[String],
+    registry: &ProgramRegistry,
+    tx_json: Option<&serde_json::Value>,
+    wrapped_sol: Option<WrappedSolAction>,
+) -> TransactionDetails {
     // Collect program names
     let program_names: Vec<String> =
         program_ids.iter().filter_map(|id| registry.get_program_name(id).map(|s| s.to_string())).collect();
 
-    // Create base label
-    let label = tx_type.as_str().to_string();
-    let full_label =
-        if !program_names.is_empty() { format!("{} ({})", label, program_names.join(", ")) } else { label };
-
     // Try to extract detailed information if we have the transaction JSON
     let mut amount = None;
     let mut token_address = None;
+    let mut ui_amount = None;
     let mut from_account = None;
     let mut to_account = None;
+    let mut stake_action = None;
+    let mut liquid_staking_action = None;
+    let mut account_owner = None;
+    let mut token_program = None;
+    let mut transfer_fee = None;
+    let mut associated_account = None;
+    let memo = tx_json.and_then(extract_memo);
 
     if let Some(json) = tx_json {
         // Debug: Print transaction structure for SOL transfers
@@ -307,21 +818,75 @@ pub fn analyze_transaction_with_registry(
         if let Some(message) = json.get("message") {
             if let Some(instructions) = message.get("instructions").and_then(|i| i.as_array()) {
                 for instruction in instructions {
+                    // An associated token account set up before a transfer only fills in the
+                    // details when nothing else does
+                    if let Some(creation) = parse_associated_account_creation(instruction, &account_keys) {
+                        associated_account.get_or_insert(creation);
+                        continue;
+                    }
+
+                    // Liquid staking pools are not parsed by the RPC, only their action is
+                    // read here; the amounts come from the balance changes (see
+                    // `parse_liquid_staking`)
+                    if let Some(action) = parse_liquid_staking_instruction(instruction, &account_keys) {
+                        liquid_staking_action = Some(action);
+                        break;
+                    }
+
                     // Check for System Program transfers (SOL)
                     if let Some(program) = instruction.get("program").and_then(|p| p.as_str()) {
                         if registry.is_system(program) || program == "system" {
-                            if let Some((amt, from, to)) = parse_system_transfer(instruction, &account_keys) {
-                                amount = Some(amt);
-                                from_account = Some(from);
-                                to_account = Some(to);
+                            match parse_system_instruction(instruction, &account_keys) {
+                                Some(SystemInstruction::Transfer { lamports, source, destination }) => {
+                                    amount = Some(lamports);
+                                    from_account = Some(source);
+                                    to_account = Some(destination);
+                                    break;
+                                }
+                                Some(SystemInstruction::CreateAccount {
+                                    source, new_account, owner, lamports, ..
+                                }) => {
+                                    amount = Some(lamports);
+                                    from_account = Some(source);
+                                    to_account = Some(new_account);
+                                    account_owner = Some(owner);
+                                    break;
+                                }
+                                Some(SystemInstruction::Allocate { account, .. }) => {
+                                    to_account = Some(account);
+                                    break;
+                                }
+                                None => {}
+                            }
+                        } else if registry.is_token(program) || program == "spl-token" || program == "spl-token-2022" {
+                            if let Some(transfer) = parse_token_transfer(instruction, &account_keys) {
+                                amount = Some(transfer.amount);
+                                ui_amount = transfer.ui_amount();
+                                token_program = Some(transfer.token_program);
+                                transfer_fee = transfer.transfer_fee;
+                                token_address = transfer.mint;
+                                from_account = Some(transfer.source);
+                                to_account = Some(transfer.destination);
+                                break;
+                            }
+                            if let Some(mint) = parse_token_mint(instruction, &account_keys) {
+                                amount = Some(mint.amount);
+                                token_address = Some(mint.mint);
+                                to_account = Some(mint.account);
+                                break;
+                            }
+                            if let Some(burn) = parse_token_burn(instruction, &account_keys) {
+                                amount = Some(burn.amount);
+                                token_address = Some(burn.mint);
+                                from_account = Some(burn.account);
                                 break;
                             }
-                        } else if registry.is_token(program) || program == "spl-token" {
-                            if let Some((amt, mint, from, to)) = parse_token_transfer(instruction, &account_keys) {
-                                amount = Some(amt);
-                                token_address = Some(mint);
-                                from_account = Some(from);
-                                to_account = Some(to);
+                        } else if registry.is_stake(program) || program == "stake" {
+                            if let Some(stake) = parse_stake(instruction, &account_keys) {
+                                amount = stake.lamports;
+                                from_account = Some(stake.stake_account);
+                                to_account = stake.vote_account.or(stake.destination);
+                                stake_action = Some(stake.action);
                                 break;
                             }
                         }
@@ -331,7 +896,70 @@ pub fn analyze_transaction_with_registry(
         }
     }
 
-    TransactionDetails { tx_type, label: full_label, amount, token_address, from_account, to_account, program_names }
+    // The mint being set up, the new account and its owner for account creations
+    let mut token_account_owner = None;
+    if let Some(creation) = associated_account.filter(|_| to_account.is_none()) {
+        token_address = Some(creation.mint);
+        from_account = Some(creation.source);
+        to_account = Some(creation.account);
+        token_account_owner = Some(creation.owner);
+    }
+
+    // wSOL moves SOL rather than a token, so plain transfers and account setups that wrap,
+    // unwrap or send it are tagged (e.g. "SPL Token Transfer wSOL unwrap"); swaps and other
+    // operations that wrap SOL along the way are not
+    let is_wrapped_sol = wrapped_sol.is_some() || token_address.as_deref() == Some(WRAPPED_SOL_MINT);
+    let wrapped_sol_tag = match wrapped_sol {
+        Some(action) => format!("wSOL {}", action.as_str()),
+        None => "wSOL".to_string(),
+    };
+    let wrapped_sol_tag = (is_wrapped_sol &&
+        matches!(
+            tx_type,
+            TransactionType::SolTransfer | TransactionType::AccountCreation | TransactionType::SplTokenTransfer
+        ))
+    .then_some(wrapped_sol_tag);
+
+    // Create base label, naming the stake action when there is one (e.g. "Stake delegate",
+    // "Liquid Staking deposit")
+    let action = stake_action
+        .as_ref()
+        .map(StakeAction::as_str)
+        .or(liquid_staking_action.as_ref().map(LiquidStakingAction::as_str))
+        .or(wrapped_sol_tag.as_deref());
+    let label = match action {
+        Some(action) => format!("{} {}", tx_type.as_str(), action),
+        None => tx_type.as_str().to_string(),
+    };
+    let full_label =
+        if !program_names.is_empty() { format!("{} ({})", label, program_names.join(", ")) } else { label };
+
+    let secondary_types = secondary_transaction_types(&tx_type, program_ids, registry, tx_json);
+
+    TransactionDetails {
+        tx_type,
+        secondary_types,
+        label: full_label,
+        amount,
+        token_address,
+        ui_amount,
+        from_account,
+        to_account,
+        program_names,
+        stake_action,
+        liquid_staking_action,
+        account_owner,
+        token_account_owner,
+        token_program,
+        transfer_fee,
+        wrapped_sol,
+        is_wrapped_sol,
+        input_mint: None,
+        output_mint: None,
+        in_amount: None,
+        out_amount: None,
+        memo,
+    }
 }
 
 /// Get statistics about transaction types in a batch
@@ -339,11 +967,21 @@ pub fn analyze_transaction_with_registry(
 #[allow(dead_code)]
 pub struct TransactionTypeStats {
     pub sol_transfers: usize,
+    pub account_creations: usize,
     pub token_transfers: usize,
+    pub token_mints: usize,
+    pub token_burns: usize,
     pub dex_swaps: usize,
+    pub liquidity_adds: usize,
+    pub liquidity_removes: usize,
+    pub memos: usize,
+    pub stakes: usize,
+    pub liquid_staking: usize,
+    pub votes: usize,
     pub nft_operations: usize,
     pub program_interactions: usize,
     pub unknown: usize,
+    pub custom: HashMap<String, usize>, // Transactions per user-defined type
     pub total: usize,
 }
 
@@ -358,11 +996,21 @@ impl TransactionTypeStats {
         self.total += 1;
         match tx_type {
             TransactionType::SolTransfer => self.sol_transfers += 1,
+            TransactionType::AccountCreation => self.account_creations += 1,
             TransactionType::SplTokenTransfer => self.token_transfers += 1,
+            TransactionType::TokenMint => self.token_mints += 1,
+            TransactionType::TokenBurn => self.token_burns += 1,
             TransactionType::DexSwap => self.dex_swaps += 1,
+            TransactionType::LiquidityAdd => self.liquidity_adds += 1,
+            TransactionType::LiquidityRemove => self.liquidity_removes += 1,
+            TransactionType::Memo => self.memos += 1,
+            TransactionType::Stake => self.stakes += 1,
+            TransactionType::LiquidStaking => self.liquid_staking += 1,
+            TransactionType::Vote => self.votes += 1,
             TransactionType::NftMint | TransactionType::NftTransfer => self.nft_operations += 1,
             TransactionType::ProgramInteraction => self.program_interactions += 1,
             TransactionType::Unknown => self.unknown += 1,
+            TransactionType::Custom(program_type) => *self.custom.entry(program_type.clone()).or_insert(0) += 1,
         }
     }
 
@@ -375,3 +1023,478 @@ impl TransactionTypeStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> ProgramRegistry {
+        ProgramRegistry::from_database(vec![crate::db::ProgramInfo {
+            program_id: SYSTEM_PROGRAM.to_string(),
+            program_name: "System Program".to_string(),
+            program_type: Some("System".to_string()),
+        }])
+    }
+
+    /// Registry seeded with `BUILTIN_PROGRAMS`, as after `seed-registry`
+    fn builtin_registry() -> ProgramRegistry {
+        ProgramRegistry::from_database(
+            BUILTIN_PROGRAMS
+                .iter()
+                .map(|(id, name, program_type)| crate::db::ProgramInfo {
+                    program_id: id.to_string(),
+                    program_name: name.to_string(),
+                    program_type: Some(program_type.to_string()),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_extract_instructions() {
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Sender11111111111111111111111111111111111" },
+                    { "pubkey": "Receiver111111111111111111111111111111111" },
+                    { "pubkey": "Custom1111111111111111111111111111111111111" }
+                ],
+                "instructions": [
+                    {
+                        "program": "system",
+                        "programId": SYSTEM_PROGRAM,
+                        "parsed": {
+                            "type": "transfer",
+                            "info": {
+                                "lamports": 1000,
+                                "source": "Sender11111111111111111111111111111111111",
+                                "destination": "Receiver111111111111111111111111111111111"
+                            }
+                        }
+                    },
+                    {
+                        "programId": "Custom1111111111111111111111111111111111111",
+                        "accounts": ["Sender11111111111111111111111111111111111"],
+                        "data": "3Bxs4h24hBtQy9rw"
+                    }
+                ]
+            }
+        });
+
+        let instructions = extract_instructions("sig", &tx_json, &test_registry());
+        assert_eq!(instructions.len(), 2);
+
+        assert_eq!(instructions[0].instruction_index, 0);
+        assert_eq!(instructions[0].program_name.as_deref(), Some("System Program"));
+        assert_eq!(instructions[0].instruction_type.as_deref(), Some("transfer"));
+        assert_eq!(instructions[0].accounts.len(), 2);
+
+        assert_eq!(instructions[1].program_id, "Custom1111111111111111111111111111111111111");
+        assert_eq!(instructions[1].program_name, None);
+        assert_eq!(instructions[1].instruction_type, None);
+        assert_eq!(instructions[1].accounts, vec!["Sender11111111111111111111111111111111111"]);
+    }
+
+    #[test]
+    fn test_extract_signers() {
+        // JsonParsed: a 2-of-n multisig execution, signer flags on each key
+        let parsed = serde_json::json!({
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Payer111", "signer": true, "writable": true },
+                    { "pubkey": "Member222", "signer": true, "writable": false },
+                    { "pubkey": "Vault333", "signer": false, "writable": true }
+                ]
+            }
+        });
+        assert_eq!(extract_signers(&parsed), vec!["Payer111", "Member222"]);
+
+        // Compiled: the first numRequiredSignatures keys
+        let compiled = serde_json::json!({
+            "message": {
+                "header": { "numRequiredSignatures": 2, "numReadonlySignedAccounts": 1, "numReadonlyUnsignedAccounts": 1 },
+                "accountKeys": ["Payer111", "Member222", "Vault333", SYSTEM_PROGRAM]
+            }
+        });
+        assert_eq!(extract_signers(&compiled), vec!["Payer111", "Member222"]);
+
+        assert!(extract_signers(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_extract_memo_concatenates_in_order() {
+        let tx_json = serde_json::json!({
+            "message": {
+                "instructions": [
+                    { "program": "spl-memo", "programId": crate::etl::parsers::memo::MEMO_PROGRAM, "parsed": "first" },
+                    { "program": "system", "programId": SYSTEM_PROGRAM, "parsed": { "type": "transfer", "info": {} } },
+                    { "program": "spl-memo", "programId": crate::etl::parsers::memo::MEMO_PROGRAM, "parsed": "second" }
+                ]
+            }
+        });
+
+        assert_eq!(extract_memo(&tx_json).as_deref(), Some("first\nsecond"));
+        assert_eq!(extract_memo(&serde_json::json!({ "message": { "instructions": [] } })), None);
+    }
+
+    #[test]
+    fn test_classify_memo_only() {
+        let memo_only = vec![COMPUTE_BUDGET_PROGRAM.to_string(), crate::etl::parsers::memo::MEMO_PROGRAM.to_string()];
+        assert_eq!(
+            classify_transaction_with_registry(&memo_only, &test_registry(), None).primary,
+            TransactionType::Memo
+        );
+
+        let transfer_with_memo = vec![SYSTEM_PROGRAM.to_string(), crate::etl::parsers::memo::MEMO_PROGRAM.to_string()];
+        assert_eq!(
+            classify_transaction_with_registry(&transfer_with_memo, &test_registry(), None).primary,
+            TransactionType::ProgramInteraction
+        );
+    }
+
+    #[test]
+    fn test_analyze_stake_delegation() {
+        let registry = ProgramRegistry::from_database(vec![crate::db::ProgramInfo {
+            program_id: STAKE_PROGRAM.to_string(),
+            program_name: "Stake Program".to_string(),
+            program_type: Some("Stake".to_string()),
+        }]);
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [],
+                "instructions": [{
+                    "parsed": {
+                        "type": "delegate",
+                        "info": { "stakeAccount": "StakeAcct111", "voteAccount": "VoteAcct111" }
+                    },
+                    "program": "stake",
+                    "programId": STAKE_PROGRAM
+                }]
+            }
+        });
+
+        let details = analyze_transaction_with_registry(&[STAKE_PROGRAM.to_string()], &registry, Some(&tx_json));
+        assert_eq!(details.tx_type, TransactionType::Stake);
+        assert_eq!(details.label, "Stake delegate (Stake Program)");
+        assert_eq!(details.from_account.as_deref(), Some("StakeAcct111"));
+        assert_eq!(details.to_account.as_deref(), Some("VoteAcct111"));
+    }
+
+    #[test]
+    fn test_analyze_liquid_staking_deposit() {
+        let registry = builtin_registry();
+        // DepositSol into a stake pool, which mints the pool token through the Token program
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [],
+                "instructions": [{
+                    "programId": SPL_STAKE_POOL_PROGRAM,
+                    "accounts": [],
+                    "data": bs58::encode([14, 0, 202, 154, 59, 0, 0, 0, 0]).into_string()
+                }]
+            }
+        });
+        let program_ids = vec![SPL_STAKE_POOL_PROGRAM.to_string(), TOKEN_PROGRAM.to_string()];
+
+        let details = analyze_transaction_with_registry(&program_ids, &registry, Some(&tx_json));
+        assert_eq!(details.tx_type, TransactionType::LiquidStaking);
+        assert_eq!(details.liquid_staking_action, Some(LiquidStakingAction::Deposit));
+        assert_eq!(details.label, "Liquid Staking deposit (SPL Stake Pool, Token Program)");
+    }
+
+    #[test]
+    fn test_analyze_wrapped_sol_unwrap() {
+        // Closing a wSOL account; that the account holds wSOL is known from extraction
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [],
+                "instructions": [{
+                    "parsed": {
+                        "type": "closeAccount",
+                        "info": { "account": "WsolAccount111", "destination": "Wallet111", "owner": "Wallet111" }
+                    },
+                    "program": "spl-token",
+                    "programId": TOKEN_PROGRAM
+                }]
+            }
+        });
+        let program_ids = vec![TOKEN_PROGRAM.to_string()];
+        let registry = builtin_registry();
+        let tx_type = TransactionType::SplTokenTransfer;
+
+        let details = analyze_classified_transaction(
+            tx_type.clone(),
+            &program_ids,
+            &registry,
+            Some(&tx_json),
+            Some(WrappedSolAction::Unwrap),
+        );
+        assert!(details.is_wrapped_sol);
+        assert_eq!(details.wrapped_sol, Some(WrappedSolAction::Unwrap));
+        assert_eq!(details.label, "SPL Token Transfer wSOL unwrap (Token Program)");
+
+        let details = analyze_classified_transaction(tx_type, &program_ids, &registry, Some(&tx_json), None);
+        assert!(!details.is_wrapped_sol);
+        assert_eq!(details.label, "SPL Token Transfer (Token Program)");
+    }
+
+    #[test]
+    fn test_classify_account_creation() {
+        let program_ids = vec![SYSTEM_PROGRAM.to_string()];
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [],
+                "instructions": [{
+                    "parsed": {
+                        "type": "createAccount",
+                        "info": {
+                            "source": "Payer111",
+                            "newAccount": "NewAcct111",
+                            "lamports": 890_880,
+                            "space": 0,
+                            "owner": SYSTEM_PROGRAM
+                        }
+                    },
+                    "program": "system",
+                    "programId": SYSTEM_PROGRAM
+                }]
+            }
+        });
+
+        let details = analyze_transaction_with_registry(&program_ids, &test_registry(), Some(&tx_json));
+        assert_eq!(details.tx_type, TransactionType::AccountCreation);
+        assert_eq!(details.amount, Some(890_880));
+        assert_eq!(details.from_account.as_deref(), Some("Payer111"));
+        assert_eq!(details.to_account.as_deref(), Some("NewAcct111"));
+        assert_eq!(details.account_owner.as_deref(), Some(SYSTEM_PROGRAM));
+
+        // Without the instructions a System-only transaction is still a transfer
+        assert_eq!(
+            classify_transaction_with_registry(&program_ids, &test_registry(), None).primary,
+            TransactionType::SolTransfer
+        );
+    }
+
+    #[test]
+    fn test_classify_associated_account_creation() {
+        let create_ata = serde_json::json!({
+            "parsed": {
+                "type": "create",
+                "info": {
+                    "account": "NewAta111",
+                    "mint": "Mint111",
+                    "source": "Payer111",
+                    "systemProgram": SYSTEM_PROGRAM,
+                    "tokenProgram": TOKEN_PROGRAM,
+                    "wallet": "Wallet111"
+                }
+            },
+            "program": "spl-associated-token-account",
+            "programId": ASSOCIATED_TOKEN_PROGRAM
+        });
+        let program_ids =
+            vec![ASSOCIATED_TOKEN_PROGRAM.to_string(), SYSTEM_PROGRAM.to_string(), TOKEN_PROGRAM.to_string()];
+        let tx_json = serde_json::json!({ "message": { "accountKeys": [], "instructions": [create_ata] } });
+
+        // Recognized without a seeded registry
+        let details = analyze_transaction_with_registry(&program_ids, &test_registry(), Some(&tx_json));
+        assert_eq!(details.tx_type, TransactionType::AccountCreation);
+        assert_eq!(details.token_address.as_deref(), Some("Mint111"));
+        assert_eq!(details.to_account.as_deref(), Some("NewAta111"));
+        assert_eq!(details.from_account.as_deref(), Some("Payer111"));
+        assert_eq!(details.token_account_owner.as_deref(), Some("Wallet111"));
+
+        // Creating the recipient's account before a transfer is still a transfer
+        let transfer = serde_json::json!({
+            "parsed": {
+                "type": "transfer",
+                "info": { "source": "From111", "destination": "NewAta111", "authority": "Payer111", "amount": "42" }
+            },
+            "program": "spl-token",
+            "programId": TOKEN_PROGRAM
+        });
+        let tx_json = serde_json::json!({ "message": { "accountKeys": [], "instructions": [create_ata, transfer] } });
+        let details = analyze_transaction_with_registry(&program_ids, &test_registry(), Some(&tx_json));
+        assert_eq!(details.tx_type, TransactionType::SplTokenTransfer);
+        assert_eq!(details.amount, Some(42));
+        assert_eq!(details.token_account_owner, None);
+    }
+
+    #[test]
+    fn test_classify_vote() {
+        let vote = vec![VOTE_PROGRAM.to_string()];
+        assert!(is_vote_transaction(&vote));
+        assert_eq!(classify_transaction_with_registry(&vote, &test_registry(), None).primary, TransactionType::Vote);
+        assert_eq!(classify_transaction(&vote), TransactionType::Vote);
+
+        // Anything else alongside the Vote program is not a plain validator vote
+        let vote_with_transfer = vec![VOTE_PROGRAM.to_string(), SYSTEM_PROGRAM.to_string()];
+        assert!(!is_vote_transaction(&vote_with_transfer));
+    }
+
+    #[test]
+    fn test_classify_with_logs() {
+        let logs = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        let swap = logs(&[
+            "Program 9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin invoke [1]",
+            "Program log: Instruction: Swap",
+        ]);
+        assert_eq!(classify_with_logs(TransactionType::ProgramInteraction, &swap), TransactionType::DexSwap);
+        assert_eq!(
+            classify_with_logs(TransactionType::Unknown, &logs(&["Program log: Instruction: MintTo"])),
+            TransactionType::TokenMint
+        );
+        assert_eq!(
+            classify_with_logs(TransactionType::Unknown, &logs(&["Program log: Instruction: BurnChecked"])),
+            TransactionType::TokenBurn
+        );
+
+        // A DEX transaction is narrowed to the liquidity operation a pool logged
+        let increase = logs(&["Program log: Instruction: IncreaseLiquidityV2"]);
+        assert_eq!(classify_with_logs(TransactionType::DexSwap, &increase), TransactionType::LiquidityAdd);
+        assert_eq!(classify_with_logs(TransactionType::DexSwap, &swap), TransactionType::DexSwap);
+
+        // A program ID match is never overridden, and unrelated logs change nothing
+        assert_eq!(classify_with_logs(TransactionType::SolTransfer, &swap), TransactionType::SolTransfer);
+        assert_eq!(
+            classify_with_logs(TransactionType::ProgramInteraction, &logs(&["Program log: Instruction: Deposit"])),
+            TransactionType::ProgramInteraction
+        );
+    }
+
+    #[test]
+    fn test_classify_custom_program_type() {
+        let program = |id: &str, name: &str, program_type: Option<&str>| crate::db::ProgramInfo {
+            program_id: id.to_string(),
+            program_name: name.to_string(),
+            program_type: program_type.map(|t| t.to_string()),
+        };
+        let registry = ProgramRegistry::from_database(vec![
+            program(TOKEN_PROGRAM, "Token Program", Some("Token")),
+            program("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo", "Solend", Some("Lending")),
+            program("Untyped1111111111111111111111111111111111111", "Untyped", None),
+        ]);
+        assert_eq!(registry.custom_type(TOKEN_PROGRAM), None);
+        assert_eq!(registry.custom_type("Untyped1111111111111111111111111111111111111"), None);
+
+        let program_ids = vec![TOKEN_PROGRAM.to_string(), "So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo".to_string()];
+        let details = analyze_transaction_with_registry(&program_ids, &registry, None);
+        assert_eq!(details.tx_type, TransactionType::Custom("Lending".to_string()));
+        assert_eq!(details.label, "Lending (Token Program, Solend)");
+
+        let mut stats = TransactionTypeStats::new();
+        stats.add(&details.tx_type);
+        assert_eq!(stats.custom["Lending"], 1);
+    }
+
+    #[test]
+    fn test_classify_token_mint_and_burn() {
+        let registry = ProgramRegistry::from_database(vec![crate::db::ProgramInfo {
+            program_id: TOKEN_PROGRAM.to_string(),
+            program_name: "Token Program".to_string(),
+            program_type: Some("Token".to_string()),
+        }]);
+        let program_ids = vec![TOKEN_PROGRAM.to_string()];
+        let token_tx = |instruction_type: &str| {
+            serde_json::json!({
+                "message": {
+                    "instructions": [{
+                        "program": "spl-token",
+                        "programId": TOKEN_PROGRAM,
+                        "parsed": {
+                            "type": instruction_type,
+                            "info": { "amount": "10", "mint": "Mint111", "account": "TokenAcct111" }
+                        }
+                    }]
+                }
+            })
+        };
+
+        let mint = token_tx("mintTo");
+        assert_eq!(
+            classify_transaction_with_registry(&program_ids, &registry, Some(&mint)).primary,
+            TransactionType::TokenMint
+        );
+        let details = analyze_transaction_with_registry(&program_ids, &registry, Some(&mint));
+        assert_eq!(details.token_address.as_deref(), Some("Mint111"));
+        assert_eq!(details.to_account.as_deref(), Some("TokenAcct111"));
+
+        let burn = token_tx("burn");
+        assert_eq!(
+            classify_transaction_with_registry(&program_ids, &registry, Some(&burn)).primary,
+            TransactionType::TokenBurn
+        );
+
+        // Without the instruction data a token transaction still counts as a transfer
+        assert_eq!(
+            classify_transaction_with_registry(&program_ids, &registry, None).primary,
+            TransactionType::SplTokenTransfer
+        );
+    }
+
+    #[test]
+    fn test_classify_composite_transaction() {
+        let registry = builtin_registry();
+        // Create the output token account, wrap SOL for the input, swap through Jupiter
+        let program_ids: Vec<String> =
+            [ASSOCIATED_TOKEN_PROGRAM, SYSTEM_PROGRAM, JUPITER_V6].iter().map(|id| id.to_string()).collect();
+        let tx_json = serde_json::json!({
+            "message": {
+                "accountKeys": [],
+                "instructions": [
+                    {
+                        "program": "spl-associated-token-account",
+                        "programId": ASSOCIATED_TOKEN_PROGRAM,
+                        "parsed": {
+                            "type": "createIdempotent",
+                            "info": { "account": "Ata111", "mint": "Mint111", "source": "Payer111", "wallet": "Payer111" }
+                        }
+                    },
+                    {
+                        "program": "system",
+                        "programId": SYSTEM_PROGRAM,
+                        "parsed": {
+                            "type": "transfer",
+                            "info": { "source": "Payer111", "destination": "Wsol111", "lamports": 1000 }
+                        }
+                    },
+                    { "programId": JUPITER_V6, "accounts": [], "data": "" }
+                ]
+            }
+        });
+
+        let classification = classify_transaction_with_registry(&program_ids, &registry, Some(&tx_json));
+        assert_eq!(classification.primary, TransactionType::DexSwap);
+        assert_eq!(
+            classification.secondary_types,
+            vec![TransactionType::AccountCreation, TransactionType::SolTransfer]
+        );
+
+        let details = analyze_transaction_with_registry(&program_ids, &registry, Some(&tx_json));
+        assert_eq!(details.secondary_types, classification.secondary_types);
+
+        // A single-purpose transaction has no secondary types
+        let transfer = vec![SYSTEM_PROGRAM.to_string()];
+        assert!(classify_transaction_with_registry(&transfer, &registry, None).secondary_types.is_empty());
+    }
+
+    #[test]
+    fn test_builtin_programs_classify_like_fallback() {
+        let registry = builtin_registry();
+
+        for program_ids in [
+            vec![SYSTEM_PROGRAM.to_string()],
+            vec![TOKEN_PROGRAM.to_string()],
+            vec![JUPITER_V6.to_string(), TOKEN_PROGRAM.to_string()],
+            vec![METAPLEX_TOKEN_METADATA.to_string()],
+            vec![STAKE_PROGRAM.to_string(), SYSTEM_PROGRAM.to_string()],
+            vec![MARINADE_PROGRAM.to_string(), TOKEN_PROGRAM.to_string(), SYSTEM_PROGRAM.to_string()],
+        ] {
+            assert_eq!(
+                classify_transaction_with_registry(&program_ids, &registry, None).primary,
+                classify_transaction(&program_ids)
+            );
+        }
+    }
+}
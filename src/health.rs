@@ -0,0 +1,173 @@
+/// Health Module
+///
+/// Liveness and readiness checks for container probes (the `healthcheck` subcommand): the RPC
+/// endpoint answers, the database accepts queries, and the chain tip seen through the RPC keeps
+/// advancing. Every probe runs in a fresh process, so the last seen slot and the time it was
+/// first seen are kept in a small state file between runs. Nothing is fetched or written to the
+/// database.
+use crate::{
+    db::Database,
+    output::{self, console},
+    rpc::SolanaRpcClient,
+};
+use anyhow::{Context, Result};
+use std::{path::Path, time::Duration};
+
+/// Outcome of one check: a short description on success, the diagnostic on failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub result: std::result::Result<String, String>,
+}
+
+/// Result of a `healthcheck` run
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Whether every check passed
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.result.is_ok())
+    }
+
+    /// Diagnostics of the failed checks, prefixed with the check name
+    pub fn failures(&self) -> Vec<String> {
+        self.checks
+            .iter()
+            .filter_map(|check| check.result.as_ref().err().map(|error| format!("{}: {}", check.name, error)))
+            .collect()
+    }
+
+    /// Print one line per check
+    pub fn print_summary(&self) {
+        for check in &self.checks {
+            match &check.result {
+                Ok(detail) if output::console_enabled() => console!("✅ {}: {}", check.name, detail),
+                Err(error) if output::console_enabled() => console!("❌ {}: {}", check.name, error),
+                Ok(detail) => tracing::info!(check = check.name, "Healthy: {}", detail),
+                Err(error) => tracing::error!(check = check.name, "Unhealthy: {}", error),
+            }
+        }
+    }
+}
+
+/// Latest slot seen by an earlier check and when it was first seen (Unix seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotProgress {
+    pub slot: u64,
+    pub since: i64,
+}
+
+impl SlotProgress {
+    /// Read the state file, None when it is missing or unreadable (e.g. the first check)
+    fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let (slot, since) = contents.trim().split_once(' ')?;
+        Some(Self { slot: slot.parse().ok()?, since: since.parse().ok()? })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, format!("{} {}\n", self.slot, self.since))
+            .context(format!("Failed to write healthcheck state {}", path.display()))
+    }
+}
+
+/// Compare the latest slot with the one seen by the previous check
+///
+/// The chain is stalled when the slot has not moved for more than `max_stall`; checks run in
+/// quick succession may see the same slot without failing. Returns the progress to remember
+/// and the check outcome.
+fn check_progress(
+    previous: Option<SlotProgress>,
+    latest_slot: u64,
+    now: i64,
+    max_stall: Duration,
+) -> (SlotProgress, std::result::Result<String, String>) {
+    match previous {
+        Some(previous) if latest_slot <= previous.slot => {
+            let stalled_for = now.saturating_sub(previous.since).max(0) as u64;
+            let result = if stalled_for > max_stall.as_secs() {
+                Err(format!(
+                    "latest slot stuck at {} for {}s (limit {}s)",
+                    latest_slot,
+                    stalled_for,
+                    max_stall.as_secs()
+                ))
+            } else {
+                Ok(format!("slot {}, unchanged for {}s", latest_slot, stalled_for))
+            };
+            (previous, result)
+        }
+        Some(previous) => (
+            SlotProgress { slot: latest_slot, since: now },
+            Ok(format!("slot {} (+{} since the last check)", latest_slot, latest_slot - previous.slot)),
+        ),
+        None => (SlotProgress { slot: latest_slot, since: now }, Ok(format!("slot {} (first check)", latest_slot))),
+    }
+}
+
+/// Check RPC and database connectivity and that the latest slot advances
+///
+/// The database is reached with a single connection and no migrations. `state_file` holds the
+/// slot progress between runs; failing to write it is reported as a failed check.
+pub async fn check_health(
+    rpc_client: &SolanaRpcClient,
+    database_url: &str,
+    state_file: &Path,
+    max_stall: Duration,
+) -> HealthReport {
+    let mut report = HealthReport::default();
+
+    let rpc = rpc_client.test_connection().await.map(|_| rpc_client.endpoint().to_string());
+    report.checks.push(HealthCheck { name: "RPC", result: rpc.map_err(|e| e.to_string()) });
+
+    let database = match Database::with_pool_options(database_url, 1, crate::db::DEFAULT_ACQUIRE_TIMEOUT).await {
+        Ok(database) => database.test_connection().await.map(|_| "connected".to_string()),
+        Err(e) => Err(e),
+    };
+    report.checks.push(HealthCheck { name: "Database", result: database.map_err(|e| format!("{:#}", e)) });
+
+    let progress = match rpc_client.get_latest_slot().await {
+        Ok(latest_slot) => {
+            let (progress, result) =
+                check_progress(SlotProgress::load(state_file), latest_slot, chrono::Utc::now().timestamp(), max_stall);
+            match progress.save(state_file) {
+                Ok(()) => result,
+                Err(e) => Err(format!("{:#}", e)),
+            }
+        }
+        Err(e) => Err(e.to_string()),
+    };
+    report.checks.push(HealthCheck { name: "Slot progress", result: progress });
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_progress() {
+        let max_stall = Duration::from_secs(60);
+
+        let (first, result) = check_progress(None, 100, 1_000, max_stall);
+        assert_eq!(first, SlotProgress { slot: 100, since: 1_000 });
+        assert!(result.is_ok());
+
+        // Advancing resets the timer
+        let (advanced, result) = check_progress(Some(first), 150, 1_010, max_stall);
+        assert_eq!(advanced, SlotProgress { slot: 150, since: 1_010 });
+        assert!(result.is_ok());
+
+        // The same slot is fine for a while, and keeps the time it was first seen
+        let (unchanged, result) = check_progress(Some(advanced), 150, 1_050, max_stall);
+        assert_eq!(unchanged, advanced);
+        assert!(result.is_ok());
+
+        let (_, result) = check_progress(Some(advanced), 150, 1_100, max_stall);
+        assert!(result.unwrap_err().contains("stuck at 150 for 90s"));
+    }
+}
@@ -0,0 +1,161 @@
+/// Inspect Module
+///
+/// Re-fetches a single transaction by signature (the `inspect` subcommand), e.g. to look at a
+/// suspicious row of the database again without knowing its slot. The transaction goes through
+/// the same parsing and classification as in the pipeline; nothing is written to the database.
+use crate::{
+    etl::{
+        extract::{extract_transaction, FetchedTransaction},
+        transform::{Classifier, ProgramRegistry, TransactionDetails},
+    },
+    models::block_time_to_utc,
+    output::{self, console},
+    rpc::SolanaRpcClient,
+};
+use anyhow::{Context, Result};
+
+/// Lamports per SOL, for printing balance changes
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// A transaction fetched by signature, with its classification and extracted details
+#[derive(Debug, Clone)]
+pub struct TransactionInspection {
+    pub fetched: FetchedTransaction,
+    pub details: TransactionDetails,
+}
+
+/// Fetch, parse and classify the transaction with `signature`
+///
+/// Fails with a clear error when the RPC node does not have the transaction (unconfirmed, or
+/// older than the node's ledger retention).
+pub async fn inspect_transaction(
+    rpc_client: &SolanaRpcClient,
+    signature: &str,
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+) -> Result<TransactionInspection> {
+    let fetched = extract_transaction(rpc_client, signature)
+        .await
+        .context(format!("Failed to fetch transaction {}", signature))?;
+    let details = fetched.transaction.analyze_with(classifier, registry);
+
+    Ok(TransactionInspection { fetched, details })
+}
+
+impl TransactionInspection {
+    /// Print the transaction: block, status, fees, classification and parsed transfers
+    pub fn print_details(&self, registry: &ProgramRegistry) {
+        let tx = &self.fetched.transaction;
+        if !output::console_enabled() {
+            tracing::info!(
+                signature = %tx.signature,
+                slot = self.fetched.slot,
+                success = tx.success,
+                error_reason = tx.error_reason.as_deref(),
+                max_cpi_depth = tx.max_cpi_depth,
+                fee = tx.fee,
+                transaction_type = self.details.tx_type.as_str(),
+                label = %self.details.label,
+                "Transaction details"
+            );
+            return;
+        }
+
+        console!("\n🔎 Transaction {}", tx.signature);
+        let block_time = block_time_to_utc(self.fetched.block_time);
+        match block_time {
+            Some(time) => console!("   📍 Slot: {} ({})", self.fetched.slot, time.format("%Y-%m-%d %H:%M:%S UTC")),
+            None => console!("   📍 Slot: {}", self.fetched.slot),
+        }
+        console!(
+            "   {} Status: {}",
+            if tx.success { "✅" } else { "❌" },
+            if tx.success { "success" } else { "failed" }
+        );
+        if let Some(reason) = &tx.error_reason {
+            console!("   ⚠️  Error: {}", reason);
+        }
+        console!("   🏷️  Type: {}", self.details.label);
+        if !self.details.secondary_types.is_empty() {
+            let secondary: Vec<&str> = self.details.secondary_types.iter().map(|t| t.as_str()).collect();
+            console!("   🏷️  Also: {}", secondary.join(", "));
+        }
+        match tx.priority_fee {
+            Some(priority_fee) => console!("   💸 Fee: {} lamports ({} priority)", tx.fee, priority_fee),
+            None => console!("   💸 Fee: {} lamports", tx.fee),
+        }
+        if let Some(compute_units) = tx.compute_units {
+            console!("   ⚙️  Compute units: {}", compute_units);
+        }
+        if let Some(depth) = tx.max_cpi_depth.filter(|&depth| depth > 0) {
+            console!("   🪆 CPI depth: {}", depth);
+        }
+
+        console!("   🧩 Programs:");
+        for program_id in &tx.program_ids {
+            let inner = if tx.inner_program_ids.contains(program_id) { " (CPI)" } else { "" };
+            match registry.get_program_name(program_id) {
+                Some(name) => console!("      {} - {}{}", program_id, name, inner),
+                None => console!("      {}{}", program_id, inner),
+            }
+        }
+
+        if let (Some(from), Some(to)) = (&self.details.from_account, &self.details.to_account) {
+            console!("   ↔️  {} → {}", from, to);
+        }
+        match (&self.details.token_address, self.details.amount) {
+            (Some(mint), Some(amount)) => match self.details.ui_amount {
+                Some(ui_amount) => console!("   💰 Amount: {} ({} base units) of mint {}", ui_amount, amount, mint),
+                None => console!("   💰 Amount: {} base units of mint {}", amount, mint),
+            },
+            (None, Some(lamports)) => console!("   💰 Amount: {} SOL", lamports as f64 / LAMPORTS_PER_SOL),
+            _ => {}
+        }
+        if let Some(fee) = self.details.transfer_fee {
+            console!("   🧾 Transfer fee withheld: {} base units", fee);
+        }
+        if let Some(swap) = &tx.jupiter_swap {
+            console!(
+                "   🔁 Swap: {} of {} → {} of {}{}",
+                swap.in_amount,
+                swap.input_mint,
+                swap.out_amount,
+                swap.output_mint,
+                if swap.inferred { " (inferred from balances)" } else { "" }
+            );
+        }
+        if let Some(staking) = &tx.liquid_staking {
+            let sol = staking.sol_amount.map(|lamports| format!("{} SOL", lamports as f64 / LAMPORTS_PER_SOL));
+            let lst = staking
+                .lst_amount
+                .zip(staking.lst_mint.as_ref())
+                .map(|(amount, mint)| format!("{} of {}", amount, mint));
+            let amounts: Vec<String> = [sol, lst].into_iter().flatten().collect();
+            console!("   🌊 Liquid staking {}: {}", staking.action.as_str(), amounts.join(" ↔ "));
+        }
+
+        let sol_changes: Vec<&(String, i64)> = tx.balance_changes.iter().filter(|(_, delta)| *delta != 0).collect();
+        if !sol_changes.is_empty() {
+            console!("   ◎ SOL balance changes:");
+            for (account, delta) in sol_changes {
+                console!("      {}: {:+.9} SOL", account, *delta as f64 / LAMPORTS_PER_SOL);
+            }
+        }
+        if !tx.token_balance_changes.is_empty() {
+            console!("   🪙 Token balance changes:");
+            for change in &tx.token_balance_changes {
+                let delta = change.amount_delta as f64 / 10f64.powi(change.decimals as i32);
+                console!("      {}: {:+} of mint {}", change.owner, delta, change.mint);
+            }
+        }
+
+        if let Some(memo) = &self.details.memo {
+            console!("   📝 Memo: {}", memo);
+        }
+        console!(
+            "   📜 {} log lines{}",
+            tx.log_messages.len(),
+            if tx.logs_truncated { " (truncated by the RPC)" } else { "" }
+        );
+    }
+}
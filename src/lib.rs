@@ -0,0 +1,39 @@
+/// Solana Block Data Fetcher
+///
+/// An ETL pipeline for extracting, transforming, and loading Solana blockchain data.
+/// The `block-data-fetcher` binary is a thin CLI on top of this library; other crates can
+/// drive the same pipeline, or use the RPC client and extract functions on their own.
+///
+/// Decorated console output (progress lines and summaries) is off by default, so library
+/// users only get `tracing` events. Call `output::set_console_enabled(true)` to turn it on.
+pub mod db;
+pub mod error;
+pub mod etl;
+pub mod health;
+pub mod inspect;
+pub mod metrics;
+pub mod models;
+pub mod output;
+pub mod pipeline;
+pub mod reclassify;
+pub mod rpc;
+pub mod validate;
+
+pub use db::Database;
+pub use error::FetcherError;
+pub use etl::{
+    extract::{
+        extract_block, extract_block_range, extract_block_range_concurrent, extract_transaction,
+        extract_transactions_for_address, subscribe_extracted_blocks, ExtractedBlock, ExtractedTransaction,
+        ExtractionStats, Sampler, StatusFilter,
+    },
+    source::BlockSource,
+    transform::{Classifier, DefaultClassifier, ProgramRegistry},
+};
+pub use health::{check_health, HealthReport};
+pub use inspect::{inspect_transaction, TransactionInspection};
+pub use models::{BlockSummary, TransactionType};
+pub use pipeline::{Pipeline, PipelineConfig, PipelineStats};
+pub use reclassify::{reclassify_transactions, ReclassifyReport};
+pub use rpc::{cache::BlockCache, transport::HttpConfig, SolanaRpcClient};
+pub use validate::{validate_blocks, ValidationReport};
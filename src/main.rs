@@ -1,45 +1,125 @@
 /// Solana Block Data Fetcher
 ///
-/// An ETL pipeline for extracting, transforming, and loading Solana blockchain data.
+/// Command-line entry point of the ETL pipeline. The pipeline itself lives in the library
+/// crate; this binary parses arguments, prints the decorated console output and runs it.
 mod cli;
-mod db;
-mod etl;
-mod models;
-mod pipeline;
-mod rpc;
+mod config;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use block_data_fetcher::{
+    etl, metrics,
+    output::{self, console},
+    pipeline, BlockCache, Database, HttpConfig, SolanaRpcClient,
+};
+use clap::{CommandFactory, FromArgMatches};
 use cli::Cli;
-use db::Database;
-use rpc::SolanaRpcClient;
-use std::env;
+use std::{env, path::PathBuf};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables from .env file
     dotenv::dotenv().ok();
 
+    // Parse CLI arguments, filling in the options not passed as flags from the config file
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Some(path) = cli.config.clone() {
+        let config = config::ConfigFile::load(&path)?;
+        cli.merge_config(config, &matches);
+    }
+
+    // NDJSON mode keeps stdout for records: no decorated output, logs go to stderr.
+    // Quiet mode and JSON logs report progress through tracing events instead of decorated lines.
+    let ndjson = cli.format == cli::OutputFormat::Ndjson;
+    let json_logs = cli.log_format == cli::LogFormat::Json;
+    output::set_console_enabled(!ndjson && !cli.quiet && !json_logs);
+    output::set_progress_bar(cli.progress_bar);
+
     // Initialize logging
-    tracing_subscriber::fmt()
+    let writer = if ndjson { BoxMakeWriter::new(std::io::stderr) } else { BoxMakeWriter::new(std::io::stdout) };
+    let logging = tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
-        .init();
+        .with_writer(writer);
+    if json_logs {
+        logging.json().init();
+    } else {
+        logging.init();
+    }
 
-    // Parse CLI arguments
-    let cli = Cli::parse();
     cli.validate().context("Invalid CLI arguments")?;
 
-    println!("🚀 Starting Solana Block Fetcher...");
+    if let Some(cli::Command::SeedRegistry) = cli.command {
+        let database = connect_database(&cli).await?;
 
-    // Get RPC URL from CLI or environment
-    let rpc_url = cli
-        .rpc_url
-        .clone()
-        .or_else(|| env::var("HELIUS_RPC_URL").ok())
-        .context("RPC URL not provided. Use --rpc-url or set HELIUS_RPC_URL environment variable")?;
+        console!("🌱 Seeding program registry...");
+        let seeded = database.seed_program_registry().await.context("Failed to seed program registry")?;
+        console!("✅ {} of {} built-in programs added or updated", seeded, etl::transform::BUILTIN_PROGRAMS.len());
+        return Ok(());
+    }
+
+    // Status mode: totals of the stored data, no RPC needed
+    if let Some(cli::Command::Status) = cli.command {
+        let database = connect_database(&cli).await?;
+        let stats = database.stats().await.context("Failed to query database stats")?;
+        print_db_stats(&stats);
+        return Ok(());
+    }
+
+    // Reclassify mode: re-label stored transactions from their raw data, no RPC needed
+    if let Some(cli::Command::Reclassify { start, end }) = cli.command {
+        let database = connect_database(&cli).await?;
+        let program_infos = database.load_program_registry().await.context("Failed to load program registry")?;
+        let program_registry = etl::transform::ProgramRegistry::from_database(program_infos);
+
+        let report = block_data_fetcher::reclassify_transactions(
+            &database,
+            &program_registry,
+            &block_data_fetcher::DefaultClassifier,
+            start,
+            end,
+        )
+        .await
+        .context("Reclassification failed")?;
+        report.print_summary();
+        return Ok(());
+    }
+
+    // Healthcheck mode: connectivity and chain progress only, no ETL
+    if let Some(cli::Command::Healthcheck { state_file, max_stall }) = &cli.command {
+        let rpc_client = rpc_client(&cli)?;
+        let report = block_data_fetcher::health::check_health(
+            &rpc_client,
+            &database_url(&cli)?,
+            state_file,
+            std::time::Duration::from_secs(*max_stall),
+        )
+        .await;
+        report.print_summary();
+
+        if !report.is_healthy() {
+            anyhow::bail!("Unhealthy: {}", report.failures().join("; "));
+        }
+        return Ok(());
+    }
+
+    console!("🚀 Starting Solana Block Fetcher...");
+
+    if let Some(port) = cli.metrics_port {
+        let addr = metrics::spawn_server(port).await?;
+        console!("📈 Prometheus metrics on http://{}/metrics", addr);
+    }
 
     // Initialize RPC client
-    let rpc_client = SolanaRpcClient::new(rpc_url).context("Failed to create RPC client")?;
+    let mut rpc_client = rpc_client(&cli)?;
+
+    // Block cache from CLI or environment, unless disabled
+    let cache_dir = cli.cache_dir.clone().or_else(|| env::var("BLOCK_CACHE_DIR").ok().map(PathBuf::from));
+    if let Some(dir) = cache_dir.filter(|_| !cli.no_cache) {
+        let cache = BlockCache::open(&dir).context("Failed to open block cache")?;
+        console!("🗄️  Block cache: {}", dir.display());
+        rpc_client = rpc_client.with_block_cache(cache);
+    }
 
     // Test RPC connection
     rpc_client.test_connection().await.context("Failed to connect to Solana RPC")?;
@@ -47,109 +127,331 @@ async fn main() -> Result<()> {
     // Get and display connection info
     let conn_info = rpc_client.get_connection_info().await.context("Failed to get connection info")?;
 
-    println!("✅ Connected to: {}", conn_info.endpoint);
-    println!(
+    console!("✅ Connected to: {}", conn_info.endpoint);
+    if rpc_client.endpoints().len() > 1 {
+        console!("🔀 Failover endpoints: {}", rpc_client.endpoints()[1..].join(", "));
+    }
+    if !rpc_client.commitment().is_finalized() {
+        console!(
+            "⚠️  Using {:?} commitment: loaded blocks may still be rolled back by a fork",
+            rpc_client.commitment().commitment
+        );
+    }
+    console!(
         "📦 Latest Blockhash: {}...{}",
         &conn_info.blockhash[..7],
         &conn_info.blockhash[conn_info.blockhash.len() - 3..]
     );
-    println!("🎯 Current Slot: {:?}", format_number(conn_info.slot));
-    println!("⏰ Timestamp: {}", conn_info.timestamp.format("%Y-%m-%d %H:%M:%S UTC"));
+    console!("🎯 Current Slot: {:?}", format_number(conn_info.slot));
+    console!("🧱 Block Height: {:?}", format_number(conn_info.block_height));
+    console!("⏰ Timestamp: {}", conn_info.timestamp.format("%Y-%m-%d %H:%M:%S UTC"));
 
     // Initialize database connection
-    let database_url = cli
-        .database_url
-        .clone()
-        .or_else(|| env::var("DATABASE_URL").ok())
-        .context("Database URL not provided. Use --database-url or set DATABASE_URL environment variable")?;
+    let database = connect_database(&cli).await?;
 
-    println!("\n💾 Connecting to PostgreSQL database...");
-    let database = Database::new(&database_url).await.context("Failed to connect to PostgreSQL database")?;
+    // Load program registry from database
+    console!("📚 Loading program registry from database...");
+    let program_infos = database.load_program_registry().await.context("Failed to load program registry")?;
+    let program_registry = etl::transform::ProgramRegistry::from_database(program_infos);
+    console!("✅ Loaded {} programs from registry", program_registry.programs.len());
 
-    // Test database connection
-    database.test_connection().await.context("Database connection test failed")?;
+    tracing::info!("Solana Block Fetcher initialized successfully");
 
-    println!("✅ Database connected successfully!");
+    // Backfill mode: only the slots missing from the blocks table
+    if let Some(cli::Command::Backfill { start, end }) = cli.command {
+        let database_for_heights = database.clone();
+        let pipeline =
+            pipeline::Pipeline::new(rpc_client, database, program_registry, pipeline_config(&cli, start, end));
+        pipeline.run_backfill(start, end).await.context("Backfill failed")?;
 
-    // Run database migrations
-    println!("📋 Running database migrations...");
-    database.migrate().await.context("Failed to run database migrations")?;
+        if !cli.dry_run {
+            let filled = database_for_heights.backfill_block_heights().await?;
+            console!("🧱 Filled in {} missing block heights", format_number(filled));
+        }
 
-    println!("✅ Database schema created successfully!");
+        console!("\n✨ Pipeline execution complete!");
+        return Ok(());
+    }
 
-    // Load program registry from database
-    println!("📚 Loading program registry from database...");
-    let program_infos = database.load_program_registry().await.context("Failed to load program registry")?;
-    let program_registry = etl::transform::ProgramRegistry::from_database(program_infos);
-    println!("✅ Loaded {} programs from registry", program_registry.programs.len());
+    // Retry mode: the slots that exhausted their retries in earlier runs
+    if let Some(cli::Command::RetryFailed) = cli.command {
+        let pipeline = pipeline::Pipeline::new(rpc_client, database, program_registry, pipeline_config(&cli, 0, 0));
+        pipeline.run_retry_failed().await.context("Retrying failed slots failed")?;
 
-    tracing::info!("Solana Block Fetcher initialized successfully");
+        console!("\n✨ Pipeline execution complete!");
+        return Ok(());
+    }
+
+    // Validate mode: compare stored blocks with the chain, failing on any mismatch
+    if let Some(cli::Command::Validate { start, end, sample }) = cli.command {
+        let report = block_data_fetcher::validate_blocks(&rpc_client, &database, start, end, sample, cli.concurrency)
+            .await
+            .context("Validation failed")?;
+        report.print_summary();
+
+        if !report.is_valid() {
+            anyhow::bail!(
+                "{} stored blocks do not match the chain, {} could not be checked",
+                report.mismatched.len(),
+                report.errors.len()
+            );
+        }
+        console!("\n✨ Stored blocks match the chain!");
+        return Ok(());
+    }
+
+    // Inspect mode: one transaction by signature, printed instead of stored
+    if let Some(cli::Command::Inspect { signature }) = &cli.command {
+        let inspection = block_data_fetcher::inspect_transaction(
+            &rpc_client,
+            signature,
+            &program_registry,
+            &block_data_fetcher::DefaultClassifier,
+        )
+        .await?;
+        inspection.print_details(&program_registry);
+        return Ok(());
+    }
+
+    // Address mode: only the transactions involving one account, optionally bounded by slot
+    if let Some(address) = &cli.address {
+        let end_slot = match (cli.start_slot, cli.end_slot.is_some() || cli.num_blocks.is_some()) {
+            (Some(start), true) => cli.calculate_end_slot(start),
+            _ => cli.end_slot.unwrap_or(0),
+        };
+        let pipeline_config = pipeline_config(&cli, cli.start_slot.unwrap_or(0), end_slot);
+        let pipeline = pipeline::Pipeline::new(rpc_client, database, program_registry, pipeline_config);
+
+        pipeline.run_address(address, cli.address_limit).await.context("Address extraction failed")?;
+
+        console!("\n✨ Pipeline execution complete!");
+        return Ok(());
+    }
 
     // ========== PHASE 6: Run ETL Pipeline ==========
-    println!("\n🔍 Determining block range...");
+    console!("\n🔍 Determining block range...");
     let latest_slot = rpc_client.get_latest_slot().await.context("Failed to get latest slot")?;
 
-    // Calculate start and end slots based on CLI arguments
-    let start_slot = cli.start_slot.unwrap_or(latest_slot - 30);
-    let end_slot = if cli.end_slot.is_some() || cli.num_blocks.is_some() {
+    // Calculate start and end slots based on CLI arguments, resolving timestamps to slots first
+    let start_from_time = match cli.start_time {
+        Some(time) => Some(resolve_slot_at_time(&rpc_client, time).await?),
+        None => None,
+    };
+    let mut start_slot = cli.start_slot.or(start_from_time).unwrap_or_else(|| cli.default_start_slot(latest_slot));
+    let end_slot = if let Some(time) = cli.end_time {
+        resolve_slot_at_time(&rpc_client, time).await?
+    } else if cli.end_slot.is_some() || cli.num_blocks.is_some() {
         cli.calculate_end_slot(start_slot)
     } else {
-        latest_slot - 20 // Default: recent finalized blocks
+        cli.default_end_slot(latest_slot) // Default: recent finalized blocks
     };
 
-    println!("📍 Latest confirmed slot: {}", format_number(latest_slot));
-    println!(
+    // Resume after the last committed batch of this job
+    if cli.resume {
+        match database.load_checkpoint(&cli.job_name).await.context("Failed to load checkpoint")? {
+            Some(checkpoint) => {
+                console!("⏯️  Resuming job '{}' from checkpoint slot {}", cli.job_name, format_number(checkpoint));
+                start_slot = checkpoint + 1;
+            }
+            None => console!("⏯️  No checkpoint found for job '{}', starting fresh", cli.job_name),
+        }
+
+        if start_slot > end_slot && !cli.continuous {
+            console!("\n✨ Job '{}' already completed up to slot {}", cli.job_name, format_number(end_slot));
+            return Ok(());
+        }
+    }
+
+    console!("📍 Latest confirmed slot: {}", format_number(latest_slot));
+    console!(
         "📊 Processing range: {} to {} ({} blocks)",
         format_number(start_slot),
         format_number(end_slot),
-        end_slot - start_slot + 1
+        (end_slot + 1).saturating_sub(start_slot)
     );
 
     // Configure and run the pipeline
-    let pipeline_config = pipeline::PipelineConfig {
+    let pipeline_config = pipeline_config(&cli, start_slot, end_slot);
+    let pipeline = pipeline::Pipeline::new(rpc_client, database, program_registry, pipeline_config);
+
+    // Run the pipeline with error handling and retry logic.
+    // Continuous mode keeps processing newly finalized blocks until Ctrl+C.
+    let _pipeline_stats = if cli.subscribe {
+        pipeline.run_subscribed(cli.ws_url.as_deref()).await
+    } else if cli.continuous {
+        pipeline.run_continuous(std::time::Duration::from_secs(cli.interval)).await
+    } else {
+        pipeline.run().await
+    }
+    .context("Pipeline execution failed")?;
+
+    console!("\n✨ Pipeline execution complete!");
+
+    Ok(())
+}
+
+/// Resolve a `--start-time`/`--end-time` timestamp to a slot
+async fn resolve_slot_at_time(rpc_client: &SolanaRpcClient, time: chrono::DateTime<chrono::Utc>) -> Result<u64> {
+    let slot = rpc_client.get_slot_at_time(time).await.context(format!("Failed to find the slot at {}", time))?;
+    console!("🕒 {} resolves to slot {}", time.format("%Y-%m-%d %H:%M:%S UTC"), format_number(slot));
+    Ok(slot)
+}
+
+/// Pipeline configuration from the CLI arguments for a slot range
+fn pipeline_config(cli: &Cli, start_slot: u64, end_slot: u64) -> pipeline::PipelineConfig {
+    pipeline::PipelineConfig {
         start_slot,
         end_slot,
         max_retries: cli.max_retries,
         retry_delay: std::time::Duration::from_secs(cli.retry_delay),
         batch_size: cli.batch_size,
-    };
+        concurrency: cli.concurrency,
+        job_name: cli.job_name.clone(),
+        skip_votes: cli.skip_votes,
+        status_filter: if cli.only_success {
+            Some(etl::extract::StatusFilter::Successful)
+        } else if cli.only_failed {
+            Some(etl::extract::StatusFilter::Failed)
+        } else {
+            None
+        },
+        sampler: cli.sample_rate.map(|rate| etl::extract::Sampler::new(rate, cli.sample_seed)),
+        program_filter: (!cli.programs.is_empty()).then(|| cli.programs.iter().cloned().collect()),
+        max_transactions_per_block: cli.max_txs_per_block.map(|max_transactions| etl::extract::TransactionLimit {
+            max_transactions,
+            truncate: cli.truncate_large_blocks,
+        }),
+        skip_existing: cli.skip_existing,
+        verify_tx_count: cli.verify_tx_count,
+        bulk_load: cli.bulk_load,
+        signatures_only: cli.signatures_only,
+        registry_reload_batches: cli.registry_reload_batches,
+        ndjson: cli.format == cli::OutputFormat::Ndjson,
+        dry_run: cli.dry_run,
+        finality_offset_slots: cli.finality_offset,
+        reorg_overlap_slots: cli.reorg_overlap,
+        dedup_cache_size: cli.dedup_cache_size,
+        stats_out: cli.stats_out.clone(),
+        adaptive_batching: cli.adaptive_batching.then(|| pipeline::AdaptiveBatching {
+            target_commit: std::time::Duration::from_secs_f64(cli.target_commit_secs),
+            min_batch_size: cli.min_batch_size,
+            max_batch_size: cli.max_batch_size,
+        }),
+        export: cli.export.map(|format| match format {
+            cli::ExportFormat::Parquet => etl::export::ExportTarget::Parquet { output_dir: cli.output_dir.clone() },
+            cli::ExportFormat::Csv => etl::export::ExportTarget::Csv {
+                path: cli.output.clone().expect("validated: csv export requires --output"),
+            },
+        }),
+    }
+}
 
-    let pipeline = pipeline::Pipeline::new(rpc_client.clone(), database, program_registry, pipeline_config);
+/// Create the RPC client from the CLI or environment; the single URL is the primary endpoint
+fn rpc_client(cli: &Cli) -> Result<SolanaRpcClient> {
+    let mut rpc_urls: Vec<String> =
+        cli.rpc_url.clone().or_else(|| env::var("HELIUS_RPC_URL").ok()).into_iter().collect();
+    rpc_urls.extend(cli.rpc_urls.iter().filter(|url| !url.is_empty()).cloned());
+    if rpc_urls.is_empty() {
+        anyhow::bail!("RPC URL not provided. Use --rpc-url, --rpc-urls or set HELIUS_RPC_URL environment variable");
+    }
 
-    // Run the pipeline with error handling and retry logic
-    let _pipeline_stats = pipeline.run().await.context("Pipeline execution failed")?;
+    let http_config =
+        HttpConfig { timeout: std::time::Duration::from_secs(cli.http_timeout), pool_size: cli.http_pool_size };
+    Ok(SolanaRpcClient::with_http_config(rpc_urls, http_config)
+        .context("Failed to create RPC client")?
+        .with_commitment(cli.commitment.into())
+        .with_batch_size(cli.rpc_batch_size)
+        .with_rate_limit(cli.rps.unwrap_or(0))
+        .with_owner_resolution(cli.resolve_owners)
+        .with_signatures_only(cli.signatures_only))
+}
 
-    // Continuous mode: keep processing latest blocks
-    if cli.continuous {
-        println!("\n🔄 Entering continuous mode (processing every {} seconds)", cli.interval);
-        println!("   Press Ctrl+C to stop...\n");
+/// Database URL from the CLI or environment
+fn database_url(cli: &Cli) -> Result<String> {
+    cli.database_url
+        .clone()
+        .or_else(|| env::var("DATABASE_URL").ok())
+        .context("Database URL not provided. Use --database-url or set DATABASE_URL environment variable")
+}
 
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(cli.interval)).await;
+/// How loads store the raw transaction JSON (`--compress-raw`, `--no-raw-json`)
+fn raw_storage(cli: &Cli) -> etl::load::RawDataStorage {
+    if cli.no_raw_json {
+        etl::load::RawDataStorage::Omitted
+    } else if cli.compress_raw {
+        etl::load::RawDataStorage::Compressed
+    } else {
+        etl::load::RawDataStorage::Json
+    }
+}
 
-            let latest_slot = match rpc_client.get_latest_slot().await {
-                Ok(slot) => slot,
-                Err(e) => {
-                    tracing::warn!("Failed to get latest slot: {}", e);
-                    continue;
-                }
-            };
+/// Connect to PostgreSQL and bring the schema up to date
+async fn connect_database(cli: &Cli) -> Result<Database> {
+    let database_url = database_url(cli)?;
+
+    console!("\n💾 Connecting to PostgreSQL database...");
+    let database = Database::with_pool_options(
+        &database_url,
+        cli.db_max_connections,
+        std::time::Duration::from_secs(cli.db_acquire_timeout),
+    )
+    .await
+    .context("Failed to connect to PostgreSQL database")?
+    .with_partition_size(cli.partition_size)
+    .with_on_conflict(cli.on_conflict.into())
+    .with_raw_storage(raw_storage(cli))
+    .with_commit_every(cli.commit_every);
 
-            // Process the most recent finalized blocks
-            let end_slot = latest_slot - 20;
-            let start_slot = end_slot - (cli.num_blocks.unwrap_or(10) - 1);
+    // Test database connection
+    database.test_connection().await.context("Database connection test failed")?;
 
-            println!("🔄 Processing slots {} to {}", format_number(start_slot), format_number(end_slot));
+    console!("✅ Database connected successfully!");
 
-            // Note: In continuous mode, we need to recreate the pipeline with new config
-            // This is a simplified version - production would need better state management
-            tracing::info!("Continuous mode: processing slots {} to {}", start_slot, end_slot);
-        }
-    }
+    // Run database migrations
+    console!("📋 Running database migrations...");
+    database.migrate().await.context("Failed to run database migrations")?;
 
-    println!("\n✨ Pipeline execution complete!");
+    console!("✅ Database schema created successfully!");
 
-    Ok(())
+    Ok(database)
+}
+
+/// Print the `status` report, or log it when console output is off
+fn print_db_stats(stats: &block_data_fetcher::models::DbStats) {
+    if !output::console_enabled() {
+        tracing::info!(
+            total_blocks = stats.total_blocks,
+            total_transactions = stats.total_transactions,
+            min_slot = stats.min_slot,
+            max_slot = stats.max_slot,
+            skipped_slots = stats.skipped_slots,
+            unaccounted_slots = stats.unaccounted_slots(),
+            "Database status"
+        );
+        return;
+    }
+
+    console!("\n🗃️  Database Status:");
+    console!("   📦 Blocks: {}", format_number(stats.total_blocks));
+    console!("   🧾 Transactions: {}", format_number(stats.total_transactions));
+    if let (Some(min), Some(max)) = (stats.min_slot, stats.max_slot) {
+        console!(
+            "   📍 Slot range: {} to {} ({} slots)",
+            format_number(min),
+            format_number(max),
+            format_number(max - min + 1)
+        );
+        console!("   ⏭️  Skipped by leader: {}", format_number(stats.skipped_slots));
+        console!("   🕳️  Not stored: {}", format_number(stats.unaccounted_slots()));
+    }
+    if !stats.by_type.is_empty() {
+        console!("   📊 Transactions by type:");
+        let mut by_type: Vec<(&String, &u64)> = stats.by_type.iter().collect();
+        by_type.sort_by(|a, b| b.1.cmp(a.1));
+        for (transaction_type, count) in by_type {
+            console!("      {}: {}", transaction_type, format_number(*count));
+        }
+    }
 }
 
 /// Format a number with thousand separators
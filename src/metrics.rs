@@ -0,0 +1,214 @@
+/// Metrics Module
+///
+/// Process-wide pipeline counters exposed in the Prometheus text format over HTTP
+/// (`--metrics-port`). Counters are plain atomics, so recording them is cheap even
+/// when no metrics server is running.
+use anyhow::{Context, Result};
+use http_body_util::Full;
+use hyper::{body::Bytes, server::conn::http1, service::service_fn, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::{
+    convert::Infallible,
+    fmt::Write,
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::net::TcpListener;
+
+/// Upper bounds (seconds) of the RPC request duration histogram buckets
+const RPC_DURATION_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Metrics shared by the whole process
+pub static METRICS: Metrics = Metrics::new();
+
+/// Pipeline counters and gauges
+pub struct Metrics {
+    blocks_fetched: AtomicU64,
+    blocks_failed: AtomicU64,
+    transactions_inserted: AtomicU64,
+    current_slot: AtomicU64,
+    blocks_per_second: AtomicU64, // f64 bits
+    rpc_duration: Histogram,
+}
+
+/// Cumulative histogram with fixed buckets
+struct Histogram {
+    buckets: [AtomicU64; RPC_DURATION_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            blocks_fetched: AtomicU64::new(0),
+            blocks_failed: AtomicU64::new(0),
+            transactions_inserted: AtomicU64::new(0),
+            current_slot: AtomicU64::new(0),
+            blocks_per_second: AtomicU64::new(0),
+            rpc_duration: Histogram {
+                buckets: [const { AtomicU64::new(0) }; RPC_DURATION_BUCKETS.len()],
+                count: AtomicU64::new(0),
+                sum_micros: AtomicU64::new(0),
+            },
+        }
+    }
+
+    /// Record a fetched block and advance the current slot gauge
+    pub fn record_block_fetched(&self, slot: u64) {
+        self.blocks_fetched.fetch_add(1, Ordering::Relaxed);
+        self.current_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub fn record_block_failed(&self) {
+        self.blocks_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_transactions_inserted(&self, count: usize) {
+        self.transactions_inserted.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_blocks_per_second(&self, rate: f64) {
+        self.blocks_per_second.store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Record the duration of one RPC request
+    pub fn observe_rpc_duration(&self, duration: Duration) {
+        let histogram = &self.rpc_duration;
+        let secs = duration.as_secs_f64();
+        for (bound, bucket) in RPC_DURATION_BUCKETS.iter().zip(&histogram.buckets) {
+            if secs <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        histogram.count.fetch_add(1, Ordering::Relaxed);
+        histogram.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counter = |out: &mut String, name: &str, help: &str, value: u64| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}");
+        };
+
+        counter(
+            &mut out,
+            "blocks_fetched_total",
+            "Blocks fetched and parsed",
+            self.blocks_fetched.load(Ordering::Relaxed),
+        );
+        counter(
+            &mut out,
+            "blocks_failed_total",
+            "Blocks that failed to fetch",
+            self.blocks_failed.load(Ordering::Relaxed),
+        );
+        counter(
+            &mut out,
+            "transactions_inserted_total",
+            "Transactions written to the database",
+            self.transactions_inserted.load(Ordering::Relaxed),
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP current_slot Highest slot fetched\n# TYPE current_slot gauge\ncurrent_slot {}",
+            self.current_slot.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP blocks_per_second Fetch rate of the last extracted range\n# TYPE blocks_per_second gauge\nblocks_per_second {}",
+            f64::from_bits(self.blocks_per_second.load(Ordering::Relaxed))
+        );
+
+        let histogram = &self.rpc_duration;
+        let count = histogram.count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "# HELP rpc_request_duration_seconds RPC request latency\n# TYPE rpc_request_duration_seconds histogram"
+        );
+        for (bound, bucket) in RPC_DURATION_BUCKETS.iter().zip(&histogram.buckets) {
+            let _ = writeln!(
+                out,
+                "rpc_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let _ = writeln!(out, "rpc_request_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+        let _ = writeln!(
+            out,
+            "rpc_request_duration_seconds_sum {}",
+            histogram.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(out, "rpc_request_duration_seconds_count {}", count);
+
+        out
+    }
+}
+
+/// Bind the metrics port and serve `/metrics` from a background task
+///
+/// Binding happens before returning so a port that is already in use fails startup.
+pub async fn spawn_server(port: u16) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .await
+        .context(format!("Failed to bind metrics server on port {}", port))?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Metrics server failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+
+            tokio::spawn(async move {
+                if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service_fn(handle)).await {
+                    tracing::debug!("Metrics connection error: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(addr)
+}
+
+async fn handle(request: Request<hyper::body::Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+    let response = if request.uri().path() == "/metrics" {
+        Response::builder()
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(Full::new(Bytes::from(METRICS.render())))
+    } else {
+        Response::builder().status(StatusCode::NOT_FOUND).body(Full::new(Bytes::from_static(b"Not Found\n")))
+    };
+
+    Ok(response.expect("static response parts are valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_histogram_is_cumulative() {
+        let metrics = Metrics::new();
+        metrics.observe_rpc_duration(Duration::from_millis(80));
+        metrics.observe_rpc_duration(Duration::from_secs(3));
+        metrics.record_block_fetched(120);
+        metrics.record_block_fetched(110);
+
+        let output = metrics.render();
+        assert!(output.contains("blocks_fetched_total 2\n"));
+        assert!(output.contains("current_slot 120\n"));
+        assert!(output.contains("rpc_request_duration_seconds_bucket{le=\"0.05\"} 0\n"));
+        assert!(output.contains("rpc_request_duration_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(output.contains("rpc_request_duration_seconds_bucket{le=\"5\"} 2\n"));
+        assert!(output.contains("rpc_request_duration_seconds_count 2\n"));
+    }
+}
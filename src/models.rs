@@ -5,6 +5,7 @@
 /// and their database representations.
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Represents a Solana block
 #[allow(dead_code)]
@@ -31,7 +32,6 @@ pub struct Transaction {
 }
 
 /// Represents a transaction instruction
-#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Instruction {
     pub transaction_signature: String,
@@ -42,28 +42,79 @@ pub struct Instruction {
     pub accounts: Vec<String>,
 }
 
+/// Kind of a block reward
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RewardType {
+    Fee,     // Leader's share of the block's transaction fees
+    Rent,    // Rent collected by the leader
+    Staking, // Inflation reward to a stake account (paid at epoch boundaries)
+    Voting,  // Inflation reward to a vote account (paid at epoch boundaries)
+}
+
+impl RewardType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Fee => "Fee",
+            Self::Rent => "Rent",
+            Self::Staking => "Staking",
+            Self::Voting => "Voting",
+        }
+    }
+}
+
+/// A reward credited (or debited) to an account in a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reward {
+    pub pubkey: String,
+    pub lamports: i64,           // Negative for rent debits
+    pub post_balance: u64,       // Account balance after the reward
+    pub reward_type: RewardType, // Fee, Rent, Staking or Voting
+    pub commission: Option<u8>,  // Vote account commission, only for staking and voting rewards
+}
+
 /// Types of transactions we can classify
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TransactionType {
     SolTransfer,
+    AccountCreation,
     SplTokenTransfer,
     NftMint,
     NftTransfer,
+    TokenMint,
+    TokenBurn,
     DexSwap,
+    LiquidityAdd,
+    LiquidityRemove,
+    Memo,
+    Stake,
+    LiquidStaking,
+    Vote,
     ProgramInteraction,
     Unknown,
+    /// A user-defined category from `program_registry.program_type`, e.g. "Lending"
+    Custom(String),
 }
 
 impl TransactionType {
     pub fn as_str(&self) -> &str {
         match self {
             Self::SolTransfer => "SOL Transfer",
+            Self::AccountCreation => "Account Creation",
             Self::SplTokenTransfer => "SPL Token Transfer",
             Self::NftMint => "NFT Mint",
             Self::NftTransfer => "NFT Transfer",
+            Self::TokenMint => "Token Mint",
+            Self::TokenBurn => "Token Burn",
             Self::DexSwap => "DEX Swap",
+            Self::LiquidityAdd => "Liquidity Add",
+            Self::LiquidityRemove => "Liquidity Remove",
+            Self::Memo => "Memo",
+            Self::Stake => "Stake",
+            Self::LiquidStaking => "Liquid Staking",
+            Self::Vote => "Vote",
             Self::ProgramInteraction => "Program Interaction",
             Self::Unknown => "Unknown",
+            Self::Custom(program_type) => program_type,
         }
     }
 }
@@ -74,5 +125,109 @@ pub struct ConnectionInfo {
     pub endpoint: String,
     pub blockhash: String,
     pub slot: u64,
+    pub block_height: u64, // Produced blocks so far; trails `slot` by the slots skipped by their leader
     pub timestamp: DateTime<Utc>,
 }
+
+/// Aggregates of the stored transactions of a slot or slot range
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSummary {
+    pub start_slot: u64,
+    pub end_slot: u64, // Same as start_slot for a single block
+    pub transaction_count: u64,
+    pub successful: u64,
+    pub failed: u64,
+    pub total_fees: u64,                // Lamports, failed transactions included
+    pub by_type: BTreeMap<String, u64>, // Transaction count per type label
+}
+
+impl BlockSummary {
+    /// Summary of `start_slot..=end_slot` from per-type rows of (type, count, successful, fees)
+    pub fn from_type_rows(
+        start_slot: u64,
+        end_slot: u64,
+        rows: impl IntoIterator<Item = (String, u64, u64, u64)>,
+    ) -> Self {
+        let mut summary = Self { start_slot, end_slot, ..Self::default() };
+        for (transaction_type, count, successful, fees) in rows {
+            summary.transaction_count += count;
+            summary.successful += successful;
+            summary.failed += count - successful;
+            summary.total_fees += fees;
+            *summary.by_type.entry(transaction_type).or_default() += count;
+        }
+        summary
+    }
+}
+
+/// Totals of everything stored, reported by the `status` command
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbStats {
+    pub total_blocks: u64,
+    pub total_transactions: u64,
+    pub min_slot: Option<u64>, // None while no block is stored
+    pub max_slot: Option<u64>,
+    pub skipped_slots: u64, // Slots in min_slot..=max_slot recorded as skipped by their leader
+    pub by_type: BTreeMap<String, u64>, // Transaction count per type label
+}
+
+impl DbStats {
+    /// Slots between the lowest and highest stored slot that are neither stored nor known to be
+    /// skipped: gaps a `backfill` of that range would fetch
+    pub fn unaccounted_slots(&self) -> u64 {
+        match (self.min_slot, self.max_slot) {
+            (Some(min), Some(max)) => (max - min + 1).saturating_sub(self.total_blocks + self.skipped_slots),
+            _ => 0,
+        }
+    }
+}
+
+/// Convert a block's Unix `block_time` to UTC
+///
+/// None when the node did not record a time for the block, or when the timestamp is negative
+/// or out of range (no Solana block predates the Unix epoch), so every caller treats a missing
+/// and a bogus time the same way.
+pub fn block_time_to_utc(block_time: Option<i64>) -> Option<DateTime<Utc>> {
+    block_time.filter(|&seconds| seconds >= 0).and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_time_to_utc() {
+        assert_eq!(block_time_to_utc(Some(1_700_000_000)).unwrap().to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert_eq!(block_time_to_utc(None), None);
+        assert_eq!(block_time_to_utc(Some(-1)), None);
+        assert_eq!(block_time_to_utc(Some(i64::MAX)), None);
+    }
+
+    #[test]
+    fn test_db_stats_unaccounted_slots() {
+        let stats = DbStats {
+            total_blocks: 90,
+            min_slot: Some(1_000),
+            max_slot: Some(1_099),
+            skipped_slots: 4,
+            ..DbStats::default()
+        };
+        assert_eq!(stats.unaccounted_slots(), 6);
+        assert_eq!(DbStats::default().unaccounted_slots(), 0);
+    }
+
+    #[test]
+    fn test_block_summary_from_type_rows() {
+        let summary = BlockSummary::from_type_rows(
+            100,
+            101,
+            vec![("Vote".to_string(), 10, 10, 50_000), ("DEX Swap".to_string(), 4, 3, 120_000)],
+        );
+        assert_eq!((summary.transaction_count, summary.successful, summary.failed), (14, 13, 1));
+        assert_eq!(summary.total_fees, 170_000);
+        assert_eq!(summary.by_type.get("DEX Swap"), Some(&4));
+
+        // A slot without stored transactions
+        assert_eq!(BlockSummary::from_type_rows(7, 7, Vec::new()).transaction_count, 0);
+    }
+}
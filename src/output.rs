@@ -0,0 +1,176 @@
+/// Output Module
+///
+/// Console output of the fetcher. The decorated progress and summary lines go through
+/// `console!`, which is off unless enabled: the binary turns it on, except in `--format ndjson`
+/// mode so stdout only carries one JSON record per transaction, and library users keep a
+/// clean stdout.
+use crate::etl::{
+    extract::{ExtractedBlock, ExtractedTransaction},
+    transform::{Classifier, ProgramRegistry},
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Whether decorated console output is printed
+static CONSOLE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable decorated console output for the whole process
+pub fn set_console_enabled(enabled: bool) {
+    CONSOLE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn console_enabled() -> bool {
+    CONSOLE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether block extraction shows an updating progress bar instead of progress lines (`--progress-bar`)
+static PROGRESS_BAR: AtomicBool = AtomicBool::new(false);
+
+/// Show extraction progress as a progress bar when stdout is a terminal
+pub fn set_progress_bar(enabled: bool) {
+    PROGRESS_BAR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn progress_bar_enabled() -> bool {
+    PROGRESS_BAR.load(Ordering::Relaxed)
+}
+
+/// `println!` for decorated console output, suppressed unless enabled with `set_console_enabled`
+#[macro_export]
+macro_rules! console {
+    ($($arg:tt)*) => {
+        if $crate::output::console_enabled() {
+            println!($($arg)*);
+        }
+    };
+}
+pub use crate::console;
+
+/// One NDJSON line: a transaction with its block context and classification
+#[derive(Serialize)]
+struct TransactionRecord<'a> {
+    slot: u64,
+    block_time: Option<i64>,
+    transaction_type: &'a str,
+    label: String,
+    #[serde(flatten)]
+    transaction: &'a ExtractedTransaction,
+}
+
+/// Write every transaction of the blocks to stdout as newline-delimited JSON
+///
+/// Records are written in block order and stdout is flushed after each batch, so a
+/// downstream reader (e.g. `jq`) sees complete lines as soon as a batch is loaded.
+pub fn write_ndjson(blocks: &[ExtractedBlock], registry: &ProgramRegistry, classifier: &dyn Classifier) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    write_transaction_records(&mut out, blocks, registry, classifier)?;
+    out.flush().context("Failed to flush NDJSON output")?;
+
+    Ok(())
+}
+
+fn write_transaction_records(
+    out: &mut impl Write,
+    blocks: &[ExtractedBlock],
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+) -> Result<()> {
+    for block in blocks {
+        for transaction in &block.transactions {
+            let details = transaction.analyze_with(classifier, registry);
+            let record = TransactionRecord {
+                slot: block.slot,
+                block_time: block.block_time,
+                transaction_type: details.tx_type.as_str(),
+                label: details.label,
+                transaction,
+            };
+
+            serde_json::to_writer(&mut *out, &record)
+                .context(format!("Failed to serialize transaction {}", transaction.signature))?;
+            out.write_all(b"\n").context("Failed to write NDJSON output")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::etl::transform::DefaultClassifier;
+    use serde_json::json;
+
+    #[test]
+    fn test_write_transaction_records() {
+        let transaction = ExtractedTransaction {
+            signature: "sig1".to_string(),
+            signers: vec![],
+            version: None,
+            recent_blockhash: None,
+            transaction_index: 0,
+            success: true,
+            error_reason: None,
+            fee: 5000,
+            priority_fee: None,
+            compute_unit_price: None,
+            compute_units: None,
+            num_accounts: 2,
+            num_instructions: 1,
+            num_inner_instructions: 0,
+            max_cpi_depth: Some(0),
+            program_ids: vec!["11111111111111111111111111111111".to_string()],
+            inner_program_ids: vec![],
+            balance_changes: vec![],
+            signer_balance_delta: 0,
+            token_balance_changes: vec![],
+            token_transfer: None,
+            jupiter_swap: None,
+            liquid_staking: None,
+            wrapped_sol: None,
+            log_messages: vec![],
+            logs_truncated: false,
+            raw_json: json!({ "message": {} }),
+        };
+        let block = ExtractedBlock {
+            slot: 42,
+            blockhash: "hash".to_string(),
+            parent_slot: 41,
+            block_time: Some(1_700_000_000),
+            block_height: None,
+            rewards: vec![],
+            transactions: vec![
+                transaction.clone(),
+                ExtractedTransaction { signature: "sig2".to_string(), ..transaction },
+            ],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 2,
+            signatures_only: false,
+        };
+
+        let registry = ProgramRegistry::from_database(vec![crate::db::ProgramInfo {
+            program_id: "11111111111111111111111111111111".to_string(),
+            program_name: "System Program".to_string(),
+            program_type: Some("System".to_string()),
+        }]);
+
+        let mut out = Vec::new();
+        write_transaction_records(&mut out, &[block], &registry, &DefaultClassifier).unwrap();
+
+        let lines: Vec<serde_json::Value> =
+            String::from_utf8(out).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["slot"], 42);
+        assert_eq!(lines[0]["signature"], "sig1");
+        assert_eq!(lines[0]["transaction_type"], "SOL Transfer");
+        assert_eq!(lines[0]["label"], "SOL Transfer (System Program)");
+        assert_eq!(lines[1]["signature"], "sig2");
+    }
+}
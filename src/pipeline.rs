@@ -2,22 +2,156 @@
 ///
 /// Orchestrates the complete ETL pipeline: Extract → Transform → Load
 /// with proper error handling, retry logic, and statistics tracking.
-use crate::db::Database;
+use crate::db::{BatchMetrics, Database, RunOutcome};
 use crate::{
-    etl::{extract::ExtractedBlock, transform::ProgramRegistry},
-    rpc::SolanaRpcClient,
+    etl::{
+        export::{self, ExportTarget},
+        extract::{ExtractedBlock, Sampler, StatusFilter, TransactionLimit},
+        load::{ConflictMode, RawDataStorage},
+        sink::{BlockSink, BulkLoadSink, SignatureSink},
+        transform::{Classifier, DefaultClassifier, ProgramRegistry},
+    },
+    output::{self, console},
+    rpc::{backoff::jittered, SolanaRpcClient},
 };
-use anyhow::Result;
-use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, OwnedRwLockReadGuard, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// Default distance kept from the chain tip, so only blocks that have settled are read
+pub const DEFAULT_FINALITY_OFFSET_SLOTS: u64 = 20;
+
+/// Default reorg overlap: slots behind the last processed one re-checked by continuous passes when
+/// blocks are not loaded at `finalized` commitment, roughly the distance between the confirmed and
+/// finalized tips
+pub const DEFAULT_REORG_OVERLAP_SLOTS: u64 = 32;
+
+/// Default number of recently loaded slots remembered by the continuous loop
+pub const DEFAULT_DEDUP_CACHE_SIZE: usize = 10_000;
+
+/// Default load duration adaptive batching steers the batch size toward
+pub const DEFAULT_TARGET_COMMIT: Duration = Duration::from_secs(2);
+
+/// Delay between block requests when the RPC client has no requests-per-second cap (`--rps`)
+const REQUEST_DELAY_MS: u64 = 100;
+
+/// Extracted batches that may wait for their load, bounding how far extraction runs ahead
+const LOAD_QUEUE_DEPTH: usize = 2;
+
+/// Widest slot range `retry-failed` lists with one `getBlocks` call
+///
+/// Well under the RPC's range limit (about 500,000 slots), and small enough that the listed slots
+/// stay cheap when only a few of them are queued.
+const RETRY_SPAN_SLOTS: u64 = 10_000;
+
+/// Throughput-adaptive batch sizing
+///
+/// After every load the batch size is scaled by how far the load time was from `target_commit`:
+/// small batches under-use a database transaction, large ones hold it long enough to risk lock
+/// contention and statement timeouts. Block density varies a lot over a backfill, so a size that
+/// fits one range rarely fits the next.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AdaptiveBatching {
+    #[serde(rename = "target_commit_secs", serialize_with = "serialize_secs")]
+    pub target_commit: Duration,
+    pub min_batch_size: usize,
+    pub max_batch_size: usize,
+}
+
+impl AdaptiveBatching {
+    /// Batch size to use after a batch of `batch_size` slots took `load_time` to load
+    ///
+    /// The size changes at most twofold per batch, so one slow commit (a lock wait, a
+    /// checkpoint) does not collapse it, and loads within 10% of the target keep it unchanged.
+    pub fn next_batch_size(&self, batch_size: usize, load_time: Duration) -> usize {
+        let ratio = self.target_commit.as_secs_f64() / load_time.as_secs_f64().max(0.001);
+        if (0.9..=1.1).contains(&ratio) {
+            return batch_size.clamp(self.min_batch_size, self.max_batch_size);
+        }
+
+        let scaled = (batch_size as f64 * ratio.clamp(0.5, 2.0)).round() as usize;
+        scaled.clamp(self.min_batch_size, self.max_batch_size)
+    }
+}
+
+/// Recently loaded slots and the chain tip when they were loaded, for the continuous loop
+///
+/// A slot loaded at least the reorg overlap behind the tip can no longer be replaced by a fork, so
+/// later passes skip it instead of re-fetching it. Memory is bounded: past `capacity` the least
+/// recently loaded slot is evicted, and slots that fell behind the overlap window are dropped.
+struct RecentSlots {
+    capacity: usize,
+    loaded_at: HashMap<u64, u64>, // Slot -> latest slot when it was last loaded
+    order: VecDeque<u64>,         // Least recently loaded first
+}
+
+impl RecentSlots {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, loaded_at: HashMap::new(), order: VecDeque::new() }
+    }
+
+    /// Remember that `slot` was loaded while the chain tip was at `tip`
+    fn record(&mut self, slot: u64, tip: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.loaded_at.insert(slot, tip).is_some() {
+            self.order.retain(|&s| s != slot);
+        }
+        self.order.push_back(slot);
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.loaded_at.remove(&evicted);
+            }
+        }
+    }
+
+    /// Whether `slot` was last loaded at least `depth` slots behind the tip
+    fn is_settled(&self, slot: u64, depth: u64) -> bool {
+        self.loaded_at.get(&slot).is_some_and(|&tip| tip.saturating_sub(slot) >= depth)
+    }
+
+    /// First slot of `start..end` that still needs a reorg re-check, or `end` when all are settled
+    fn first_unsettled(&self, start: u64, end: u64, depth: u64) -> u64 {
+        (start..end).find(|&slot| !self.is_settled(slot, depth)).unwrap_or(end)
+    }
+
+    /// Forget every slot below `slot`
+    fn evict_below(&mut self, slot: u64) {
+        self.order.retain(|&s| s >= slot);
+        self.loaded_at.retain(|&s, _| s >= slot);
+    }
+}
 
 /// Pipeline execution statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PipelineStats {
     pub blocks_attempted: usize,
     pub blocks_succeeded: usize,
     pub blocks_failed: usize,
+    pub slots_skipped: usize,
+    pub blocks_existing: usize, // Blocks not fetched because they were already stored (`--skip-existing`)
+    pub blocks_reorged: usize,  // Stored blocks deleted because a fork orphaned them
+    pub blocks_oversized: usize, // Blocks over `--max-txs-per-block`, truncated or skipped
+    pub blocks_unavailable: usize, // Blocks the RPC node does not serve (too recent or pruned), skipped
     pub transactions_processed: usize,
+    pub transactions_sampled_out: usize, // Transactions seen but left out of the `--sample-rate` sample
     pub transactions_inserted: usize,
+    pub batches_processed: usize,
+    pub last_processed_slot: Option<u64>, // End slot of the last successfully loaded batch
+    #[serde(rename = "elapsed_secs", serialize_with = "serialize_secs")]
     pub elapsed_time: Duration,
     pub errors: Vec<PipelineError>,
 }
@@ -27,6 +161,23 @@ impl PipelineStats {
         Self::default()
     }
 
+    /// Add the counters and errors of `other`, e.g. the extraction of one batch
+    fn merge(&mut self, other: PipelineStats) {
+        self.blocks_attempted += other.blocks_attempted;
+        self.blocks_succeeded += other.blocks_succeeded;
+        self.blocks_failed += other.blocks_failed;
+        self.slots_skipped += other.slots_skipped;
+        self.blocks_existing += other.blocks_existing;
+        self.blocks_reorged += other.blocks_reorged;
+        self.blocks_oversized += other.blocks_oversized;
+        self.blocks_unavailable += other.blocks_unavailable;
+        self.transactions_processed += other.transactions_processed;
+        self.transactions_sampled_out += other.transactions_sampled_out;
+        self.transactions_inserted += other.transactions_inserted;
+        self.batches_processed += other.batches_processed;
+        self.errors.extend(other.errors);
+    }
+
     pub fn success_rate(&self) -> f64 {
         if self.blocks_attempted == 0 {
             0.0
@@ -55,7 +206,7 @@ impl PipelineStats {
 }
 
 /// Pipeline error with context
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PipelineError {
     pub stage: PipelineStage,
     pub slot: Option<u64>,
@@ -64,12 +215,13 @@ pub struct PipelineError {
     pub retryable: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[allow(dead_code)]
 pub enum PipelineStage {
     Extract,
     Transform,
     Load,
+    Export, // Files and NDJSON written after a batch is loaded
 }
 
 impl std::fmt::Display for PipelineStage {
@@ -78,43 +230,252 @@ impl std::fmt::Display for PipelineStage {
             PipelineStage::Extract => write!(f, "Extract"),
             PipelineStage::Transform => write!(f, "Transform"),
             PipelineStage::Load => write!(f, "Load"),
+            PipelineStage::Export => write!(f, "Export"),
         }
     }
 }
 
+/// A batch handed from the extracting half of `process_range` to the loading half
+struct ExtractedBatch {
+    start_slot: u64,
+    end_slot: u64,
+    result: Result<(Vec<ExtractedBlock>, BatchMetrics)>,
+    stats: PipelineStats, // Extraction counters and errors of the batch, merged when it is received
+    registry: OwnedRwLockReadGuard<ProgramRegistry>, // Snapshot the batch was extracted with, also used to load it
+}
+
 /// Configuration for pipeline execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PipelineConfig {
     pub start_slot: u64,
     pub end_slot: u64,
     pub max_retries: usize,
+    #[serde(rename = "retry_delay_secs", serialize_with = "serialize_secs")]
     pub retry_delay: Duration,
     pub batch_size: usize,
+    pub concurrency: usize,                      // Parallel block fetches (1 = sequential)
+    pub job_name: String,                        // Checkpoint key for resuming interrupted runs
+    pub export: Option<ExportTarget>,            // Also write batches to files
+    pub skip_votes: bool,                        // Drop validator vote transactions before loading
+    pub status_filter: Option<StatusFilter>,     // Keep only successful or only failed transactions
+    pub sampler: Option<Sampler>,                // Keep a seeded random sample of transactions
+    pub program_filter: Option<HashSet<String>>, // Keep only transactions invoking one of these programs
+    pub max_transactions_per_block: Option<TransactionLimit>, // Skip or truncate blocks with more transactions
+    pub skip_existing: bool,                     // Do not re-fetch blocks already stored in the database
+    pub verify_tx_count: bool,                   // With skip_existing, skip only fully stored blocks
+    pub registry_reload_batches: usize,          // Reload the program registry every N batches (0 = never)
+    pub bulk_load: bool,                         // Load transactions with binary COPY instead of per-row upserts
+    pub signatures_only: bool,                   // Store blocks fetched with signatures only, see `SignatureSink`
+    pub ndjson: bool,                            // Write each transaction to stdout as NDJSON
+    pub dry_run: bool,                           // Extract and classify only, never write to the database
+    pub finality_offset_slots: u64,              // Continuous mode: slots to stay behind the chain tip
+    pub reorg_overlap_slots: u64,                // Continuous mode: trailing slots re-checked for forks
+    pub dedup_cache_size: usize,                 // Continuous mode: loaded slots remembered (0 = always re-check)
+    pub adaptive_batching: Option<AdaptiveBatching>, // Steer batch_size toward a target load time
+    pub stats_out: Option<PathBuf>,              // Write a JSON summary of each run to this file
 }
 
 impl Default for PipelineConfig {
     fn default() -> Self {
-        Self { start_slot: 0, end_slot: 0, max_retries: 3, retry_delay: Duration::from_secs(2), batch_size: 10 }
+        Self {
+            start_slot: 0,
+            end_slot: 0,
+            max_retries: 3,
+            retry_delay: Duration::from_secs(2),
+            batch_size: 10,
+            concurrency: 1,
+            job_name: "default".to_string(),
+            export: None,
+            skip_votes: false,
+            status_filter: None,
+            sampler: None,
+            program_filter: None,
+            max_transactions_per_block: None,
+            skip_existing: false,
+            verify_tx_count: false,
+            registry_reload_batches: 0,
+            bulk_load: false,
+            signatures_only: false,
+            ndjson: false,
+            dry_run: false,
+            finality_offset_slots: DEFAULT_FINALITY_OFFSET_SLOTS,
+            reorg_overlap_slots: DEFAULT_REORG_OVERLAP_SLOTS,
+            dedup_cache_size: DEFAULT_DEDUP_CACHE_SIZE,
+            adaptive_batching: None,
+            stats_out: None,
+        }
     }
 }
 
+/// Summary of a finished run, written to `--stats-out` for orchestration scripts
+///
+/// The statistics and errors of the run are flattened into the top level, next to the range it
+/// covered and its rates; `config` is the pipeline configuration it ran with.
+#[derive(Debug, Serialize)]
+pub struct RunSummary<'a> {
+    pub job_name: &'a str,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub finished_at: DateTime<Utc>,
+    pub success_rate: f64, // Percentage of attempted blocks that were loaded
+    pub blocks_per_second: f64,
+    pub transactions_per_second: f64,
+    #[serde(flatten)]
+    pub stats: &'a PipelineStats,
+    pub config: &'a PipelineConfig,
+}
+
+impl<'a> RunSummary<'a> {
+    /// Summary of a run over `start_slot..=end_slot` that ended with `stats`
+    pub fn new(config: &'a PipelineConfig, start_slot: u64, end_slot: u64, stats: &'a PipelineStats) -> Self {
+        Self {
+            job_name: &config.job_name,
+            start_slot,
+            end_slot,
+            finished_at: Utc::now(),
+            success_rate: stats.success_rate(),
+            blocks_per_second: stats.blocks_per_second(),
+            transactions_per_second: stats.transactions_per_second(),
+            stats,
+            config,
+        }
+    }
+
+    /// Write the summary to `path` as pretty-printed JSON, replacing the file
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize the run summary")?;
+        std::fs::write(path, json + "\n").context(format!("Failed to write the run summary to {}", path.display()))
+    }
+}
+
+/// Split sorted slots into consecutive groups spanning at most `max_span` slots each
+///
+/// Failed slots can be millions of slots apart; each group is one `getBlocks` range.
+fn slot_spans(slots: &[u64], max_span: u64) -> Vec<&[u64]> {
+    let mut spans = Vec::new();
+    let mut rest = slots;
+    while let Some(&first) = rest.first() {
+        let len = rest.iter().take_while(|&&slot| slot - first < max_span).count();
+        let (span, tail) = rest.split_at(len);
+        spans.push(span);
+        rest = tail;
+    }
+    spans
+}
+
+/// Serialize a duration as fractional seconds
+fn serialize_secs<S: serde::Serializer>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
 /// Main ETL Pipeline
 pub struct Pipeline {
     rpc_client: SolanaRpcClient,
-    database: Database,
-    program_registry: ProgramRegistry,
+    database: Database, // Checkpoints, gap detection and registry reloads
+    sink: Box<dyn BlockSink>,
+    // Held for reading for a whole batch, from extraction to load, so every transaction in a
+    // batch is classified with the same registry; reloads wait for the in-flight and queued
+    // batches to finish
+    program_registry: Arc<RwLock<ProgramRegistry>>,
+    classifier: Arc<dyn Classifier>, // Classification rules, `DefaultClassifier` unless replaced
     config: PipelineConfig,
+    batch_size: AtomicUsize, // Slots per batch, `config.batch_size` unless adapted after each load
+    shutdown: CancellationToken,
 }
 
 impl Pipeline {
-    /// Create a new pipeline instance
+    /// Create a new pipeline instance that loads blocks into the database
     pub fn new(
         rpc_client: SolanaRpcClient,
         database: Database,
         program_registry: ProgramRegistry,
         config: PipelineConfig,
     ) -> Self {
-        Self { rpc_client, database, program_registry, config }
+        let sink: Box<dyn BlockSink> = if config.signatures_only {
+            Box::new(SignatureSink::new(database.clone()))
+        } else if config.bulk_load {
+            Box::new(BulkLoadSink::new(database.clone()))
+        } else {
+            Box::new(database.clone())
+        };
+
+        Self::with_sink(rpc_client, database, program_registry, config, sink)
+    }
+
+    /// Create a new pipeline instance that loads blocks into the given sink
+    ///
+    /// The database is still used for checkpoints, gap detection and registry reloads.
+    pub fn with_sink(
+        rpc_client: SolanaRpcClient,
+        database: Database,
+        program_registry: ProgramRegistry,
+        config: PipelineConfig,
+        sink: Box<dyn BlockSink>,
+    ) -> Self {
+        let batch_size = match config.adaptive_batching {
+            Some(adaptive) => config.batch_size.clamp(adaptive.min_batch_size, adaptive.max_batch_size),
+            None => config.batch_size,
+        };
+
+        Self {
+            rpc_client,
+            database,
+            sink,
+            program_registry: Arc::new(RwLock::new(program_registry)),
+            classifier: Arc::new(DefaultClassifier),
+            config,
+            batch_size: AtomicUsize::new(batch_size),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Classify transactions with custom rules instead of `DefaultClassifier`
+    ///
+    /// Used for the extraction statistics, the stored `transaction_type` and label, and exports.
+    pub fn with_classifier(mut self, classifier: Arc<dyn Classifier>) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// Re-query the program registry from the database and swap it in
+    ///
+    /// The database is queried before the write lock is taken, so batches are only blocked
+    /// for the swap itself. Returns the number of programs in the new registry.
+    pub async fn reload_registry(&self) -> Result<usize> {
+        let program_infos = self.database.load_program_registry().await.context("Failed to reload program registry")?;
+        let registry = ProgramRegistry::from_database(program_infos);
+        let count = registry.programs.len();
+
+        *self.program_registry.write().await = registry;
+        tracing::info!("Reloaded program registry with {} programs", count);
+
+        Ok(count)
+    }
+
+    /// Get a token that stops the pipeline after the in-flight batch when cancelled
+    #[allow(dead_code)]
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Cancel the shutdown token on Ctrl+C
+    ///
+    /// The first Ctrl+C lets the current batch finish (its database transaction either
+    /// commits or rolls back as a whole) and stops queuing new batches. A second Ctrl+C
+    /// exits immediately; the uncommitted batch transaction is rolled back by PostgreSQL.
+    fn install_shutdown_handler(&self) {
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                console!("\n🛑 Shutdown requested, finishing the current batch (Ctrl+C again to abort)...");
+                shutdown.cancel();
+
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    eprintln!("❌ Aborted, the in-flight batch was rolled back");
+                    std::process::exit(130);
+                }
+            }
+        });
     }
 
     /// Run the complete pipeline for the configured slot range
@@ -122,110 +483,1093 @@ impl Pipeline {
         let start_time = Instant::now();
         let mut stats = PipelineStats::new();
 
-        tracing::info!("Starting pipeline for slots {} to {}", self.config.start_slot, self.config.end_slot);
+        self.install_shutdown_handler();
+        self.print_header();
+        let run_id = self.record_run_start(self.config.start_slot, self.config.end_slot).await;
+        self.process_range(self.config.start_slot, self.config.end_slot, &mut stats).await;
+
+        stats.elapsed_time = start_time.elapsed();
+        self.record_run_finish(run_id, self.config.end_slot, &stats).await;
+        self.write_stats_out(self.config.start_slot, self.config.end_slot, &stats);
+
+        self.print_completion(&stats);
+        self.print_final_stats(&stats);
 
-        println!("\n🚀 Starting ETL Pipeline...");
-        println!("   📍 Slot range: {} to {}", self.config.start_slot, self.config.end_slot);
-        println!("   🔄 Max retries: {}", self.config.max_retries);
-        println!("   📦 Batch size: {}", self.config.batch_size);
+        Ok(stats)
+    }
+
+    /// Run the configured slot range, then keep following the chain tip
+    ///
+    /// After the initial range, the pipeline sleeps for `interval`, queries the latest slot
+    /// and processes every slot after the last successfully loaded one up to
+    /// `finality_offset_slots` behind it. Ctrl+C stops the loop once the current batch has
+    /// been committed.
+    pub async fn run_continuous(&self, interval: Duration) -> Result<PipelineStats> {
+        let start_time = Instant::now();
+        let mut stats = PipelineStats::new();
+
+        self.install_shutdown_handler();
+        self.print_header();
+        let run_id = self.record_run_start(self.config.start_slot, self.config.end_slot).await;
+        self.process_range(self.config.start_slot, self.config.end_slot, &mut stats).await;
 
-        // Process blocks in batches
-        let total_slots = self.config.end_slot - self.config.start_slot + 1;
-        let mut current_slot = self.config.start_slot;
+        let mut next_slot = stats.last_processed_slot.map(|s| s + 1).unwrap_or(self.config.start_slot);
+        let mut recent_slots = RecentSlots::new(self.config.dedup_cache_size);
+        let overlap = self.config.reorg_overlap_slots;
 
-        while current_slot <= self.config.end_slot {
-            let batch_end = std::cmp::min(current_slot + self.config.batch_size as u64 - 1, self.config.end_slot);
+        console!("\n🔄 Entering continuous mode (processing every {} seconds)", interval.as_secs());
+        console!("   Press Ctrl+C to stop...\n");
+
+        while !self.shutdown.is_cancelled() {
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
 
-            match self.process_batch(current_slot, batch_end, &mut stats).await {
-                Ok(_) => {
-                    let progress = ((stats.blocks_succeeded as f64 / total_slots as f64) * 100.0) as usize;
-                    println!(
-                        "   📊 Progress: {}/{} blocks ({}%) | ✅ {} succeeded | ❌ {} failed",
-                        stats.blocks_attempted, total_slots, progress, stats.blocks_succeeded, stats.blocks_failed
+            let latest_slot = match self.rpc_client.get_latest_slot().await {
+                Ok(slot) => slot,
+                Err(e) => {
+                    tracing::warn!("Failed to get latest slot: {}", e);
+                    continue;
+                }
+            };
+
+            // Only process blocks at least the finality offset behind the tip
+            let end_slot = latest_slot.saturating_sub(self.config.finality_offset_slots);
+            if end_slot < next_slot {
+                tracing::info!("No new finalized blocks since slot {}, waiting", next_slot - 1);
+                continue;
+            }
+
+            // Blocks near the tip may have been replaced by a fork since they were loaded at a
+            // non-finalized commitment, so re-check them and reload the slots a fork changed.
+            // Slots already loaded at least `overlap` slots behind the tip are settled and skipped.
+            let finalized = self.rpc_client.commitment().is_finalized();
+            if !finalized {
+                let window_start = next_slot.saturating_sub(overlap).max(self.config.start_slot);
+                let check_start = recent_slots.first_unsettled(window_start, next_slot, overlap);
+                if check_start > window_start {
+                    tracing::debug!(
+                        "Skipping {} settled slots before slot {}",
+                        check_start - window_start,
+                        check_start
                     );
                 }
+                if check_start < next_slot {
+                    self.recheck_window(check_start, next_slot - 1, &mut stats).await;
+                    for slot in check_start..next_slot {
+                        recent_slots.record(slot, latest_slot);
+                    }
+                }
+            }
+
+            console!("🔄 Processing slots {} to {}", next_slot, end_slot);
+            tracing::info!("Continuous mode: processing slots {} to {}", next_slot, end_slot);
+
+            let start_slot = next_slot;
+            self.process_range(start_slot, end_slot, &mut stats).await;
+
+            if let Some(last) = stats.last_processed_slot {
+                if !finalized && last >= start_slot {
+                    for slot in start_slot..=last {
+                        recent_slots.record(slot, latest_slot);
+                    }
+                }
+                next_slot = next_slot.max(last + 1);
+            }
+            // Slots before the next window are never re-checked again
+            recent_slots.evict_below(next_slot.saturating_sub(overlap));
+        }
+
+        stats.elapsed_time = start_time.elapsed();
+        self.record_run_finish(run_id, self.followed_end_slot(&stats), &stats).await;
+        self.write_stats_out(self.config.start_slot, self.followed_end_slot(&stats), &stats);
+
+        self.print_completion(&stats);
+        self.print_final_stats(&stats);
+
+        Ok(stats)
+    }
+
+    /// Run the configured slot range, then ingest new blocks from a WebSocket block subscription
+    ///
+    /// Like `run_continuous`, without polling: each block is loaded as soon as the subscription
+    /// delivers it. Blocks at or below the last processed slot (replays around a reconnect) are
+    /// skipped, and the slots between the last processed block and a newly delivered one are
+    /// fetched over HTTP first, which covers skipped slots as well as blocks missed while the
+    /// WebSocket was down. Ctrl+C stops the loop once the current block has been committed.
+    pub async fn run_subscribed(&self, ws_url: Option<&str>) -> Result<PipelineStats> {
+        let start_time = Instant::now();
+        let mut stats = PipelineStats::new();
+
+        self.install_shutdown_handler();
+        self.print_header();
+        let run_id = self.record_run_start(self.config.start_slot, self.config.end_slot).await;
+        self.process_range(self.config.start_slot, self.config.end_slot, &mut stats).await;
+
+        let mut next_slot = stats.last_processed_slot.map(|s| s + 1).unwrap_or(self.config.start_slot);
+
+        let blocks = match crate::etl::extract::subscribe_extracted_blocks(
+            &self.rpc_client,
+            ws_url,
+            self.config.skip_votes,
+            self.config.status_filter,
+            self.config.sampler,
+            self.config.program_filter.as_ref(),
+            self.config.max_transactions_per_block,
+        ) {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                self.record_run_finish(run_id, self.followed_end_slot(&stats), &stats).await;
+                return Err(e).context("Failed to start block subscription");
+            }
+        };
+        let mut blocks = std::pin::pin!(blocks);
+
+        console!("\n📡 Entering continuous mode (block subscription)");
+        console!("   Press Ctrl+C to stop...\n");
+
+        while !self.shutdown.is_cancelled() {
+            let update = tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                update = blocks.next() => update,
+            };
+
+            let block = match update {
+                Some(Ok(block)) => block,
+                Some(Err(e)) => {
+                    tracing::warn!("Block subscription: {:#}", e);
+                    continue;
+                }
+                None => break,
+            };
+
+            if block.slot < next_slot {
+                tracing::debug!("Slot {} already processed, skipping", block.slot);
+                continue;
+            }
+
+            // Catch up on the slots in between over HTTP
+            if block.slot > next_slot {
+                self.process_range(next_slot, block.slot - 1, &mut stats).await;
+                if self.shutdown.is_cancelled() {
+                    break;
+                }
+            }
+
+            next_slot = block.slot + 1;
+            if self.config.skip_existing && self.database.block_exists(block.slot).await.unwrap_or(false) {
+                tracing::debug!("Slot {} already stored, skipping", block.slot);
+                stats.blocks_existing += 1;
+                continue;
+            }
+            self.process_streamed_block(block, &mut stats).await;
+        }
+
+        stats.elapsed_time = start_time.elapsed();
+        self.record_run_finish(run_id, self.followed_end_slot(&stats), &stats).await;
+        self.write_stats_out(self.config.start_slot, self.followed_end_slot(&stats), &stats);
+
+        self.print_completion(&stats);
+        self.print_final_stats(&stats);
+
+        Ok(stats)
+    }
+
+    /// Fill gaps in the `blocks` table between `start_slot` and `end_slot`
+    ///
+    /// Missing slots are checked against `getBlocks`: slots that produced a block are fetched
+    /// and loaded in batches, slots skipped by their leader are recorded in `skipped_slots` so
+    /// they are not reported as gaps again. No checkpoint is saved.
+    pub async fn run_backfill(&self, start_slot: u64, end_slot: u64) -> Result<PipelineStats> {
+        let start_time = Instant::now();
+        let mut stats = PipelineStats::new();
+
+        self.install_shutdown_handler();
+        self.print_header();
+        let run_id = self.record_run_start(start_slot, end_slot).await;
+
+        let result = self.backfill(start_slot, end_slot, &mut stats).await;
+
+        stats.elapsed_time = start_time.elapsed();
+        self.record_run_finish(run_id, end_slot, &stats).await;
+        self.write_stats_out(start_slot, end_slot, &stats);
+        result?;
+
+        self.print_completion(&stats);
+        self.print_final_stats(&stats);
+
+        Ok(stats)
+    }
+
+    /// Fetch the missing blocks of `start_slot..=end_slot` and record its skipped slots
+    async fn backfill(&self, start_slot: u64, end_slot: u64, stats: &mut PipelineStats) -> Result<()> {
+        let missing = self.database.find_missing_slots(start_slot, end_slot).await?;
+        let produced: std::collections::HashSet<u64> = if missing.is_empty() {
+            Default::default()
+        } else {
+            self.rpc_client.get_blocks(start_slot, end_slot).await?.into_iter().collect()
+        };
+        let (to_fetch, skipped): (Vec<u64>, Vec<u64>) = missing.into_iter().partition(|slot| produced.contains(slot));
+
+        if !self.config.dry_run {
+            self.database.record_skipped_slots(&skipped).await?;
+        }
+        stats.slots_skipped += skipped.len();
+
+        console!(
+            "\n🩹 Backfill: {} missing blocks to fetch, {} newly recorded skipped slots",
+            to_fetch.len(),
+            skipped.len()
+        );
+
+        self.process_slots(&to_fetch, stats).await?;
+        Ok(())
+    }
+
+    /// Re-attempt the slots in the `failed_slots` queue
+    ///
+    /// Each slot is checked against `getBlocks` first: slots that turn out to have been skipped
+    /// by their leader are moved to `skipped_slots`. The others are fetched and loaded in
+    /// batches and removed from the queue once loaded; slots that fail again stay queued with
+    /// their attempt count increased.
+    pub async fn run_retry_failed(&self) -> Result<PipelineStats> {
+        let start_time = Instant::now();
+        let mut stats = PipelineStats::new();
+
+        self.install_shutdown_handler();
+        self.print_header();
+
+        let queue = self.database.list_failed_slots().await?;
+        console!("\n🩹 Retrying {} failed slots", queue.len());
+        for failed in queue.iter().take(5) {
+            let last_failed = failed.last_failed_at.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+            console!(
+                "   Slot {} [{}] {} attempts, last at {}: {}",
+                failed.slot,
+                failed.stage,
+                failed.attempts,
+                last_failed.as_deref().unwrap_or("unknown"),
+                failed.error
+            );
+        }
+        if queue.len() > 5 {
+            console!("   ... and {} more", queue.len() - 5);
+        }
+        let failed: Vec<u64> = queue.iter().map(|f| f.slot as u64).collect();
+        let mut remaining = failed.as_slice();
+
+        while !remaining.is_empty() {
+            let (chunk, rest) = remaining.split_at(self.batch_size().min(remaining.len()));
+            remaining = rest;
+
+            if self.shutdown.is_cancelled() {
+                tracing::info!("Shutdown requested, stopping before slot {}", chunk[0]);
+                break;
+            }
+
+            // Queued slots can be far apart, so list the produced blocks span by span
+            let mut produced = HashSet::new();
+            for span in slot_spans(chunk, RETRY_SPAN_SLOTS) {
+                produced.extend(self.rpc_client.get_blocks(span[0], span[span.len() - 1]).await?);
+            }
+            let (to_fetch, skipped): (Vec<u64>, Vec<u64>) = chunk.iter().partition(|slot| produced.contains(slot));
+
+            if !self.config.dry_run && !skipped.is_empty() {
+                self.database.record_skipped_slots(&skipped).await?;
+                self.database.clear_failed_slots(&skipped).await?;
+            }
+            stats.slots_skipped += skipped.len();
+
+            let loaded = self.process_slots(&to_fetch, &mut stats).await?;
+            if !self.config.dry_run && !loaded.is_empty() {
+                let cleared = self.database.clear_failed_slots(&loaded).await?;
+                tracing::info!("Cleared {} slots from the failed-slot queue", cleared);
+            }
+        }
+
+        stats.elapsed_time = start_time.elapsed();
+        self.write_stats_out(
+            failed.first().copied().unwrap_or_default(),
+            failed.last().copied().unwrap_or_default(),
+            &stats,
+        );
+        self.print_completion(&stats);
+        self.print_final_stats(&stats);
+
+        Ok(stats)
+    }
+
+    /// Fetch and load the given slots in batches, returning the slots that were loaded
+    ///
+    /// The slots must have produced a block. Blocks that cannot be fetched are recorded in
+    /// `failed_slots` and left out of the result.
+    async fn process_slots(&self, slots: &[u64], stats: &mut PipelineStats) -> Result<Vec<u64>> {
+        let mut loaded = Vec::new();
+        let mut remaining = slots;
+
+        while !remaining.is_empty() {
+            let (batch, rest) = remaining.split_at(self.batch_size().min(remaining.len()));
+            remaining = rest;
+
+            if self.shutdown.is_cancelled() {
+                tracing::info!("Shutdown requested, stopping before slot {}", batch[0]);
+                break;
+            }
+
+            let registry = self.program_registry.read().await;
+            let (blocks, extract_stats) = crate::etl::extract::extract_slots(
+                &self.rpc_client,
+                batch,
+                0,
+                self.request_delay_ms(),
+                Some(&registry),
+                self.classifier.as_ref(),
+                self.config.skip_votes,
+                self.config.status_filter,
+                self.config.sampler,
+                self.config.program_filter.as_ref(),
+                self.config.max_transactions_per_block,
+            )
+            .await?;
+
+            stats.blocks_attempted += (extract_stats.blocks_fetched + extract_stats.blocks_failed) as usize;
+            stats.blocks_failed += extract_stats.blocks_failed as usize;
+            stats.blocks_succeeded += blocks.len();
+            stats.transactions_processed += blocks.iter().map(|b| b.transactions.len()).sum::<usize>();
+            stats.transactions_sampled_out += extract_stats.sampled_out as usize;
+            stats.blocks_oversized += extract_stats.oversized_blocks as usize;
+            stats.blocks_unavailable += extract_stats.unavailable_blocks as usize;
+            self.record_extract_failures(&extract_stats.failed_slots).await;
+
+            if blocks.is_empty() {
+                continue;
+            }
+
+            self.load_blocks(&blocks, &registry, stats).await?;
+            loaded.extend(blocks.iter().map(|b| b.slot));
+            stats.last_processed_slot = blocks.last().map(|b| b.slot);
+            stats.batches_processed += 1;
+
+            // The blocks are stored: a failed export stops here without reporting them unloaded
+            if let Err(e) = self.export_outputs(batch[0], batch[batch.len() - 1], &blocks, &registry) {
+                tracing::error!(
+                    "Export failed for slots {}-{} (loaded), stopping: {:#}",
+                    batch[0],
+                    batch[batch.len() - 1],
+                    e
+                );
+                stats.errors.push(PipelineError {
+                    stage: PipelineStage::Export,
+                    slot: Some(batch[0]),
+                    message: format!("Export failed: {}", e),
+                    retryable: true,
+                });
+                break;
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Load the transactions involving one address instead of a block range
+    ///
+    /// `start_slot`/`end_slot` of the config bound the search when set (0 = unbounded) and at most
+    /// `limit` transactions are loaded, newest first. No checkpoint is saved for address runs.
+    pub async fn run_address(&self, address: &str, limit: usize) -> Result<PipelineStats> {
+        let start_time = Instant::now();
+        let mut stats = PipelineStats::new();
+
+        self.print_header();
+
+        let registry = self.program_registry.read().await;
+        let (blocks, extract_stats) = crate::etl::extract::extract_transactions_for_address(
+            &self.rpc_client,
+            address,
+            Some(self.config.start_slot).filter(|&slot| slot > 0),
+            Some(self.config.end_slot).filter(|&slot| slot > 0),
+            limit,
+            self.request_delay_ms(),
+            Some(&registry),
+            self.classifier.as_ref(),
+            self.config.skip_votes,
+            self.config.status_filter,
+            self.config.sampler,
+            self.config.program_filter.as_ref(),
+        )
+        .await?;
+
+        stats.blocks_attempted += (extract_stats.blocks_fetched + extract_stats.blocks_failed) as usize;
+        stats.blocks_failed += extract_stats.blocks_failed as usize;
+        stats.blocks_succeeded += blocks.len();
+        stats.transactions_processed += blocks.iter().map(|b| b.transactions.len()).sum::<usize>();
+        stats.transactions_sampled_out += extract_stats.sampled_out as usize;
+
+        if !blocks.is_empty() {
+            self.load_blocks(&blocks, &registry, &mut stats).await?;
+            stats.last_processed_slot = blocks.last().map(|b| b.slot);
+
+            let (first, last) = (blocks[0].slot, blocks[blocks.len() - 1].slot);
+            if let Err(e) = self.export_outputs(first, last, &blocks, &registry) {
+                tracing::error!("Export failed for slots {}-{} (loaded): {:#}", first, last, e);
+                stats.errors.push(PipelineError {
+                    stage: PipelineStage::Export,
+                    slot: Some(first),
+                    message: format!("Export failed: {}", e),
+                    retryable: true,
+                });
+            }
+        }
+
+        stats.elapsed_time = start_time.elapsed();
+        self.write_stats_out(self.config.start_slot, self.config.end_slot, &stats);
+        self.print_final_stats(&stats);
+
+        Ok(stats)
+    }
+
+    /// Print the completion banner, noting where to resume after a shutdown
+    fn print_completion(&self, stats: &PipelineStats) {
+        if self.shutdown.is_cancelled() {
+            match stats.last_processed_slot {
+                Some(slot) => console!("\n🛑 Pipeline stopped early. Last committed slot: {}", slot),
+                None => console!("\n🛑 Pipeline stopped early. No batch was committed"),
+            }
+        } else {
+            console!("\n✅ Pipeline complete!");
+        }
+    }
+
+    /// Print the pipeline configuration banner
+    fn print_header(&self) {
+        tracing::info!("Starting pipeline for slots {} to {}", self.config.start_slot, self.config.end_slot);
+
+        console!("\n🚀 Starting ETL Pipeline...");
+        console!("   📍 Slot range: {} to {}", self.config.start_slot, self.config.end_slot);
+        console!("   🔄 Max retries: {}", self.config.max_retries);
+        match self.config.adaptive_batching {
+            Some(adaptive) => console!(
+                "   📦 Batch size: {} adaptive ({}-{}, target {:.1}s per load)",
+                self.batch_size(),
+                adaptive.min_batch_size,
+                adaptive.max_batch_size,
+                adaptive.target_commit.as_secs_f64()
+            ),
+            None => console!("   📦 Batch size: {}", self.config.batch_size),
+        }
+        console!("   ⚡ Concurrency: {}", self.config.concurrency);
+        if let Some(requests_per_second) = self.rpc_client.rate_limit() {
+            console!("   🪣 RPC rate limit: {} requests/sec across all workers", requests_per_second);
+        }
+        console!("   🏷️  Job: {}", self.config.job_name);
+        if self.config.registry_reload_batches > 0 {
+            console!("   📚 Registry reload: every {} batches", self.config.registry_reload_batches);
+        }
+        if self.config.bulk_load {
+            console!("   🚚 Bulk load: binary COPY");
+        }
+        if self.config.signatures_only {
+            console!("   ✍️  Signatures only: no transaction details or classification (transaction_signatures)");
+        }
+        match self.database.raw_storage() {
+            RawDataStorage::Json => {}
+            RawDataStorage::Compressed => {
+                console!("   🗜️  Raw transaction JSON: zstd-compressed (raw_data_compressed)")
+            }
+            RawDataStorage::Omitted => console!("   🗜️  Raw transaction JSON: not stored"),
+        }
+        match self.database.on_conflict() {
+            ConflictMode::Update => {}
+            ConflictMode::Skip => console!("   ⏭️  Already stored rows: left untouched"),
+            ConflictMode::Error => console!("   🛑 Already stored rows: fail the batch"),
+        }
+        if self.config.skip_votes {
+            console!("   🗳️  Skipping vote transactions");
+        }
+        match self.config.status_filter {
+            Some(StatusFilter::Successful) => console!("   🚦 Keeping successful transactions only"),
+            Some(StatusFilter::Failed) => console!("   🚦 Keeping failed transactions only"),
+            None => {}
+        }
+        if let Some(sampler) = self.config.sampler {
+            console!("   🎲 Sampling {}% of transactions (seed {})", sampler.rate() * 100.0, sampler.seed());
+        }
+        if self.config.skip_existing {
+            let check = if self.config.verify_tx_count { "stored transactions verified" } else { "by slot" };
+            console!("   ♻️  Skipping blocks already stored ({})", check);
+        }
+        if let Some(programs) = &self.config.program_filter {
+            console!("   🔎 Program filter: {} programs", programs.len());
+        }
+        if let Some(limit) = self.config.max_transactions_per_block {
+            let action = if limit.truncate { "truncating" } else { "skipping" };
+            console!("   🐘 Max transactions per block: {} ({} larger blocks)", limit.max_transactions, action);
+        }
+        if self.config.dry_run {
+            console!("   🧪 Dry run: extract and classify only, no database writes");
+        }
+        match &self.config.export {
+            Some(ExportTarget::Parquet { output_dir }) => console!("   🗂️  Parquet export: {}", output_dir.display()),
+            Some(ExportTarget::Csv { path }) => console!("   🗂️  CSV export: {}", path.display()),
+            None => {}
+        }
+    }
+
+    /// Process a slot range in batches, recording results in `stats`
+    ///
+    /// Extraction and loading overlap: one half fetches batches and queues them on a bounded
+    /// channel while the other loads the previous ones, so neither the RPC nor the database sits
+    /// idle. At most `LOAD_QUEUE_DEPTH` extracted batches wait for their load. A batch that fails
+    /// to load stops the range: the extraction in flight is cancelled and the queued batches are
+    /// dropped, so the checkpoint stays at the last loaded batch and a resumed run (or the next
+    /// continuous pass) starts again from the failed one.
+    async fn process_range(&self, start_slot: u64, end_slot: u64, stats: &mut PipelineStats) {
+        if start_slot > end_slot {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel(LOAD_QUEUE_DEPTH);
+        tokio::join!(
+            self.extract_range(start_slot, end_slot, sender),
+            self.load_range(start_slot, end_slot, receiver, stats)
+        );
+    }
+
+    /// Extracting half of `process_range`: fetch the batches in slot order and queue them
+    ///
+    /// Stops at the end of the range, on shutdown, or as soon as the loading half hangs up.
+    async fn extract_range(&self, start_slot: u64, end_slot: u64, sender: mpsc::Sender<ExtractedBatch>) {
+        let mut current_slot = start_slot;
+        let mut batches_queued = 0;
+
+        while current_slot <= end_slot {
+            // Stop queuing new batches once shutdown is requested
+            if self.shutdown.is_cancelled() {
+                tracing::info!("Shutdown requested, stopping before slot {}", current_slot);
+                break;
+            }
+
+            let batch_end = std::cmp::min(current_slot + self.batch_size() as u64 - 1, end_slot);
+
+            // One registry snapshot for the whole batch (extract stats, load and export)
+            let registry = self.program_registry.clone().read_owned().await;
+            let mut batch_stats = PipelineStats::new();
+            let result = tokio::select! {
+                _ = sender.closed() => break,
+                result = self.extract_blocks(current_slot, batch_end, &registry, &mut batch_stats) => result,
+            };
+
+            let batch =
+                ExtractedBatch { start_slot: current_slot, end_slot: batch_end, result, stats: batch_stats, registry };
+            if sender.send(batch).await.is_err() {
+                break;
+            }
+
+            // Reloading waits for the queued batches to release their snapshots
+            batches_queued += 1;
+            self.reload_registry_if_due(batches_queued).await;
+
+            current_slot = batch_end + 1;
+        }
+    }
+
+    /// Loading half of `process_range`: load the queued batches in order and commit their progress
+    async fn load_range(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+        mut receiver: mpsc::Receiver<ExtractedBatch>,
+        stats: &mut PipelineStats,
+    ) {
+        let total_slots = end_slot - start_slot + 1;
+
+        while let Some(batch) = receiver.recv().await {
+            stats.merge(batch.stats);
+
+            let (blocks, metrics) = match batch.result {
+                Ok(extracted) => extracted,
                 Err(e) => {
-                    tracing::error!("Batch processing failed for slots {}-{}: {}", current_slot, batch_end, e);
+                    // `extract_blocks` reported the error and queued the slots in `failed_slots`, so
+                    // moving the checkpoint past them does not lose them
+                    tracing::error!("Batch processing failed for slots {}-{}: {}", batch.start_slot, batch.end_slot, e);
+                    continue;
+                }
+            };
+
+            if !blocks.is_empty() {
+                if let Err(e) = self.load_blocks(&blocks, &batch.registry, stats).await {
+                    tracing::error!(
+                        "Batch processing failed for slots {}-{}, stopping the range: {:#}",
+                        batch.start_slot,
+                        batch.end_slot,
+                        e
+                    );
                     stats.errors.push(PipelineError {
-                        stage: PipelineStage::Extract,
-                        slot: Some(current_slot),
+                        stage: PipelineStage::Load,
+                        slot: Some(batch.start_slot),
                         message: format!("Batch failed: {}", e),
                         retryable: true,
                     });
+                    break;
                 }
             }
 
-            current_slot = batch_end + 1;
+            // The batch is committed: record it before an export can fail, so a resume does not
+            // load it again
+            self.commit_progress(batch.end_slot, stats).await;
+            self.record_batch_metrics(&metrics).await;
+
+            if !blocks.is_empty() {
+                if let Err(e) = self.export_outputs(batch.start_slot, batch.end_slot, &blocks, &batch.registry) {
+                    tracing::error!(
+                        "Export failed for slots {}-{} (loaded), stopping the range: {:#}",
+                        batch.start_slot,
+                        batch.end_slot,
+                        e
+                    );
+                    stats.errors.push(PipelineError {
+                        stage: PipelineStage::Export,
+                        slot: Some(batch.start_slot),
+                        message: format!("Export failed: {}", e),
+                        retryable: true,
+                    });
+                    break;
+                }
+            }
+            drop(batch.registry);
+
+            let slots_done = batch.end_slot - start_slot + 1;
+            let progress = ((slots_done as f64 / total_slots as f64) * 100.0) as usize;
+            if !output::console_enabled() {
+                tracing::info!(
+                    slots_done,
+                    total_slots,
+                    succeeded = stats.blocks_succeeded,
+                    failed = stats.blocks_failed,
+                    skipped = stats.slots_skipped,
+                    "Pipeline progress"
+                );
+            } else {
+                console!(
+                    "   📊 Progress: {}/{} slots ({}%) | ✅ {} succeeded | ❌ {} failed | ⏭️  {} skipped",
+                    slots_done,
+                    total_slots,
+                    progress,
+                    stats.blocks_succeeded,
+                    stats.blocks_failed,
+                    stats.slots_skipped
+                );
+            }
+
+            // Finish the batch being loaded, leave the queued ones
+            if self.shutdown.is_cancelled() {
+                break;
+            }
         }
+    }
 
-        stats.elapsed_time = start_time.elapsed();
+    /// Re-check `start..=end` for forks and reload the slots whose canonical block changed
+    async fn recheck_window(&self, start_slot: u64, end_slot: u64, stats: &mut PipelineStats) {
+        let reload = self.check_reorgs(start_slot, end_slot, stats).await;
+        if reload.is_empty() {
+            return;
+        }
 
-        println!("\n✅ Pipeline complete!");
-        self.print_final_stats(&stats);
+        console!("🔀 Reloading {} slots changed by a fork", reload.len());
+        // Reloaded slots are behind the followed tip; keep reporting the tip as processed
+        let last_processed_slot = stats.last_processed_slot;
+        if let Err(e) = self.process_slots(&reload, stats).await {
+            tracing::error!("Failed to reload slots changed by a fork: {:#}", e);
+            stats.errors.push(PipelineError {
+                stage: PipelineStage::Load,
+                slot: reload.first().copied(),
+                message: format!("Fork reload failed: {}", e),
+                retryable: true,
+            });
+        }
+        stats.last_processed_slot = last_processed_slot;
+    }
 
-        Ok(stats)
+    /// Compare the stored blocks in `start..=end` with the chain and delete the orphaned ones
+    ///
+    /// Below `finalized` commitment a fork can replace a stored block (another blockhash at the
+    /// same slot), drop it (the slot ends up skipped) or produce a block in a slot that was
+    /// skipped. Stored blocks are fetched again with signatures only and compared by blockhash;
+    /// orphaned ones are deleted with their transactions. Returns the slots whose canonical block
+    /// must be loaded. Nothing is checked in dry-run mode, as nothing was stored.
+    async fn check_reorgs(&self, start_slot: u64, end_slot: u64, stats: &mut PipelineStats) -> Vec<u64> {
+        if self.config.dry_run {
+            return Vec::new();
+        }
+
+        let stored = match self.database.load_stored_blocks(start_slot, end_slot).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!("Failed to load stored blocks {}-{} for a fork check: {:#}", start_slot, end_slot, e);
+                return Vec::new();
+            }
+        };
+        let produced: HashSet<u64> = match self.rpc_client.get_blocks(start_slot, end_slot).await {
+            Ok(produced) => produced.into_iter().collect(),
+            Err(e) => {
+                tracing::warn!("Failed to check slots {}-{} for forks: {}", start_slot, end_slot, e);
+                return Vec::new();
+            }
+        };
+
+        let stored_slots: HashSet<u64> = stored.iter().map(|b| b.slot as u64).collect();
+        let mut reload: Vec<u64> = produced.difference(&stored_slots).copied().collect();
+        let mut orphaned = Vec::new();
+
+        let mut fetched = futures::stream::iter(stored.iter().filter(|b| produced.contains(&(b.slot as u64))))
+            .map(|block| async move { (block, self.rpc_client.fetch_block_signatures(block.slot as u64).await) })
+            .buffered(self.config.concurrency.max(1));
+        while let Some((block, result)) = fetched.next().await {
+            match result {
+                Ok(chain) if chain.blockhash != block.blockhash => {
+                    orphaned.push(block.slot as u64);
+                    reload.push(block.slot as u64);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to re-check block {} for a fork: {:#}", block.slot, e),
+            }
+        }
+        drop(fetched);
+
+        let dropped: Vec<u64> = stored_slots.iter().filter(|slot| !produced.contains(slot)).copied().collect();
+        if !dropped.is_empty() {
+            if let Err(e) = self.database.record_skipped_slots(&dropped).await {
+                tracing::warn!("Failed to record slots dropped by a fork as skipped: {:#}", e);
+            }
+        }
+
+        for slot in orphaned.into_iter().chain(dropped) {
+            match self.database.delete_block(slot).await {
+                Ok(true) => {
+                    tracing::warn!(slot, "Deleted block orphaned by a fork");
+                    stats.blocks_reorged += 1;
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to delete orphaned block {}: {:#}", slot, e),
+            }
+        }
+
+        reload.sort_unstable();
+        reload
+    }
+
+    /// Insert the `processing_runs` row of this run, None in dry-run mode or when it cannot be written
+    async fn record_run_start(&self, start_slot: u64, end_slot: u64) -> Option<i64> {
+        if self.config.dry_run {
+            return None;
+        }
+        match self.database.record_run_start(&self.config.job_name, start_slot, end_slot).await {
+            Ok(run_id) => Some(run_id),
+            Err(e) => {
+                tracing::warn!("Failed to record run start: {:#}", e);
+                None
+            }
+        }
     }
 
-    /// Process a batch of blocks
-    async fn process_batch(&self, start_slot: u64, end_slot: u64, stats: &mut PipelineStats) -> Result<()> {
-        // Extract: Fetch blocks from RPC
-        let blocks = self.extract_blocks(start_slot, end_slot, stats).await?;
+    /// Complete the `processing_runs` row of this run with its outcome so far
+    async fn record_run_finish(&self, run_id: Option<i64>, end_slot: u64, stats: &PipelineStats) {
+        let Some(run_id) = run_id else { return };
+        let outcome = RunOutcome {
+            blocks_succeeded: stats.blocks_succeeded as u64,
+            blocks_failed: stats.blocks_failed as u64,
+            transactions_inserted: stats.transactions_inserted as u64,
+            success_rate: stats.success_rate(),
+        };
+        if let Err(e) = self.database.record_run_finish(run_id, end_slot, &outcome).await {
+            tracing::warn!("Failed to record the end of run {}: {:#}", run_id, e);
+        }
+    }
 
-        if blocks.is_empty() {
-            return Ok(());
+    /// Write the summary of the run to `stats_out`, when set
+    fn write_stats_out(&self, start_slot: u64, end_slot: u64, stats: &PipelineStats) {
+        let Some(path) = &self.config.stats_out else { return };
+        if let Err(e) = RunSummary::new(&self.config, start_slot, end_slot, stats).write_to(path) {
+            tracing::warn!("{:#}", e);
+        }
+    }
+
+    /// End slot of a run that followed the chain past its configured range
+    fn followed_end_slot(&self, stats: &PipelineStats) -> u64 {
+        stats.last_processed_slot.map_or(self.config.end_slot, |slot| slot.max(self.config.end_slot))
+    }
+
+    /// Fixed delay between block requests, none when the RPC client's token bucket paces them
+    fn request_delay_ms(&self) -> u64 {
+        if self.rpc_client.rate_limit().is_some() {
+            0
+        } else {
+            REQUEST_DELAY_MS
+        }
+    }
+
+    /// Record a successfully loaded batch ending at `slot`
+    async fn commit_progress(&self, slot: u64, stats: &mut PipelineStats) {
+        stats.last_processed_slot = Some(slot);
+        stats.batches_processed += 1;
+
+        // Record progress so an interrupted run can resume after this batch
+        if !self.config.dry_run {
+            if let Err(e) = self.database.save_checkpoint(&self.config.job_name, slot).await {
+                tracing::warn!("Failed to save checkpoint at slot {}: {}", slot, e);
+            }
+        }
+    }
+
+    /// Append the metrics of a committed batch to `metrics_timeseries`, except in dry-run mode
+    async fn record_batch_metrics(&self, metrics: &BatchMetrics) {
+        if self.config.dry_run {
+            return;
         }
+        if let Err(e) = self.database.record_batch_metrics(&self.config.job_name, metrics).await {
+            tracing::warn!("Failed to record batch metrics: {:#}", e);
+        }
+    }
 
-        // Transform: Classification happens during load (already implemented)
-        // No explicit transform step needed as it's integrated
+    /// Slots per batch: fixed, or the latest size chosen by adaptive batching
+    fn batch_size(&self) -> usize {
+        self.batch_size.load(Ordering::Relaxed)
+    }
+
+    /// Resize the next batches after a successful load, when adaptive batching is on
+    fn adapt_batch_size(&self, load_time: Duration) {
+        let Some(adaptive) = self.config.adaptive_batching else {
+            return;
+        };
+
+        let current = self.batch_size();
+        let next = adaptive.next_batch_size(current, load_time);
+        if next != current {
+            tracing::debug!("Batch size {} -> {} after a {:.2}s load", current, next, load_time.as_secs_f64());
+            self.batch_size.store(next, Ordering::Relaxed);
+        }
+    }
 
-        // Load: Batch insert into database
-        self.load_blocks(&blocks, stats).await?;
+    /// Pick up programs added to the registry table since the last reload, every `registry_reload_batches`
+    async fn reload_registry_if_due(&self, batches: usize) {
+        let reload_every = self.config.registry_reload_batches;
+        if reload_every > 0 && batches > 0 && batches.is_multiple_of(reload_every) {
+            if let Err(e) = self.reload_registry().await {
+                tracing::warn!("Keeping the current program registry: {:#}", e);
+            }
+        }
+    }
+
+    /// Write loaded blocks to the configured export target and NDJSON output
+    ///
+    /// Runs after the batch's progress is committed, and its errors are reported under
+    /// `PipelineStage::Export`, so a failed export never makes a committed batch look unloaded.
+    fn export_outputs(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+    ) -> Result<()> {
+        // Export: Write the batch to files as well
+        if let Some(target) = &self.config.export {
+            self.export_blocks(target, start_slot, end_slot, blocks, registry)?;
+        }
+
+        if self.config.ndjson {
+            output::write_ndjson(blocks, registry, self.classifier.as_ref())?;
+        }
 
         Ok(())
     }
 
-    /// Extract blocks with retry logic
+    /// Load a block delivered by the block subscription as a batch of its own
+    async fn process_streamed_block(&self, block: ExtractedBlock, stats: &mut PipelineStats) {
+        let slot = block.slot;
+        stats.blocks_attempted += 1;
+        stats.blocks_succeeded += 1;
+        stats.transactions_processed += block.transactions.len();
+
+        let blocks = [block];
+        let registry = self.program_registry.read().await;
+        let result = self.load_blocks(&blocks, &registry, stats).await;
+
+        match result {
+            Ok(()) => {
+                self.commit_progress(slot, stats).await;
+                if let Err(e) = self.export_outputs(slot, slot, &blocks, &registry) {
+                    tracing::error!("Failed to export streamed block {} (loaded): {:#}", slot, e);
+                    stats.errors.push(PipelineError {
+                        stage: PipelineStage::Export,
+                        slot: Some(slot),
+                        message: format!("Streamed block export failed: {}", e),
+                        retryable: true,
+                    });
+                }
+                if !output::console_enabled() {
+                    tracing::info!(
+                        slot,
+                        succeeded = stats.blocks_succeeded,
+                        failed = stats.blocks_failed,
+                        skipped = stats.slots_skipped,
+                        "Streamed block loaded"
+                    );
+                } else {
+                    console!(
+                        "   📡 Slot {} loaded | ✅ {} succeeded | ❌ {} failed | ⏭️  {} skipped",
+                        slot,
+                        stats.blocks_succeeded,
+                        stats.blocks_failed,
+                        stats.slots_skipped
+                    );
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to load streamed block {}: {:#}", slot, e);
+                stats.errors.push(PipelineError {
+                    stage: PipelineStage::Load,
+                    slot: Some(slot),
+                    message: format!("Streamed block failed: {}", e),
+                    retryable: true,
+                });
+            }
+        }
+        drop(registry);
+
+        self.reload_registry_if_due(stats.batches_processed).await;
+    }
+
+    /// Export a batch of blocks to the configured file target
+    fn export_blocks(
+        &self,
+        target: &ExportTarget,
+        start_slot: u64,
+        end_slot: u64,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+    ) -> Result<()> {
+        match target {
+            ExportTarget::Parquet { output_dir } => {
+                let path = output_dir.join(export::parquet_file_name(start_slot, end_slot));
+                export::export_blocks_to_parquet(blocks, &path, registry, self.classifier.as_ref())?;
+            }
+            ExportTarget::Csv { path } => {
+                export::append_transactions_to_csv(blocks, registry, self.classifier.as_ref(), path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extract blocks with retry logic, along with the metrics of the batch
     async fn extract_blocks(
         &self,
         start_slot: u64,
         end_slot: u64,
+        registry: &ProgramRegistry,
         stats: &mut PipelineStats,
-    ) -> Result<Vec<ExtractedBlock>> {
+    ) -> Result<(Vec<ExtractedBlock>, BatchMetrics)> {
         let mut retry_count = 0;
+        let existing_slots = self.find_existing_slots(start_slot, end_slot).await;
 
         loop {
-            stats.blocks_attempted += (end_slot - start_slot + 1) as usize;
+            let result = if self.config.concurrency > 1 {
+                crate::etl::extract::extract_block_range_concurrent(
+                    &self.rpc_client,
+                    start_slot,
+                    end_slot,
+                    self.config.concurrency,
+                    self.request_delay_ms(),
+                    Some(registry),
+                    self.classifier.as_ref(),
+                    self.config.skip_votes,
+                    self.config.status_filter,
+                    self.config.sampler,
+                    self.config.program_filter.as_ref(),
+                    self.config.max_transactions_per_block,
+                    existing_slots.as_ref(),
+                )
+                .await
+            } else {
+                crate::etl::extract::extract_block_range(
+                    &self.rpc_client,
+                    start_slot,
+                    end_slot,
+                    self.request_delay_ms(),
+                    Some(registry),
+                    self.classifier.as_ref(),
+                    self.config.skip_votes,
+                    self.config.status_filter,
+                    self.config.sampler,
+                    self.config.program_filter.as_ref(),
+                    self.config.max_transactions_per_block,
+                    existing_slots.as_ref(),
+                )
+                .await
+            };
 
-            match crate::etl::extract::extract_block_range(
-                &self.rpc_client,
-                start_slot,
-                end_slot,
-                100,
-                Some(&self.program_registry),
-            )
-            .await
-            {
-                Ok((blocks, _extract_stats)) => {
+            match result {
+                Ok((blocks, extract_stats)) => {
+                    // Skipped slots never produced a block, so they are neither attempted nor failed
+                    stats.blocks_attempted += (extract_stats.blocks_fetched + extract_stats.blocks_failed) as usize;
+                    stats.blocks_failed += extract_stats.blocks_failed as usize;
+                    stats.slots_skipped += extract_stats.skipped_slots as usize;
+                    stats.blocks_existing += extract_stats.existing_slots as usize;
                     stats.blocks_succeeded += blocks.len();
+                    self.record_extract_failures(&extract_stats.failed_slots).await;
 
                     // Count transactions
                     let tx_count: usize = blocks.iter().map(|b| b.transactions.len()).sum();
                     stats.transactions_processed += tx_count;
+                    stats.transactions_sampled_out += extract_stats.sampled_out as usize;
+                    stats.blocks_oversized += extract_stats.oversized_blocks as usize;
+                    stats.blocks_unavailable += extract_stats.unavailable_blocks as usize;
 
-                    return Ok(blocks);
+                    let metrics = BatchMetrics::from_extraction(start_slot, end_slot, &extract_stats);
+                    return Ok((blocks, metrics));
+                }
+                Err(e) if !e.is_retryable() => {
+                    // An invalid range or a missing block is the same answer on every attempt; queue
+                    // the slots anyway, so the range is not lost once later batches move the checkpoint
+                    let failed_slots = self.produced_slots(start_slot, end_slot).await;
+                    stats.blocks_attempted += failed_slots.len();
+                    stats.blocks_failed += failed_slots.len();
+                    stats.errors.push(PipelineError {
+                        stage: PipelineStage::Extract,
+                        slot: Some(start_slot),
+                        message: format!("Non-retryable extract error: {}", e),
+                        retryable: false,
+                    });
+                    self.record_failed_slots(
+                        failed_slots,
+                        PipelineStage::Extract,
+                        &format!("{:#}", e),
+                        retry_count as u32 + 1,
+                    )
+                    .await;
+                    return Err(anyhow::Error::new(e).context("Failed to extract blocks (not retryable)"));
                 }
                 Err(e) => {
                     retry_count += 1;
 
                     if retry_count >= self.config.max_retries {
-                        stats.blocks_failed += (end_slot - start_slot + 1) as usize;
+                        let failed_slots = self.produced_slots(start_slot, end_slot).await;
+                        stats.blocks_attempted += failed_slots.len();
+                        stats.blocks_failed += failed_slots.len();
                         stats.errors.push(PipelineError {
                             stage: PipelineStage::Extract,
                             slot: Some(start_slot),
                             message: format!("Max retries exceeded: {}", e),
-                            retryable: false,
+                            retryable: true,
                         });
-                        return Err(e.context(format!(
+                        self.record_failed_slots(
+                            failed_slots,
+                            PipelineStage::Extract,
+                            &format!("{:#}", e),
+                            retry_count as u32,
+                        )
+                        .await;
+                        return Err(anyhow::Error::new(e).context(format!(
                             "Failed to extract blocks {}-{} after {} retries",
                             start_slot, end_slot, retry_count
                         )));
@@ -240,29 +1584,87 @@ impl Pipeline {
                         e
                     );
 
-                    tokio::time::sleep(self.config.retry_delay * retry_count as u32).await;
+                    // Jittered, so workers that failed together do not retry in lockstep
+                    tokio::time::sleep(jittered(self.config.retry_delay * retry_count as u32)).await;
                 }
             }
         }
     }
 
-    /// Load blocks into database with retry logic
-    async fn load_blocks(&self, blocks: &[ExtractedBlock], stats: &mut PipelineStats) -> Result<()> {
+    /// Slots of `start..=end` that produced a block, for a range whose extraction gave up
+    ///
+    /// Keeps slots skipped by their leader out of the failure counts and the failed-slot queue.
+    /// When `getBlocks` still does not answer the whole range is returned; `retry-failed` moves
+    /// its skipped slots out of the queue later.
+    async fn produced_slots(&self, start_slot: u64, end_slot: u64) -> Vec<u64> {
+        match self.rpc_client.get_blocks(start_slot, end_slot).await {
+            Ok(slots) => slots,
+            Err(e) => {
+                tracing::warn!(
+                    "Could not list the blocks of slots {}-{}, recording all of them: {}",
+                    start_slot,
+                    end_slot,
+                    e
+                );
+                (start_slot..=end_slot).collect()
+            }
+        }
+    }
+
+    /// Blocks in the range already stored in the database, when `skip_existing` is set
+    ///
+    /// Loaded once per batch rather than per block. If the lookup fails, nothing is skipped.
+    async fn find_existing_slots(&self, start_slot: u64, end_slot: u64) -> Option<HashSet<u64>> {
+        if !self.config.skip_existing {
+            return None;
+        }
+
+        match self.database.find_existing_slots(start_slot, end_slot, self.config.verify_tx_count).await {
+            Ok(slots) => Some(slots),
+            Err(e) => {
+                tracing::warn!("Fetching every block of {}-{}: {:#}", start_slot, end_slot, e);
+                None
+            }
+        }
+    }
+
+    /// Load blocks into the sink, retrying transient database errors with exponential backoff
+    async fn load_blocks(
+        &self,
+        blocks: &[ExtractedBlock],
+        registry: &ProgramRegistry,
+        stats: &mut PipelineStats,
+    ) -> Result<()> {
+        if self.config.dry_run {
+            tracing::info!("Dry run: skipping load of {} blocks", blocks.len());
+            return Ok(());
+        }
+
         let mut retry_count = 0;
 
         loop {
-            match crate::etl::load::batch_insert_blocks_with_transactions(
-                self.database.pool(),
-                blocks,
-                &self.program_registry,
-            )
-            .await
-            {
+            let load_started = Instant::now();
+            match self.sink.load_blocks(blocks, registry, self.classifier.as_ref()).await {
                 Ok((blocks_inserted, txs_inserted)) => {
                     stats.transactions_inserted += txs_inserted;
+                    crate::metrics::METRICS.record_transactions_inserted(txs_inserted);
                     tracing::info!("Loaded {} blocks with {} transactions", blocks_inserted, txs_inserted);
+                    self.adapt_batch_size(load_started.elapsed());
                     return Ok(());
                 }
+                Err(e) if !crate::etl::load::is_retryable_load_error(&e) => {
+                    // A constraint violation or bad data fails the same way on every attempt
+                    stats.errors.push(PipelineError {
+                        stage: PipelineStage::Load,
+                        slot: blocks.first().map(|b| b.slot),
+                        message: format!("Non-retryable load error: {:#}", e),
+                        retryable: false,
+                    });
+                    let slots = blocks.iter().map(|b| b.slot);
+                    self.record_failed_slots(slots, PipelineStage::Load, &format!("{:#}", e), retry_count as u32 + 1)
+                        .await;
+                    return Err(e.context("Failed to load blocks (not retryable)"));
+                }
                 Err(e) => {
                     retry_count += 1;
 
@@ -271,41 +1673,455 @@ impl Pipeline {
                             stage: PipelineStage::Load,
                             slot: blocks.first().map(|b| b.slot),
                             message: format!("Max retries exceeded: {}", e),
-                            retryable: false,
+                            retryable: true,
                         });
+                        let slots = blocks.iter().map(|b| b.slot);
+                        self.record_failed_slots(slots, PipelineStage::Load, &format!("{:#}", e), retry_count as u32)
+                            .await;
                         return Err(e.context(format!("Failed to load blocks after {} retries", retry_count)));
                     }
 
                     tracing::warn!("Load failed, retrying ({}/{}): {}", retry_count, self.config.max_retries, e);
 
-                    tokio::time::sleep(self.config.retry_delay * retry_count as u32).await;
+                    // Jittered exponential backoff, so a deadlock or failover has time to clear
+                    tokio::time::sleep(jittered(self.config.retry_delay * 2u32.pow(retry_count as u32 - 1))).await;
                 }
             }
         }
     }
 
-    /// Print final statistics
+    /// Add slots to the `failed_slots` queue, so `retry-failed` can re-attempt them later
+    ///
+    /// Failing to record is only logged: the batch error is already being reported.
+    async fn record_failed_slots(
+        &self,
+        slots: impl IntoIterator<Item = u64>,
+        stage: PipelineStage,
+        error: &str,
+        attempts: u32,
+    ) {
+        let failures: Vec<(u64, &str)> = slots.into_iter().map(|slot| (slot, error)).collect();
+        self.store_failed_slots(&failures, stage, attempts).await;
+    }
+
+    /// Queue the blocks an extraction could not fetch, each with its own error, in one statement
+    async fn record_extract_failures(&self, failed_slots: &[(u64, String)]) {
+        let failures: Vec<(u64, &str)> = failed_slots.iter().map(|(slot, error)| (*slot, error.as_str())).collect();
+        self.store_failed_slots(&failures, PipelineStage::Extract, 1).await;
+    }
+
+    /// Write failed slots to the queue, except in dry-run mode; errors are only logged
+    async fn store_failed_slots(&self, failures: &[(u64, &str)], stage: PipelineStage, attempts: u32) {
+        if self.config.dry_run || failures.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.database.record_failed_slots(failures, &stage.to_string(), attempts).await {
+            tracing::warn!("Failed to record {} failed slots: {:#}", failures.len(), e);
+        }
+    }
+
+    /// Print final statistics, or log them as one `tracing` event when console output is off
     fn print_final_stats(&self, stats: &PipelineStats) {
-        println!("\n📊 Pipeline Statistics:");
-        println!("   ⏱️  Total time: {:.2}s", stats.elapsed_time.as_secs_f64());
-        println!(
+        if !output::console_enabled() {
+            tracing::info!(
+                elapsed_secs = stats.elapsed_time.as_secs_f64(),
+                blocks_attempted = stats.blocks_attempted,
+                blocks_succeeded = stats.blocks_succeeded,
+                blocks_failed = stats.blocks_failed,
+                slots_skipped = stats.slots_skipped,
+                blocks_reorged = stats.blocks_reorged,
+                blocks_oversized = stats.blocks_oversized,
+                blocks_unavailable = stats.blocks_unavailable,
+                transactions_processed = stats.transactions_processed,
+                transactions_sampled_out = stats.transactions_sampled_out,
+                transactions_inserted = stats.transactions_inserted,
+                batch_size = self.batch_size(),
+                errors = stats.errors.len(),
+                "Pipeline statistics"
+            );
+            return;
+        }
+
+        console!("\n📊 Pipeline Statistics:");
+        console!("   ⏱️  Total time: {:.2}s", stats.elapsed_time.as_secs_f64());
+        console!(
             "   📦 Blocks: {} attempted, {} succeeded, {} failed",
-            stats.blocks_attempted, stats.blocks_succeeded, stats.blocks_failed
+            stats.blocks_attempted,
+            stats.blocks_succeeded,
+            stats.blocks_failed
         );
-        println!("   ✅ Success rate: {:.1}%", stats.success_rate());
-        println!("   📝 Transactions processed: {}", stats.transactions_processed);
-        println!("   💾 Transactions inserted: {}", stats.transactions_inserted);
-        println!("   ⚡ Speed: {:.2} blocks/sec", stats.blocks_per_second());
-        println!("   ⚡ Throughput: {:.0} txs/sec", stats.transactions_per_second());
+        console!("   ⏭️  Skipped slots: {}", stats.slots_skipped);
+        if stats.blocks_reorged > 0 {
+            console!("   🔀 Blocks orphaned by a fork (deleted and reloaded): {}", stats.blocks_reorged);
+        }
+        if self.config.skip_existing {
+            console!("   ♻️  Already stored (not fetched): {}", stats.blocks_existing);
+        }
+        if stats.blocks_oversized > 0 {
+            console!("   🐘 Blocks over --max-txs-per-block: {}", stats.blocks_oversized);
+        }
+        if stats.blocks_unavailable > 0 {
+            console!("   🕳️  Blocks not available from the node: {}", stats.blocks_unavailable);
+        }
+        console!("   ✅ Success rate: {:.1}%", stats.success_rate());
+        console!("   📝 Transactions processed: {}", stats.transactions_processed);
+        if self.config.sampler.is_some() {
+            console!(
+                "   🎲 Sampled: {} of {} observed",
+                stats.transactions_processed,
+                stats.transactions_processed + stats.transactions_sampled_out
+            );
+        }
+        if self.config.dry_run {
+            console!(
+                "   💾 Transactions inserted: {} (dry run, nothing written to the database)",
+                stats.transactions_inserted
+            );
+        } else {
+            console!("   💾 Transactions inserted: {}", stats.transactions_inserted);
+        }
+        if self.config.adaptive_batching.is_some() {
+            console!("   📦 Final batch size: {} slots", self.batch_size());
+        }
+        console!("   ⚡ Speed: {:.2} blocks/sec", stats.blocks_per_second());
+        console!("   ⚡ Throughput: {:.0} txs/sec", stats.transactions_per_second());
 
         if !stats.errors.is_empty() {
-            println!("\n❌ Errors encountered: {}", stats.errors.len());
+            console!("\n❌ Errors encountered: {}", stats.errors.len());
             for (i, error) in stats.errors.iter().take(5).enumerate() {
-                println!("   {}. [{}] Slot {:?}: {}", i + 1, error.stage, error.slot, error.message);
+                console!("   {}. [{}] Slot {:?}: {}", i + 1, error.stage, error.slot, error.message);
             }
             if stats.errors.len() > 5 {
-                println!("   ... and {} more errors", stats.errors.len() - 5);
+                console!("   ... and {} more errors", stats.errors.len() - 5);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Sink that records the slots it receives and fails the first call with `first_error`
+    struct RecordingSink {
+        slots: Arc<Mutex<Vec<u64>>>,
+        calls: Arc<Mutex<usize>>,
+        first_error: fn() -> anyhow::Error,
+    }
+
+    impl Default for RecordingSink {
+        fn default() -> Self {
+            Self::failing_with(|| anyhow::anyhow!("connection reset"))
+        }
+    }
+
+    impl RecordingSink {
+        fn failing_with(first_error: fn() -> anyhow::Error) -> Self {
+            Self { slots: Arc::default(), calls: Arc::default(), first_error }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BlockSink for RecordingSink {
+        async fn load_blocks(
+            &self,
+            blocks: &[ExtractedBlock],
+            _registry: &ProgramRegistry,
+            _classifier: &dyn Classifier,
+        ) -> Result<(usize, usize)> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            if *calls == 1 {
+                return Err((self.first_error)());
+            }
+
+            self.slots.lock().unwrap().extend(blocks.iter().map(|b| b.slot));
+            Ok((blocks.len(), blocks.iter().map(|b| b.transactions.len()).sum()))
+        }
+    }
+
+    fn block(slot: u64) -> ExtractedBlock {
+        ExtractedBlock {
+            slot,
+            blockhash: format!("hash{}", slot),
+            parent_slot: slot - 1,
+            block_time: None,
+            block_height: None,
+            rewards: vec![],
+            transactions: vec![],
+            filtered_transactions: 0,
+            truncated_transactions: 0,
+            transaction_count: 0,
+            signatures_only: false,
+        }
+    }
+
+    fn pipeline_with_sink(sink: RecordingSink) -> Pipeline {
+        pipeline_with_config(sink, PipelineConfig { retry_delay: Duration::ZERO, ..PipelineConfig::default() })
+    }
+
+    fn pipeline_with_config(sink: RecordingSink, config: PipelineConfig) -> Pipeline {
+        // Nothing listens there: recording failed slots fails at once instead of waiting out the
+        // default acquire timeout
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(50))
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        Pipeline::with_sink(
+            SolanaRpcClient::new("http://localhost:8899".to_string()).unwrap(),
+            Database::from_pool(pool),
+            ProgramRegistry::from_database(vec![]),
+            config,
+            Box::new(sink),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_load_blocks_retries_through_sink() {
+        let sink = RecordingSink::default();
+        let (slots, calls) = (sink.slots.clone(), sink.calls.clone());
+        let pipeline = pipeline_with_sink(sink);
+
+        let registry = ProgramRegistry::from_database(vec![]);
+        let mut stats = PipelineStats::new();
+        pipeline.load_blocks(&[block(10), block(11)], &registry, &mut stats).await.unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert_eq!(*slots.lock().unwrap(), vec![10, 11]);
+        assert!(stats.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_blocks_retries_transient_database_errors() {
+        let sink = RecordingSink::failing_with(|| anyhow::Error::new(sqlx::Error::PoolTimedOut));
+        let calls = sink.calls.clone();
+        let pipeline = pipeline_with_sink(sink);
+
+        let registry = ProgramRegistry::from_database(vec![]);
+        let mut stats = PipelineStats::new();
+        pipeline.load_blocks(&[block(10)], &registry, &mut stats).await.unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert!(stats.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_blocks_fails_fast_on_bad_data() {
+        let sink = RecordingSink::failing_with(|| {
+            anyhow::Error::new(sqlx::Error::Encode("invalid JSON".into())).context("Failed to insert transaction")
+        });
+        let (slots, calls) = (sink.slots.clone(), sink.calls.clone());
+        let pipeline = pipeline_with_sink(sink);
+
+        let registry = ProgramRegistry::from_database(vec![]);
+        let mut stats = PipelineStats::new();
+        assert!(pipeline.load_blocks(&[block(10)], &registry, &mut stats).await.is_err());
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(slots.lock().unwrap().is_empty());
+        assert_eq!(stats.errors.len(), 1);
+        assert_eq!(stats.errors[0].stage, PipelineStage::Load);
+        assert!(!stats.errors[0].retryable);
+    }
+
+    #[tokio::test]
+    async fn test_load_failure_stops_the_range() {
+        let sink = RecordingSink::failing_with(|| anyhow::Error::new(sqlx::Error::Encode("invalid JSON".into())));
+        let (slots, calls) = (sink.slots.clone(), sink.calls.clone());
+        let pipeline = pipeline_with_sink(sink);
+
+        let (sender, receiver) = mpsc::channel(LOAD_QUEUE_DEPTH);
+        for (start_slot, end_slot) in [(10, 11), (12, 13)] {
+            let batch = ExtractedBatch {
+                start_slot,
+                end_slot,
+                result: Ok((vec![block(start_slot), block(end_slot)], BatchMetrics::default())),
+                stats: PipelineStats { blocks_attempted: 2, blocks_succeeded: 2, ..PipelineStats::default() },
+                registry: pipeline.program_registry.clone().read_owned().await,
+            };
+            sender.send(batch).await.unwrap();
+        }
+
+        // Recording the failed slots must not wait on the unreachable database
+        let mut stats = PipelineStats::new();
+        tokio::time::timeout(Duration::from_secs(5), pipeline.load_range(10, 13, receiver, &mut stats))
+            .await
+            .expect("load_range waited on the database");
+
+        // The second batch is never loaded and the extractor sees the channel closed
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(slots.lock().unwrap().is_empty());
+        assert!(sender.is_closed());
+        assert_eq!((stats.blocks_succeeded, stats.last_processed_slot), (2, None));
+        // Dropped batches release their registry snapshots, so a reload is not blocked
+        assert!(pipeline.program_registry.try_write().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_extract_failure_is_reported_once() {
+        let sink = RecordingSink::default();
+        let slots = sink.slots.clone();
+        let pipeline = pipeline_with_sink(sink);
+
+        // extract_blocks has already reported the failed batch (and queued its slots)
+        let extract_error = PipelineError {
+            stage: PipelineStage::Extract,
+            slot: Some(10),
+            message: "Non-retryable extract error: block not available".to_string(),
+            retryable: false,
+        };
+        let (sender, receiver) = mpsc::channel(LOAD_QUEUE_DEPTH);
+        let batches = [
+            (10, 11, Err(anyhow::anyhow!("block not available")), vec![extract_error]),
+            (12, 13, Ok((vec![block(12), block(13)], BatchMetrics::default())), vec![]),
+        ];
+        for (start_slot, end_slot, result, errors) in batches {
+            let batch = ExtractedBatch {
+                start_slot,
+                end_slot,
+                result,
+                stats: PipelineStats { errors, ..PipelineStats::default() },
+                registry: pipeline.program_registry.clone().read_owned().await,
+            };
+            sender.send(batch).await.unwrap();
+        }
+        drop(sender);
+
+        let mut stats = PipelineStats::new();
+        tokio::time::timeout(Duration::from_secs(5), pipeline.load_range(10, 13, receiver, &mut stats))
+            .await
+            .expect("load_range waited on the database");
+
+        assert_eq!(*slots.lock().unwrap(), vec![12, 13]);
+        assert_eq!(stats.last_processed_slot, Some(13));
+        assert_eq!(stats.errors.len(), 1);
+        assert!(!stats.errors[0].retryable);
+    }
+
+    #[tokio::test]
+    async fn test_export_failure_keeps_the_loaded_batch() {
+        let sink = RecordingSink::default();
+        let slots = sink.slots.clone();
+        // A directory cannot be opened as the CSV file
+        let export = Some(ExportTarget::Csv { path: std::env::temp_dir() });
+        let pipeline = pipeline_with_config(
+            sink,
+            PipelineConfig { export, retry_delay: Duration::ZERO, ..PipelineConfig::default() },
+        );
+
+        let (sender, receiver) = mpsc::channel(LOAD_QUEUE_DEPTH);
+        let batch = ExtractedBatch {
+            start_slot: 10,
+            end_slot: 11,
+            result: Ok((vec![block(10), block(11)], BatchMetrics::default())),
+            stats: PipelineStats { blocks_attempted: 2, blocks_succeeded: 2, ..PipelineStats::default() },
+            registry: pipeline.program_registry.clone().read_owned().await,
+        };
+        sender.send(batch).await.unwrap();
+        drop(sender);
+
+        let mut stats = PipelineStats::new();
+        tokio::time::timeout(Duration::from_secs(5), pipeline.load_range(10, 11, receiver, &mut stats))
+            .await
+            .expect("load_range waited on the database");
+
+        // Loaded and committed, only the export is reported as failed
+        assert_eq!(*slots.lock().unwrap(), vec![10, 11]);
+        assert_eq!(stats.last_processed_slot, Some(11));
+        assert_eq!(stats.errors.len(), 1);
+        assert_eq!(stats.errors[0].stage, PipelineStage::Export);
+    }
+
+    #[test]
+    fn test_adaptive_batch_size() {
+        let adaptive =
+            AdaptiveBatching { target_commit: Duration::from_secs(2), min_batch_size: 5, max_batch_size: 100 };
+
+        // Fast loads grow the batch, at most doubling it
+        assert_eq!(adaptive.next_batch_size(10, Duration::from_millis(1_000)), 20);
+        assert_eq!(adaptive.next_batch_size(10, Duration::from_millis(50)), 20);
+        // Slow loads shrink it, at most halving it
+        assert_eq!(adaptive.next_batch_size(40, Duration::from_millis(2_500)), 32);
+        assert_eq!(adaptive.next_batch_size(40, Duration::from_secs(30)), 20);
+        // Close to the target: unchanged
+        assert_eq!(adaptive.next_batch_size(40, Duration::from_millis(2_100)), 40);
+        // Within the bounds
+        assert_eq!(adaptive.next_batch_size(80, Duration::ZERO), 100);
+        assert_eq!(adaptive.next_batch_size(6, Duration::from_secs(60)), 5);
+    }
+
+    #[test]
+    fn test_slot_spans() {
+        let slots = [10, 11, 15, 109, 110, 5_000_000];
+        assert_eq!(slot_spans(&slots, 50), vec![&[10, 11, 15][..], &[109, 110][..], &[5_000_000][..]]);
+        assert_eq!(slot_spans(&slots, 101), vec![&[10, 11, 15, 109, 110][..], &[5_000_000][..]]);
+        assert!(slot_spans(&[], 100).is_empty());
+    }
+
+    #[test]
+    fn test_recent_slots() {
+        let mut recent = RecentSlots::new(3);
+        // Loaded with the tip at 140: 100..=108 are settled for a 32-slot overlap, 109 is not
+        for slot in [100, 108, 109] {
+            recent.record(slot, 140);
+        }
+        assert!(recent.is_settled(100, 32));
+        assert!(!recent.is_settled(109, 32));
+        assert!(!recent.is_settled(101, 32)); // Never loaded
+        assert_eq!(recent.first_unsettled(100, 110, 32), 101);
+        assert_eq!(recent.first_unsettled(108, 110, 32), 109);
+        assert_eq!(recent.first_unsettled(108, 109, 32), 109);
+
+        // Reloading moves a slot to the back; past capacity the least recently loaded goes
+        recent.record(100, 140);
+        recent.record(110, 150);
+        assert!(recent.is_settled(100, 32));
+        assert!(!recent.is_settled(108, 32));
+
+        recent.evict_below(105);
+        assert!(!recent.is_settled(100, 32));
+        assert_eq!(recent.loaded_at.len(), 2);
+
+        // A zero capacity remembers nothing
+        let mut disabled = RecentSlots::new(0);
+        disabled.record(100, 200);
+        assert!(!disabled.is_settled(100, 32));
+    }
+
+    #[test]
+    fn test_run_summary_json() {
+        let config = PipelineConfig {
+            job_name: "backfill-q4".to_string(),
+            status_filter: Some(StatusFilter::Successful),
+            ..PipelineConfig::default()
+        };
+        let stats = PipelineStats {
+            blocks_attempted: 4,
+            blocks_succeeded: 3,
+            blocks_failed: 1,
+            elapsed_time: Duration::from_millis(1500),
+            errors: vec![PipelineError {
+                stage: PipelineStage::Extract,
+                slot: Some(12),
+                message: "Batch failed: connection reset".to_string(),
+                retryable: true,
+            }],
+            ..PipelineStats::default()
+        };
+
+        let summary = serde_json::to_value(RunSummary::new(&config, 10, 13, &stats)).unwrap();
+        assert_eq!(summary["job_name"], "backfill-q4");
+        assert_eq!((summary["start_slot"].as_u64(), summary["end_slot"].as_u64()), (Some(10), Some(13)));
+        assert_eq!(summary["success_rate"], 75.0);
+        assert_eq!(summary["blocks_per_second"], 2.0);
+        // Statistics sit at the top level, durations in seconds
+        assert_eq!((summary["blocks_succeeded"].as_u64(), summary["blocks_failed"].as_u64()), (Some(3), Some(1)));
+        assert_eq!(summary["elapsed_secs"], 1.5);
+        assert_eq!(summary["errors"][0]["stage"], "Extract");
+        assert_eq!(summary["errors"][0]["slot"], 12);
+        assert_eq!(summary["config"]["retry_delay_secs"], 2.0);
+        assert_eq!(summary["config"]["status_filter"], "successful");
+    }
+}
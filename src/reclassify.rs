@@ -0,0 +1,230 @@
+/// Reclassify Module
+///
+/// Re-labels stored transactions after the classification rules or the program registry
+/// changed (the `reclassify` subcommand), without fetching anything from the RPC. Each
+/// transaction is rebuilt from its stored `raw_data` and columns, classified and analyzed as in
+/// the pipeline, and its `transaction_type`, `transaction_label` and `secondary_types` are
+/// updated in place when they changed.
+///
+/// Only what is stored can be re-derived: details read from the transaction meta that have no
+/// column of their own (a wrapped SOL unwrap, the amounts of a liquid staking operation) are
+/// missing, so a label relying on them can come out less specific than at load time.
+/// Transactions stored without raw JSON are skipped.
+use crate::{
+    db::{ClassificationUpdate, Database, StoredTransaction},
+    etl::{
+        extract::ExtractedTransaction,
+        parsers::JupiterSwap,
+        transform::{Classifier, ProgramRegistry},
+    },
+    output::{self, console},
+};
+use anyhow::Result;
+use futures::TryStreamExt;
+
+/// Stored transactions read, classified and updated at a time
+const RECLASSIFY_PAGE_SIZE: usize = 1000;
+
+/// Result of a `reclassify` run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReclassifyReport {
+    pub scanned: u64,          // Transactions stored in the range
+    pub updated: u64,          // Transactions whose type, label or secondary types changed
+    pub without_raw_data: u64, // Transactions skipped because they were stored without raw JSON
+}
+
+impl ReclassifyReport {
+    /// Print how many transactions were scanned, relabeled and skipped
+    pub fn print_summary(&self) {
+        if !output::console_enabled() {
+            tracing::info!(
+                scanned = self.scanned,
+                updated = self.updated,
+                without_raw_data = self.without_raw_data,
+                "Reclassification summary"
+            );
+            return;
+        }
+
+        console!("\n🏷️  Reclassification Summary:");
+        console!("   📦 Scanned: {}", self.scanned);
+        console!("   ✏️  Relabeled: {}", self.updated);
+        console!("   ✅ Unchanged: {}", self.scanned - self.updated - self.without_raw_data);
+        if self.without_raw_data > 0 {
+            console!("   ⚠️  Skipped without raw data: {}", self.without_raw_data);
+        }
+    }
+}
+
+/// Classify the transactions stored in `start..=end` again and update the ones whose
+/// classification changed
+///
+/// The range is read in pages (see `Database::stream_transactions_raw`) and each page's changes
+/// are written with one batched UPDATE before the next page is read, so memory stays flat on
+/// ranges of any size and an interrupted run keeps the pages it finished.
+pub async fn reclassify_transactions(
+    database: &Database,
+    registry: &ProgramRegistry,
+    classifier: &dyn Classifier,
+    start_slot: u64,
+    end_slot: u64,
+) -> Result<ReclassifyReport> {
+    console!("\n🏷️  Reclassifying stored transactions in slots {} to {}...", start_slot, end_slot);
+
+    let mut report = ReclassifyReport::default();
+    let mut pages = Box::pin(database.stream_transactions_raw(start_slot, end_slot, RECLASSIFY_PAGE_SIZE));
+
+    while let Some(page) = pages.try_next().await? {
+        report.scanned += page.len() as u64;
+
+        let mut updates = Vec::new();
+        for stored in &page {
+            let Some(transaction) = rebuild_transaction(stored) else {
+                report.without_raw_data += 1;
+                continue;
+            };
+            let details = transaction.analyze_with(classifier, registry);
+            if !is_unchanged(stored, &details) {
+                updates.push(ClassificationUpdate {
+                    signature: stored.signature.clone(),
+                    block_slot: stored.block_slot,
+                    transaction_type: details.tx_type,
+                    label: details.label,
+                    secondary_types: details.secondary_types,
+                });
+            }
+        }
+
+        report.updated += database.update_transaction_classifications(&updates).await?;
+        if let Some(last) = page.last() {
+            tracing::debug!("Reclassified {} transactions up to slot {}", report.scanned, last.block_slot);
+        }
+    }
+
+    tracing::info!(
+        "Reclassified slots {}-{}: {} transactions scanned, {} relabeled",
+        start_slot,
+        end_slot,
+        report.scanned,
+        report.updated
+    );
+    Ok(report)
+}
+
+/// Rebuild the extracted transaction a stored row was loaded from, as far as it is stored
+///
+/// None when the row has no raw JSON to classify.
+fn rebuild_transaction(stored: &StoredTransaction) -> Option<ExtractedTransaction> {
+    let raw_json = stored.raw_data.clone()?;
+
+    let jupiter_swap = match (&stored.swap_input_mint, &stored.swap_output_mint) {
+        (Some(input_mint), Some(output_mint)) => Some(JupiterSwap {
+            input_mint: input_mint.clone(),
+            output_mint: output_mint.clone(),
+            in_amount: stored.swap_in_amount.as_deref().and_then(|amount| amount.parse().ok()).unwrap_or(0),
+            out_amount: stored.swap_out_amount.as_deref().and_then(|amount| amount.parse().ok()).unwrap_or(0),
+            inferred: false,
+        }),
+        _ => None,
+    };
+
+    Some(ExtractedTransaction {
+        signature: stored.signature.clone(),
+        signers: stored.signers.clone().unwrap_or_default(),
+        transaction_index: stored.transaction_index as usize,
+        success: stored.success,
+        fee: stored.fee as u64,
+        program_ids: stored.program_ids.clone().unwrap_or_default(),
+        log_messages: stored.log_messages.clone().unwrap_or_default(),
+        jupiter_swap,
+        raw_json,
+        ..ExtractedTransaction::default()
+    })
+}
+
+/// Whether the stored classification already matches `details`
+fn is_unchanged(stored: &StoredTransaction, details: &crate::etl::transform::TransactionDetails) -> bool {
+    let secondary_types: Vec<&str> = details.secondary_types.iter().map(|t| t.as_str()).collect();
+    stored.transaction_type.as_deref() == Some(details.tx_type.as_str()) &&
+        stored.transaction_label.as_deref() == Some(details.label.as_str()) &&
+        stored.secondary_types.as_deref().unwrap_or_default() == secondary_types.as_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{etl::transform::DefaultClassifier, models::TransactionType};
+
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+
+    fn stored_transfer() -> StoredTransaction {
+        StoredTransaction {
+            signature: "sig1".to_string(),
+            block_slot: 100,
+            transaction_index: 2,
+            success: true,
+            fee: 5000,
+            transaction_type: Some("Unknown".to_string()),
+            transaction_label: Some("Unknown".to_string()),
+            secondary_types: None,
+            signers: Some(vec!["Sender111".to_string()]),
+            program_ids: Some(vec![SYSTEM_PROGRAM.to_string()]),
+            log_messages: None,
+            swap_input_mint: None,
+            swap_output_mint: None,
+            swap_in_amount: None,
+            swap_out_amount: None,
+            raw_data: Some(serde_json::json!({
+                "signatures": ["sig1"],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Sender111", "signer": true, "writable": true },
+                        { "pubkey": "Receiver111", "signer": false, "writable": true },
+                        { "pubkey": SYSTEM_PROGRAM, "signer": false, "writable": false }
+                    ],
+                    "instructions": [{
+                        "program": "system",
+                        "programId": SYSTEM_PROGRAM,
+                        "parsed": {
+                            "type": "transfer",
+                            "info": { "source": "Sender111", "destination": "Receiver111", "lamports": 1_000_000 }
+                        }
+                    }]
+                }
+            })),
+            raw_data_compressed: None,
+        }
+    }
+
+    #[test]
+    fn test_reclassify_stored_transaction() {
+        let registry = ProgramRegistry::from_database(
+            crate::etl::transform::BUILTIN_PROGRAMS
+                .iter()
+                .map(|(id, name, program_type)| crate::db::ProgramInfo {
+                    program_id: id.to_string(),
+                    program_name: name.to_string(),
+                    program_type: Some(program_type.to_string()),
+                })
+                .collect(),
+        );
+        let stored = stored_transfer();
+
+        let transaction = rebuild_transaction(&stored).unwrap();
+        assert_eq!((transaction.transaction_index, transaction.fee), (2, 5000));
+        let details = transaction.analyze_with(&DefaultClassifier, &registry);
+        assert_eq!(details.tx_type, TransactionType::SolTransfer);
+        assert!(!is_unchanged(&stored, &details));
+
+        // Stored with the current classification: nothing to update
+        let relabeled = StoredTransaction {
+            transaction_type: Some(details.tx_type.as_str().to_string()),
+            transaction_label: Some(details.label.clone()),
+            ..stored_transfer()
+        };
+        assert!(is_unchanged(&relabeled, &details));
+
+        // Loaded without raw JSON: nothing to classify
+        assert!(rebuild_transaction(&StoredTransaction { raw_data: None, ..stored_transfer() }).is_none());
+    }
+}
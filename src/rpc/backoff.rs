@@ -0,0 +1,50 @@
+/// Retry Backoff Module
+///
+/// Randomizes retry delays so concurrent workers that failed together do not all retry at
+/// the same instant and overload the RPC (or database) the moment it recovers. Delays use
+/// "equal jitter": half of the computed backoff is kept and the other half is drawn at
+/// random, so a retry still waits at least half as long as without jitter.
+use rand::Rng;
+use std::time::Duration;
+
+/// Apply equal jitter to a backoff delay, drawing from the thread-local RNG
+pub fn jittered(delay: Duration) -> Duration {
+    jittered_with(delay, &mut rand::thread_rng())
+}
+
+/// Apply equal jitter to a backoff delay, drawing from `rng`
+///
+/// Returns a delay in `[delay / 2, delay]`. Tests pass a seeded RNG for reproducible delays.
+pub fn jittered_with<R: Rng + ?Sized>(delay: Duration, rng: &mut R) -> Duration {
+    let half = delay / 2;
+    half + half.mul_f64(rng.gen::<f64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let delay = Duration::from_secs(4);
+
+        let delays: Vec<Duration> = (0..100).map(|_| jittered_with(delay, &mut rng)).collect();
+        assert!(delays.iter().all(|d| *d >= Duration::from_secs(2) && *d <= delay));
+        // Retries are spread out rather than all landing on the same delay
+        assert!(delays.iter().any(|d| *d != delays[0]));
+
+        assert_eq!(jittered_with(Duration::ZERO, &mut rng), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_jitter_is_deterministic_with_a_seeded_rng() {
+        let delay = Duration::from_millis(1500);
+        let sample = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..5).map(|_| jittered_with(delay, &mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(sample(42), sample(42));
+    }
+}
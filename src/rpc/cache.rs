@@ -0,0 +1,155 @@
+/// Block Cache
+///
+/// Keeps fetched blocks on disk, one zstd-compressed JSON file per slot, so a slot range can be
+/// fetched once and run through transform and load again without the RPC (e.g. to compare
+/// classifications). `fetch_block` and `fetch_blocks` return a cached block instead of calling
+/// `getBlock`, and store every block they fetch. Only blocks fetched at `finalized` commitment
+/// are stored: a confirmed block can still be replaced by a fork.
+///
+/// The cache directory holds a `VERSION` file with `CACHE_FORMAT_VERSION`. A cache written with
+/// another version (other file layout or other `getBlock` options) is rejected when opened
+/// instead of being mixed with newly fetched blocks.
+use anyhow::{Context, Result};
+use solana_transaction_status::UiConfirmedBlock;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Format of the cached blocks; bump when the file layout or the `getBlock` options change
+pub const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// File holding the format version of a cache directory
+const VERSION_FILE: &str = "VERSION";
+
+/// zstd level of cached blocks: fast, and JSON blocks still shrink about tenfold
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// On-disk cache of fetched blocks keyed by slot
+#[derive(Debug, Clone)]
+pub struct BlockCache {
+    dir: PathBuf,
+}
+
+impl BlockCache {
+    /// Open the cache in `dir`, creating it when it does not exist
+    ///
+    /// Fails when the directory holds a cache of another format version, or files without a
+    /// `VERSION` file (a cache from before versioning, or an unrelated directory).
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).context(format!("Failed to create block cache directory {}", dir.display()))?;
+
+        let version_path = dir.join(VERSION_FILE);
+        match fs::read_to_string(&version_path) {
+            Ok(version) => {
+                if version.trim() != CACHE_FORMAT_VERSION.to_string() {
+                    anyhow::bail!(
+                        "Block cache {} has format version {}, expected {}: delete it or run with --no-cache",
+                        dir.display(),
+                        version.trim(),
+                        CACHE_FORMAT_VERSION
+                    );
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let mut entries =
+                    fs::read_dir(&dir).context(format!("Failed to read block cache directory {}", dir.display()))?;
+                if entries.next().is_some() {
+                    anyhow::bail!(
+                        "{} is not empty and has no {} file, so it is not a block cache of format version {}",
+                        dir.display(),
+                        VERSION_FILE,
+                        CACHE_FORMAT_VERSION
+                    );
+                }
+                fs::write(&version_path, format!("{}\n", CACHE_FORMAT_VERSION))
+                    .context(format!("Failed to write {}", version_path.display()))?;
+            }
+            Err(e) => return Err(e).context(format!("Failed to read {}", version_path.display())),
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Directory of the cache
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, slot: u64) -> PathBuf {
+        self.dir.join(format!("{}.json.zst", slot))
+    }
+
+    /// Cached block of `slot`, None when it is not cached or cannot be read
+    pub fn get(&self, slot: u64) -> Option<UiConfirmedBlock> {
+        let file = match fs::File::open(self.path(slot)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                tracing::warn!("Failed to open cached block {}: {}", slot, e);
+                return None;
+            }
+        };
+
+        let block = zstd::stream::Decoder::new(file)
+            .map_err(anyhow::Error::from)
+            .and_then(|decoder| Ok(serde_json::from_reader(decoder)?));
+        match block {
+            Ok(block) => Some(block),
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable cached block {}: {:#}", slot, e);
+                None
+            }
+        }
+    }
+
+    /// Store the block of `slot`, replacing a cached one
+    ///
+    /// The block is written to a temporary file and renamed into place, so an interrupted
+    /// write never leaves a truncated block behind.
+    pub fn put(&self, slot: u64, block: &UiConfirmedBlock) -> Result<()> {
+        let path = self.path(slot);
+        let temp_path = path.with_extension("zst.tmp");
+
+        let file = fs::File::create(&temp_path).context(format!("Failed to create {}", temp_path.display()))?;
+        let mut encoder = zstd::stream::Encoder::new(file, COMPRESSION_LEVEL)?;
+        serde_json::to_writer(&mut encoder, block).context(format!("Failed to encode block {}", slot))?;
+        encoder.finish().context(format!("Failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, &path).context(format!("Failed to move cached block to {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_cache_round_trip_and_version() {
+        let dir = std::env::temp_dir().join(format!("block-cache-{}", std::process::id()));
+        let block: UiConfirmedBlock = serde_json::from_value(serde_json::json!({
+            "previousBlockhash": "prev",
+            "blockhash": "hash",
+            "parentSlot": 99,
+            "transactions": [],
+            "rewards": [],
+            "blockTime": 1_700_000_000,
+            "blockHeight": 90
+        }))
+        .unwrap();
+
+        let cache = BlockCache::open(&dir).unwrap();
+        assert!(cache.get(100).is_none());
+        cache.put(100, &block).unwrap();
+        assert_eq!(cache.get(100).unwrap().blockhash, "hash");
+
+        // Reopening the same version keeps the blocks, another version is rejected
+        assert!(BlockCache::open(&dir).unwrap().get(100).is_some());
+        fs::write(dir.join(VERSION_FILE), "0\n").unwrap();
+        assert!(BlockCache::open(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,89 +3,1098 @@
 /// This module handles all interactions with the Solana blockchain via RPC.
 /// It wraps the Solana client and provides convenient methods for fetching
 /// block and transaction data from Helius RPC endpoints.
-use anyhow::{Context, Result};
+///
+/// Several endpoints can be configured. Requests go to the first healthy
+/// endpoint and fail over to the next one on transient errors; an endpoint
+/// that keeps returning HTTP 429 is taken out of rotation for a cooldown period.
+///
+/// Blocks are fetched at `finalized` commitment unless configured otherwise. Lower
+/// commitments reduce latency but a confirmed block can still be dropped by a fork
+/// before it finalizes, so data loaded at `confirmed` may later need to be re-checked.
+///
+/// With a requests-per-second limit (`with_rate_limit`), every request, including each call
+/// of a batch, takes a token from one bucket shared by all clones of the client, so the
+/// aggregate rate stays under the cap however many workers fetch concurrently.
+///
+/// With a block cache (`with_block_cache`), blocks are read from disk when cached and every
+/// finalized block fetched is written there, see `cache::BlockCache`.
+///
+/// All endpoints share one pooled HTTP client, tuned with `with_http_config`, see `transport::HttpConfig`.
+pub mod backoff;
+pub mod cache;
+pub mod pubsub;
+pub mod throttle;
+pub mod transport;
+
 use chrono::{DateTime, Utc};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcBlockConfig};
-use solana_transaction_status::{TransactionDetails, UiConfirmedBlock, UiTransactionEncoding};
-use std::sync::Arc;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClientConfig},
+    rpc_config::{RpcBlockConfig, RpcTransactionConfig},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED,
+        JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+    },
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionDetails, UiConfirmedBlock, UiTransactionEncoding,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{FetcherError, Result},
+    etl::parsers::TokenProgram,
+    models::{block_time_to_utc, ConnectionInfo},
+};
+use cache::BlockCache;
+use throttle::{AdaptiveThrottle, RateLimitObserver};
+use transport::HttpConfig;
+
+/// Consecutive 429 responses after which an endpoint is put on cooldown
+const RATE_LIMIT_STRIKES: u32 = 3;
+
+/// How long a rate-limited endpoint stays out of rotation
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Offset of the owner in an SPL Token account, after the 32-byte mint
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// Attempts for calls made outside the pipeline's retry loop (startup, chain tip polling)
+const CALL_ATTEMPTS: u32 = 3;
+
+/// Base delay between those attempts, multiplied by the attempt number and jittered
+const CALL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How many slots `get_connection_info` steps back when the tip has no block time yet
+const BLOCK_TIME_LOOKBACK: u64 = 10;
+
+/// Upper bound on `getBlockTime` probes in `get_slot_at_time`
+///
+/// A binary search over the whole ledger (~2^29 slots) needs about 30 probes, so the cap
+/// only stops a search whose block times are not monotonic.
+const SLOT_SEARCH_MAX_PROBES: u32 = 40;
+
+/// Start of the message nodes answer with when they do not have a block
+///
+/// Some providers and proxies relay it under a generic error code instead of -32004.
+const BLOCK_NOT_AVAILABLE_MESSAGE: &str = "Block not available for slot";
+
+/// A single RPC endpoint in the failover rotation
+struct RpcEndpoint {
+    url: String,
+    client: Arc<RpcClient>,
+    http: reqwest_middleware::ClientWithMiddleware, // Same HTTP client, for requests `RpcClient` cannot make (batches)
+    rate_limit_strikes: AtomicU32,
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl RpcEndpoint {
+    /// Endpoint sending its requests through `http_client`, whose connection pool it shares
+    fn new(url: String, http_client: reqwest::Client, throttle: Arc<AdaptiveThrottle>) -> Self {
+        let http_client =
+            reqwest_middleware::ClientBuilder::new(http_client).with(RateLimitObserver::new(throttle)).build();
+
+        let sender = HttpSender::new_with_client_with_middleware(url.clone(), http_client.clone());
+        let client = Arc::new(RpcClient::new_sender(sender, RpcClientConfig::default()));
+
+        Self { url, client, http: http_client, rate_limit_strikes: AtomicU32::new(0), cooldown_until: Mutex::new(None) }
+    }
 
-use crate::models::ConnectionInfo;
+    fn is_cooling_down(&self) -> bool {
+        let cooldown_until = self.cooldown_until.lock().unwrap();
+        cooldown_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self) {
+        self.rate_limit_strikes.store(0, Ordering::Relaxed);
+    }
+
+    fn record_rate_limited(&self) {
+        let strikes = self.rate_limit_strikes.fetch_add(1, Ordering::Relaxed) + 1;
+        if strikes >= RATE_LIMIT_STRIKES {
+            tracing::warn!(
+                "RPC endpoint {} rate-limited {} times in a row, cooling down for {}s",
+                self.url,
+                strikes,
+                RATE_LIMIT_COOLDOWN.as_secs()
+            );
+            *self.cooldown_until.lock().unwrap() = Some(Instant::now() + RATE_LIMIT_COOLDOWN);
+            self.rate_limit_strikes.store(0, Ordering::Relaxed);
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct SolanaRpcClient {
-    client: Arc<RpcClient>,
-    endpoint: String,
+    endpoints: Arc<Vec<RpcEndpoint>>,
+    throttle: Arc<AdaptiveThrottle>,
+    commitment: CommitmentConfig,
+    batch_size: usize, // Blocks per batched getBlock request, see `fetch_blocks`
+    rate_limit: Option<(u32, Arc<DefaultDirectRateLimiter>)>, // Requests per second and their shared bucket
+    block_cache: Option<BlockCache>, // Blocks read from and written to disk
+    token_decimals: Arc<Mutex<HashMap<String, u8>>>, // Mint decimals, cached for the process
+    resolve_owners: bool, // Look up token account owners missing from transfers, see `get_account_owner`
+    token_owners: Arc<Mutex<HashMap<String, String>>>, // Token account owners, cached for the process
+    signatures_only: bool, // Fetch blocks without transaction details, see `with_signatures_only`
 }
 
 impl SolanaRpcClient {
     /// Create a new RPC client connected to the specified endpoint
+    #[allow(dead_code)]
     pub fn new(endpoint: String) -> Result<Self> {
-        let client = Arc::new(RpcClient::new(endpoint.clone()));
+        Self::with_endpoints(vec![endpoint])
+    }
+
+    /// Create a new RPC client that fails over between several endpoints
+    ///
+    /// Endpoints are tried in the given order, so the first one acts as the primary.
+    pub fn with_endpoints(endpoints: Vec<String>) -> Result<Self> {
+        Self::with_http_config(endpoints, HttpConfig::default())
+    }
+
+    /// Create a new RPC client over several endpoints with a tuned HTTP transport
+    ///
+    /// One HTTP client is built from `http_config` and shared by every endpoint, so all
+    /// requests draw from a single pool of kept-alive connections (`--http-timeout`,
+    /// `--http-pool-size`).
+    pub fn with_http_config(endpoints: Vec<String>, http_config: HttpConfig) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(FetcherError::InvalidInput("At least one RPC endpoint is required".to_string()));
+        }
+
+        let throttle = Arc::new(AdaptiveThrottle::new());
+        let http_client = http_config.build_client()?;
+        let endpoints = endpoints
+            .into_iter()
+            .map(|url| RpcEndpoint::new(url, http_client.clone(), throttle.clone()))
+            .collect::<Vec<_>>();
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+            throttle,
+            commitment: CommitmentConfig::finalized(),
+            batch_size: 1,
+            rate_limit: None,
+            block_cache: None,
+            token_decimals: Arc::new(Mutex::new(HashMap::new())),
+            resolve_owners: false,
+            token_owners: Arc::new(Mutex::new(HashMap::new())),
+            signatures_only: false,
+        })
+    }
+
+    /// Set the commitment level used for slot queries and block fetches
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Get the configured commitment level
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    /// Set how many blocks range extraction requests per batched HTTP request (1 = no batching)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Get the number of blocks per batched request
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Cap the aggregate request rate at `requests_per_second` (0 = no cap)
+    ///
+    /// A token bucket that holds up to one second's worth of requests: short bursts go out
+    /// at once, sustained traffic is spread evenly.
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = std::num::NonZeroU32::new(requests_per_second)
+            .map(|rps| (rps.get(), Arc::new(RateLimiter::direct(Quota::per_second(rps)))));
+        self
+    }
 
-        Ok(Self { client, endpoint })
+    /// Requests-per-second cap set by `with_rate_limit`, if any
+    pub fn rate_limit(&self) -> Option<u32> {
+        self.rate_limit.as_ref().map(|(requests_per_second, _)| *requests_per_second)
     }
 
-    /// Get a reference to the underlying RPC client
+    /// Serve `getBlock` from an on-disk cache of blocks, storing every finalized block fetched
+    pub fn with_block_cache(mut self, cache: BlockCache) -> Self {
+        self.block_cache = Some(cache);
+        self
+    }
+
+    /// Resolve the owners of token transfer accounts over RPC during extraction (`--resolve-owners`)
+    pub fn with_owner_resolution(mut self, resolve_owners: bool) -> Self {
+        self.resolve_owners = resolve_owners;
+        self
+    }
+
+    /// Whether owner resolution is enabled, see `with_owner_resolution`
+    pub fn resolves_owners(&self) -> bool {
+        self.resolve_owners
+    }
+
+    /// Fetch blocks with their transaction signatures only (`--signatures-only`)
+    ///
+    /// `fetch_block` and `fetch_blocks` then request `transactionDetails: signatures` without
+    /// rewards, a small fraction of a full block, and bypass the block cache, which holds full
+    /// blocks only.
+    pub fn with_signatures_only(mut self, signatures_only: bool) -> Self {
+        self.signatures_only = signatures_only;
+        self
+    }
+
+    /// Whether blocks are fetched with signatures only, see `with_signatures_only`
+    pub fn signatures_only(&self) -> bool {
+        self.signatures_only
+    }
+
+    /// `getBlock` configuration of `fetch_block` and `fetch_blocks`
+    fn block_config(&self) -> RpcBlockConfig {
+        RpcBlockConfig {
+            encoding: Some(UiTransactionEncoding::JsonParsed),
+            transaction_details: Some(if self.signatures_only {
+                TransactionDetails::Signatures
+            } else {
+                TransactionDetails::Full
+            }),
+            rewards: Some(!self.signatures_only),
+            commitment: Some(self.block_commitment()),
+            max_supported_transaction_version: Some(0),
+        }
+    }
+
+    /// Block cache set by `with_block_cache`, if any
+    pub fn block_cache(&self) -> Option<&BlockCache> {
+        self.block_cache.as_ref()
+    }
+
+    /// Cached block of `slot`, when a block cache is configured and holds it
+    fn cached_block(&self, slot: u64) -> Option<UiConfirmedBlock> {
+        if self.signatures_only {
+            return None;
+        }
+        let block = self.block_cache.as_ref()?.get(slot)?;
+        tracing::debug!("Read block at slot {} from the block cache", slot);
+        Some(block)
+    }
+
+    /// Store a fetched block in the block cache, if any
+    ///
+    /// Blocks below `finalized` commitment are not stored, as a fork may still replace them.
+    fn cache_block(&self, slot: u64, block: &UiConfirmedBlock) {
+        let Some(cache) = &self.block_cache else { return };
+        if !self.block_commitment().is_finalized() || self.signatures_only {
+            return;
+        }
+        if let Err(e) = cache.put(slot, block) {
+            tracing::warn!("Failed to cache block at slot {}: {:#}", slot, e);
+        }
+    }
+
+    /// Wait for a token per request about to be sent
+    async fn acquire(&self, requests: usize) {
+        if let Some((_, limiter)) = &self.rate_limit {
+            for _ in 0..requests {
+                limiter.until_ready().await;
+            }
+        }
+    }
+
+    /// Commitment used for block queries
+    ///
+    /// `getBlock` and `getBlocks` do not support `processed`, so it is raised to `confirmed`.
+    fn block_commitment(&self) -> CommitmentConfig {
+        match self.commitment.commitment {
+            CommitmentLevel::Processed => CommitmentConfig::confirmed(),
+            _ => self.commitment,
+        }
+    }
+
+    /// Get a reference to the underlying RPC client of the primary endpoint
     #[allow(dead_code)]
     pub fn client(&self) -> &RpcClient {
-        &self.client
+        &self.endpoints[0].client
     }
 
-    /// Get the endpoint URL this client is connected to
-    #[allow(dead_code)]
+    /// Get the primary endpoint URL
     pub fn endpoint(&self) -> &str {
-        &self.endpoint
+        &self.endpoints[0].url
+    }
+
+    /// Get all configured endpoint URLs in failover order
+    pub fn endpoints(&self) -> Vec<&str> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.as_str()).collect()
+    }
+
+    /// Get the shared rate-limit throttle fed by every endpoint's HTTP responses
+    pub fn throttle(&self) -> &AdaptiveThrottle {
+        &self.throttle
+    }
+
+    /// Run an RPC call, failing over to the next endpoint on transient errors
+    ///
+    /// Endpoints on cooldown are only tried after every healthy endpoint has failed.
+    async fn call<T, F, Fut>(&self, method: &str, request: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        self.call_endpoint(method, 1, |endpoint| request(endpoint.client.clone())).await
+    }
+
+    /// Run a request against an endpoint, with the failover of `call`
+    ///
+    /// For requests that need more than the endpoint's `RpcClient`, such as batches sent
+    /// with its HTTP client. `requests` is the number of JSON-RPC calls sent, which providers
+    /// bill (and the rate limit counts) individually even inside a batch.
+    async fn call_endpoint<T, F, Fut>(&self, method: &str, requests: usize, request: F) -> ClientResult<T>
+    where
+        F: Fn(&RpcEndpoint) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let (healthy, cooling): (Vec<&RpcEndpoint>, Vec<&RpcEndpoint>) =
+            self.endpoints.iter().partition(|endpoint| !endpoint.is_cooling_down());
+
+        let mut last_error = None;
+        for endpoint in healthy.into_iter().chain(cooling) {
+            self.acquire(requests).await;
+            let started = std::time::Instant::now();
+            let result = request(endpoint).await;
+            crate::metrics::METRICS.observe_rpc_duration(started.elapsed());
+
+            match result {
+                Ok(value) => {
+                    endpoint.record_success();
+                    tracing::debug!("{} served by {}", method, endpoint.url);
+                    return Ok(value);
+                }
+                Err(err) => {
+                    if is_rate_limited(err.kind()) {
+                        endpoint.record_rate_limited();
+                    }
+                    if !is_transient(err.kind()) {
+                        return Err(err);
+                    }
+                    tracing::warn!("{} failed on {}: {}", method, endpoint.url, err);
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one endpoint is configured"))
+    }
+
+    /// Run an RPC call with failover, retrying the whole rotation when every endpoint failed transiently
+    ///
+    /// For calls that are not covered by the pipeline's retry loop, so a momentary outage
+    /// does not abort the program before any work starts.
+    async fn call_with_retry<T, F, Fut>(&self, method: &str, request: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match self.call(method, &request).await {
+                Err(err) if attempt < CALL_ATTEMPTS && is_transient(err.kind()) => {
+                    tracing::warn!("{} failed, retrying ({}/{}): {}", method, attempt, CALL_ATTEMPTS, err);
+                    tokio::time::sleep(backoff::jittered(CALL_RETRY_DELAY * attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 
     /// Get connection information for display
     pub async fn get_connection_info(&self) -> Result<ConnectionInfo> {
         // Get latest blockhash
-        let latest_blockhash = self.client.get_latest_blockhash().context("Failed to get latest blockhash")?;
+        let latest_blockhash = self
+            .call_with_retry("getLatestBlockhash", |client| async move { client.get_latest_blockhash().await })
+            .await
+            .map_err(|err| rpc_error("Failed to get latest blockhash", err, None))?;
 
-        // Get current slot
-        let slot = self.client.get_slot().context("Failed to get current slot")?;
+        // Get current slot and block height
+        let slot = self.get_latest_slot().await?;
+        let block_height = self.get_block_height().await?;
 
         // Get block time for the current slot
-        let block_time = self.client.get_block_time(slot).context("Failed to get block time")?;
+        let block_time = self.get_recent_block_time(slot).await?;
 
-        // Convert Unix timestamp to DateTime
-        let timestamp = DateTime::<Utc>::from_timestamp(block_time, 0).unwrap_or_else(Utc::now);
+        let timestamp = block_time_to_utc(Some(block_time)).unwrap_or_else(Utc::now);
 
-        Ok(ConnectionInfo { endpoint: self.endpoint.clone(), blockhash: latest_blockhash.to_string(), slot, timestamp })
+        Ok(ConnectionInfo {
+            endpoint: self.endpoint().to_string(),
+            blockhash: latest_blockhash.to_string(),
+            slot,
+            block_height,
+            timestamp,
+        })
+    }
+
+    /// Block time of the most recent slot at or before `slot` that has one
+    ///
+    /// The tip slot may have no block yet (or was skipped by its leader), which no amount of
+    /// retrying fixes, so those errors fall back to the previous slot instead.
+    async fn get_recent_block_time(&self, slot: u64) -> Result<i64> {
+        let oldest = slot.saturating_sub(BLOCK_TIME_LOOKBACK);
+        let mut candidate = slot;
+        loop {
+            match self
+                .call_with_retry("getBlockTime", |client| async move { client.get_block_time(candidate).await })
+                .await
+            {
+                Ok(block_time) => return Ok(block_time),
+                Err(err) if candidate > oldest && is_block_unavailable(err.kind()) => {
+                    tracing::debug!("No block time for slot {}, trying slot {}: {}", candidate, candidate - 1, err);
+                    candidate -= 1;
+                }
+                Err(err) => {
+                    return Err(rpc_error(
+                        format!("Failed to get block time for slot {}", candidate),
+                        err,
+                        Some(candidate),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Find the first slot whose block time is at or after `target`
+    ///
+    /// Binary-searches between the oldest slot the endpoint still serves and the latest slot.
+    /// Skipped slots take the block time of the closest earlier block, so block times never
+    /// decrease along the search. Targets outside the served range resolve to its first or last
+    /// slot. At most `SLOT_SEARCH_MAX_PROBES` block times are requested.
+    pub async fn get_slot_at_time(&self, target: DateTime<Utc>) -> Result<u64> {
+        let target = target.timestamp();
+        let first_slot = self
+            .call_with_retry("getFirstAvailableBlock", |client| async move { client.get_first_available_block().await })
+            .await
+            .map_err(|err| rpc_error("Failed to get first available block", err, None))?;
+        let latest_slot = self.get_latest_slot().await?;
+
+        if self.get_recent_block_time(latest_slot).await? < target {
+            tracing::warn!("Target time is after the latest block, using slot {}", latest_slot);
+            return Ok(latest_slot);
+        }
+
+        let slot =
+            search_slot_by_time(first_slot, latest_slot, target, |slot| self.get_recent_block_time(slot)).await?;
+        tracing::info!("Resolved timestamp {} to slot {}", target, slot);
+        Ok(slot)
     }
 
     /// Test the RPC connection
     pub async fn test_connection(&self) -> Result<()> {
-        self.client.get_version().context("Failed to connect to RPC endpoint")?;
+        self.call_with_retry("getVersion", |client| async move { client.get_version().await })
+            .await
+            .map_err(|err| rpc_error("Failed to connect to RPC endpoint", err, None))?;
         Ok(())
     }
 
     /// Fetch a single block by slot number
     pub async fn fetch_block(&self, slot: u64) -> Result<UiConfirmedBlock> {
+        if let Some(block) = self.cached_block(slot) {
+            return Ok(block);
+        }
         tracing::debug!("Fetching block at slot {}", slot);
 
+        let config = self.block_config();
         let block = self
-            .client
-            .get_block_with_config(
-                slot,
-                RpcBlockConfig {
-                    encoding: Some(UiTransactionEncoding::JsonParsed),
-                    transaction_details: Some(TransactionDetails::Full),
-                    rewards: Some(true),
-                    commitment: None,
-                    max_supported_transaction_version: Some(0),
-                },
-            )
-            .context(format!("Failed to fetch block at slot {}", slot))?;
+            .call("getBlock", |client| async move { client.get_block_with_config(slot, config).await })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to fetch block at slot {}", slot), err, Some(slot)))?;
 
         tracing::info!("Successfully fetched block at slot {}", slot);
+        self.cache_block(slot, &block);
         Ok(block)
     }
 
-    /// Get the latest confirmed slot
+    /// Fetch several blocks with one JSON-RPC batch request
+    ///
+    /// Packs one `getBlock` call per slot into a single HTTP request, saving a round trip per
+    /// block on high-latency connections. Results keep the order of `slots` and fail per slot,
+    /// with the same errors as `fetch_block` (e.g. `SlotSkipped`). The call fails as a whole when
+    /// the HTTP request does, including on providers that do not accept batch requests. Cached
+    /// blocks are left out of the request.
+    pub async fn fetch_blocks(&self, slots: &[u64]) -> Result<Vec<(u64, Result<UiConfirmedBlock>)>> {
+        let mut cached: HashMap<u64, UiConfirmedBlock> =
+            slots.iter().filter_map(|&slot| Some((slot, self.cached_block(slot)?))).collect();
+        let missing: Vec<u64> = slots.iter().copied().filter(|slot| !cached.contains_key(slot)).collect();
+
+        let mut fetched: HashMap<u64, Result<UiConfirmedBlock>> = HashMap::new();
+        if !missing.is_empty() {
+            for (slot, block) in self.fetch_blocks_batch(&missing).await? {
+                if let Ok(block) = &block {
+                    self.cache_block(slot, block);
+                }
+                fetched.insert(slot, block);
+            }
+        }
+
+        Ok(slots
+            .iter()
+            .map(|&slot| {
+                let block = cached
+                    .remove(&slot)
+                    .map(Ok)
+                    .or_else(|| fetched.remove(&slot))
+                    .unwrap_or(Err(FetcherError::BlockNotAvailable { slot }));
+                (slot, block)
+            })
+            .collect())
+    }
+
+    /// Send the batched `getBlock` request of `fetch_blocks`
+    async fn fetch_blocks_batch(&self, slots: &[u64]) -> Result<Vec<(u64, Result<UiConfirmedBlock>)>> {
+        tracing::debug!("Fetching {} blocks in one batch request", slots.len());
+
+        let config = self.block_config();
+        let requests: Vec<serde_json::Value> = slots
+            .iter()
+            .enumerate()
+            .map(|(id, slot)| RpcRequest::GetBlock.build_request_json(id as u64, serde_json::json!([slot, config])))
+            .collect();
+        let body =
+            serde_json::to_vec(&requests).map_err(|err| FetcherError::rpc("Failed to encode batch request", err))?;
+        let range = format!("{}-{}", slots.first().unwrap_or(&0), slots.last().unwrap_or(&0));
+
+        let responses = self
+            .call_endpoint("getBlock (batch)", slots.len(), |endpoint| {
+                let request = endpoint
+                    .http
+                    .post(&endpoint.url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.clone());
+                async move {
+                    let response = request.send().await?.error_for_status()?;
+                    Ok(response.json::<Vec<serde_json::Value>>().await?)
+                }
+            })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to fetch blocks {} in a batch", range), err, None))?;
+
+        let mut by_id: HashMap<u64, serde_json::Value> =
+            responses.into_iter().filter_map(|response| Some((response.get("id")?.as_u64()?, response))).collect();
+
+        let blocks = slots
+            .iter()
+            .enumerate()
+            .map(|(id, &slot)| (slot, parse_batch_response(slot, by_id.remove(&(id as u64)))))
+            .collect::<Vec<_>>();
+        tracing::info!(
+            "Successfully fetched {} blocks in one batch request",
+            blocks.iter().filter(|b| b.1.is_ok()).count()
+        );
+        Ok(blocks)
+    }
+
+    /// Subscribe to new blocks over WebSocket, see `pubsub::subscribe_blocks`
+    ///
+    /// Without `ws_url`, the WebSocket URL is derived from the primary endpoint. Blocks are
+    /// delivered at the block commitment (`processed` is not supported by `blockSubscribe`).
+    pub fn subscribe_blocks(
+        &self,
+        ws_url: Option<&str>,
+    ) -> Result<futures::stream::BoxStream<'static, Result<(u64, UiConfirmedBlock)>>> {
+        let ws_url = match ws_url {
+            Some(url) => url.to_string(),
+            None => pubsub::websocket_url(self.endpoint())?,
+        };
+
+        Ok(pubsub::subscribe_blocks(ws_url, self.block_commitment()))
+    }
+
+    /// Get the latest slot at the configured commitment
     pub async fn get_latest_slot(&self) -> Result<u64> {
-        let slot = self.client.get_slot().context("Failed to get latest slot")?;
+        let commitment = self.commitment;
+        let slot = self
+            .call_with_retry("getSlot", |client| async move { client.get_slot_with_commitment(commitment).await })
+            .await
+            .map_err(|err| rpc_error("Failed to get latest slot", err, None))?;
 
         Ok(slot)
     }
+
+    /// Get the current block height at the configured commitment
+    ///
+    /// The block height counts produced blocks, while the slot counts leader turns: a slot
+    /// skipped by its leader advances the slot but not the height, so the height trails the
+    /// slot by the number of skipped slots so far.
+    pub async fn get_block_height(&self) -> Result<u64> {
+        let commitment = self.commitment;
+        let block_height = self
+            .call_with_retry("getBlockHeight", |client| async move {
+                client.get_block_height_with_commitment(commitment).await
+            })
+            .await
+            .map_err(|err| rpc_error("Failed to get block height", err, None))?;
+
+        Ok(block_height)
+    }
+
+    /// Get the slots in a range that actually produced a block
+    ///
+    /// Uses the `getBlocks` RPC method. Slots skipped by their leader are not
+    /// included in the result, so callers can avoid requesting blocks that do not exist.
+    pub async fn get_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>> {
+        let commitment = self.block_commitment();
+        let slots = self
+            .call("getBlocks", |client| async move {
+                client.get_blocks_with_commitment(start_slot, Some(end_slot), commitment).await
+            })
+            .await
+            .map_err(|err| {
+                rpc_error(format!("Failed to get blocks for slots {}-{}", start_slot, end_slot), err, None)
+            })?;
+
+        tracing::debug!("Found {} produced blocks in slots {}-{}", slots.len(), start_slot, end_slot);
+        Ok(slots)
+    }
+
+    /// Fetch a block's metadata and transaction signatures, without transaction details
+    ///
+    /// Much smaller than `fetch_block`; the signature order gives each transaction's index in the block.
+    pub async fn fetch_block_signatures(&self, slot: u64) -> Result<UiConfirmedBlock> {
+        let commitment = self.block_commitment();
+        let block = self
+            .call("getBlock", |client| async move {
+                client
+                    .get_block_with_config(
+                        slot,
+                        RpcBlockConfig {
+                            encoding: None,
+                            transaction_details: Some(TransactionDetails::Signatures),
+                            rewards: Some(false),
+                            commitment: Some(commitment),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                    .await
+            })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to fetch block signatures at slot {}", slot), err, Some(slot)))?;
+
+        Ok(block)
+    }
+
+    /// Get signatures of transactions involving an address, newest first
+    ///
+    /// Uses the `getSignaturesForAddress` RPC method. Results start before the `before`
+    /// signature (or at the tip) and stop at `until`; the RPC returns at most 1,000 per call.
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let pubkey = Pubkey::from_str(address)
+            .map_err(|_| FetcherError::InvalidInput(format!("Invalid address {}", address)))?;
+        let invalid_signature = |name| move |_| FetcherError::InvalidInput(format!("Invalid `{}` signature", name));
+        let before = before.map(Signature::from_str).transpose().map_err(invalid_signature("before"))?;
+        let until = until.map(Signature::from_str).transpose().map_err(invalid_signature("until"))?;
+        let commitment = self.block_commitment();
+
+        let signatures = self
+            .call("getSignaturesForAddress", |client| async move {
+                client
+                    .get_signatures_for_address_with_config(
+                        &pubkey,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before,
+                            until,
+                            limit: Some(limit),
+                            commitment: Some(commitment),
+                        },
+                    )
+                    .await
+            })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to get signatures for address {}", address), err, None))?;
+
+        Ok(signatures)
+    }
+
+    /// Get the number of decimals of a token mint
+    ///
+    /// Uses the `getTokenSupply` RPC method. Results are cached, so each mint is looked up once per process.
+    pub async fn get_token_decimals(&self, mint: &str) -> Result<u8> {
+        if let Some(decimals) = self.token_decimals.lock().unwrap().get(mint) {
+            return Ok(*decimals);
+        }
+
+        let pubkey =
+            Pubkey::from_str(mint).map_err(|_| FetcherError::InvalidInput(format!("Invalid mint address {}", mint)))?;
+        let supply = self
+            .call("getTokenSupply", |client| async move { client.get_token_supply(&pubkey).await })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to get token supply for mint {}", mint), err, None))?;
+
+        self.token_decimals.lock().unwrap().insert(mint.to_string(), supply.decimals);
+        Ok(supply.decimals)
+    }
+
+    /// Get the wallet owning an SPL Token or Token-2022 account
+    ///
+    /// Uses the `getAccountInfo` RPC method and reads the owner field of the token account
+    /// (bytes 32..64, after the mint; Token-2022 accounts share the layout). Results are cached,
+    /// so each account is looked up once per process. Accounts closed since the transfer, such
+    /// as temporary wrapped SOL accounts, no longer exist and fail the lookup.
+    pub async fn get_account_owner(&self, token_account: &str) -> Result<String> {
+        if let Some(owner) = self.token_owners.lock().unwrap().get(token_account) {
+            return Ok(owner.clone());
+        }
+
+        let pubkey = Pubkey::from_str(token_account)
+            .map_err(|_| FetcherError::InvalidInput(format!("Invalid token account address {}", token_account)))?;
+        let account = self
+            .call("getAccountInfo", |client| async move { client.get_account(&pubkey).await })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to get token account {}", token_account), err, None))?;
+
+        if TokenProgram::from_program_id(&account.owner.to_string()).is_none() {
+            return Err(FetcherError::ParseError(format!(
+                "Account {} is owned by {}, not a token program",
+                token_account, account.owner
+            )));
+        }
+        let owner = account
+            .data
+            .get(TOKEN_ACCOUNT_OWNER_OFFSET..TOKEN_ACCOUNT_OWNER_OFFSET + 32)
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+            .ok_or_else(|| FetcherError::ParseError(format!("Account {} is not a token account", token_account)))?
+            .to_string();
+
+        self.token_owners.lock().unwrap().insert(token_account.to_string(), owner.clone());
+        Ok(owner)
+    }
+
+    /// Fetch a single transaction with JsonParsed encoding
+    ///
+    /// Uses the `getTransaction` RPC method, which answers null for a signature the node does
+    /// not know: never confirmed, or older than its ledger retention. That answer becomes
+    /// `TransactionNotFound` instead of a deserialization error.
+    pub async fn fetch_transaction(&self, signature: &str) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let parsed = Signature::from_str(signature)
+            .map_err(|_| FetcherError::InvalidInput(format!("Invalid signature {}", signature)))?;
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::JsonParsed),
+            commitment: Some(self.block_commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let params = serde_json::json!([parsed.to_string(), config]);
+
+        let transaction = self
+            .call("getTransaction", |client| {
+                let params = params.clone();
+                async move {
+                    client
+                        .send::<Option<EncodedConfirmedTransactionWithStatusMeta>>(RpcRequest::GetTransaction, params)
+                        .await
+                }
+            })
+            .await
+            .map_err(|err| rpc_error(format!("Failed to fetch transaction {}", signature), err, None))?;
+
+        transaction.ok_or_else(|| FetcherError::TransactionNotFound { signature: signature.to_string() })
+    }
+}
+
+/// The block of one `getBlock` response in a batch
+///
+/// A JSON-RPC error becomes the same `FetcherError` as for a single `getBlock` call.
+fn parse_batch_response(slot: u64, response: Option<serde_json::Value>) -> Result<UiConfirmedBlock> {
+    let context = format!("Failed to fetch block at slot {}", slot);
+    let Some(mut response) = response else {
+        return Err(FetcherError::ParseError(format!("{}: no response in the batch", context)));
+    };
+
+    if let Some(error) = response.get("error") {
+        let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or_default();
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+        let error = ClientError::from(RpcError::RpcResponseError { code, message, data: RpcResponseErrorData::Empty });
+        return Err(rpc_error(context, error, Some(slot)));
+    }
+
+    match response.get_mut("result").map(serde_json::Value::take) {
+        None | Some(serde_json::Value::Null) => Err(FetcherError::BlockNotAvailable { slot }),
+        Some(result) => {
+            serde_json::from_value(result).map_err(|e| FetcherError::ParseError(format!("{}: {}", context, e)))
+        }
+    }
+}
+
+/// Convert a failed RPC call into a `FetcherError`
+///
+/// HTTP 429 becomes `RateLimited`. For block requests (`slot` given), a skipped slot becomes
+/// `SlotSkipped` and a missing block (by code or message) `BlockNotAvailable`; anything else is
+/// an `RpcError`.
+fn rpc_error(context: impl Into<String>, err: ClientError, slot: Option<u64>) -> FetcherError {
+    if is_rate_limited(err.kind()) {
+        return FetcherError::RateLimited { context: context.into(), source: Box::new(err) };
+    }
+    if let (Some(slot), ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. })) =
+        (slot, err.kind())
+    {
+        match *code {
+            JSON_RPC_SERVER_ERROR_SLOT_SKIPPED | JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED => {
+                return FetcherError::SlotSkipped { slot };
+            }
+            _ if is_block_not_available(*code, message) => return FetcherError::BlockNotAvailable { slot },
+            _ => {}
+        }
+    }
+    FetcherError::rpc(context, err)
+}
+
+/// Check whether a JSON-RPC error says the node does not have the block
+fn is_block_not_available(code: i64, message: &str) -> bool {
+    code == JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE || message.starts_with(BLOCK_NOT_AVAILABLE_MESSAGE)
+}
+
+/// Check whether an RPC error is an HTTP 429 rate-limit response
+fn is_rate_limited(kind: &ClientErrorKind) -> bool {
+    matches!(kind, ClientErrorKind::Reqwest(err) if err.status().is_some_and(|status| status.as_u16() == 429))
+}
+
+/// Check whether an RPC error means the slot has no block (yet), rather than a failed request
+fn is_block_unavailable(kind: &ClientErrorKind) -> bool {
+    matches!(
+        kind,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. })
+            if is_block_not_available(*code, message)
+                || matches!(
+                    *code,
+                    JSON_RPC_SERVER_ERROR_SLOT_SKIPPED | JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+                )
+    )
+}
+
+/// Binary-search `low..=high` for the first slot whose block time is at or after `target`
+///
+/// `block_time` must not decrease with the slot. When the probe budget runs out the lower
+/// bound found so far is returned, which is never after the answer.
+async fn search_slot_by_time<F, Fut>(mut low: u64, mut high: u64, target: i64, block_time: F) -> Result<u64>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<i64>>,
+{
+    let mut probes = 0;
+    while low < high {
+        if probes == SLOT_SEARCH_MAX_PROBES {
+            tracing::warn!("Slot search stopped after {} probes between slots {} and {}", probes, low, high);
+            break;
+        }
+        probes += 1;
+
+        let mid = low + (high - low) / 2;
+        if block_time(mid).await? < target {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Check whether an RPC error is specific to the endpoint and worth retrying elsewhere
+///
+/// Transport failures, HTTP errors and unhealthy nodes are transient. JSON-RPC errors
+/// such as a skipped slot are answers about the chain and would be the same on any endpoint.
+fn is_transient(kind: &ClientErrorKind) -> bool {
+    match kind {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) | ClientErrorKind::Middleware(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_endpoints_requires_an_endpoint() {
+        assert!(SolanaRpcClient::with_endpoints(vec![]).is_err());
+
+        let client = SolanaRpcClient::with_endpoints(vec![
+            "http://primary.invalid".to_string(),
+            "http://backup.invalid".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(client.endpoint(), "http://primary.invalid");
+        assert_eq!(client.endpoints(), vec!["http://primary.invalid", "http://backup.invalid"]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_shared_by_clones() {
+        let client = SolanaRpcClient::new("http://primary.invalid".to_string()).unwrap().with_rate_limit(10);
+        let clone = client.clone();
+        assert_eq!(clone.rate_limit(), Some(10));
+
+        // The first second's burst is free; 5 more requests at 10/s take about half a second
+        let started = Instant::now();
+        client.acquire(10).await;
+        clone.acquire(5).await;
+        assert!(started.elapsed() >= Duration::from_millis(400));
+
+        assert_eq!(client.with_rate_limit(0).rate_limit(), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_slot_by_time() {
+        // One block every 2 seconds from slot 100 (time 1_000); slots 150-159 were skipped
+        // and report the time of slot 149
+        let block_time = |slot: u64| async move {
+            let slot = if (150..160).contains(&slot) { 149 } else { slot };
+            Ok(1_000 + 2 * (slot as i64 - 100))
+        };
+
+        assert_eq!(search_slot_by_time(100, 1_000, 1_000, block_time).await.unwrap(), 100);
+        assert_eq!(search_slot_by_time(100, 1_000, 1_098, block_time).await.unwrap(), 149);
+        // Inside the skipped run, the next block is the first at or after the target
+        assert_eq!(search_slot_by_time(100, 1_000, 1_101, block_time).await.unwrap(), 160);
+        assert_eq!(search_slot_by_time(100, 1_000, 1_200, block_time).await.unwrap(), 200);
+        assert_eq!(search_slot_by_time(100, 1_000, 99_999, block_time).await.unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_block_commitment_never_processed() {
+        let client = SolanaRpcClient::new("http://primary.invalid".to_string()).unwrap();
+        assert_eq!(client.block_commitment(), CommitmentConfig::finalized());
+
+        let client = client.with_commitment(CommitmentConfig::processed());
+        assert_eq!(client.commitment(), CommitmentConfig::processed());
+        assert_eq!(client.block_commitment(), CommitmentConfig::confirmed());
+    }
+
+    #[test]
+    fn test_endpoint_cooldown_after_repeated_rate_limits() {
+        let endpoint = RpcEndpoint::new(
+            "http://primary.invalid".to_string(),
+            HttpConfig::default().build_client().unwrap(),
+            Arc::new(AdaptiveThrottle::new()),
+        );
+
+        for _ in 0..RATE_LIMIT_STRIKES - 1 {
+            endpoint.record_rate_limited();
+        }
+        assert!(!endpoint.is_cooling_down());
+
+        endpoint.record_rate_limited();
+        assert!(endpoint.is_cooling_down());
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        let io = ClientErrorKind::Io(std::io::Error::other("connection reset"));
+        assert!(is_transient(&io));
+
+        let response_error = |code| {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code,
+                message: String::new(),
+                data: RpcResponseErrorData::Empty,
+            })
+        };
+        assert!(is_transient(&response_error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY)));
+        assert!(!is_transient(&response_error(JSON_RPC_SERVER_ERROR_SLOT_SKIPPED)));
+
+        // A missing block is an answer about the slot, not a failed request
+        assert!(is_block_unavailable(&response_error(JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE)));
+        assert!(is_block_unavailable(&response_error(JSON_RPC_SERVER_ERROR_SLOT_SKIPPED)));
+        assert!(!is_block_unavailable(&response_error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY)));
+        assert!(!is_block_unavailable(&io));
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        let skipped = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": JSON_RPC_SERVER_ERROR_SLOT_SKIPPED, "message": "Slot 43 was skipped" }
+        });
+        assert!(matches!(parse_batch_response(43, Some(skipped)), Err(FetcherError::SlotSkipped { slot: 43 })));
+
+        let null = serde_json::json!({ "jsonrpc": "2.0", "id": 2, "result": null });
+        assert!(matches!(parse_batch_response(44, Some(null)), Err(FetcherError::BlockNotAvailable { slot: 44 })));
+        assert!(matches!(parse_batch_response(45, None), Err(FetcherError::ParseError(_))));
+
+        let block = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": {
+                "previousBlockhash": "11111111111111111111111111111111",
+                "blockhash": "5xbT5Q3Wc2b4V5bDgEojXQNvNDWhbCqkUfMjeWkdPGbU",
+                "parentSlot": 41,
+                "transactions": [],
+                "rewards": [],
+                "blockTime": 1_700_000_000,
+                "blockHeight": 40
+            }
+        });
+        let block = parse_batch_response(42, Some(block)).unwrap();
+        assert_eq!(block.parent_slot, 41);
+        assert_eq!(block.block_time, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn test_rpc_error_classification() {
+        let response_error = |code| {
+            ClientError::from(RpcError::RpcResponseError {
+                code,
+                message: String::new(),
+                data: RpcResponseErrorData::Empty,
+            })
+        };
+
+        assert!(matches!(
+            rpc_error("getBlock", response_error(JSON_RPC_SERVER_ERROR_SLOT_SKIPPED), Some(42)),
+            FetcherError::SlotSkipped { slot: 42 }
+        ));
+        assert!(matches!(
+            rpc_error("getBlock", response_error(JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE), Some(42)),
+            FetcherError::BlockNotAvailable { slot: 42 }
+        ));
+        // Relayed under a generic code, the message still identifies a missing block
+        let relayed = ClientError::from(RpcError::RpcResponseError {
+            code: -32000,
+            message: "Block not available for slot 42".to_string(),
+            data: RpcResponseErrorData::Empty,
+        });
+        assert!(is_block_unavailable(relayed.kind()));
+        assert!(matches!(rpc_error("getBlock", relayed, Some(42)), FetcherError::BlockNotAvailable { slot: 42 }));
+        // Without a slot there is no block to blame
+        assert!(matches!(
+            rpc_error("getBlocks", response_error(JSON_RPC_SERVER_ERROR_SLOT_SKIPPED), None),
+            FetcherError::RpcError { .. }
+        ));
+        let unhealthy = rpc_error("getBlock", response_error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY), Some(42));
+        assert!(unhealthy.is_retryable());
+    }
 }
@@ -0,0 +1,141 @@
+/// Block Subscription Module
+///
+/// Streams new blocks over the `blockSubscribe` WebSocket method, so continuous mode
+/// can ingest blocks as they are confirmed instead of polling for the latest slot.
+/// The subscription is owned by a background task that reconnects with backoff when the
+/// WebSocket drops and never yields a slot twice. Not every provider offers
+/// `blockSubscribe`: validators only serve it with `--rpc-pubsub-enable-block-subscription`.
+use crate::error::{FetcherError, Result};
+use futures::{
+    channel::mpsc,
+    stream::{BoxStream, StreamExt},
+    SinkExt,
+};
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcBlockSubscribeConfig, RpcBlockSubscribeFilter},
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_transaction_status::{TransactionDetails, UiConfirmedBlock, UiTransactionEncoding};
+use std::time::Duration;
+
+/// Delay before the first reconnect attempt, doubled on every consecutive failure and jittered
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound for the reconnect delay
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Blocks buffered between the WebSocket reader and a slow consumer
+const SUBSCRIPTION_BUFFER: usize = 64;
+
+/// WebSocket URL of an HTTP RPC endpoint
+///
+/// `http` becomes `ws` and `https` becomes `wss`. An explicit port is incremented, since a
+/// validator serves pubsub on the port after its RPC port (8899 -> 8900); providers on the
+/// default port serve both on the same URL.
+pub fn websocket_url(rpc_url: &str) -> Result<String> {
+    let mut url =
+        reqwest::Url::parse(rpc_url).map_err(|_| FetcherError::InvalidInput(format!("Invalid RPC URL {}", rpc_url)))?;
+
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        "ws" | "wss" => return Ok(url.to_string()),
+        other => return Err(FetcherError::InvalidInput(format!("Unsupported RPC URL scheme {}", other))),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| FetcherError::InvalidInput(format!("Cannot derive a WebSocket URL from {}", rpc_url)))?;
+
+    if let Some(port) = url.port() {
+        url.set_port(Some(port + 1))
+            .map_err(|_| FetcherError::InvalidInput(format!("Cannot set WebSocket port on {}", rpc_url)))?;
+    }
+
+    Ok(url.to_string())
+}
+
+/// Subscribe to new blocks at the given commitment (`confirmed` or `finalized`)
+///
+/// Yields `(slot, block)` in increasing slot order: blocks at or below the last yielded slot,
+/// which the server may replay around a reconnect, are dropped. Connection and subscription
+/// failures are yielded as errors and followed by a reconnect; the stream only ends when it
+/// is dropped. Slots missed while disconnected are not replayed, callers fill the gap.
+pub fn subscribe_blocks(
+    ws_url: String,
+    commitment: CommitmentConfig,
+) -> BoxStream<'static, Result<(u64, UiConfirmedBlock)>> {
+    let (mut sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+
+    tokio::spawn(async move {
+        let config = RpcBlockSubscribeConfig {
+            commitment: Some(commitment),
+            encoding: Some(UiTransactionEncoding::JsonParsed),
+            transaction_details: Some(TransactionDetails::Full),
+            show_rewards: Some(true),
+            max_supported_transaction_version: Some(0),
+        };
+        let mut last_slot: Option<u64> = None;
+        let mut reconnect_delay = RECONNECT_DELAY;
+
+        while !sender.is_closed() {
+            let error = match PubsubClient::new(&ws_url).await {
+                Ok(client) => match client.block_subscribe(RpcBlockSubscribeFilter::All, Some(config.clone())).await {
+                    Ok((mut updates, _unsubscribe)) => {
+                        tracing::info!("Subscribed to blocks on {}", ws_url);
+
+                        while let Some(update) = updates.next().await {
+                            let update = update.value;
+                            if let Some(err) = update.err {
+                                tracing::warn!("Block subscription error at slot {}: {}", update.slot, err);
+                                continue;
+                            }
+                            let Some(block) = update.block else {
+                                continue;
+                            };
+                            if last_slot.is_some_and(|last| update.slot <= last) {
+                                tracing::debug!("Dropping replayed block at slot {}", update.slot);
+                                continue;
+                            }
+
+                            last_slot = Some(update.slot);
+                            reconnect_delay = RECONNECT_DELAY;
+                            if sender.send(Ok((update.slot, block))).await.is_err() {
+                                return; // The consumer is gone
+                            }
+                        }
+
+                        FetcherError::rpc(format!("Block subscription on {}", ws_url), "connection closed")
+                    }
+                    Err(e) => FetcherError::rpc("Failed to subscribe to blocks", e),
+                },
+                Err(e) => FetcherError::rpc(format!("Failed to connect to {}", ws_url), e),
+            };
+
+            let delay = super::backoff::jittered(reconnect_delay);
+            tracing::debug!("{:#}, reconnecting in {}ms", error, delay.as_millis());
+            if sender.send(Err(error)).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    });
+
+    receiver.boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("https://mainnet.helius-rpc.com/?api-key=abc").unwrap(),
+            "wss://mainnet.helius-rpc.com/?api-key=abc"
+        );
+        assert_eq!(websocket_url("http://127.0.0.1:8899").unwrap(), "ws://127.0.0.1:8900/");
+        assert_eq!(websocket_url("wss://example.com/ws").unwrap(), "wss://example.com/ws");
+        assert!(websocket_url("ftp://example.com").is_err());
+    }
+}
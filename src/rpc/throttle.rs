@@ -0,0 +1,153 @@
+/// Adaptive Throttle Module
+///
+/// Tracks HTTP 429 responses from the RPC provider and turns them into an extra
+/// delay between requests. The delay doubles on every rate-limited response (or
+/// jumps to the server's `Retry-After` value) and decays slowly once requests succeed,
+/// so the fetcher settles on whatever request rate the provider's plan allows.
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{header::RETRY_AFTER, Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Delay applied after the first rate-limited response
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Upper bound for the adaptive delay
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Fraction of the delay kept after each successful response (slow recovery)
+const RECOVERY_FACTOR: f64 = 0.9;
+
+/// Below this the delay is dropped entirely
+const RECOVERY_FLOOR: Duration = Duration::from_millis(10);
+
+/// Shared rate-limit state for all requests made through a `SolanaRpcClient`
+#[derive(Debug, Default)]
+pub struct AdaptiveThrottle {
+    delay: Mutex<Duration>,
+    rate_limited: AtomicU64,
+}
+
+impl AdaptiveThrottle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extra delay to wait between requests on top of the configured rate limit
+    pub fn current_delay(&self) -> Duration {
+        *self.delay.lock().unwrap()
+    }
+
+    /// Total number of rate-limited responses observed
+    pub fn rate_limited_count(&self) -> u64 {
+        self.rate_limited.load(Ordering::Relaxed)
+    }
+
+    /// Back off multiplicatively, honouring the server's `Retry-After` hint when it is longer
+    pub fn record_rate_limited(&self, retry_after: Option<Duration>) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+
+        let mut delay = self.delay.lock().unwrap();
+        let backoff = (*delay * 2).max(MIN_BACKOFF).max(retry_after.unwrap_or_default()).min(MAX_BACKOFF);
+        if backoff > *delay {
+            tracing::warn!("Rate limited by RPC provider, increasing request delay to {}ms", backoff.as_millis());
+        }
+        *delay = backoff;
+    }
+
+    /// Slowly shrink the delay after a successful response
+    pub fn record_success(&self) {
+        let mut delay = self.delay.lock().unwrap();
+        if delay.is_zero() {
+            return;
+        }
+
+        let recovered = delay.mul_f64(RECOVERY_FACTOR);
+        *delay = if recovered < RECOVERY_FLOOR { Duration::ZERO } else { recovered };
+    }
+}
+
+/// HTTP middleware that feeds RPC response statuses into an `AdaptiveThrottle`
+///
+/// It sits below the Solana HTTP sender, so it also sees the 429 responses the
+/// sender retries internally and the `Retry-After` headers that come with them.
+pub struct RateLimitObserver {
+    throttle: Arc<AdaptiveThrottle>,
+}
+
+impl RateLimitObserver {
+    pub fn new(throttle: Arc<AdaptiveThrottle>) -> Self {
+        Self { throttle }
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitObserver {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let response = next.run(req, extensions).await?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            self.throttle.record_rate_limited(retry_after);
+        } else if response.status().is_success() {
+            self.throttle.record_success();
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_recovery() {
+        let throttle = AdaptiveThrottle::new();
+        assert_eq!(throttle.current_delay(), Duration::ZERO);
+
+        throttle.record_rate_limited(None);
+        assert_eq!(throttle.current_delay(), MIN_BACKOFF);
+
+        throttle.record_rate_limited(None);
+        assert_eq!(throttle.current_delay(), MIN_BACKOFF * 2);
+
+        throttle.record_success();
+        assert!(throttle.current_delay() < MIN_BACKOFF * 2);
+        assert!(throttle.current_delay() > MIN_BACKOFF);
+
+        for _ in 0..100 {
+            throttle.record_success();
+        }
+        assert_eq!(throttle.current_delay(), Duration::ZERO);
+        assert_eq!(throttle.rate_limited_count(), 2);
+    }
+
+    #[test]
+    fn test_retry_after_is_honoured_and_capped() {
+        let throttle = AdaptiveThrottle::new();
+
+        throttle.record_rate_limited(Some(Duration::from_secs(5)));
+        assert_eq!(throttle.current_delay(), Duration::from_secs(5));
+
+        throttle.record_rate_limited(Some(Duration::from_secs(120)));
+        assert_eq!(throttle.current_delay(), MAX_BACKOFF);
+    }
+}
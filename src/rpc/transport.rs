@@ -0,0 +1,51 @@
+/// HTTP Transport Module
+///
+/// Settings of the HTTP client every RPC request goes through. One client, and so one
+/// connection pool, is shared by all endpoints and all clones of `SolanaRpcClient`: concurrent
+/// workers reuse kept-alive connections instead of paying a TCP and TLS handshake per request.
+/// HTTP/2 is negotiated over TLS (ALPN) when the provider supports it, multiplexing requests
+/// over a single connection.
+use crate::error::{FetcherError, Result};
+use solana_rpc_client::http_sender::HttpSender;
+use std::time::Duration;
+
+/// HTTP request timeout, matching the Solana client default
+pub const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Idle connections kept open per host, enough for a high `--concurrency`
+pub const DEFAULT_HTTP_POOL_SIZE: usize = 64;
+
+/// How long an idle pooled connection is kept before it is closed
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Interval of TCP keep-alive probes, so idle connections survive NATs and load balancers
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Tuning of the HTTP client behind the RPC client, see `SolanaRpcClient::with_http_config`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub timeout: Duration, // Whole-request timeout, including reading a large block (`--http-timeout`)
+    pub pool_size: usize,  // Idle connections kept open per host (`--http-pool-size`)
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { timeout: DEFAULT_HTTP_TIMEOUT, pool_size: DEFAULT_HTTP_POOL_SIZE }
+    }
+}
+
+impl HttpConfig {
+    /// Build the HTTP client: Solana's default headers, the configured timeout and pool size,
+    /// TCP keep-alive and no Nagle delay on small JSON-RPC requests
+    pub fn build_client(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .default_headers(HttpSender::default_headers())
+            .timeout(self.timeout)
+            .pool_max_idle_per_host(self.pool_size)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .tcp_nodelay(true)
+            .build()
+            .map_err(|err| FetcherError::rpc("Failed to build HTTP client", err))
+    }
+}
@@ -0,0 +1,233 @@
+/// Validate Module
+///
+/// Checks stored data against the chain after a backfill. Each stored block's blockhash and
+/// transaction count are fetched again (signatures only, no transaction details) and compared
+/// with its `blocks` and `transactions` rows, catching blocks left stale by a fork and blocks
+/// whose transactions were only partially inserted.
+use crate::{
+    db::{Database, StoredBlock},
+    output::{self, console},
+    rpc::SolanaRpcClient,
+};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
+
+/// Mismatched and missing slots listed in the summary
+const LISTED_SLOTS: usize = 10;
+
+/// How a stored block differs from the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The slot has no block on chain: it was skipped, e.g. after a fork
+    NotOnChain,
+    /// The block on chain has another blockhash
+    Blockhash { stored: String, chain: String },
+    /// `blocks.transaction_count` differs from the block on chain
+    TransactionCount { stored: u64, chain: u64 },
+    /// The stored transaction rows do not match the block on chain, e.g. after a partial insert
+    StoredTransactions { stored: u64, chain: u64 },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotOnChain => write!(f, "no block on chain"),
+            Self::Blockhash { stored, chain } => write!(f, "blockhash {} stored, {} on chain", stored, chain),
+            Self::TransactionCount { stored, chain } => {
+                write!(f, "transaction_count {} stored, {} on chain", stored, chain)
+            }
+            Self::StoredTransactions { stored, chain } => {
+                write!(f, "{} transactions stored, {} on chain", stored, chain)
+            }
+        }
+    }
+}
+
+/// Result of a `validate` run
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub stored_blocks: usize,                  // Blocks stored in the range
+    pub checked: usize,                        // Stored blocks compared with the chain
+    pub mismatched: Vec<(u64, Vec<Mismatch>)>, // Stored blocks that differ from the chain
+    pub missing: Vec<u64>,                     // Slots with a block on chain but none stored
+    pub errors: Vec<(u64, String)>,            // Stored blocks that could not be fetched
+}
+
+impl ValidationReport {
+    /// Whether every checked block matches the chain
+    ///
+    /// Missing blocks do not count: they are a gap for `backfill`, not wrong data.
+    pub fn is_valid(&self) -> bool {
+        self.mismatched.is_empty() && self.errors.is_empty()
+    }
+
+    /// Print the summary of checked, mismatched and missing blocks
+    pub fn print_summary(&self) {
+        if !output::console_enabled() {
+            tracing::info!(
+                stored_blocks = self.stored_blocks,
+                checked = self.checked,
+                mismatched = self.mismatched.len(),
+                missing = self.missing.len(),
+                errors = self.errors.len(),
+                "Validation summary"
+            );
+            for (slot, mismatches) in &self.mismatched {
+                for mismatch in mismatches {
+                    tracing::warn!(slot, "Block mismatch: {}", mismatch);
+                }
+            }
+            return;
+        }
+
+        console!("\n🔍 Validation Summary:");
+        console!("   📦 Stored blocks: {}", self.stored_blocks);
+        console!("   ✅ Checked: {}", self.checked);
+        console!("   ❌ Mismatched: {}", self.mismatched.len());
+        for (slot, mismatches) in self.mismatched.iter().take(LISTED_SLOTS) {
+            let details: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+            console!("      Slot {}: {}", slot, details.join("; "));
+        }
+        if self.mismatched.len() > LISTED_SLOTS {
+            console!("      ... and {} more", self.mismatched.len() - LISTED_SLOTS);
+        }
+        console!("   🕳️  Missing: {}", self.missing.len());
+        if !self.missing.is_empty() {
+            let listed: Vec<String> = self.missing.iter().take(LISTED_SLOTS).map(|s| s.to_string()).collect();
+            let more = if self.missing.len() > LISTED_SLOTS { ", ..." } else { "" };
+            console!("      Slots {}{} (fill them with `backfill`)", listed.join(", "), more);
+        }
+        if !self.errors.is_empty() {
+            console!("   ⚠️  Could not check: {}", self.errors.len());
+            for (slot, error) in self.errors.iter().take(LISTED_SLOTS) {
+                console!("      Slot {}: {}", slot, error);
+            }
+        }
+    }
+}
+
+/// Compare the stored blocks in `start..=end` with the chain
+///
+/// With `sample`, only that many stored blocks are fetched, spread evenly over the range;
+/// otherwise every stored block is. Missing blocks are found for the whole range with one
+/// `getBlocks` call. Up to `concurrency` blocks are fetched at a time.
+pub async fn validate_blocks(
+    rpc_client: &SolanaRpcClient,
+    database: &Database,
+    start_slot: u64,
+    end_slot: u64,
+    sample: Option<usize>,
+    concurrency: usize,
+) -> Result<ValidationReport> {
+    let stored = database.load_stored_blocks(start_slot, end_slot).await?;
+    let produced: HashSet<u64> = rpc_client.get_blocks(start_slot, end_slot).await?.into_iter().collect();
+
+    let stored_slots: HashSet<u64> = stored.iter().map(|b| b.slot as u64).collect();
+    let mut missing: Vec<u64> = produced.difference(&stored_slots).copied().collect();
+    missing.sort_unstable();
+
+    let mut report = ValidationReport { stored_blocks: stored.len(), missing, ..ValidationReport::default() };
+    let to_check = sample_evenly(&stored, sample);
+    report.checked = to_check.len();
+
+    // A stored slot without a block on chain is stale; there is nothing to fetch for it
+    let (to_fetch, not_on_chain): (Vec<&StoredBlock>, Vec<&StoredBlock>) =
+        to_check.into_iter().partition(|b| produced.contains(&(b.slot as u64)));
+    report.mismatched.extend(not_on_chain.iter().map(|b| (b.slot as u64, vec![Mismatch::NotOnChain])));
+
+    console!("\n🔍 Validating {} of {} stored blocks against the chain...", report.checked, report.stored_blocks);
+
+    let mut fetched = stream::iter(to_fetch)
+        .map(|block| async move { (block, rpc_client.fetch_block_signatures(block.slot as u64).await) })
+        .buffered(concurrency.max(1));
+
+    while let Some((block, result)) = fetched.next().await {
+        match result {
+            Ok(chain) => {
+                let chain_transactions = chain.signatures.map_or(0, |signatures| signatures.len()) as u64;
+                let mismatches = compare_block(block, &chain.blockhash, chain_transactions);
+                if !mismatches.is_empty() {
+                    report.mismatched.push((block.slot as u64, mismatches));
+                }
+            }
+            Err(e) => report.errors.push((block.slot as u64, e.to_string())),
+        }
+    }
+    report.mismatched.sort_by_key(|(slot, _)| *slot);
+
+    tracing::info!(
+        "Validated {} blocks in {}-{}: {} mismatched, {} missing",
+        report.checked,
+        start_slot,
+        end_slot,
+        report.mismatched.len(),
+        report.missing.len()
+    );
+    Ok(report)
+}
+
+/// Differences between a stored block and the same slot on chain
+fn compare_block(stored: &StoredBlock, chain_blockhash: &str, chain_transactions: u64) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+
+    if stored.blockhash != chain_blockhash {
+        mismatches.push(Mismatch::Blockhash { stored: stored.blockhash.clone(), chain: chain_blockhash.to_string() });
+    }
+    // NULL for blocks loaded before the column existed, nothing to compare
+    if let Some(count) = stored.transaction_count {
+        if count as u64 != chain_transactions {
+            mismatches.push(Mismatch::TransactionCount { stored: count as u64, chain: chain_transactions });
+        }
+    }
+    if stored.stored_transactions as u64 != chain_transactions {
+        mismatches.push(Mismatch::StoredTransactions {
+            stored: stored.stored_transactions as u64,
+            chain: chain_transactions,
+        });
+    }
+
+    mismatches
+}
+
+/// `sample` items spread evenly over `items` (first item included), or all of them
+fn sample_evenly<T>(items: &[T], sample: Option<usize>) -> Vec<&T> {
+    match sample {
+        Some(count) if count < items.len() => (0..count).map(|i| &items[i * items.len() / count]).collect(),
+        _ => items.iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_block() {
+        let stored = StoredBlock {
+            slot: 100,
+            blockhash: "hashA".to_string(),
+            transaction_count: Some(3),
+            stored_transactions: 3,
+        };
+        assert!(compare_block(&stored, "hashA", 3).is_empty());
+
+        // A fork replaced the block
+        assert_eq!(
+            compare_block(&stored, "hashB", 3),
+            vec![Mismatch::Blockhash { stored: "hashA".to_string(), chain: "hashB".to_string() }]
+        );
+
+        // A partial insert, in a block loaded before transaction_count existed
+        let partial = StoredBlock { transaction_count: None, stored_transactions: 1, ..stored };
+        assert_eq!(compare_block(&partial, "hashA", 3), vec![Mismatch::StoredTransactions { stored: 1, chain: 3 }]);
+    }
+
+    #[test]
+    fn test_sample_evenly() {
+        let slots: Vec<u64> = (0..10).collect();
+        assert_eq!(sample_evenly(&slots, Some(3)), vec![&0, &3, &6]);
+        assert_eq!(sample_evenly(&slots, Some(20)).len(), 10);
+        assert_eq!(sample_evenly(&slots, None).len(), 10);
+    }
+}
@@ -0,0 +1,96 @@
+/// Classification of whole blocks
+///
+/// Each fixture in `tests/fixtures/` is a `getBlock` response (jsonParsed encoding, full
+/// transaction details) with a known mix of transactions: a DEX-heavy block, a vote-heavy block
+/// and an NFT block. The transactions use the real program IDs and instruction layouts, with
+/// generated accounts and signatures. The tests run the same path as the pipeline
+/// (`parse_transactions_from_block`, then `DefaultClassifier` with the built-in registry) and
+/// pin the resulting type breakdown, so a change to the rules shows up as a changed count.
+use block_data_fetcher::{
+    db::ProgramInfo,
+    etl::{
+        extract::parse_transactions_from_block,
+        transform::{Classifier, DefaultClassifier, ProgramRegistry, TransactionTypeStats, BUILTIN_PROGRAMS},
+    },
+};
+use solana_transaction_status::UiConfirmedBlock;
+use std::path::Path;
+
+/// Load a block saved under `tests/fixtures/`
+fn load_fixture(name: &str) -> UiConfirmedBlock {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let contents =
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path.display(), e));
+    serde_json::from_str(&contents).unwrap_or_else(|e| panic!("Failed to parse fixture {}: {}", path.display(), e))
+}
+
+/// Registry seeded with the built-in programs, as on a fresh database
+fn builtin_registry() -> ProgramRegistry {
+    ProgramRegistry::from_database(
+        BUILTIN_PROGRAMS
+            .iter()
+            .map(|(program_id, program_name, program_type)| ProgramInfo {
+                program_id: program_id.to_string(),
+                program_name: program_name.to_string(),
+                program_type: Some(program_type.to_string()),
+            })
+            .collect(),
+    )
+}
+
+/// Classify every transaction of a fixture block
+fn classify_fixture(name: &str) -> TransactionTypeStats {
+    let block = load_fixture(name);
+    let (transactions, filtered) = parse_transactions_from_block(&block, None, None).unwrap();
+    assert_eq!(filtered, 0);
+    assert_eq!(transactions.len(), block.transactions.as_ref().unwrap().len(), "every transaction parses");
+
+    let registry = builtin_registry();
+    let mut stats = TransactionTypeStats::new();
+    for tx in &transactions {
+        stats.add(&DefaultClassifier.classify(tx, &registry));
+    }
+    stats
+}
+
+#[test]
+fn test_classify_dex_block() {
+    let stats = classify_fixture("dex_block.json");
+
+    // Jupiter routes (one failed on slippage), a Raydium swapBaseIn and an Orca swap
+    assert_eq!(stats.dex_swaps, 4);
+    // Raydium AMM v4 deposit
+    assert_eq!(stats.liquidity_adds, 1);
+    assert_eq!(stats.token_transfers, 1);
+    assert_eq!(stats.votes, 2);
+    assert_eq!(stats.total, 8);
+}
+
+#[test]
+fn test_classify_vote_block() {
+    let stats = classify_fixture("vote_block.json");
+
+    assert_eq!(stats.votes, 9);
+    assert_eq!(stats.sol_transfers, 1);
+    assert_eq!(stats.memos, 1);
+    // createIdempotent of an associated token account
+    assert_eq!(stats.account_creations, 1);
+    assert_eq!(stats.stakes, 1);
+    assert_eq!(stats.total, 13);
+    assert!(stats.percentage(stats.votes) > 69.0);
+}
+
+#[test]
+fn test_classify_nft_block() {
+    let stats = classify_fixture("nft_block.json");
+
+    // Two Metaplex mints (the token mint and ATA creation are part of them) and a Magic Eden buy
+    assert_eq!(stats.nft_operations, 3);
+    assert_eq!(stats.token_mints, 1);
+    assert_eq!(stats.token_burns, 1);
+    // A program outside the registry, alone and creating an account through the System program
+    assert_eq!(stats.unknown, 1);
+    assert_eq!(stats.program_interactions, 1);
+    assert_eq!(stats.votes, 1);
+    assert_eq!(stats.total, 8);
+}